@@ -1,4 +1,6 @@
-use ear_trainer::notes::{Note, NoteWithOctave, Scale, ScaleType, play_frequency};
+use ear_trainer::audio::play_frequency;
+use ear_trainer::chords::{Chord, ChordQuality};
+use ear_trainer::notes::{Note, NoteWithOctave, Scale, ScaleType};
 use std::time::Duration;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -49,6 +51,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     scale.play_default()?;
     println!();
 
+    // Demo 4: Polyphony
+    println!("🎼 Playing C major chord (block):");
+    let chord = Chord::new(
+        NoteWithOctave {
+            note: Note::C,
+            octave: 4,
+        },
+        ChordQuality::Major,
+    );
+    chord.play_together(Duration::from_millis(1500))?;
+    println!();
+
     println!("\n✨ Audio demo complete!");
     Ok(())
 }
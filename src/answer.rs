@@ -0,0 +1,228 @@
+use crate::chords::{Chord, ChordMatch};
+use crate::intervals::Interval;
+use crate::notes::{Note, NoteWithOctave};
+use std::fmt;
+use std::io::{self, BufRead};
+use std::time::{Duration, Instant};
+
+/// How long to wait after the last key before treating the notes played as one answer,
+/// long enough to play a melodic interval
+pub const ANSWER_PAUSE: Duration = Duration::from_millis(700);
+
+/// What the user answered with: a typed line or notes played on a keyboard
+#[derive(Debug, PartialEq, Clone)]
+pub enum Answer {
+    Text(String),
+    /// Notes played, lowest first
+    Notes(Vec<NoteWithOctave>),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::Notes(notes) => {
+                let names: Vec<String> = notes.iter().map(|n| n.to_string()).collect();
+                write!(f, "{}", names.join(" "))
+            }
+        }
+    }
+}
+
+impl Answer {
+    /// Notes from MIDI note numbers, skipping any below octave 0
+    pub fn from_midi(numbers: &[u8]) -> Self {
+        let mut notes: Vec<NoteWithOctave> = numbers
+            .iter()
+            .filter_map(|&number| NoteWithOctave::from_midi(number).ok())
+            .collect();
+        notes.sort();
+        notes.dedup();
+        Answer::Notes(notes)
+    }
+
+    /// The typed text, trimmed, if the answer was typed
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Answer::Text(text) => Some(text.trim()),
+            Answer::Notes(_) => None,
+        }
+    }
+
+    /// The note name, if exactly one note was played
+    pub fn note(&self) -> Option<Note> {
+        match self {
+            Answer::Notes(notes) if notes.len() == 1 => Some(notes[0].note),
+            _ => None,
+        }
+    }
+
+    /// The chord that best fits the notes played, if any
+    pub fn chord(&self) -> Option<ChordMatch> {
+        match self {
+            Answer::Notes(notes) => Chord::identify(notes).into_iter().next(),
+            Answer::Text(_) => None,
+        }
+    }
+
+    /// The interval from the lowest to the highest note, if more than one was played
+    pub fn interval(&self) -> Option<Interval> {
+        match self {
+            Answer::Notes(notes) if notes.len() >= 2 => {
+                let span =
+                    notes[notes.len() - 1].absolute_semitone() - notes[0].absolute_semitone();
+                u8::try_from(span).ok().map(Interval::from_semitones)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Somewhere quiz answers come from, so quizzes work the same typed or played
+pub trait AnswerInput {
+    /// Wait for the next answer, or `None` once the input has closed
+    fn next_answer(&mut self) -> Option<Answer>;
+}
+
+/// Answers typed on the terminal. Stdin is only locked while a line is read,
+/// so prompts outside the quiz can keep reading it too.
+#[derive(Debug, Default)]
+pub struct TerminalInput;
+
+impl AnswerInput for TerminalInput {
+    fn next_answer(&mut self) -> Option<Answer> {
+        read_text(&mut io::stdin().lock())
+    }
+}
+
+/// Answers read a line at a time from any reader, e.g. a script of answers
+pub struct TextInput<R> {
+    reader: R,
+}
+
+impl<R: BufRead> TextInput<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+}
+
+impl<R: BufRead> AnswerInput for TextInput<R> {
+    fn next_answer(&mut self) -> Option<Answer> {
+        read_text(&mut self.reader)
+    }
+}
+
+fn read_text(reader: &mut impl BufRead) -> Option<Answer> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(Answer::Text(line.trim().to_string())),
+    }
+}
+
+/// Gathers keys pressed in quick succession into one answer,
+/// so a chord or a two-note interval arrives as a single `Answer`
+#[derive(Debug, PartialEq, Clone)]
+pub struct NoteGrouper {
+    pause: Duration,
+    notes: Vec<u8>,
+    last: Option<Instant>,
+}
+
+impl Default for NoteGrouper {
+    fn default() -> Self {
+        Self::new(ANSWER_PAUSE)
+    }
+}
+
+impl NoteGrouper {
+    pub fn new(pause: Duration) -> Self {
+        Self {
+            pause,
+            notes: Vec::new(),
+            last: None,
+        }
+    }
+
+    /// Add a key pressed at `at`
+    pub fn press(&mut self, note: u8, at: Instant) {
+        self.notes.push(note);
+        self.last = Some(at);
+    }
+
+    /// When the notes so far become an answer, if any have been played
+    pub fn deadline(&self) -> Option<Instant> {
+        self.last.map(|last| last + self.pause)
+    }
+
+    /// The notes played, once nothing new has been pressed for the pause
+    pub fn take_if_done(&mut self, now: Instant) -> Option<Vec<u8>> {
+        if self.deadline()? > now {
+            return None;
+        }
+        self.last = None;
+        Some(std::mem::take(&mut self.notes))
+    }
+
+    /// Forget notes that haven't become an answer yet
+    pub fn clear(&mut self) {
+        self.notes.clear();
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_input() {
+        let mut input = TextInput::new("P5\n  q \n".as_bytes());
+        assert_eq!(input.next_answer(), Some(Answer::Text("P5".to_string())));
+        assert_eq!(input.next_answer().unwrap().text(), Some("q"));
+        assert_eq!(input.next_answer(), None);
+    }
+
+    #[test]
+    fn test_played_answers() {
+        let single = Answer::from_midi(&[66]);
+        assert_eq!(single.note(), Some(Note::FSharp));
+        assert_eq!(single.interval(), None);
+        assert_eq!(single.text(), None);
+
+        // Played top note first, still a fifth up from the lowest
+        let fifth = Answer::from_midi(&[67, 60]);
+        assert_eq!(fifth.interval(), Some(Interval::from_semitones(7)));
+        assert_eq!(fifth.note(), None);
+        assert_eq!(fifth.to_string(), "C4 G4");
+        assert_eq!(Answer::from_midi(&[60, 60]).note(), Some(Note::C));
+
+        let chord = Answer::from_midi(&[64, 67, 72]).chord().unwrap();
+        assert_eq!(chord.chord.to_string(), "C/E");
+        assert_eq!(Answer::Text("C".to_string()).chord(), None);
+    }
+
+    #[test]
+    fn test_note_grouper() {
+        let start = Instant::now();
+        let mut grouper = NoteGrouper::new(Duration::from_millis(100));
+        assert_eq!(grouper.deadline(), None);
+        assert_eq!(grouper.take_if_done(start), None);
+
+        grouper.press(60, start);
+        grouper.press(64, start + Duration::from_millis(80));
+        assert_eq!(
+            grouper.take_if_done(start + Duration::from_millis(150)),
+            None
+        );
+        assert_eq!(
+            grouper.take_if_done(start + Duration::from_millis(180)),
+            Some(vec![60, 64])
+        );
+        assert_eq!(grouper.deadline(), None);
+
+        grouper.press(67, start);
+        grouper.clear();
+        assert_eq!(grouper.take_if_done(start + Duration::from_secs(1)), None);
+    }
+}
@@ -0,0 +1,250 @@
+#[cfg(feature = "playback")]
+use crate::audio::{AudioEngine, Playable, PlaybackOptions};
+use crate::chords::Chord;
+use crate::generator::seeded;
+use crate::melody::Melody;
+use crate::notes::NoteWithOctave;
+use crate::spelling::SpelledPitch;
+use crate::tempo::NoteValue;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::fmt;
+use std::str::FromStr;
+
+/// Most octaves an arpeggio can span
+pub const MAX_ARPEGGIO_OCTAVES: u8 = 4;
+
+/// The order an arpeggio's chord tones are played in
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ArpeggioPattern {
+    /// From the bass up to the same tone at the top
+    #[default]
+    Up,
+    /// From the top down to the bass
+    Down,
+    /// Up to the top and back down, without repeating it
+    UpDown,
+    /// Every tone once, in a shuffled order
+    Random,
+}
+
+impl fmt::Display for ArpeggioPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pattern = match self {
+            ArpeggioPattern::Up => "up",
+            ArpeggioPattern::Down => "down",
+            ArpeggioPattern::UpDown => "up-down",
+            ArpeggioPattern::Random => "random",
+        };
+        write!(f, "{}", pattern)
+    }
+}
+
+impl FromStr for ArpeggioPattern {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "up" => Ok(ArpeggioPattern::Up),
+            "down" => Ok(ArpeggioPattern::Down),
+            "up-down" | "updown" | "up_down" => Ok(ArpeggioPattern::UpDown),
+            "random" => Ok(ArpeggioPattern::Random),
+            _ => Err(format!("Invalid arpeggio pattern: {}", s)),
+        }
+    }
+}
+
+/// A chord broken into single notes in a pattern, over one or more octaves
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arpeggio {
+    pub chord: Chord,
+    pub pattern: ArpeggioPattern,
+    /// Octaves covered above the bass, 1 to `MAX_ARPEGGIO_OCTAVES`
+    pub octaves: u8,
+    /// Decides the order of a random pattern, so playing and exporting agree
+    seed: u64,
+}
+
+impl fmt::Display for Arpeggio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} arpeggio ({}", self.chord, self.pattern)?;
+        if self.octaves > 1 {
+            write!(f, ", {} octaves", self.octaves)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Arpeggio {
+    /// One octave of `chord` in `pattern`
+    pub fn new(chord: Chord, pattern: ArpeggioPattern) -> Self {
+        Self {
+            chord,
+            pattern,
+            octaves: 1,
+            seed: rand::thread_rng().r#gen(),
+        }
+    }
+
+    /// Span `octaves` octaves, clamped to 1-`MAX_ARPEGGIO_OCTAVES`
+    pub fn with_octaves(mut self, octaves: u8) -> Self {
+        self.octaves = octaves.clamp(1, MAX_ARPEGGIO_OCTAVES);
+        self
+    }
+
+    /// Fix the order of a random pattern
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The tones in playing order with their theoretically correct names
+    pub fn spelled_notes(&self) -> Vec<SpelledPitch> {
+        let tones = self.chord.spelled_notes();
+        let mut up: Vec<SpelledPitch> = (0..self.octaves)
+            .flat_map(|octave| {
+                tones.iter().map(move |tone| SpelledPitch {
+                    note: tone.note,
+                    octave: tone.octave + octave,
+                })
+            })
+            .collect();
+        if let Some(bass) = tones.first() {
+            up.push(SpelledPitch {
+                octave: bass.octave + self.octaves,
+                ..*bass
+            });
+        }
+        // Tones beyond the highest octave are left out rather than wrapped
+        up.retain(|note| note.pitch().is_some());
+
+        match self.pattern {
+            ArpeggioPattern::Up => up,
+            ArpeggioPattern::Down => up.into_iter().rev().collect(),
+            ArpeggioPattern::UpDown => {
+                let down: Vec<SpelledPitch> = up.iter().rev().skip(1).copied().collect();
+                up.extend(down);
+                up
+            }
+            ArpeggioPattern::Random => {
+                up.shuffle(&mut seeded(self.seed));
+                up
+            }
+        }
+    }
+
+    /// The tones in playing order
+    pub fn notes(&self) -> Vec<NoteWithOctave> {
+        self.spelled_notes()
+            .iter()
+            .filter_map(|note| note.pitch())
+            .collect()
+    }
+
+    /// The arpeggio as a melody of `value` notes
+    pub fn melody(&self, value: NoteValue) -> Melody {
+        Melody::from_notes(&self.notes(), value)
+    }
+}
+
+#[cfg(feature = "playback")]
+impl Playable for Arpeggio {
+    /// Play the tones in eighth notes, printing each as it sounds
+    fn play(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let labels: Vec<String> = self.spelled_notes().iter().map(|n| n.to_string()).collect();
+        self.melody(NoteValue::Eighth)
+            .play_events(engine, opts, Some(&labels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chords::ChordQuality;
+
+    fn names(arpeggio: &Arpeggio) -> Vec<String> {
+        arpeggio
+            .spelled_notes()
+            .iter()
+            .map(|n| n.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_pattern_parsing() {
+        assert_eq!(
+            "up".parse::<ArpeggioPattern>().unwrap(),
+            ArpeggioPattern::Up
+        );
+        assert_eq!(
+            "Up-Down".parse::<ArpeggioPattern>().unwrap(),
+            ArpeggioPattern::UpDown
+        );
+        assert_eq!(
+            "random".parse::<ArpeggioPattern>().unwrap(),
+            ArpeggioPattern::Random
+        );
+        assert!("sideways".parse::<ArpeggioPattern>().is_err());
+        assert_eq!(ArpeggioPattern::UpDown.to_string(), "up-down");
+    }
+
+    #[test]
+    fn test_arpeggio_patterns() {
+        let chord = Chord::spelled("Eb4".parse().unwrap(), ChordQuality::Major).unwrap();
+        let up = Arpeggio::new(chord.clone(), ArpeggioPattern::Up);
+        assert_eq!(names(&up), vec!["Eb4", "G4", "Bb4", "Eb5"]);
+
+        let down = Arpeggio::new(chord.clone(), ArpeggioPattern::Down);
+        assert_eq!(names(&down), vec!["Eb5", "Bb4", "G4", "Eb4"]);
+
+        let up_down = Arpeggio::new(chord, ArpeggioPattern::UpDown);
+        assert_eq!(
+            names(&up_down),
+            vec!["Eb4", "G4", "Bb4", "Eb5", "Bb4", "G4", "Eb4"]
+        );
+    }
+
+    #[test]
+    fn test_arpeggio_octaves() {
+        let chord = Chord::new("C4".parse().unwrap(), ChordQuality::Dominant7);
+        let arpeggio = Arpeggio::new(chord.clone(), ArpeggioPattern::Up).with_octaves(2);
+        assert_eq!(
+            names(&arpeggio),
+            vec!["C4", "E4", "G4", "Bb4", "C5", "E5", "G5", "Bb5", "C6"]
+        );
+        assert_eq!(arpeggio.to_string(), "C7 arpeggio (up, 2 octaves)");
+
+        let clamped = Arpeggio::new(chord, ArpeggioPattern::Up).with_octaves(9);
+        assert_eq!(clamped.octaves, MAX_ARPEGGIO_OCTAVES);
+    }
+
+    #[test]
+    fn test_random_arpeggio_is_repeatable() {
+        let chord = Chord::new("A3".parse().unwrap(), ChordQuality::Minor7);
+        let arpeggio = Arpeggio::new(chord.clone(), ArpeggioPattern::Random)
+            .with_octaves(2)
+            .with_seed(7);
+        assert_eq!(arpeggio.notes(), arpeggio.notes());
+
+        let mut shuffled = arpeggio.notes();
+        shuffled.sort();
+        let up = Arpeggio::new(chord, ArpeggioPattern::Up).with_octaves(2);
+        assert_eq!(shuffled, up.notes());
+        assert_eq!(arpeggio.melody(NoteValue::Eighth).len(), 9);
+    }
+
+    #[test]
+    fn test_inverted_arpeggio() {
+        let chord = Chord::new("C4".parse().unwrap(), ChordQuality::Major)
+            .invert(1)
+            .unwrap();
+        let arpeggio = Arpeggio::new(chord, ArpeggioPattern::Up);
+        assert_eq!(names(&arpeggio), vec!["E4", "G4", "C5", "E5"]);
+    }
+}
@@ -0,0 +1,1450 @@
+use crate::melody::MAX_SWING;
+#[cfg(feature = "playback")]
+use crate::notes::{NoteWithOctave, Pitch, Tuning};
+use crate::tempo::Tempo;
+#[cfg(feature = "audio")]
+use rodio::buffer::SamplesBuffer;
+#[cfg(feature = "audio")]
+use rodio::{OutputStream, OutputStreamHandle, Sink, Source};
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "audio")]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Sample rate used for all generated tones
+pub const SAMPLE_RATE: u32 = 44100;
+
+/// Number of rendered tones kept around for instant replay
+const RENDER_CACHE_CAPACITY: usize = 64;
+
+/// Shared cache so repeated prompts skip re-synthesis
+static RENDER_CACHE: Mutex<RenderCache> = Mutex::new(RenderCache::new(RENDER_CACHE_CAPACITY));
+
+/// Shape of the oscillator used to generate tones
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Waveform {
+    #[default]
+    Sine,
+    Square,
+    Triangle,
+    Sawtooth,
+}
+
+impl fmt::Display for Waveform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let waveform_name = match self {
+            Waveform::Sine => "Sine",
+            Waveform::Square => "Square",
+            Waveform::Triangle => "Triangle",
+            Waveform::Sawtooth => "Sawtooth",
+        };
+        write!(f, "{}", waveform_name)
+    }
+}
+
+impl FromStr for Waveform {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sine" => Ok(Waveform::Sine),
+            "square" => Ok(Waveform::Square),
+            "triangle" => Ok(Waveform::Triangle),
+            "sawtooth" | "saw" => Ok(Waveform::Sawtooth),
+            _ => Err(format!("Invalid waveform: {}", s)),
+        }
+    }
+}
+
+impl Waveform {
+    /// Value of the waveform at `phase`, a fraction of one cycle in `0.0..1.0`
+    fn sample_at(&self, phase: f32) -> f32 {
+        match self {
+            Waveform::Sine => (phase * 2.0 * std::f32::consts::PI).sin(),
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Waveform::Sawtooth => 2.0 * phase - 1.0,
+        }
+    }
+}
+
+/// Attack/decay/sustain/release envelope that shapes a tone's loudness over time,
+/// so notes fade in and out instead of clicking
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Envelope {
+    pub attack: Duration,
+    pub decay: Duration,
+    /// Level held after the decay, from 0.0 to 1.0
+    pub sustain: f32,
+    pub release: Duration,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            attack: Duration::from_millis(10),
+            decay: Duration::from_millis(50),
+            sustain: 0.8,
+            release: Duration::from_millis(50),
+        }
+    }
+}
+
+impl Envelope {
+    /// No shaping at all: full level from the first to the last sample
+    pub const FLAT: Envelope = Envelope {
+        attack: Duration::ZERO,
+        decay: Duration::ZERO,
+        sustain: 1.0,
+        release: Duration::ZERO,
+    };
+
+    /// Gain at time `t` into a note lasting `total`. The release happens inside
+    /// `total`; stages are shortened proportionally when the note is too short.
+    pub fn gain_at(&self, t: Duration, total: Duration) -> f32 {
+        let t = t.as_secs_f32();
+        let total = total.as_secs_f32();
+        let mut attack = self.attack.as_secs_f32();
+        let mut decay = self.decay.as_secs_f32();
+        let mut release = self.release.as_secs_f32();
+
+        let stages = attack + decay + release;
+        if stages > total && stages > 0.0 {
+            let scale = total / stages;
+            attack *= scale;
+            decay *= scale;
+            release *= scale;
+        }
+
+        let sustain = self.sustain.clamp(0.0, 1.0);
+        let release_start = total - release;
+        let level = if t < attack {
+            t / attack
+        } else if t < attack + decay {
+            1.0 - (1.0 - sustain) * (t - attack) / decay
+        } else {
+            sustain
+        };
+
+        if t >= release_start && release > 0.0 {
+            // Fade from wherever the envelope is down to silence
+            level * ((total - t) / release).clamp(0.0, 1.0)
+        } else {
+            level
+        }
+    }
+
+    /// Gain at time `t` into a note held with no set length, like a key on a keyboard:
+    /// attack and decay, then the sustain level until it is let go `released` into the
+    /// note, then the release. `None` once the release has faded out.
+    pub fn held_gain_at(&self, t: Duration, released: Option<Duration>) -> Option<f32> {
+        let Some(released) = released.filter(|released| t >= *released) else {
+            return Some(self.level_at(t));
+        };
+        let fading = (t - released).as_secs_f32();
+        let release = self.release.as_secs_f32();
+        if fading >= release {
+            return None;
+        }
+        Some(self.level_at(released) * (1.0 - fading / release))
+    }
+
+    /// Level before any release: the attack, the decay, then the sustain level
+    fn level_at(&self, t: Duration) -> f32 {
+        let sustain = self.sustain.clamp(0.0, 1.0);
+        if t < self.attack {
+            t.as_secs_f32() / self.attack.as_secs_f32()
+        } else if t < self.attack + self.decay {
+            let t = (t - self.attack).as_secs_f32();
+            1.0 - (1.0 - sustain) * t / self.decay.as_secs_f32()
+        } else {
+            sustain
+        }
+    }
+}
+
+/// Default loudness of a tone, leaving headroom for chords
+pub const DEFAULT_GAIN: f32 = 0.3;
+
+/// Everything that shapes how a single tone sounds
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ToneConfig {
+    pub waveform: Waveform,
+    pub envelope: Envelope,
+    /// Peak amplitude from 0.0 to 1.0 (full scale)
+    pub gain: f32,
+}
+
+impl Default for ToneConfig {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::default(),
+            envelope: Envelope::default(),
+            gain: DEFAULT_GAIN,
+        }
+    }
+}
+
+/// Generate a periodic wave source at the specified frequency
+struct Oscillator {
+    frequency: f32,
+    waveform: Waveform,
+    sample_rate: u32,
+    sample_index: usize,
+}
+
+impl Oscillator {
+    fn new(frequency: f32, waveform: Waveform) -> Self {
+        Self {
+            frequency,
+            waveform,
+            sample_rate: SAMPLE_RATE,
+            sample_index: 0,
+        }
+    }
+}
+
+impl Iterator for Oscillator {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Work in f64 so the phase stays accurate for long drones
+        let phase = (self.sample_index as f64 * self.frequency as f64 / self.sample_rate as f64)
+            .fract() as f32;
+        let sample = self.waveform.sample_at(phase);
+        self.sample_index = self.sample_index.wrapping_add(1);
+        Some(sample)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Source for Oscillator {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// A tone that sustains until it is let go and then fades out over its release,
+/// so live notes can last as long as a key is held
+#[cfg(feature = "audio")]
+struct HeldTone {
+    oscillator: Oscillator,
+    tone: ToneConfig,
+    sample_index: u64,
+    /// Set from another thread when the note is let go
+    release: Arc<AtomicBool>,
+    released_at: Option<Duration>,
+}
+
+#[cfg(feature = "audio")]
+impl Iterator for HeldTone {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = Duration::from_secs_f64(self.sample_index as f64 / SAMPLE_RATE as f64);
+        if self.released_at.is_none() && self.release.load(Ordering::Relaxed) {
+            self.released_at = Some(t);
+        }
+        let gain = self.tone.envelope.held_gain_at(t, self.released_at)?;
+        self.sample_index += 1;
+        Some(self.oscillator.next()? * gain * self.tone.gain)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Source for HeldTone {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Key identifying a rendered tone: frequency bits, duration and tone settings
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct RenderKey {
+    frequency_bits: u32,
+    duration: Duration,
+    tone: ToneConfig,
+}
+
+/// Least-recently-used cache of rendered tone buffers
+pub struct RenderCache {
+    capacity: usize,
+    // Most recently used entries live at the back
+    entries: VecDeque<(RenderKey, Arc<Vec<f32>>)>,
+}
+
+impl RenderCache {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Get the rendered samples for a tone, synthesizing and caching them on a miss
+    pub fn get_or_render(
+        &mut self,
+        frequency: f32,
+        duration: Duration,
+        tone: ToneConfig,
+    ) -> Arc<Vec<f32>> {
+        let key = RenderKey {
+            frequency_bits: frequency.to_bits(),
+            duration,
+            tone,
+        };
+
+        if let Some(pos) = self.entries.iter().position(|(k, _)| *k == key) {
+            let entry = self.entries.remove(pos).expect("position is in bounds");
+            let samples = Arc::clone(&entry.1);
+            self.entries.push_back(entry);
+            return samples;
+        }
+
+        let samples = Arc::new(render_tone(frequency, duration, tone));
+        if self.capacity > 0 {
+            if self.entries.len() == self.capacity {
+                self.entries.pop_front();
+            }
+            self.entries.push_back((key, Arc::clone(&samples)));
+        }
+        samples
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Synthesize a tone into a mono sample buffer, shaped by the tone's envelope
+pub fn render_tone(frequency: f32, duration: Duration, tone: ToneConfig) -> Vec<f32> {
+    let length = (duration.as_secs_f64() * SAMPLE_RATE as f64) as usize;
+    Oscillator::new(frequency, tone.waveform)
+        .take(length)
+        .enumerate()
+        .map(|(i, sample)| {
+            let t = Duration::from_secs_f64(i as f64 / SAMPLE_RATE as f64);
+            sample * tone.gain * tone.envelope.gain_at(t, duration)
+        })
+        .collect()
+}
+
+/// Synthesize many tones at once, e.g. when exporting a batch of exercise clips.
+/// With the `rayon` feature the tones are rendered in parallel; the output order
+/// always matches the input order.
+pub fn render_batch(tones: &[(f32, Duration)], tone: ToneConfig) -> Vec<Vec<f32>> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        tones
+            .par_iter()
+            .map(|&(frequency, duration)| render_tone(frequency, duration, tone))
+            .collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        tones
+            .iter()
+            .map(|&(frequency, duration)| render_tone(frequency, duration, tone))
+            .collect()
+    }
+}
+
+/// Spread a mono buffer across two channels, interleaved left then right.
+/// `pan` runs from -1.0 (left only) through 0.0 (both ears, as loud as the mono
+/// buffer) to 1.0 (right only); the far side fades out while the near side stays put.
+pub fn to_stereo(samples: &[f32], pan: f32) -> Vec<f32> {
+    let pan = pan.clamp(-1.0, 1.0);
+    let left = (1.0 - pan).min(1.0);
+    let right = (1.0 + pan).min(1.0);
+    samples
+        .iter()
+        .flat_map(|&sample| [sample * left, sample * right])
+        .collect()
+}
+
+/// Mix several tones into one mono buffer so they sound at the same time.
+/// Voices are scaled by 1/sqrt(n) to keep chords from clipping.
+pub fn render_mix(frequencies: &[f32], duration: Duration, tone: ToneConfig) -> Vec<f32> {
+    let mut cache = RENDER_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let voices: Vec<Arc<Vec<f32>>> = frequencies
+        .iter()
+        .map(|&frequency| cache.get_or_render(frequency, duration, tone))
+        .collect();
+    drop(cache);
+
+    let len = voices.iter().map(|v| v.len()).max().unwrap_or(0);
+    let gain = 1.0 / (voices.len().max(1) as f32).sqrt();
+    (0..len)
+        .map(|i| {
+            let sum: f32 = voices.iter().filter_map(|v| v.get(i)).sum();
+            (sum * gain).clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+/// Get a tone from the shared render cache
+#[cfg(feature = "playback")]
+fn cached_tone(frequency: f32, duration: Duration, tone: ToneConfig) -> Arc<Vec<f32>> {
+    RENDER_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_render(frequency, duration, tone)
+}
+
+/// Where rendered tones are sent: the sound card natively, or Web Audio in the browser
+#[cfg(feature = "playback")]
+pub trait PlaybackBackend {
+    /// Play a buffer at `SAMPLE_RATE`, with `channels` samples per frame interleaved.
+    /// Blocking backends return once it has finished; scheduling ones queue it after
+    /// whatever they were given before.
+    fn play_samples(
+        &self,
+        samples: Vec<f32>,
+        channels: u16,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Leave `duration` of silence before the next sound
+    fn rest(&self, duration: Duration) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Start sustaining `frequencies` until the returned `Drone` is stopped
+    fn start_drone(
+        &self,
+        frequencies: &[f32],
+        tone: ToneConfig,
+    ) -> Result<Drone, Box<dyn std::error::Error>>;
+
+    /// Repeat a buffer in the background, alongside anything else played, until the
+    /// returned `Drone` is stopped
+    fn start_loop(
+        &self,
+        samples: Vec<f32>,
+        channels: u16,
+    ) -> Result<Drone, Box<dyn std::error::Error>>;
+
+    /// Start a single held note straight away, returning at once. Stopping the returned
+    /// `Drone` lets it go, to fade out over the envelope's release. Backends without
+    /// their own voices sustain it as a drone.
+    fn start_voice(
+        &self,
+        frequency: f32,
+        tone: ToneConfig,
+    ) -> Result<Drone, Box<dyn std::error::Error>> {
+        self.start_drone(&[frequency], tone)
+    }
+}
+
+/// Voices sustained in the background until stopped or dropped
+#[cfg(feature = "playback")]
+pub struct Drone {
+    stop: Option<Box<dyn FnOnce()>>,
+}
+
+#[cfg(feature = "playback")]
+impl Drone {
+    /// A drone that runs `stop` to silence its voices
+    pub fn new(stop: impl FnOnce() + 'static) -> Self {
+        Self {
+            stop: Some(Box::new(stop)),
+        }
+    }
+
+    /// Stop all drone voices (same as dropping the drone)
+    pub fn stop(self) {}
+}
+
+#[cfg(feature = "playback")]
+impl Drop for Drone {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop();
+        }
+    }
+}
+
+/// The default output device through rodio, waiting for each sound to finish
+#[cfg(feature = "audio")]
+pub struct NativeBackend {
+    // The stream must stay alive for the handle to keep working
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+}
+
+#[cfg(feature = "audio")]
+impl NativeBackend {
+    /// Open the default output device
+    pub fn open() -> Result<Self, Box<dyn std::error::Error>> {
+        let (stream, handle) = OutputStream::try_default()?;
+        Ok(Self {
+            _stream: stream,
+            handle,
+        })
+    }
+}
+
+#[cfg(feature = "audio")]
+impl PlaybackBackend for NativeBackend {
+    fn play_samples(
+        &self,
+        samples: Vec<f32>,
+        channels: u16,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let sink = Sink::try_new(&self.handle)?;
+        sink.append(SamplesBuffer::new(channels, SAMPLE_RATE, samples));
+        sink.sleep_until_end();
+        Ok(())
+    }
+
+    fn rest(&self, duration: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        std::thread::sleep(duration);
+        Ok(())
+    }
+
+    fn start_drone(
+        &self,
+        frequencies: &[f32],
+        tone: ToneConfig,
+    ) -> Result<Drone, Box<dyn std::error::Error>> {
+        let mut sinks = Vec::new();
+        for &frequency in frequencies {
+            let sink = Sink::try_new(&self.handle)?;
+            sink.set_volume(0.5);
+            // Drones have no fixed length, so only the attack applies
+            sink.append(
+                Oscillator::new(frequency, tone.waveform)
+                    .amplify(tone.gain)
+                    .fade_in(tone.envelope.attack),
+            );
+            sinks.push(sink);
+        }
+        Ok(Drone::new(move || {
+            for sink in &sinks {
+                sink.stop();
+            }
+        }))
+    }
+
+    fn start_loop(
+        &self,
+        samples: Vec<f32>,
+        channels: u16,
+    ) -> Result<Drone, Box<dyn std::error::Error>> {
+        let sink = Sink::try_new(&self.handle)?;
+        sink.append(SamplesBuffer::new(channels, SAMPLE_RATE, samples).repeat_infinite());
+        Ok(Drone::new(move || sink.stop()))
+    }
+
+    fn start_voice(
+        &self,
+        frequency: f32,
+        tone: ToneConfig,
+    ) -> Result<Drone, Box<dyn std::error::Error>> {
+        let sink = Sink::try_new(&self.handle)?;
+        let release = Arc::new(AtomicBool::new(false));
+        sink.append(HeldTone {
+            oscillator: Oscillator::new(frequency, tone.waveform),
+            tone,
+            sample_index: 0,
+            release: release.clone(),
+            released_at: None,
+        });
+        Ok(Drone::new(move || {
+            release.store(true, Ordering::Relaxed);
+            // Keep playing through the release; the tone ends itself
+            sink.detach();
+        }))
+    }
+}
+
+/// Owns the audio output for a whole session, so each note doesn't
+/// have to reopen the device
+#[cfg(feature = "playback")]
+pub struct AudioEngine {
+    backend: Box<dyn PlaybackBackend>,
+    tone: ToneConfig,
+    tuning: Tuning,
+    #[cfg(feature = "midi")]
+    midi: Option<crate::midi::MidiOut>,
+}
+
+#[cfg(feature = "playback")]
+impl AudioEngine {
+    /// Open the default output device, or Web Audio when built with only the `web` feature.
+    /// With neither backend built in, use `with_backend`.
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        #[cfg(feature = "audio")]
+        let backend = NativeBackend::open()?;
+        #[cfg(all(feature = "web", not(feature = "audio")))]
+        let backend = crate::web::WebAudioBackend::new()?;
+        #[cfg(not(any(feature = "audio", feature = "web")))]
+        return Err("Built without an audio backend; enable the audio or web feature".into());
+        #[cfg(any(feature = "audio", feature = "web"))]
+        Ok(Self::with_backend(backend))
+    }
+
+    /// Play everything through `backend`
+    pub fn with_backend(backend: impl PlaybackBackend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+            tone: ToneConfig::default(),
+            tuning: Tuning::default(),
+            #[cfg(feature = "midi")]
+            midi: None,
+        }
+    }
+
+    /// Send notes to a MIDI synth instead of the built-in oscillators, or `None` to go back.
+    /// The synth uses its own tuning, and bare frequencies and drones still play here.
+    #[cfg(feature = "midi")]
+    pub fn set_midi_output(&mut self, midi: Option<crate::midi::MidiOut>) {
+        self.midi = midi;
+    }
+
+    #[cfg(feature = "midi")]
+    pub fn midi_output(&self) -> Option<&crate::midi::MidiOut> {
+        self.midi.as_ref()
+    }
+
+    /// Reference pitch used to turn notes into frequencies
+    pub fn tuning(&self) -> &Tuning {
+        &self.tuning
+    }
+
+    pub fn set_tuning(&mut self, tuning: Tuning) {
+        self.tuning = tuning;
+    }
+
+    /// Waveform and envelope used for everything the engine plays
+    pub fn tone_config(&self) -> ToneConfig {
+        self.tone
+    }
+
+    pub fn set_tone_config(&mut self, tone: ToneConfig) {
+        self.tone = tone;
+    }
+
+    pub fn waveform(&self) -> Waveform {
+        self.tone.waveform
+    }
+
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.tone.waveform = waveform;
+    }
+
+    /// Master volume from 0.0 to 1.0, before each play's own `PlaybackOptions::gain`
+    pub fn gain(&self) -> f32 {
+        self.tone.gain
+    }
+
+    pub fn set_gain(&mut self, gain: f32) {
+        self.tone.gain = gain.clamp(0.0, 1.0);
+    }
+
+    /// Play a frequency for the specified duration
+    pub fn play_frequency(
+        &self,
+        frequency: f32,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Reuse the rendered tone if this prompt was played before
+        let samples = cached_tone(frequency, duration, self.tone);
+        self.play_samples(samples.as_ref().clone(), 0.0)
+    }
+
+    /// Play several frequencies simultaneously for the specified duration
+    pub fn play_frequencies(
+        &self,
+        frequencies: &[f32],
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_samples(render_mix(frequencies, duration, self.tone), 0.0)
+    }
+
+    /// Play the same notes together in each of `tunings` in turn, with a rest between,
+    /// e.g. a chord in equal temperament and then in just intonation
+    pub fn play_in_tunings(
+        &self,
+        notes: &[NoteWithOctave],
+        tunings: &[Tuning],
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for (i, tuning) in tunings.iter().enumerate() {
+            if i > 0 {
+                self.rest(duration / 2)?;
+            }
+            let frequencies: Vec<f32> = notes
+                .iter()
+                .map(|n| tuning.frequency_of(n) as f32)
+                .collect();
+            self.play_frequencies(&frequencies, duration)?;
+        }
+        Ok(())
+    }
+
+    pub fn play_note(
+        &self,
+        note: &NoteWithOctave,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_notes_with(
+            std::slice::from_ref(note),
+            duration,
+            &PlaybackOptions::default(),
+        )
+    }
+
+    /// Play several notes simultaneously, e.g. a block chord or a harmonic interval
+    pub fn play_notes(
+        &self,
+        notes: &[NoteWithOctave],
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_notes_with(notes, duration, &PlaybackOptions::default())
+    }
+
+    /// Play a note taking up `duration`, shaped by `opts`
+    pub fn play_note_with(
+        &self,
+        note: &NoteWithOctave,
+        duration: Duration,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_notes_with(std::slice::from_ref(note), duration, opts)
+    }
+
+    /// Play notes together taking up `duration`, shaped by `opts`: they sound for
+    /// the articulated part of it and are followed by the rest and the gap in silence
+    pub fn play_notes_with(
+        &self,
+        notes: &[NoteWithOctave],
+        duration: Duration,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.articulate(duration, opts, |sounding| {
+            self.sound_notes(notes, sounding, opts)
+        })
+    }
+
+    /// Play a pitch, which may be between notes, like `play_note_with`.
+    /// Always synthesized, since MIDI output can only play whole notes.
+    pub fn play_pitch_with(
+        &self,
+        pitch: &Pitch,
+        duration: Duration,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let frequency = pitch.frequency(&self.tuning) as f32;
+        self.articulate(duration, opts, |sounding| {
+            self.sound_frequencies(&[frequency], sounding, opts)
+        })
+    }
+
+    /// Sound something for the articulated part of `duration`, then stay silent for the rest
+    fn articulate(
+        &self,
+        duration: Duration,
+        opts: &PlaybackOptions,
+        sound: impl FnOnce(Duration) -> Result<(), Box<dyn std::error::Error>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let length = opts.duration.unwrap_or(duration);
+        let sounding = length.mul_f64(opts.articulation.fraction());
+        sound(sounding)?;
+
+        let silence = length - sounding + opts.gap;
+        if !silence.is_zero() {
+            self.rest(silence)?;
+        }
+        Ok(())
+    }
+
+    fn sound_notes(
+        &self,
+        notes: &[NoteWithOctave],
+        duration: Duration,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        #[cfg(feature = "midi")]
+        if let Some(midi) = &self.midi {
+            return midi.play_notes(notes, duration);
+        }
+        let frequencies: Vec<f32> = notes
+            .iter()
+            .map(|n| self.tuning.frequency_of(n) as f32)
+            .collect();
+        self.sound_frequencies(&frequencies, duration, opts)
+    }
+
+    fn sound_frequencies(
+        &self,
+        frequencies: &[f32],
+        duration: Duration,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tone = ToneConfig {
+            waveform: opts.waveform.unwrap_or(self.tone.waveform),
+            ..self.tone
+        };
+        let mut samples = match frequencies {
+            // Reuse the rendered tone if this prompt was played before
+            [frequency] => cached_tone(*frequency, duration, tone).as_ref().clone(),
+            _ => render_mix(frequencies, duration, tone),
+        };
+        if opts.gain != 1.0 {
+            for sample in &mut samples {
+                *sample *= opts.gain;
+            }
+        }
+        self.play_samples(samples, opts.pan)
+    }
+
+    /// Play notes one after another, each for `note_duration`
+    pub fn play_sequence(
+        &self,
+        notes: &[NoteWithOctave],
+        note_duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for note in notes {
+            self.play_note(note, note_duration)?;
+        }
+        Ok(())
+    }
+
+    /// Leave `duration` of silence, e.g. for a rest in a melody
+    pub fn rest(&self, duration: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        self.backend.rest(duration)
+    }
+
+    /// Start sustaining the given frequencies until the returned `Drone` is stopped
+    pub fn start_drone(&self, frequencies: &[f32]) -> Result<Drone, Box<dyn std::error::Error>> {
+        self.backend.start_drone(frequencies, self.tone)
+    }
+
+    /// Start holding `note` without waiting, e.g. while a key is down in live play.
+    /// It fades out over the release when the returned `Drone` is stopped.
+    pub fn start_note(&self, note: &NoteWithOctave) -> Result<Drone, Box<dyn std::error::Error>> {
+        let frequency = self.tuning.frequency_of(note) as f32;
+        self.backend.start_voice(frequency, self.tone)
+    }
+
+    /// Play a mono buffer rendered at `SAMPLE_RATE`, e.g. a rhythm of clicks
+    pub fn play_buffer(&self, samples: Vec<f32>) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_samples(samples, 0.0)
+    }
+
+    /// Repeat a rendered mono buffer in the background until the returned `Drone` is stopped,
+    /// e.g. a bar of metronome clicks
+    pub fn start_loop(&self, samples: Vec<f32>) -> Result<Drone, Box<dyn std::error::Error>> {
+        self.backend.start_loop(samples, 1)
+    }
+
+    /// Play a rendered mono buffer at `pan`, waiting for it to finish on blocking backends.
+    /// Centred buffers stay mono, so backends only get stereo when it changes the sound.
+    fn play_samples(&self, samples: Vec<f32>, pan: f32) -> Result<(), Box<dyn std::error::Error>> {
+        #[cfg(feature = "audio")]
+        crate::recording::tap_playback(&samples, SAMPLE_RATE);
+        if pan == 0.0 {
+            self.backend.play_samples(samples, 1)
+        } else {
+            self.backend.play_samples(to_stereo(&samples, pan), 2)
+        }
+    }
+}
+
+/// How much of each note's length it sounds for
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Articulation {
+    /// Held for its full length, running into the next note
+    #[default]
+    Legato,
+    /// Held for half its length, detached from the next note
+    Staccato,
+}
+
+impl fmt::Display for Articulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Articulation::Legato => "legato",
+            Articulation::Staccato => "staccato",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Articulation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "legato" => Ok(Articulation::Legato),
+            "staccato" => Ok(Articulation::Staccato),
+            _ => Err(format!("Invalid articulation: {}", s)),
+        }
+    }
+}
+
+impl Articulation {
+    /// Fraction of the note's length that sounds
+    pub fn fraction(&self) -> f64 {
+        match self {
+            Articulation::Legato => 1.0,
+            Articulation::Staccato => 0.5,
+        }
+    }
+}
+
+/// Settings for one call to `Playable::play` or `AudioEngine::play_notes_with`.
+/// The defaults play everything as written, in the engine's own timbre.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PlaybackOptions {
+    /// Speed of scales, chords and melodies; a single note lasts one beat
+    pub tempo: Tempo,
+    /// Length of every note, instead of the one its note value and the tempo give
+    pub duration: Option<Duration>,
+    /// Silence added after every note
+    pub gap: Duration,
+    pub articulation: Articulation,
+    /// Loudness relative to the engine's, 1.0 to leave it unchanged
+    pub gain: f32,
+    /// Timbre to use instead of the engine's
+    pub waveform: Option<Waveform>,
+    /// Stereo position from -1.0 (left) to 1.0 (right), 0.0 for the centre
+    pub pan: f32,
+    /// Swing for melodies' eighth notes instead of their own, e.g. 2.0 for a shuffle
+    pub swing: Option<f64>,
+}
+
+impl Default for PlaybackOptions {
+    fn default() -> Self {
+        Self {
+            tempo: Tempo::default(),
+            duration: None,
+            gap: Duration::ZERO,
+            articulation: Articulation::default(),
+            gain: 1.0,
+            waveform: None,
+            pan: 0.0,
+            swing: None,
+        }
+    }
+}
+
+impl PlaybackOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tempo(mut self, tempo: Tempo) -> Self {
+        self.tempo = tempo;
+        self
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub fn with_gap(mut self, gap: Duration) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn with_articulation(mut self, articulation: Articulation) -> Self {
+        self.articulation = articulation;
+        self
+    }
+
+    /// Scale the loudness, clamped to 0.0-1.0
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_waveform(mut self, waveform: Waveform) -> Self {
+        self.waveform = Some(waveform);
+        self
+    }
+
+    /// Place the sound between the ears, clamped to -1.0 (left) to 1.0 (right)
+    pub fn with_pan(mut self, pan: f32) -> Self {
+        self.pan = pan.clamp(-1.0, 1.0);
+        self
+    }
+
+    /// Swing melodies by `ratio`, clamped to 1.0 (straight) to `MAX_SWING`
+    pub fn with_swing(mut self, ratio: f64) -> Self {
+        self.swing = Some(ratio.clamp(1.0, MAX_SWING));
+        self
+    }
+}
+
+/// Anything that can be heard: notes, scales, chords, progressions and melodies.
+/// Lets callers play whatever they were given without matching on its type.
+#[cfg(feature = "playback")]
+pub trait Playable {
+    fn play(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Play a frequency for the specified duration on a one-off engine
+#[cfg(feature = "playback")]
+pub fn play_frequency(
+    frequency: f32,
+    duration: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    AudioEngine::new()?.play_frequency(frequency, duration)
+}
+
+/// Play several frequencies simultaneously on a one-off engine
+#[cfg(feature = "playback")]
+pub fn play_frequencies(
+    frequencies: &[f32],
+    duration: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    AudioEngine::new()?.play_frequencies(frequencies, duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_tone_length() {
+        let samples = render_tone(440.0, Duration::from_millis(500), ToneConfig::default());
+        assert_eq!(samples.len(), SAMPLE_RATE as usize / 2);
+    }
+
+    #[test]
+    fn test_render_cache_reuses_buffers() {
+        let mut cache = RenderCache::new(4);
+        let first = cache.get_or_render(440.0, Duration::from_millis(100), ToneConfig::default());
+        let second = cache.get_or_render(440.0, Duration::from_millis(100), ToneConfig::default());
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.len(), 1);
+
+        // Different duration is a different prompt
+        let longer = cache.get_or_render(440.0, Duration::from_millis(200), ToneConfig::default());
+        assert!(!Arc::ptr_eq(&first, &longer));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_render_cache_evicts_least_recently_used() {
+        let mut cache = RenderCache::new(2);
+        let duration = Duration::from_millis(10);
+        let a = cache.get_or_render(440.0, duration, ToneConfig::default());
+        cache.get_or_render(261.63, duration, ToneConfig::default());
+
+        // Touch A4 so C4 becomes the eviction candidate
+        cache.get_or_render(440.0, duration, ToneConfig::default());
+        cache.get_or_render(329.63, duration, ToneConfig::default());
+
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(
+            &a,
+            &cache.get_or_render(440.0, duration, ToneConfig::default())
+        ));
+    }
+
+    #[test]
+    fn test_render_batch_preserves_order() {
+        let tones: Vec<(f32, Duration)> = (1..=8)
+            .map(|i| (110.0 * i as f32, Duration::from_millis(10 * i)))
+            .collect();
+        let rendered = render_batch(&tones, ToneConfig::default());
+
+        assert_eq!(rendered.len(), tones.len());
+        for (samples, &(frequency, duration)) in rendered.iter().zip(&tones) {
+            assert_eq!(
+                *samples,
+                render_tone(frequency, duration, ToneConfig::default())
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_mix_sums_voices() {
+        let duration = Duration::from_millis(50);
+        let a4 = render_tone(440.0, duration, ToneConfig::default());
+        let e5 = render_tone(659.25, duration, ToneConfig::default());
+        let mixed = render_mix(&[440.0, 659.25], duration, ToneConfig::default());
+
+        assert_eq!(mixed.len(), a4.len());
+        let gain = 1.0 / 2.0_f32.sqrt();
+        for i in [0, 10, 100, 1000] {
+            assert!((mixed[i] - (a4[i] + e5[i]) * gain).abs() < 1e-6);
+        }
+        assert!(mixed.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_render_mix_single_voice_is_unchanged() {
+        let duration = Duration::from_millis(20);
+        assert_eq!(
+            render_mix(&[440.0], duration, ToneConfig::default()),
+            render_tone(440.0, duration, ToneConfig::default())
+        );
+        assert!(render_mix(&[], duration, ToneConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_waveform_parsing() {
+        assert_eq!("square".parse::<Waveform>().unwrap(), Waveform::Square);
+        assert_eq!("saw".parse::<Waveform>().unwrap(), Waveform::Sawtooth);
+        assert!("noise".parse::<Waveform>().is_err());
+        assert_eq!(Waveform::default(), Waveform::Sine);
+    }
+
+    #[test]
+    fn test_waveform_shapes() {
+        assert!(Waveform::Sine.sample_at(0.0).abs() < 1e-6);
+        assert!((Waveform::Sine.sample_at(0.25) - 1.0).abs() < 1e-6);
+        assert_eq!(Waveform::Square.sample_at(0.25), 1.0);
+        assert_eq!(Waveform::Square.sample_at(0.75), -1.0);
+        assert_eq!(Waveform::Triangle.sample_at(0.0), -1.0);
+        assert_eq!(Waveform::Triangle.sample_at(0.5), 1.0);
+        assert_eq!(Waveform::Sawtooth.sample_at(0.0), -1.0);
+        assert_eq!(Waveform::Sawtooth.sample_at(0.75), 0.5);
+    }
+
+    #[test]
+    fn test_render_cache_keys_on_waveform() {
+        let mut cache = RenderCache::new(4);
+        let duration = Duration::from_millis(10);
+        let sine = cache.get_or_render(440.0, duration, ToneConfig::default());
+        let square = ToneConfig {
+            waveform: Waveform::Square,
+            ..ToneConfig::default()
+        };
+        let square = cache.get_or_render(440.0, duration, square);
+        assert!(!Arc::ptr_eq(&sine, &square));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_envelope_stages() {
+        let envelope = Envelope {
+            attack: Duration::from_millis(100),
+            decay: Duration::from_millis(100),
+            sustain: 0.5,
+            release: Duration::from_millis(100),
+        };
+        let total = Duration::from_secs(1);
+        let at = |ms| envelope.gain_at(Duration::from_millis(ms), total);
+
+        assert_eq!(at(0), 0.0);
+        assert!((at(50) - 0.5).abs() < 1e-4);
+        assert!((at(100) - 1.0).abs() < 1e-4);
+        assert!((at(150) - 0.75).abs() < 1e-4);
+        assert!((at(500) - 0.5).abs() < 1e-4);
+        assert!((at(950) - 0.25).abs() < 1e-4);
+        assert!(at(1000).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_envelope_shrinks_for_short_notes() {
+        let envelope = Envelope::default();
+        let total = Duration::from_millis(20);
+        // Attack + decay + release are 110ms, so each stage is scaled down to fit
+        assert!(envelope.gain_at(Duration::ZERO, total) < 0.01);
+        assert!(envelope.gain_at(total, total) < 0.01);
+        assert!(envelope.gain_at(Duration::from_millis(10), total) > 0.0);
+    }
+
+    #[test]
+    fn test_held_envelope() {
+        let envelope = Envelope {
+            attack: Duration::from_millis(100),
+            decay: Duration::from_millis(100),
+            sustain: 0.5,
+            release: Duration::from_millis(100),
+        };
+        let at = |ms, released: Option<u64>| {
+            envelope.held_gain_at(
+                Duration::from_millis(ms),
+                released.map(Duration::from_millis),
+            )
+        };
+
+        // Sustains for as long as the note is held
+        assert!((at(50, None).unwrap() - 0.5).abs() < 1e-4);
+        assert!((at(60_000, None).unwrap() - 0.5).abs() < 1e-4);
+        assert!((at(1000, Some(2000)).unwrap() - 0.5).abs() < 1e-4);
+        // Fades out from the level it was let go at
+        assert!((at(1050, Some(1000)).unwrap() - 0.25).abs() < 1e-4);
+        assert!((at(75, Some(50)).unwrap() - 0.375).abs() < 1e-4);
+        assert_eq!(at(1100, Some(1000)), None);
+        assert_eq!(
+            Envelope::FLAT.held_gain_at(Duration::ZERO, Some(Duration::ZERO)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rendered_tones_fade_in_and_out() {
+        let duration = Duration::from_millis(200);
+        let samples = render_tone(440.0, duration, ToneConfig::default());
+        assert!(samples.first().unwrap().abs() < 1e-6);
+        assert!(samples.last().unwrap().abs() < 0.01);
+
+        let flat = ToneConfig {
+            waveform: Waveform::Square,
+            envelope: Envelope::FLAT,
+            ..ToneConfig::default()
+        };
+        let samples = render_tone(440.0, duration, flat);
+        assert!(
+            samples
+                .iter()
+                .all(|s| (s.abs() - DEFAULT_GAIN).abs() < 1e-6)
+        );
+
+        let loud = render_tone(440.0, duration, ToneConfig { gain: 0.9, ..flat });
+        assert!(loud.iter().all(|s| (s.abs() - 0.9).abs() < 1e-6));
+    }
+
+    /// Notes what it is asked to play instead of making a sound
+    #[cfg(feature = "playback")]
+    #[derive(Default, Clone)]
+    struct LoggingBackend {
+        log: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+        last: std::rc::Rc<std::cell::RefCell<Vec<f32>>>,
+    }
+
+    #[cfg(feature = "playback")]
+    impl PlaybackBackend for LoggingBackend {
+        fn play_samples(
+            &self,
+            samples: Vec<f32>,
+            channels: u16,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let entry = match channels {
+                1 => format!("{} samples", samples.len()),
+                _ => format!("{} samples in {} channels", samples.len(), channels),
+            };
+            self.log.borrow_mut().push(entry);
+            *self.last.borrow_mut() = samples;
+            Ok(())
+        }
+
+        fn rest(&self, duration: Duration) -> Result<(), Box<dyn std::error::Error>> {
+            self.log.borrow_mut().push(format!("rest {:?}", duration));
+            Ok(())
+        }
+
+        fn start_drone(
+            &self,
+            frequencies: &[f32],
+            _tone: ToneConfig,
+        ) -> Result<Drone, Box<dyn std::error::Error>> {
+            self.log
+                .borrow_mut()
+                .push(format!("drone {:?}", frequencies));
+            let log = self.log.clone();
+            Ok(Drone::new(move || {
+                log.borrow_mut().push("stop".to_string())
+            }))
+        }
+
+        fn start_loop(
+            &self,
+            samples: Vec<f32>,
+            _channels: u16,
+        ) -> Result<Drone, Box<dyn std::error::Error>> {
+            self.log
+                .borrow_mut()
+                .push(format!("loop {} samples", samples.len()));
+            let log = self.log.clone();
+            Ok(Drone::new(move || {
+                log.borrow_mut().push("stop".to_string())
+            }))
+        }
+    }
+
+    #[cfg(feature = "playback")]
+    #[test]
+    fn test_metronome_loops_in_background() {
+        let backend = LoggingBackend::default();
+        let engine = AudioEngine::with_backend(backend.clone());
+        let metronome = crate::metronome::Metronome::new(Tempo(120), Default::default());
+        let clicks = metronome.start(&engine).unwrap();
+        engine
+            .play_frequencies(&[440.0], Duration::from_millis(100))
+            .unwrap();
+        clicks.stop();
+        assert_eq!(
+            *backend.log.borrow(),
+            ["loop 88200 samples", "4410 samples", "stop"]
+        );
+    }
+
+    #[cfg(feature = "playback")]
+    #[test]
+    fn test_engine_plays_through_backend() {
+        let backend = LoggingBackend::default();
+        let engine = AudioEngine::with_backend(backend.clone());
+        let melody: crate::melody::Melody = "A4:q r:q A4:e".parse().unwrap();
+        melody.play_on(&engine, crate::tempo::Tempo(120)).unwrap();
+        engine.start_drone(&[110.0]).unwrap().stop();
+        engine.start_note(&"A4".parse().unwrap()).unwrap().stop();
+
+        assert_eq!(
+            *backend.log.borrow(),
+            vec![
+                "22050 samples",
+                "rest 500ms",
+                "11025 samples",
+                "drone [110.0]",
+                "stop",
+                "drone [440.0]",
+                "stop"
+            ]
+        );
+    }
+
+    #[cfg(feature = "playback")]
+    #[test]
+    fn test_playables_share_one_entry_point() {
+        use crate::chords::{Chord, ChordQuality};
+        let backend = LoggingBackend::default();
+        let engine = AudioEngine::with_backend(backend.clone());
+        let c4: NoteWithOctave = "C4".parse().unwrap();
+        let items: Vec<Box<dyn Playable>> = vec![
+            Box::new(c4),
+            Box::new(Chord::new(c4, ChordQuality::Minor)),
+            Box::new("C4:h r:e".parse::<crate::melody::Melody>().unwrap()),
+        ];
+        let opts = PlaybackOptions::new().with_tempo(Tempo(60));
+        for item in &items {
+            item.play(&engine, &opts).unwrap();
+        }
+
+        // One beat for the note, the arpeggio then the block chord, then the melody
+        let log = backend.log.borrow();
+        assert_eq!(log[0], "44100 samples");
+        assert_eq!(log[1..4], ["44100 samples"; 3]);
+        assert_eq!(log[4], "132300 samples");
+        assert_eq!(log[5..], ["88200 samples", "rest 500ms"]);
+    }
+
+    #[cfg(feature = "playback")]
+    #[test]
+    fn test_playback_options() {
+        let backend = LoggingBackend::default();
+        let mut engine = AudioEngine::with_backend(backend.clone());
+        engine.set_tone_config(ToneConfig {
+            waveform: Waveform::Square,
+            envelope: Envelope::FLAT,
+            ..ToneConfig::default()
+        });
+        let a4: NoteWithOctave = "A4".parse().unwrap();
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+        engine.play_note(&a4, Duration::from_millis(100)).unwrap();
+        let full = peak(&backend.last.borrow());
+        assert!((full - DEFAULT_GAIN).abs() < 1e-6);
+
+        // Staccato sounds half the beat, and the other half joins the gap in silence
+        let opts = PlaybackOptions::new()
+            .with_tempo(Tempo(60))
+            .with_articulation(Articulation::Staccato)
+            .with_gap(Duration::from_millis(100))
+            .with_gain(0.5);
+        Playable::play(&a4, &engine, &opts).unwrap();
+        assert_eq!(backend.log.borrow()[1..], ["22050 samples", "rest 600ms"]);
+        assert!((peak(&backend.last.borrow()) - full / 2.0).abs() < 1e-6);
+
+        // A fixed duration replaces the note values
+        let melody: crate::melody::Melody = "A4:w A4:s".parse().unwrap();
+        let opts = PlaybackOptions::new().with_duration(Duration::from_millis(250));
+        Playable::play(&melody, &engine, &opts).unwrap();
+        assert_eq!(backend.log.borrow()[3..], ["11025 samples"; 2]);
+
+        assert_eq!(
+            "Staccato".parse::<Articulation>().unwrap(),
+            Articulation::Staccato
+        );
+        // Swing stretches the first eighth of the beat and shortens the second
+        let melody: crate::melody::Melody = "A4:e A4:e A4:q".parse().unwrap();
+        let opts = PlaybackOptions::new().with_tempo(Tempo(60)).with_swing(3.0);
+        Playable::play(&melody, &engine, &opts).unwrap();
+        assert_eq!(
+            backend.log.borrow()[5..],
+            ["33075 samples", "11025 samples", "44100 samples"]
+        );
+
+        assert_eq!(PlaybackOptions::new().with_gain(3.0).gain, 1.0);
+        engine.set_gain(1.5);
+        assert_eq!(engine.gain(), 1.0);
+    }
+
+    #[test]
+    fn test_to_stereo() {
+        let mono = [0.5, -0.25];
+        assert_eq!(to_stereo(&mono, 0.0), vec![0.5, 0.5, -0.25, -0.25]);
+        assert_eq!(to_stereo(&mono, -1.0), vec![0.5, 0.0, -0.25, 0.0]);
+        assert_eq!(to_stereo(&mono, 1.0), vec![0.0, 0.5, 0.0, -0.25]);
+        assert_eq!(to_stereo(&mono, 0.5), vec![0.25, 0.5, -0.125, -0.25]);
+        assert_eq!(to_stereo(&mono, 4.0), to_stereo(&mono, 1.0));
+    }
+
+    #[cfg(feature = "playback")]
+    #[test]
+    fn test_panned_notes_play_in_stereo() {
+        let backend = LoggingBackend::default();
+        let engine = AudioEngine::with_backend(backend.clone());
+        let a4: NoteWithOctave = "A4".parse().unwrap();
+        let opts = PlaybackOptions::new()
+            .with_duration(Duration::from_millis(100))
+            .with_pan(-1.0);
+        Playable::play(&a4, &engine, &opts).unwrap();
+        assert_eq!(*backend.log.borrow(), ["8820 samples in 2 channels"]);
+
+        let last = backend.last.borrow();
+        assert!(last.iter().step_by(2).any(|s| *s != 0.0));
+        assert!(last.iter().skip(1).step_by(2).all(|s| *s == 0.0));
+        assert_eq!(PlaybackOptions::new().with_pan(-2.0).pan, -1.0);
+    }
+}
@@ -0,0 +1,1190 @@
+#[cfg(feature = "playback")]
+use crate::audio::{AudioEngine, Playable, PlaybackOptions};
+#[cfg(feature = "playback")]
+use crate::melody::Melody;
+use crate::notes::{Note, NoteWithOctave};
+use crate::spelling::{NoteName, SpelledNote, SpelledPitch, transposition_fifths};
+#[cfg(feature = "playback")]
+use crate::tempo::{NoteValue, Tempo};
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "playback")]
+use std::time::Duration;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ChordQuality {
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Dominant7,
+    Major7,
+    Minor7,
+    Diminished7,
+    /// Minor seventh chord with a flat fifth, m7b5
+    HalfDiminished7,
+    /// Minor triad with a major seventh
+    MinorMajor7,
+}
+
+/// A change to a chord's basic tones, written after the quality in a chord symbol
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Extension {
+    /// The second replaces the third
+    Sus2,
+    /// The fourth replaces the third
+    Sus4,
+    FlatFive,
+    SharpFive,
+    /// A tone added above the chord: a 6th, 9th, 11th or 13th, raised or lowered by
+    /// `alteration` semitones as in b9, #9, #11 and b13
+    Add {
+        degree: u8,
+        alteration: i8,
+    },
+}
+
+/// Which chord tone is in the bass
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Inversion {
+    #[default]
+    Root,
+    First,
+    Second,
+    /// Only seventh chords have a third inversion, with the seventh in the bass
+    Third,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chord {
+    pub root: NoteWithOctave,
+    pub quality: ChordQuality,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inversion: Inversion,
+    /// Sus, altered fifths and added tones, kept sorted
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extensions: Vec<Extension>,
+    /// A slash bass that isn't a chord tone, sounded below the chord, e.g. the D of C/D
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bass: Option<SpelledNote>,
+    /// How the root is written, which decides the letter names of the other tones
+    root_spelling: SpelledNote,
+}
+
+impl fmt::Display for ChordQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quality_name = match self {
+            ChordQuality::Major => "Major",
+            ChordQuality::Minor => "Minor",
+            ChordQuality::Diminished => "Diminished",
+            ChordQuality::Augmented => "Augmented",
+            ChordQuality::Dominant7 => "Dominant 7th",
+            ChordQuality::Major7 => "Major 7th",
+            ChordQuality::Minor7 => "Minor 7th",
+            ChordQuality::Diminished7 => "Diminished 7th",
+            ChordQuality::HalfDiminished7 => "Half-Diminished 7th",
+            ChordQuality::MinorMajor7 => "Minor-Major 7th",
+        };
+        write!(f, "{}", quality_name)
+    }
+}
+
+impl fmt::Display for Extension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Extension::Sus2 => write!(f, "sus2"),
+            Extension::Sus4 => write!(f, "sus4"),
+            Extension::FlatFive => write!(f, "b5"),
+            Extension::SharpFive => write!(f, "#5"),
+            Extension::Add {
+                degree,
+                alteration: ..=-1,
+            } => write!(f, "b{}", degree),
+            Extension::Add {
+                degree,
+                alteration: 1..,
+            } => write!(f, "#{}", degree),
+            Extension::Add { degree, .. } => write!(f, "add{}", degree),
+        }
+    }
+}
+
+impl Extension {
+    const fn add(degree: u8, alteration: i8) -> Extension {
+        Extension::Add { degree, alteration }
+    }
+}
+
+/// Chord symbol suffixes and what they stand for, e.g. "m9" is a minor 7th with a 9th.
+/// Where several spell the same chord, `Display` uses the first.
+const QUALITY_SYMBOLS: [(&str, ChordQuality, &[Extension]); 25] = [
+    ("", ChordQuality::Major, &[]),
+    ("maj", ChordQuality::Major, &[]),
+    ("M", ChordQuality::Major, &[]),
+    ("6", ChordQuality::Major, &[Extension::add(6, 0)]),
+    (
+        "69",
+        ChordQuality::Major,
+        &[Extension::add(6, 0), Extension::add(9, 0)],
+    ),
+    ("m", ChordQuality::Minor, &[]),
+    ("m6", ChordQuality::Minor, &[Extension::add(6, 0)]),
+    (
+        "m69",
+        ChordQuality::Minor,
+        &[Extension::add(6, 0), Extension::add(9, 0)],
+    ),
+    ("dim", ChordQuality::Diminished, &[]),
+    ("aug", ChordQuality::Augmented, &[]),
+    ("7", ChordQuality::Dominant7, &[]),
+    ("aug7", ChordQuality::Dominant7, &[Extension::SharpFive]),
+    ("9", ChordQuality::Dominant7, &[Extension::add(9, 0)]),
+    (
+        "11",
+        ChordQuality::Dominant7,
+        &[Extension::add(9, 0), Extension::add(11, 0)],
+    ),
+    (
+        "13",
+        ChordQuality::Dominant7,
+        &[Extension::add(9, 0), Extension::add(13, 0)],
+    ),
+    ("maj7", ChordQuality::Major7, &[]),
+    ("maj9", ChordQuality::Major7, &[Extension::add(9, 0)]),
+    (
+        "maj13",
+        ChordQuality::Major7,
+        &[Extension::add(9, 0), Extension::add(13, 0)],
+    ),
+    ("m7", ChordQuality::Minor7, &[]),
+    ("m9", ChordQuality::Minor7, &[Extension::add(9, 0)]),
+    (
+        "m11",
+        ChordQuality::Minor7,
+        &[Extension::add(9, 0), Extension::add(11, 0)],
+    ),
+    ("dim7", ChordQuality::Diminished7, &[]),
+    ("m7b5", ChordQuality::HalfDiminished7, &[]),
+    ("mMaj7", ChordQuality::MinorMajor7, &[]),
+    ("mMaj9", ChordQuality::MinorMajor7, &[Extension::add(9, 0)]),
+];
+
+/// Suffixes with the same meaning as one in `QUALITY_SYMBOLS`, checked in order
+const SYMBOL_ALIASES: [(&str, &str); 20] = [
+    ("minMaj", "mMaj"),
+    ("minmaj", "mMaj"),
+    ("mmaj", "mMaj"),
+    ("mM7", "mMaj7"),
+    ("-maj", "mMaj"),
+    ("min", "m"),
+    ("-", "m"),
+    ("M7", "maj7"),
+    ("M9", "maj9"),
+    ("M13", "maj13"),
+    ("Δ7", "maj7"),
+    ("Δ", "maj7"),
+    ("ø7", "m7b5"),
+    ("ø", "m7b5"),
+    ("°7", "dim7"),
+    ("o7", "dim7"),
+    ("°", "dim"),
+    ("o", "dim"),
+    ("+7", "aug7"),
+    ("+", "aug"),
+];
+
+/// What may follow the quality in a chord symbol, checked in order
+const MODIFIERS: [(&str, Extension); 20] = [
+    ("sus2", Extension::Sus2),
+    ("sus4", Extension::Sus4),
+    ("sus", Extension::Sus4),
+    ("add13", Extension::add(13, 0)),
+    ("add11", Extension::add(11, 0)),
+    ("add9", Extension::add(9, 0)),
+    ("add6", Extension::add(6, 0)),
+    ("add4", Extension::add(11, 0)),
+    ("add2", Extension::add(9, 0)),
+    ("b5", Extension::FlatFive),
+    ("-5", Extension::FlatFive),
+    ("#5", Extension::SharpFive),
+    ("+5", Extension::SharpFive),
+    ("b9", Extension::add(9, -1)),
+    ("-9", Extension::add(9, -1)),
+    ("#9", Extension::add(9, 1)),
+    ("+9", Extension::add(9, 1)),
+    ("#11", Extension::add(11, 1)),
+    ("+11", Extension::add(11, 1)),
+    ("b13", Extension::add(13, -1)),
+];
+
+impl fmt::Display for Inversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Inversion::Root => "root position",
+            Inversion::First => "first inversion",
+            Inversion::Second => "second inversion",
+            Inversion::Third => "third inversion",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Inversion {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "root" | "root position" | "r" | "0" => Ok(Inversion::Root),
+            "first" | "first inversion" | "1st" | "1" => Ok(Inversion::First),
+            "second" | "second inversion" | "2nd" | "2" => Ok(Inversion::Second),
+            "third" | "third inversion" | "3rd" | "3" => Ok(Inversion::Third),
+            _ => Err(format!("Invalid inversion: {}", s)),
+        }
+    }
+}
+
+impl Inversion {
+    pub const ALL: [Inversion; 4] = [
+        Inversion::Root,
+        Inversion::First,
+        Inversion::Second,
+        Inversion::Third,
+    ];
+
+    /// The inversion with the `n`th chord tone in the bass, counting the root as 0
+    pub fn from_index(n: usize) -> Option<Inversion> {
+        Inversion::ALL.get(n).copied()
+    }
+
+    /// Which chord tone is in the bass, counting the root as 0
+    pub fn index(&self) -> usize {
+        Inversion::ALL.iter().position(|i| i == self).unwrap_or(0)
+    }
+}
+
+/// A chord that fits a set of played notes, from `Chord::identify`
+#[derive(Debug, PartialEq, Clone)]
+pub struct ChordMatch {
+    /// The chord, inverted so its bass is the lowest note played when that's a chord tone
+    pub chord: Chord,
+    /// Chord tones that weren't played, like an omitted fifth
+    pub missing: Vec<Note>,
+    /// Notes played that aren't chord tones
+    pub extra: Vec<Note>,
+}
+
+impl ChordMatch {
+    /// Whether the notes were exactly the chord's tones
+    pub fn is_exact(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+impl fmt::Display for ChordMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = |notes: &[Note]| -> String {
+            let names: Vec<String> = notes.iter().map(|n| n.to_string()).collect();
+            names.join(" ")
+        };
+        write!(f, "{}", self.chord)?;
+        match (self.missing.is_empty(), self.extra.is_empty()) {
+            (true, true) => Ok(()),
+            (false, true) => write!(f, " (no {})", names(&self.missing)),
+            (true, false) => write!(f, " (with {})", names(&self.extra)),
+            (false, false) => write!(
+                f,
+                " (no {}, with {})",
+                names(&self.missing),
+                names(&self.extra)
+            ),
+        }
+    }
+}
+
+/// The chord symbol, e.g. "Bbm7", "G7b9" or "C/E". Symbols don't say which octave the
+/// root is in; see `Chord::name` for that.
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.root_spelling, self.suffix())?;
+        if let Some(bass) = self.slash_bass() {
+            write!(f, "/{}", bass)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse chord symbols like "C", "F#m7b5", "Bbsus4", "Ebmaj9", "G7(b9,#11)" or "G/B",
+/// with the root in octave 4. A slash bass that is a chord tone inverts the chord;
+/// any other note is added below it.
+impl FromStr for Chord {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid chord symbol: {}", s);
+        let symbol = s.trim().replace("6/9", "69");
+        let (symbol, bass) = match symbol.split_once('/') {
+            Some((symbol, bass)) => (symbol, Some(bass.trim().parse::<SpelledNote>()?)),
+            None => (symbol.as_str(), None),
+        };
+        let (root, suffix) = split_root(symbol).ok_or_else(invalid)?;
+        let root = SpelledPitch {
+            note: root.parse()?,
+            octave: 4,
+        };
+
+        let suffix: String = suffix
+            .chars()
+            .filter(|c| !matches!(c, '(' | ')' | ',' | ' '))
+            .collect();
+        let suffix = SYMBOL_ALIASES
+            .iter()
+            .find(|(alias, _)| suffix.starts_with(alias))
+            .map_or(suffix.clone(), |(alias, symbol)| {
+                format!("{}{}", symbol, &suffix[alias.len()..])
+            });
+        let (symbol, quality, implied) = QUALITY_SYMBOLS
+            .iter()
+            .filter(|(symbol, ..)| suffix.starts_with(symbol))
+            .max_by_key(|(symbol, ..)| symbol.len())
+            .ok_or_else(invalid)?;
+
+        let mut chord = Chord::spelled(root, quality.clone())?;
+        for extension in implied.iter() {
+            chord = chord.with_extension(*extension);
+        }
+        let mut rest = &suffix[symbol.len()..];
+        while !rest.is_empty() {
+            let (modifier, extension) = MODIFIERS
+                .iter()
+                .find(|(modifier, _)| rest.starts_with(modifier))
+                .ok_or_else(invalid)?;
+            chord = chord.with_extension(*extension);
+            rest = &rest[modifier.len()..];
+        }
+
+        match bass {
+            Some(bass) => chord.over(bass),
+            None => Ok(chord),
+        }
+    }
+}
+
+/// Split a chord symbol into its written root and the rest, e.g. "Bbm7" into "Bb" and "m7".
+/// A second flat is part of the root unless a number follows, so "Dbb9" is Db with a b9.
+fn split_root(symbol: &str) -> Option<(&str, &str)> {
+    let bytes = symbol.as_bytes();
+    if !bytes.first()?.is_ascii_uppercase() {
+        return None;
+    }
+    let mut end = 1;
+    match bytes.get(end) {
+        Some(b'x') => end += 1,
+        Some(b'#') => {
+            end += 1;
+            if bytes.get(end) == Some(&b'#') {
+                end += 1;
+            }
+        }
+        Some(b'b') => {
+            end += 1;
+            if bytes.get(end) == Some(&b'b')
+                && !bytes.get(end + 1).is_some_and(|b| b.is_ascii_digit())
+            {
+                end += 1;
+            }
+        }
+        _ => {}
+    }
+    Some(symbol.split_at(end))
+}
+
+impl FromStr for ChordQuality {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "major" | "maj" => Ok(ChordQuality::Major),
+            "minor" | "min" => Ok(ChordQuality::Minor),
+            "diminished" | "dim" => Ok(ChordQuality::Diminished),
+            "augmented" | "aug" => Ok(ChordQuality::Augmented),
+            "dominant7" | "dom7" | "7" => Ok(ChordQuality::Dominant7),
+            "major7" | "maj7" => Ok(ChordQuality::Major7),
+            "minor7" | "min7" | "m7" => Ok(ChordQuality::Minor7),
+            "diminished7" | "dim7" => Ok(ChordQuality::Diminished7),
+            "half-diminished7" | "halfdim7" | "m7b5" => Ok(ChordQuality::HalfDiminished7),
+            "minor-major7" | "minmaj7" => Ok(ChordQuality::MinorMajor7),
+            _ => Err(format!("Invalid chord quality: {}", s)),
+        }
+    }
+}
+
+impl ChordQuality {
+    const ALL: [ChordQuality; 10] = [
+        ChordQuality::Major,
+        ChordQuality::Minor,
+        ChordQuality::Diminished,
+        ChordQuality::Augmented,
+        ChordQuality::Dominant7,
+        ChordQuality::Major7,
+        ChordQuality::Minor7,
+        ChordQuality::Diminished7,
+        ChordQuality::HalfDiminished7,
+        ChordQuality::MinorMajor7,
+    ];
+
+    /// The quality whose chord tones are exactly `intervals` semitones above the root
+    pub fn from_intervals(intervals: &[u8]) -> Option<ChordQuality> {
+        ChordQuality::ALL
+            .into_iter()
+            .find(|quality| quality.intervals() == intervals)
+    }
+
+    /// The name `from_str` reads back, e.g. "dom7"
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            ChordQuality::Major => "major",
+            ChordQuality::Minor => "minor",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::Dominant7 => "dom7",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::Diminished7 => "dim7",
+            ChordQuality::HalfDiminished7 => "m7b5",
+            ChordQuality::MinorMajor7 => "minmaj7",
+        }
+    }
+
+    /// Semitones above the root for each chord tone
+    pub fn intervals(&self) -> Vec<u8> {
+        match self {
+            ChordQuality::Major => vec![0, 4, 7],
+            ChordQuality::Minor => vec![0, 3, 7],
+            ChordQuality::Diminished => vec![0, 3, 6],
+            ChordQuality::Augmented => vec![0, 4, 8],
+            ChordQuality::Dominant7 => vec![0, 4, 7, 10],
+            ChordQuality::Major7 => vec![0, 4, 7, 11],
+            ChordQuality::Minor7 => vec![0, 3, 7, 10],
+            ChordQuality::Diminished7 => vec![0, 3, 6, 9],
+            ChordQuality::HalfDiminished7 => vec![0, 3, 6, 10],
+            ChordQuality::MinorMajor7 => vec![0, 3, 7, 11],
+        }
+    }
+}
+
+impl Chord {
+    /// Chord on `root`, spelled with sharps for a black-key root
+    pub fn new(root: NoteWithOctave, quality: ChordQuality) -> Self {
+        Self {
+            root_spelling: SpelledNote::from_note(&root.note),
+            root,
+            quality,
+            inversion: Inversion::Root,
+            extensions: Vec::new(),
+            bass: None,
+        }
+    }
+
+    /// Chord on a written root, so Db major is Db F Ab rather than C# F G#
+    pub fn spelled(root: SpelledPitch, quality: ChordQuality) -> Result<Self, String> {
+        let pitch = root
+            .pitch()
+            .ok_or_else(|| format!("{} is out of range", root))?;
+        Ok(Self {
+            root: pitch,
+            quality,
+            inversion: Inversion::Root,
+            extensions: Vec::new(),
+            bass: None,
+            root_spelling: root.note,
+        })
+    }
+
+    /// The same chord with `extension` added, e.g. a b9 or a sus4
+    pub fn with_extension(mut self, extension: Extension) -> Self {
+        if let Err(i) = self.extensions.binary_search(&extension) {
+            self.extensions.insert(i, extension);
+        }
+        self
+    }
+
+    /// The chord over `bass`: inverted when it's a chord tone, with it added below otherwise
+    pub fn over(&self, bass: SpelledNote) -> Result<Chord, String> {
+        let tone = self.tones().iter().position(|&(_, semitones)| {
+            self.root
+                .checked_add(semitones)
+                .is_some_and(|note| note.note == bass.pitch())
+        });
+        match tone {
+            Some(n) if Inversion::from_index(n).is_some() => self.invert(n),
+            _ => Ok(Chord {
+                bass: Some(bass),
+                ..self.clone()
+            }),
+        }
+    }
+
+    /// The long name with the root's octave, e.g. "Bb3 Minor 7th" or "C4 Major, first inversion"
+    pub fn name(&self) -> String {
+        let mut name = format!("{} {}", self.spelled_root(), self.quality);
+        for extension in &self.extensions {
+            name.push_str(&format!(" {}", extension));
+        }
+        if let Some(bass) = self.bass {
+            name.push_str(&format!(" over {}", bass));
+        } else if self.inversion != Inversion::Root {
+            name.push_str(&format!(", {}", self.inversion));
+        }
+        name
+    }
+
+    /// The root as written
+    pub fn spelled_root(&self) -> SpelledPitch {
+        SpelledPitch::spell(&self.root, self.root_spelling.name)
+            .unwrap_or_else(|| SpelledPitch::from_pitch(&self.root))
+    }
+
+    /// Chord tones with their theoretically correct names, from the bass up
+    pub fn spelled_notes(&self) -> Vec<SpelledPitch> {
+        self.voicing()
+            .into_iter()
+            .filter_map(|(name, semitones)| {
+                let note = self.root.checked_add(semitones)?;
+                Some(
+                    SpelledPitch::spell(&note, name)
+                        .unwrap_or_else(|| SpelledPitch::from_pitch(&note)),
+                )
+            })
+            .collect()
+    }
+
+    /// The same chord built on a root moved by `semitones`, in the same inversion, with the
+    /// root and any slash bass written with the fewest accidentals (F up 5 is Bb, not A#)
+    pub fn transpose(&self, semitones: i32) -> Result<Chord, String> {
+        let root = self.root.transpose(semitones)?;
+        let (root_spelling, bass) = transposition_fifths(semitones)
+            .into_iter()
+            .filter_map(|fifths| {
+                let bass = match self.bass {
+                    Some(bass) => Some(bass.shift_fifths(fifths)?),
+                    None => None,
+                };
+                Some((self.root_spelling.shift_fifths(fifths)?, bass))
+            })
+            .min_by_key(|(root, bass)| {
+                let accidentals = |note: &SpelledNote| note.accidental.semitones().abs();
+                accidentals(root) + bass.as_ref().map_or(0, accidentals)
+            })
+            .unwrap_or_else(|| {
+                let bass = self.bass.and_then(|bass| {
+                    Note::from_semitone(
+                        (bass.pitch().to_semitone() + semitones).rem_euclid(12) as u8
+                    )
+                });
+                (
+                    SpelledNote::from_note(&root.note),
+                    bass.map(|bass| SpelledNote::from_note(&bass)),
+                )
+            });
+        Ok(Chord {
+            root,
+            root_spelling,
+            bass,
+            ..self.clone()
+        })
+    }
+
+    /// The same chord with its `n`th tone in the bass: 0 for root position, 1 for first
+    /// inversion and so on. Tones below the new bass move up an octave.
+    pub fn invert(&self, n: usize) -> Result<Chord, String> {
+        let inversion = Inversion::from_index(n)
+            .filter(|_| n < self.tones().len())
+            .ok_or_else(|| format!("{} has no inversion {}", self, n))?;
+        Ok(Chord {
+            inversion,
+            bass: None,
+            ..self.clone()
+        })
+    }
+
+    /// Chord tones from the bass upwards
+    pub fn notes(&self) -> Vec<NoteWithOctave> {
+        self.voicing()
+            .into_iter()
+            .filter_map(|(_, semitones)| self.root.checked_add(semitones))
+            .collect()
+    }
+
+    /// The lowest sounding note
+    pub fn bass(&self) -> Option<NoteWithOctave> {
+        self.notes().first().copied()
+    }
+
+    /// The chords that best fit `notes`, best first. A chord fits if at most one of its
+    /// tones is missing and the notes include its root; exact matches come before ones
+    /// with missing or extra tones, and root position before inversions.
+    pub fn identify(notes: &[NoteWithOctave]) -> Vec<ChordMatch> {
+        let mut notes = notes.to_vec();
+        notes.sort();
+        let Some(&bass) = notes.first() else {
+            return Vec::new();
+        };
+        let mut played: Vec<Note> = Vec::new();
+        for note in &notes {
+            if !played.contains(&note.note) {
+                played.push(note.note);
+            }
+        }
+
+        let mut matches = Vec::new();
+        for (&root, quality) in played
+            .iter()
+            .flat_map(|root| ChordQuality::ALL.iter().map(move |quality| (root, quality)))
+        {
+            let intervals = quality.intervals();
+            let tones: Vec<Note> = intervals
+                .iter()
+                .filter_map(|&interval| {
+                    Note::from_semitone((root.to_semitone() + interval as i32).rem_euclid(12) as u8)
+                })
+                .collect();
+            let missing: Vec<Note> = tones
+                .iter()
+                .filter(|tone| !played.contains(tone))
+                .copied()
+                .collect();
+            let extra: Vec<Note> = played
+                .iter()
+                .filter(|note| !tones.contains(note))
+                .copied()
+                .collect();
+            let matched = tones.len() - missing.len();
+            if missing.len() > 1 || matched < 2 || extra.len() >= matched {
+                continue;
+            }
+
+            // Measure the root from the bass when the bass is a chord tone, so the
+            // inverted chord sounds the notes played
+            let chord = match tones.iter().position(|&tone| tone == bass.note) {
+                Some(tone) => bass
+                    .checked_sub(intervals[tone] as i32)
+                    .and_then(|root| Chord::new(root, quality.clone()).invert(tone).ok()),
+                None => notes
+                    .iter()
+                    .find(|note| note.note == root)
+                    .map(|&root| Chord::new(root, quality.clone())),
+            };
+            if let Some(chord) = chord {
+                matches.push(ChordMatch {
+                    chord,
+                    missing,
+                    extra,
+                });
+            }
+        }
+        matches.sort_by_key(|m| {
+            (
+                m.missing.len() + m.extra.len(),
+                m.extra.len(),
+                m.chord.inversion != Inversion::Root,
+            )
+        });
+        matches
+    }
+
+    /// Whether `note` sounds in this voicing of the chord
+    pub fn contains(&self, note: &NoteWithOctave) -> bool {
+        self.notes().contains(note)
+    }
+
+    /// Whether `note` is a chord tone in any octave
+    pub fn contains_pitch_class(&self, note: Note) -> bool {
+        self.notes().iter().any(|n| n.note == note)
+    }
+
+    /// The chord symbol after the root, e.g. "m7" or "7b9"
+    fn suffix(&self) -> String {
+        // The symbol covering the most of the extensions, then the rest written out
+        let (symbol, implied) = QUALITY_SYMBOLS
+            .iter()
+            .rev()
+            .filter(|(_, quality, implied)| {
+                *quality == self.quality && implied.iter().all(|e| self.extensions.contains(e))
+            })
+            .max_by_key(|(_, _, implied)| implied.len())
+            .map_or(("", &[][..]), |(symbol, _, implied)| (*symbol, *implied));
+        let mut suffix = symbol.to_string();
+        for extension in self.extensions.iter().filter(|e| !implied.contains(e)) {
+            suffix.push_str(&extension.to_string());
+        }
+        suffix
+    }
+
+    /// The note after the slash in the chord symbol, if the root isn't in the bass
+    fn slash_bass(&self) -> Option<SpelledNote> {
+        if self.bass.is_some() {
+            return self.bass;
+        }
+        if self.inversion == Inversion::Root {
+            return None;
+        }
+        self.spelled_notes().first().map(|note| note.note)
+    }
+
+    /// Each chord tone's scale degree above the root (1, 3, 5, 7, 9...) and its
+    /// semitones above the root, from the root up
+    fn tones(&self) -> Vec<(u8, i32)> {
+        let mut tones: Vec<(u8, i32)> = [1, 3, 5, 7]
+            .into_iter()
+            .zip(self.quality.intervals())
+            .map(|(degree, interval)| (degree, interval as i32))
+            .collect();
+        for extension in &self.extensions {
+            match *extension {
+                Extension::Sus2 | Extension::Sus4 => {
+                    let tone = if *extension == Extension::Sus2 {
+                        (2, 2)
+                    } else {
+                        (4, 5)
+                    };
+                    match tones.iter_mut().find(|(degree, _)| *degree == 3) {
+                        Some(third) => *third = tone,
+                        None => tones.push(tone),
+                    }
+                }
+                Extension::FlatFive | Extension::SharpFive => {
+                    let fifth = if *extension == Extension::FlatFive {
+                        6
+                    } else {
+                        8
+                    };
+                    if let Some(tone) = tones.iter_mut().find(|(degree, _)| *degree == 5) {
+                        tone.1 = fifth;
+                    }
+                }
+                Extension::Add { degree, alteration } => {
+                    // Sixths stay within the octave; 9ths, 11ths and 13ths go above it
+                    let natural = match degree {
+                        6 => 9,
+                        9 => 14,
+                        11 => 17,
+                        13 => 21,
+                        _ => continue,
+                    };
+                    tones.push((degree, natural + alteration as i32));
+                }
+            }
+        }
+        tones.sort();
+        tones
+    }
+
+    /// The letter name and semitones above the root of each sounding note, from the bass
+    /// up: tones below an inversion's bass are raised an octave, and a slash bass that
+    /// isn't a chord tone goes below the lowest of them
+    fn voicing(&self) -> Vec<(NoteName, i32)> {
+        let root_name = self.root_spelling.name.index();
+        let tones = self.tones();
+        let bass = self.inversion.index().min(tones.len());
+        let (below, above) = tones.split_at(bass);
+        let mut voicing: Vec<(NoteName, i32)> = above
+            .iter()
+            .copied()
+            .chain(
+                below
+                    .iter()
+                    .map(|&(degree, semitones)| (degree, semitones + 12)),
+            )
+            .map(|(degree, semitones)| {
+                (
+                    NoteName::from_index(root_name + degree as i32 - 1),
+                    semitones,
+                )
+            })
+            .collect();
+
+        if let (Some(bass), Some(&(_, lowest))) = (self.bass, voicing.first()) {
+            let above_root = bass.pitch().to_semitone() - self.root.note.to_semitone();
+            let gap = match (lowest - above_root).rem_euclid(12) {
+                0 => 12,
+                gap => gap,
+            };
+            voicing.insert(0, (bass.name, lowest - gap));
+        }
+        voicing
+    }
+
+    /// Play the chord tones one after another in quarter notes, from the bass up
+    #[cfg(feature = "playback")]
+    pub fn play(&self, tempo: Tempo) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_on(&AudioEngine::new()?, tempo)
+    }
+
+    /// Play the chord tones one after another on an already open audio engine
+    #[cfg(feature = "playback")]
+    pub fn play_on(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.arpeggiate(engine, &PlaybackOptions::new().with_tempo(tempo))
+    }
+
+    /// Sound all chord tones together as a block chord
+    #[cfg(feature = "playback")]
+    pub fn play_together(&self, duration: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_together_on(&AudioEngine::new()?, duration)
+    }
+
+    /// Sound all chord tones together on an already open audio engine
+    #[cfg(feature = "playback")]
+    pub fn play_together_on(
+        &self,
+        engine: &AudioEngine,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        engine.play_notes(&self.notes(), duration)
+    }
+
+    #[cfg(feature = "playback")]
+    fn arpeggiate(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let labels: Vec<String> = self.spelled_notes().iter().map(|n| n.to_string()).collect();
+        Melody::from_chord(self, NoteValue::Quarter).play_events(engine, opts, Some(&labels))
+    }
+
+    /// Play the chord at the default tempo (120 BPM)
+    #[cfg(feature = "playback")]
+    pub fn play_default(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.play(Tempo::default())
+    }
+}
+
+#[cfg(feature = "playback")]
+impl Playable for Chord {
+    /// Arpeggiate the chord, then sound it as a block chord
+    fn play(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.arpeggiate(engine, opts)?;
+        engine.play_notes_with(
+            &self.notes(),
+            opts.tempo.duration_of(NoteValue::DottedHalf),
+            opts,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::Note;
+
+    #[test]
+    fn test_chord_quality_parsing() {
+        assert_eq!("maj".parse::<ChordQuality>().unwrap(), ChordQuality::Major);
+        assert_eq!(
+            "dim".parse::<ChordQuality>().unwrap(),
+            ChordQuality::Diminished
+        );
+        assert_eq!(
+            "7".parse::<ChordQuality>().unwrap(),
+            ChordQuality::Dominant7
+        );
+        assert_eq!("m7".parse::<ChordQuality>().unwrap(), ChordQuality::Minor7);
+        assert!("invalid".parse::<ChordQuality>().is_err());
+        for quality in ChordQuality::ALL {
+            assert_eq!(quality.keyword().parse::<ChordQuality>(), Ok(quality));
+        }
+    }
+
+    #[test]
+    fn test_c_major_chord() {
+        let chord = Chord::new(
+            NoteWithOctave {
+                note: Note::C,
+                octave: 4,
+            },
+            ChordQuality::Major,
+        );
+        let notes = chord.notes();
+
+        assert_eq!(notes.len(), 3);
+        assert_eq!(notes[0].note, Note::C);
+        assert_eq!(notes[1].note, Note::E);
+        assert_eq!(notes[2].note, Note::G);
+    }
+
+    #[test]
+    fn test_seventh_chord_crosses_octave() {
+        let chord = Chord::new(
+            NoteWithOctave {
+                note: Note::A,
+                octave: 3,
+            },
+            ChordQuality::Dominant7,
+        );
+        let notes = chord.notes();
+
+        assert_eq!(notes.len(), 4);
+        assert_eq!(notes[1].note, Note::CSharp);
+        assert_eq!(notes[1].octave, 4);
+        assert_eq!(notes[3].note, Note::G);
+        assert_eq!(notes[3].octave, 4);
+    }
+
+    #[test]
+    fn test_chord_display() {
+        let chord = Chord::new(
+            NoteWithOctave {
+                note: Note::B,
+                octave: 3,
+            },
+            ChordQuality::Minor7,
+        );
+        assert_eq!(chord.name(), "B3 Minor 7th");
+        assert_eq!(chord.to_string(), "Bm7");
+    }
+
+    #[test]
+    fn test_transpose_chord() {
+        let chord = Chord::new("G4".parse().unwrap(), ChordQuality::Dominant7);
+        let up = chord.transpose(5).unwrap();
+        assert_eq!(up.name(), "C5 Dominant 7th");
+
+        let down = chord.transpose(-7).unwrap();
+        assert_eq!(down.name(), "C4 Dominant 7th");
+
+        assert!(chord.transpose(-100).is_err());
+    }
+
+    #[test]
+    fn test_transpose_keeps_spelling() {
+        let f = Chord::new("F4".parse().unwrap(), ChordQuality::Major);
+        let b_flat = f.transpose(5).unwrap();
+        assert_eq!(b_flat.to_string(), "Bb");
+        let names: Vec<String> = b_flat
+            .spelled_notes()
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(names, ["Bb4", "D5", "F5"]);
+
+        let e_flat_minor = "Ebm7".parse::<Chord>().unwrap();
+        assert_eq!(e_flat_minor.transpose(0).unwrap(), e_flat_minor);
+        assert_eq!(e_flat_minor.transpose(-12).unwrap().to_string(), "Ebm7");
+
+        // The slash bass moves by the same interval as the root
+        let over_e_flat = "Ab/Eb".parse::<Chord>().unwrap();
+        assert_eq!(over_e_flat.transpose(0).unwrap(), over_e_flat);
+        assert_eq!(over_e_flat.transpose(2).unwrap().to_string(), "Bb/F");
+        let over_g_flat = "Ab/Gb".parse::<Chord>().unwrap();
+        assert_eq!(over_g_flat.transpose(1).unwrap().to_string(), "A/G");
+    }
+
+    #[test]
+    fn test_spelled_chords() {
+        let names = |chord: &Chord| -> Vec<String> {
+            chord
+                .spelled_notes()
+                .iter()
+                .map(|n| n.to_string())
+                .collect()
+        };
+
+        let d_flat = Chord::spelled("Db4".parse().unwrap(), ChordQuality::Major).unwrap();
+        assert_eq!(names(&d_flat), vec!["Db4", "F4", "Ab4"]);
+        assert_eq!(d_flat.name(), "Db4 Major");
+
+        let e_flat_minor7 = Chord::spelled("Eb4".parse().unwrap(), ChordQuality::Minor7).unwrap();
+        assert_eq!(names(&e_flat_minor7), vec!["Eb4", "Gb4", "Bb4", "Db5"]);
+
+        let c_augmented = Chord::new("C4".parse().unwrap(), ChordQuality::Augmented);
+        assert_eq!(names(&c_augmented), vec!["C4", "E4", "G#4"]);
+    }
+
+    #[test]
+    fn test_inversion_parsing() {
+        assert_eq!("1st".parse::<Inversion>().unwrap(), Inversion::First);
+        assert_eq!("root".parse::<Inversion>().unwrap(), Inversion::Root);
+        assert_eq!(
+            "Third Inversion".parse::<Inversion>().unwrap(),
+            Inversion::Third
+        );
+        assert!("fourth".parse::<Inversion>().is_err());
+        assert_eq!(Inversion::from_index(2), Some(Inversion::Second));
+        assert_eq!(Inversion::from_index(4), None);
+    }
+
+    #[test]
+    fn test_chord_inversions() {
+        let names = |chord: &Chord| -> Vec<String> {
+            chord
+                .spelled_notes()
+                .iter()
+                .map(|n| n.to_string())
+                .collect()
+        };
+
+        let c_major = Chord::new("C4".parse().unwrap(), ChordQuality::Major);
+        let first = c_major.invert(1).unwrap();
+        assert_eq!(names(&first), vec!["E4", "G4", "C5"]);
+        assert_eq!(first.bass(), Some("E4".parse().unwrap()));
+        assert_eq!(first.name(), "C4 Major, first inversion");
+        assert_eq!(first.to_string(), "C/E");
+        assert_eq!(names(&c_major.invert(2).unwrap()), vec!["G4", "C5", "E5"]);
+        assert_eq!(c_major.invert(0).unwrap(), c_major);
+        assert!(c_major.invert(3).is_err());
+
+        let g7 = Chord::spelled("G3".parse().unwrap(), ChordQuality::Dominant7).unwrap();
+        let third = g7.invert(3).unwrap();
+        assert_eq!(names(&third), vec!["F4", "G4", "B4", "D5"]);
+        assert_eq!(third.transpose(2).unwrap().inversion, Inversion::Third);
+    }
+
+    #[test]
+    fn test_chord_contains() {
+        let g7 = "G7".parse::<Chord>().unwrap();
+        assert!(g7.contains(&"F5".parse().unwrap()));
+        assert!(!g7.contains(&"F4".parse().unwrap()));
+        assert!(g7.contains_pitch_class(Note::F));
+        assert!(g7.contains_pitch_class(Note::B));
+        assert!(!g7.contains_pitch_class(Note::C));
+    }
+
+    #[test]
+    fn test_identify_chords() {
+        let notes = |names: &[&str]| -> Vec<NoteWithOctave> {
+            names.iter().map(|n| n.parse().unwrap()).collect()
+        };
+
+        let best = &Chord::identify(&notes(&["C4", "E4", "G4", "A#4"]))[0];
+        assert!(best.is_exact());
+        assert_eq!(best.chord.to_string(), "C7");
+
+        let first = &Chord::identify(&notes(&["E3", "C4", "G4"]))[0];
+        assert!(first.is_exact());
+        assert_eq!(first.chord.inversion, Inversion::First);
+        assert_eq!(first.chord.bass(), Some("E3".parse().unwrap()));
+        assert_eq!(first.chord.to_string(), "C/E");
+
+        // Seventh with the fifth left out
+        let shell = &Chord::identify(&notes(&["G3", "B3", "F4"]))[0];
+        assert_eq!(shell.chord.to_string(), "G7");
+        assert_eq!(shell.missing, vec![Note::D]);
+        assert_eq!(shell.to_string(), "G7 (no D)");
+
+        let added = &Chord::identify(&notes(&["C4", "D4", "E4", "G4"]))[0];
+        assert_eq!(added.chord.quality, ChordQuality::Major);
+        assert_eq!(added.extra, vec![Note::D]);
+
+        assert!(Chord::identify(&[]).is_empty());
+        assert!(Chord::identify(&notes(&["C4"])).is_empty());
+    }
+
+    #[test]
+    fn test_quality_from_intervals() {
+        assert_eq!(
+            ChordQuality::from_intervals(&[0, 3, 6]),
+            Some(ChordQuality::Diminished)
+        );
+        assert_eq!(
+            ChordQuality::from_intervals(&[0, 4, 7, 10]),
+            Some(ChordQuality::Dominant7)
+        );
+        assert_eq!(ChordQuality::from_intervals(&[0, 5, 7]), None);
+    }
+
+    fn symbol_notes(symbol: &str) -> Vec<String> {
+        symbol
+            .parse::<Chord>()
+            .unwrap()
+            .spelled_notes()
+            .iter()
+            .map(|n| n.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_chord_symbol_parsing() {
+        let chord = "F#m7b5".parse::<Chord>().unwrap();
+        assert_eq!(chord.quality, ChordQuality::HalfDiminished7);
+        assert_eq!(chord.name(), "F#4 Half-Diminished 7th");
+        assert_eq!(symbol_notes("F#m7b5"), vec!["F#4", "A4", "C5", "E5"]);
+        assert_eq!(symbol_notes("Bbsus4"), vec!["Bb4", "Eb5", "F5"]);
+        assert_eq!(symbol_notes("Ebmaj9"), vec!["Eb4", "G4", "Bb4", "D5", "F5"]);
+        assert_eq!(symbol_notes("C6/9"), vec!["C4", "E4", "G4", "A4", "D5"]);
+        assert_eq!(
+            symbol_notes("G7(b9,#11)"),
+            vec!["G4", "B4", "D5", "F5", "Ab5", "C#6"]
+        );
+        assert_eq!(symbol_notes("Dbb9"), vec!["Db4", "F4", "Ab4", "Ebb5"]);
+        assert_eq!(symbol_notes("Bbb"), vec!["Bbb4", "Db5", "Fb5"]);
+        assert_eq!(symbol_notes("Cdim7"), vec!["C4", "Eb4", "Gb4", "Bbb4"]);
+        assert_eq!(symbol_notes("C+"), vec!["C4", "E4", "G#4"]);
+
+        for (alias, symbol) in [
+            ("CM7", "Cmaj7"),
+            ("CΔ", "Cmaj7"),
+            ("C-7", "Cm7"),
+            ("Cmin", "Cm"),
+            ("Cø", "Cm7b5"),
+            ("C°7", "Cdim7"),
+            ("CmM7", "CmMaj7"),
+            ("Csus", "Csus4"),
+            ("Cadd2", "Cadd9"),
+            ("C7#5", "Caug7"),
+        ] {
+            assert_eq!(alias.parse::<Chord>().unwrap().to_string(), symbol);
+        }
+    }
+
+    #[test]
+    fn test_chord_symbol_round_trip() {
+        for symbol in [
+            "C", "Am", "Bdim", "Eaug", "G7", "Cmaj7", "Dm7", "Bm7b5", "Cdim7", "AmMaj7", "C6",
+            "Am69", "D9", "F11", "Eb13", "Abmaj13", "Cm11", "Dsus2", "G7sus4", "Cadd9", "G7b9",
+            "E7#9", "Fmaj7#11", "A7b13", "Caug7", "C7b5", "C/E", "G7/F", "Am/C", "C/D",
+        ] {
+            let chord = symbol.parse::<Chord>().unwrap();
+            assert_eq!(chord.to_string(), symbol);
+            assert_eq!(chord.to_string().parse::<Chord>().unwrap(), chord);
+        }
+    }
+
+    #[test]
+    fn test_slash_chords() {
+        let g_over_b = "G/B".parse::<Chord>().unwrap();
+        assert_eq!(g_over_b.inversion, Inversion::First);
+        assert_eq!(g_over_b.bass, None);
+        assert_eq!(symbol_notes("G/B"), vec!["B4", "D5", "G5"]);
+
+        let c_over_d = "C/D".parse::<Chord>().unwrap();
+        assert_eq!(c_over_d.inversion, Inversion::Root);
+        assert_eq!(c_over_d.bass, Some("D".parse().unwrap()));
+        assert_eq!(symbol_notes("C/D"), vec!["D3", "C4", "E4", "G4"]);
+        assert_eq!(symbol_notes("C/Bb"), vec!["Bb3", "C4", "E4", "G4"]);
+        assert_eq!(c_over_d.name(), "C4 Major over D");
+        assert_eq!(c_over_d.transpose(2).unwrap().to_string(), "D/E");
+    }
+
+    #[test]
+    fn test_invalid_chord_symbols() {
+        for symbol in ["", "c", "H7", "Cfoo", "C7/X", "C/", "C7#"] {
+            assert!(
+                symbol.parse::<Chord>().is_err(),
+                "{} should not parse",
+                symbol
+            );
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::chords::ChordQuality;
+use crate::generator::random_note;
+use crate::intervals::{Interval, IntervalPlayStyle};
+use crate::notes::{Note, NoteWithOctave};
+#[cfg(feature = "serde")]
+use crate::training::parse_note_range;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::fs;
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::path::PathBuf;
+
+/// File name of the quiz settings inside the config directory, next to the stats
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+/// Limits on what the quizzes ask about, so a beginner can start with a couple of intervals
+/// in one octave and open up the rest over time. An empty list allows everything.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct QuizConfig {
+    /// Intervals the interval quizzes ask about, matched by size
+    pub intervals: Vec<Interval>,
+    /// Chord qualities the chord quizzes play
+    pub chords: Vec<ChordQuality>,
+    /// Tonics of the major keys that cadences, scale degrees and melodies are played in
+    pub keys: Vec<Note>,
+    /// Lowest and highest note a question's notes are picked from
+    pub range: Option<(NoteWithOctave, NoteWithOctave)>,
+    /// Ways of sounding intervals, picked at random for each question
+    pub directions: Vec<IntervalPlayStyle>,
+}
+
+/// One line per limit, e.g. "Intervals: M3, P5", with "all" for anything unrestricted
+impl fmt::Display for QuizConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list<T>(items: &[T], name: impl Fn(&T) -> String) -> String {
+            if items.is_empty() {
+                return "all".to_string();
+            }
+            items.iter().map(name).collect::<Vec<_>>().join(", ")
+        }
+        writeln!(
+            f,
+            "Intervals: {}",
+            list(&self.intervals, Interval::short_name)
+        )?;
+        writeln!(f, "Chords: {}", list(&self.chords, |q| q.keyword().into()))?;
+        writeln!(f, "Keys: {}", list(&self.keys, Note::to_string))?;
+        match self.range {
+            Some((low, high)) => writeln!(f, "Range: {}-{}", low, high)?,
+            None => writeln!(f, "Range: all")?,
+        }
+        write!(
+            f,
+            "Directions: {}",
+            list(&self.directions, |d| d.keyword().into())
+        )
+    }
+}
+
+impl QuizConfig {
+    pub fn with_intervals(mut self, intervals: Vec<Interval>) -> Self {
+        self.intervals = intervals;
+        self
+    }
+
+    pub fn with_chords(mut self, chords: Vec<ChordQuality>) -> Self {
+        self.chords = chords;
+        self
+    }
+
+    pub fn with_keys(mut self, keys: Vec<Note>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Keep notes between `low` and `high`, swapping them if they're the wrong way round
+    pub fn with_range(mut self, low: NoteWithOctave, high: NoteWithOctave) -> Self {
+        self.range = Some((low.min(high), low.max(high)));
+        self
+    }
+
+    pub fn with_directions(mut self, directions: Vec<IntervalPlayStyle>) -> Self {
+        self.directions = directions;
+        self
+    }
+
+    /// Load the settings at `path`, with no limits if the file doesn't exist yet
+    #[cfg(feature = "serde")]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str::<ConfigFile>(&json)?.parse()?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Load the settings in the user's config directory
+    #[cfg(feature = "serde")]
+    pub fn open_default() -> Result<Self, Box<dyn std::error::Error>> {
+        let path = default_path().ok_or("Could not find a config directory")?;
+        Self::open(path)
+    }
+
+    /// Write the settings to `path`, creating its directory if needed
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&ConfigFile::from(self))?)?;
+        Ok(())
+    }
+
+    /// Whether interval questions may be this size
+    pub fn allows_interval(&self, interval: &Interval) -> bool {
+        self.intervals.is_empty()
+            || self
+                .intervals
+                .iter()
+                .any(|allowed| allowed.semitones() == interval.semitones())
+    }
+
+    /// The `candidates` the quizzes may ask about
+    pub fn filter_intervals(&self, candidates: &[Interval]) -> Vec<Interval> {
+        candidates
+            .iter()
+            .filter(|interval| self.allows_interval(interval))
+            .copied()
+            .collect()
+    }
+
+    /// The chord `qualities` the quizzes may play
+    pub fn filter_chords(&self, qualities: &[ChordQuality]) -> Vec<ChordQuality> {
+        qualities
+            .iter()
+            .filter(|quality| self.chords.is_empty() || self.chords.contains(quality))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether questions may be played in the major key on `tonic`
+    pub fn allows_key(&self, tonic: &Note) -> bool {
+        self.keys.is_empty() || self.keys.contains(tonic)
+    }
+
+    /// A note within the range, or between `low` and `high` when there's no limit
+    pub fn random_note<R: Rng>(
+        &self,
+        rng: &mut R,
+        low: &NoteWithOctave,
+        high: &NoteWithOctave,
+    ) -> NoteWithOctave {
+        let (low, high) = self.range.unwrap_or((*low, *high));
+        random_note(rng, &low, &high).unwrap_or(low)
+    }
+
+    /// One of the allowed directions, or `None` to leave it to the user
+    pub fn random_direction<R: Rng>(&self, rng: &mut R) -> Option<IntervalPlayStyle> {
+        self.directions.choose(rng).copied()
+    }
+}
+
+/// `$XDG_CONFIG_HOME/ear-trainer/config.json`, in the same directory as the stats
+pub fn default_path() -> Option<PathBuf> {
+    Some(crate::stats::default_path()?.with_file_name(CONFIG_FILE_NAME))
+}
+
+/// The settings as written in the config file, with everything named the way it's typed
+#[cfg(feature = "serde")]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    intervals: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    chords: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    keys: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    range: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    directions: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<&QuizConfig> for ConfigFile {
+    fn from(config: &QuizConfig) -> Self {
+        Self {
+            intervals: config.intervals.iter().map(Interval::short_name).collect(),
+            chords: config.chords.iter().map(|q| q.keyword().into()).collect(),
+            keys: config.keys.iter().map(Note::to_string).collect(),
+            range: config.range.map(|(low, high)| format!("{}-{}", low, high)),
+            directions: config
+                .directions
+                .iter()
+                .map(|d| d.keyword().into())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ConfigFile {
+    fn parse(&self) -> Result<QuizConfig, String> {
+        let mut config = QuizConfig {
+            intervals: parse_all(&self.intervals)?,
+            chords: parse_all(&self.chords)?,
+            keys: parse_all(&self.keys)?,
+            range: None,
+            directions: parse_all(&self.directions)?,
+        };
+        if let Some(range) = &self.range {
+            let (low, high) = parse_note_range(range)?;
+            config = config.with_range(low, high);
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn parse_all<T: std::str::FromStr<Err = String>>(names: &[String]) -> Result<Vec<T>, String> {
+    names.iter().map(|name| name.parse()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::seeded;
+
+    fn note(s: &str) -> NoteWithOctave {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_filters() {
+        let config = QuizConfig::default()
+            .with_intervals(vec!["M3".parse().unwrap(), "P5".parse().unwrap()])
+            .with_chords(vec![ChordQuality::Major])
+            .with_keys(vec![Note::C, Note::G]);
+
+        let all: Vec<Interval> = (1..=12).map(Interval::from_semitones).collect();
+        let allowed = config.filter_intervals(&all);
+        assert_eq!(allowed.len(), 2);
+        assert!(config.allows_interval(&"d4".parse().unwrap()));
+        assert_eq!(
+            config.filter_chords(&[ChordQuality::Minor, ChordQuality::Major]),
+            vec![ChordQuality::Major]
+        );
+        assert!(config.allows_key(&Note::G));
+        assert!(!config.allows_key(&Note::D));
+
+        let open = QuizConfig::default();
+        assert_eq!(open.filter_intervals(&all), all);
+        assert!(open.allows_key(&Note::D));
+    }
+
+    #[test]
+    fn test_range_and_directions() {
+        let config = QuizConfig::default()
+            .with_range(note("C5"), note("C4"))
+            .with_directions(vec![IntervalPlayStyle::MelodicAscending]);
+        assert_eq!(config.range, Some((note("C4"), note("C5"))));
+
+        let mut rng = seeded(2);
+        for _ in 0..20 {
+            let picked = config.random_note(&mut rng, &note("C2"), &note("C7"));
+            assert!((note("C4")..=note("C5")).contains(&picked));
+        }
+        assert_eq!(
+            config.random_direction(&mut rng),
+            Some(IntervalPlayStyle::MelodicAscending)
+        );
+        assert_eq!(QuizConfig::default().random_direction(&mut rng), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_and_reopen() {
+        let dir = std::env::temp_dir().join(format!("ear-trainer-config-{}", std::process::id()));
+        let path = dir.join(CONFIG_FILE_NAME);
+        assert_eq!(QuizConfig::open(&path).unwrap(), QuizConfig::default());
+
+        let config = QuizConfig::default()
+            .with_intervals(vec!["m3".parse().unwrap(), Interval::TRITONE])
+            .with_chords(vec![ChordQuality::HalfDiminished7])
+            .with_keys(vec![Note::ASharp])
+            .with_range(note("C4"), note("C5"))
+            .with_directions(vec![IntervalPlayStyle::Harmonic]);
+        config.save(&path).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(json.contains("\"C4-C5\""));
+        assert!(json.contains("\"m7b5\""));
+        assert_eq!(QuizConfig::open(&path).unwrap(), config);
+
+        fs::write(&path, r#"{"intervals": ["P9000"]}"#).unwrap();
+        assert!(QuizConfig::open(&path).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_display() {
+        let config = QuizConfig::default()
+            .with_intervals(vec!["M3".parse().unwrap(), "P5".parse().unwrap()])
+            .with_range(note("C4"), note("C5"));
+        assert_eq!(
+            config.to_string(),
+            "Intervals: M3, P5\nChords: all\nKeys: all\nRange: C4-C5\nDirections: all"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::chords::ChordQuality;
+use crate::intervals::Interval;
+use crate::notes::{Note, NoteWithOctave};
+use crate::tempo::Tempo;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Highest difficulty level; every setting is at its hardest here
+pub const MAX_LEVEL: u8 = 5;
+
+/// Answers considered when deciding whether to move up or down a level
+pub const DEFAULT_WINDOW: usize = 8;
+
+/// Rolling accuracy needed to move up a level
+pub const PROMOTE_ACCURACY: f64 = 0.8;
+
+/// Rolling accuracy at or below which the level drops
+pub const DEMOTE_ACCURACY: f64 = 0.5;
+
+/// Intervals in semitones added at each level, easiest first
+const INTERVALS_BY_LEVEL: [&[u8]; 6] = [&[4, 7, 12], &[3, 5], &[2, 9], &[1, 8], &[10, 11], &[6]];
+
+/// Which way the level moved after an answer
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LevelChange {
+    Raised,
+    Lowered,
+}
+
+impl fmt::Display for LevelChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelChange::Raised => write!(f, "up"),
+            LevelChange::Lowered => write!(f, "down"),
+        }
+    }
+}
+
+/// Settings for one difficulty level, shared by every exercise
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Difficulty {
+    pub level: u8,
+}
+
+impl Difficulty {
+    pub fn new(level: u8) -> Self {
+        Self {
+            level: level.min(MAX_LEVEL),
+        }
+    }
+
+    /// Intervals to ask about, from thirds, fifths and octaves up to all twelve
+    pub fn intervals(&self) -> Vec<Interval> {
+        let mut semitones: Vec<u8> = INTERVALS_BY_LEVEL[..=self.level as usize]
+            .iter()
+            .flat_map(|level| level.iter().copied())
+            .collect();
+        semitones.sort();
+        semitones
+            .into_iter()
+            .map(Interval::from_semitones)
+            .collect()
+    }
+
+    /// Chord qualities to ask about, from major and minor triads up to sevenths
+    pub fn chord_qualities(&self) -> Vec<ChordQuality> {
+        let mut qualities = vec![ChordQuality::Major, ChordQuality::Minor];
+        if self.level >= 1 {
+            qualities.extend([ChordQuality::Diminished, ChordQuality::Augmented]);
+        }
+        if self.level >= 2 {
+            qualities.push(ChordQuality::Dominant7);
+        }
+        if self.level >= 3 {
+            qualities.extend([ChordQuality::Major7, ChordQuality::Minor7]);
+        }
+        qualities
+    }
+
+    /// Lowest and highest notes to play, starting at C4-B4 and
+    /// gaining an octave every other level, alternately below and above
+    pub fn note_range(&self) -> (NoteWithOctave, NoteWithOctave) {
+        let below = self.level.div_ceil(2).min(2);
+        let above = (self.level / 2).min(1);
+        (
+            NoteWithOctave {
+                note: Note::C,
+                octave: 4 - below,
+            },
+            NoteWithOctave {
+                note: Note::B,
+                octave: 4 + above,
+            },
+        )
+    }
+
+    /// `base` sped up by 10% a level, so notes get shorter as the level rises
+    pub fn tempo(&self, base: Tempo) -> Tempo {
+        let bpm = base.bpm() as f64 * (1.0 + 0.1 * self.level as f64);
+        Tempo::from_bpm(bpm).unwrap_or(base)
+    }
+}
+
+/// Moves the difficulty level up when rolling accuracy is high and down when it is low.
+/// Each exercise turns the level into its own settings through `Difficulty`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DifficultyController {
+    level: u8,
+    recent: VecDeque<bool>,
+    window: usize,
+}
+
+impl Default for DifficultyController {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl DifficultyController {
+    /// Start at `level`, judging over the last `DEFAULT_WINDOW` answers
+    pub fn new(level: u8) -> Self {
+        Self::with_window(level, DEFAULT_WINDOW)
+    }
+
+    pub fn with_window(level: u8, window: usize) -> Self {
+        Self {
+            level: level.min(MAX_LEVEL),
+            recent: VecDeque::with_capacity(window),
+            window: window.max(1),
+        }
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::new(self.level)
+    }
+
+    /// Fraction correct over the answers since the last level change,
+    /// or `None` before any answers
+    pub fn rolling_accuracy(&self) -> Option<f64> {
+        if self.recent.is_empty() {
+            return None;
+        }
+        let correct = self.recent.iter().filter(|&&c| c).count();
+        Some(correct as f64 / self.recent.len() as f64)
+    }
+
+    /// Add an answer, changing level once a full window is above or below the thresholds
+    pub fn record(&mut self, correct: bool) -> Option<LevelChange> {
+        if self.recent.len() == self.window {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(correct);
+        if self.recent.len() < self.window {
+            return None;
+        }
+
+        let accuracy = self.rolling_accuracy()?;
+        let change = if accuracy >= PROMOTE_ACCURACY && self.level < MAX_LEVEL {
+            self.level += 1;
+            LevelChange::Raised
+        } else if accuracy <= DEMOTE_ACCURACY && self.level > 0 {
+            self.level -= 1;
+            LevelChange::Lowered
+        } else {
+            return None;
+        };
+        // Judge the new level on its own answers
+        self.recent.clear();
+        Some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_widen_settings() {
+        let easy = Difficulty::new(0);
+        let hard = Difficulty::new(MAX_LEVEL);
+        let semitones: Vec<u8> = easy.intervals().iter().map(|i| i.semitones()).collect();
+        assert_eq!(semitones, vec![4, 7, 12]);
+        assert_eq!(hard.intervals().len(), 12);
+        assert_eq!(easy.chord_qualities().len(), 2);
+        assert_eq!(hard.chord_qualities().len(), 7);
+
+        assert_eq!(easy.note_range().0.to_string(), "C4");
+        assert_eq!(easy.note_range().1.to_string(), "B4");
+        assert_eq!(Difficulty::new(1).note_range().0.to_string(), "C3");
+        assert_eq!(hard.note_range().0.to_string(), "C2");
+        assert_eq!(hard.note_range().1.to_string(), "B5");
+
+        assert_eq!(easy.tempo(Tempo(100)), Tempo(100));
+        assert_eq!(hard.tempo(Tempo(100)), Tempo(150));
+        assert_eq!(Difficulty::new(9).level, MAX_LEVEL);
+    }
+
+    #[test]
+    fn test_controller_promotes_and_demotes() {
+        let mut controller = DifficultyController::with_window(0, 4);
+        assert_eq!(controller.record(true), None);
+        assert_eq!(controller.record(true), None);
+        assert_eq!(controller.record(false), None);
+        assert_eq!(controller.record(true), None); // 75% stays put
+        // The miss stays in the window for four answers
+        assert_eq!(controller.record(true), None);
+        assert_eq!(controller.record(true), None);
+        assert_eq!(controller.record(true), Some(LevelChange::Raised));
+        assert_eq!(controller.level(), 1);
+        assert_eq!(controller.rolling_accuracy(), None);
+
+        for _ in 0..3 {
+            assert_eq!(controller.record(false), None);
+        }
+        assert_eq!(controller.record(true), Some(LevelChange::Lowered));
+        assert_eq!(controller.level(), 0);
+
+        // The level never drops below zero
+        for _ in 0..8 {
+            assert_eq!(controller.record(false), None);
+        }
+    }
+
+    #[test]
+    fn test_controller_stops_at_max_level() {
+        let mut controller = DifficultyController::with_window(MAX_LEVEL, 2);
+        assert_eq!(controller.record(true), None);
+        assert_eq!(controller.record(true), None);
+        assert_eq!(controller.level(), MAX_LEVEL);
+        assert_eq!(controller.difficulty(), Difficulty::new(MAX_LEVEL));
+    }
+}
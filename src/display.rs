@@ -0,0 +1,732 @@
+use crate::keys::KeySignature;
+use crate::melody::{Event, Melody};
+use crate::notes::{MAX_OCTAVE, Note, NoteWithOctave, Scale};
+use crate::spelling::{Accidental, SpelledPitch};
+use std::fmt;
+use std::str::FromStr;
+
+/// Columns each white key takes up, including its left edge
+const WHITE_KEY_WIDTH: usize = 4;
+
+/// Rows where the black keys reach down between the white keys
+const BLACK_KEY_ROWS: usize = 2;
+
+/// Fill of a black key that isn't highlighted
+const BLACK_KEY: char = '#';
+
+/// Mark on a highlighted key, white or black
+const HIGHLIGHT: char = '*';
+
+/// Frets shown on a fretboard unless asked otherwise, up to the octave
+pub const DEFAULT_FRETS: u8 = 12;
+
+/// Most frets a fretboard can show
+pub const MAX_FRETS: u8 = 24;
+
+/// Mark on a fret that plays the root
+const ROOT_MARK: char = 'R';
+
+/// Mark on a fret that plays any other scale or chord tone
+const TONE_MARK: char = 'o';
+
+/// Mark on a note on the staff
+const NOTEHEAD: char = 'o';
+
+/// Mark on the middle line of the staff for a rest
+const REST_MARK: char = 'r';
+
+/// Staff steps from the bottom line to the top line
+const STAFF_HEIGHT: i32 = 8;
+
+/// Where the sharps of a key signature sit on the treble staff, F5 C5 G5 D5 A4 E5 B4,
+/// as letter-name steps above C0
+const TREBLE_SHARPS: [i32; 7] = [38, 35, 39, 36, 33, 37, 34];
+
+/// Where the flats sit on the treble staff, B4 E5 A4 D5 G4 C5 F4
+const TREBLE_FLATS: [i32; 7] = [34, 37, 33, 36, 32, 35, 31];
+
+/// Named tunings, lowest string first
+const TUNINGS: [(&str, [&str; 6]); 4] = [
+    ("standard", ["E2", "A2", "D3", "G3", "B3", "E4"]),
+    ("drop-d", ["D2", "A2", "D3", "G3", "B3", "E4"]),
+    ("dadgad", ["D2", "A2", "D3", "G3", "A3", "D4"]),
+    ("open-g", ["D2", "G2", "D3", "G3", "B3", "D4"]),
+];
+
+/// White keys in an octave, with the black key just above each one where there is one
+const WHITE_KEYS: [(Note, Option<Note>); 7] = [
+    (Note::C, Some(Note::CSharp)),
+    (Note::D, Some(Note::DSharp)),
+    (Note::E, None),
+    (Note::F, Some(Note::FSharp)),
+    (Note::G, Some(Note::GSharp)),
+    (Note::A, Some(Note::ASharp)),
+    (Note::B, None),
+];
+
+/// A terminal piano keyboard of whole octaves, with some of its keys marked, e.g. the
+/// notes of a chord
+#[derive(Debug, PartialEq, Clone)]
+pub struct Keyboard {
+    /// Octave of the leftmost C
+    pub first_octave: u8,
+    /// Octaves shown, at least one
+    pub octaves: u8,
+    /// Keys marked with a `*`, and named under the keyboard
+    pub highlighted: Vec<NoteWithOctave>,
+}
+
+/// Black keys reach down over the white keys, and highlighted keys are marked with a `*`
+/// and named underneath, white keys first and then black keys, e.g. for C4 and E4:
+///
+/// ```text
+/// |  |#| |#|  |  |#| |#| |#|  |
+/// |  |#| |#|  |  |#| |#| |#|  |
+/// | * |   | * |   |   |   |   |
+/// |___|___|___|___|___|___|___|
+///   C       E
+/// ```
+impl fmt::Display for Keyboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.white_keys() * WHITE_KEY_WIDTH + 1;
+        let mut rows = vec![vec![' '; width]; BLACK_KEY_ROWS + 2];
+        // Names of the white keys, then of the black keys, which would run into them
+        let mut labels = vec![vec![' '; width + 1]; 2];
+
+        for (index, (white, _)) in self.keys().enumerate() {
+            let left = index * WHITE_KEY_WIDTH;
+            for row in rows.iter_mut() {
+                row[left] = '|';
+            }
+            let bottom = rows.len() - 1;
+            for cell in &mut rows[bottom][left + 1..left + WHITE_KEY_WIDTH] {
+                *cell = '_';
+            }
+            let middle = left + WHITE_KEY_WIDTH / 2;
+            if self.is_highlighted(&white) {
+                rows[BLACK_KEY_ROWS][middle] = HIGHLIGHT;
+                label(&mut labels[0], middle, &white.note);
+            }
+        }
+        for row in rows.iter_mut() {
+            row[width - 1] = '|';
+        }
+
+        // Each black key straddles the edge between its white key and the next
+        for (index, (_, black)) in self.keys().enumerate() {
+            let Some(black) = black else {
+                continue;
+            };
+            let edge = (index + 1) * WHITE_KEY_WIDTH;
+            let fill = if self.is_highlighted(&black) {
+                label(&mut labels[1], edge, &black.note);
+                HIGHLIGHT
+            } else {
+                BLACK_KEY
+            };
+            for row in &mut rows[..BLACK_KEY_ROWS] {
+                row[edge - 1] = '|';
+                row[edge] = fill;
+                row[edge + 1] = '|';
+            }
+        }
+
+        let mut lines: Vec<String> = rows.iter().map(|row| row.iter().collect()).collect();
+        for labels in &labels {
+            let labels: String = labels.iter().collect();
+            if !labels.trim().is_empty() {
+                lines.push(labels.trim_end().to_string());
+            }
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Keyboard {
+    /// `octaves` octaves from C in `first_octave`, with nothing highlighted
+    pub fn new(first_octave: u8, octaves: u8) -> Self {
+        let first_octave = first_octave.min(MAX_OCTAVE);
+        Self {
+            first_octave,
+            octaves: octaves.clamp(1, MAX_OCTAVE + 1 - first_octave),
+            highlighted: Vec::new(),
+        }
+    }
+
+    /// The octaves from the lowest to the highest of `notes`, with them highlighted
+    pub fn spanning(notes: &[NoteWithOctave]) -> Self {
+        let low = notes.iter().map(|note| note.octave).min().unwrap_or(4);
+        let high = notes.iter().map(|note| note.octave).max().unwrap_or(4);
+        Self::new(low, high - low + 1).with_highlighted(notes)
+    }
+
+    /// Mark `notes`; any outside the keyboard are left out
+    pub fn with_highlighted(mut self, notes: &[NoteWithOctave]) -> Self {
+        self.highlighted = notes.to_vec();
+        self
+    }
+
+    pub fn is_highlighted(&self, note: &NoteWithOctave) -> bool {
+        self.highlighted.contains(note)
+    }
+
+    fn white_keys(&self) -> usize {
+        self.octaves as usize * WHITE_KEYS.len()
+    }
+
+    /// Every white key from the left, with the black key to its right if it has one
+    fn keys(&self) -> impl Iterator<Item = (NoteWithOctave, Option<NoteWithOctave>)> + '_ {
+        (self.first_octave..self.first_octave + self.octaves).flat_map(|octave| {
+            WHITE_KEYS.iter().map(move |(white, black)| {
+                (
+                    NoteWithOctave {
+                        note: *white,
+                        octave,
+                    },
+                    black.map(|note| NoteWithOctave { note, octave }),
+                )
+            })
+        })
+    }
+}
+
+/// Write the name of `note` into `labels` from `column`
+fn label(labels: &mut [char], column: usize, note: &Note) {
+    for (cell, c) in labels[column..].iter_mut().zip(note.to_string().chars()) {
+        *cell = c;
+    }
+}
+
+/// The open strings of a fretted instrument, lowest first
+#[derive(Debug, PartialEq, Clone)]
+pub struct StringTuning {
+    pub strings: Vec<NoteWithOctave>,
+}
+
+impl Default for StringTuning {
+    /// Six-string guitar in standard tuning, E2 A2 D3 G3 B3 E4
+    fn default() -> Self {
+        "standard".parse().expect("standard tuning is valid")
+    }
+}
+
+impl fmt::Display for StringTuning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let strings: Vec<String> = self.strings.iter().map(|s| s.to_string()).collect();
+        write!(f, "{}", strings.join(" "))
+    }
+}
+
+/// Parse a named tuning (standard, drop-d, dadgad, open-g, bass) or the open strings from
+/// lowest to highest, e.g. "D2 A2 D3 G3 B3 E4" or "E1,A1,D2,G2"
+impl FromStr for StringTuning {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        if name == "bass" {
+            return "E1 A1 D2 G2".parse();
+        }
+        if let Some((_, strings)) = TUNINGS.iter().find(|(tuning, _)| *tuning == name) {
+            return strings.join(" ").parse();
+        }
+        let strings = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|string| !string.is_empty())
+            .map(|string| string.parse::<NoteWithOctave>())
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(|_| format!("Invalid tuning: {}", s))?;
+        if strings.is_empty() {
+            return Err("A tuning needs at least one string".to_string());
+        }
+        Ok(Self { strings })
+    }
+}
+
+/// A guitar neck in the terminal, with the frets that play a scale or chord marked
+#[derive(Debug, PartialEq, Clone)]
+pub struct Fretboard {
+    pub tuning: StringTuning,
+    /// Frets shown after the open strings, from 1 to `MAX_FRETS`
+    pub frets: u8,
+    /// Pitch classes to mark on every string
+    pub tones: Vec<Note>,
+    /// Tone marked with an `R` rather than an `o`
+    pub root: Option<Note>,
+}
+
+impl Default for Fretboard {
+    fn default() -> Self {
+        Self {
+            tuning: StringTuning::default(),
+            frets: DEFAULT_FRETS,
+            tones: Vec::new(),
+            root: None,
+        }
+    }
+}
+
+/// Drawn like tablature with the highest string on top: a column of open strings, the nut,
+/// then one cell per fret, with the fret numbers along the top, e.g. for an E minor chord:
+///
+/// ```text
+///      0    1   2   3 ...
+/// E    R ||---|---|-o-|...
+/// ```
+impl fmt::Display for Fretboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut header = String::from("     0  ");
+        for fret in 1..=self.frets {
+            header.push_str(&format!("{:>3} ", fret));
+        }
+        write!(f, "{}", header.trim_end())?;
+
+        for string in self.tuning.strings.iter().rev() {
+            let mut line = format!(
+                "\n{:<4} {} ||",
+                string.note.to_string(),
+                self.mark(string, 0).unwrap_or(' ')
+            );
+            for fret in 1..=self.frets {
+                line.push_str(&format!("-{}-|", self.mark(string, fret).unwrap_or('-')));
+            }
+            write!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl Fretboard {
+    pub fn with_tuning(mut self, tuning: StringTuning) -> Self {
+        self.tuning = tuning;
+        self
+    }
+
+    /// Show `frets` frets, clamped to 1-`MAX_FRETS`
+    pub fn with_frets(mut self, frets: u8) -> Self {
+        self.frets = frets.clamp(1, MAX_FRETS);
+        self
+    }
+
+    /// Mark the pitch classes of `notes`, e.g. a scale or chord, with `root` set apart
+    pub fn with_notes(mut self, notes: &[NoteWithOctave], root: Note) -> Self {
+        self.tones = notes.iter().map(|note| note.note).collect();
+        self.tones.sort();
+        self.tones.dedup();
+        self.root = Some(root);
+        self
+    }
+
+    /// Every marked (string, fret), strings counted from the lowest
+    pub fn positions(&self) -> Vec<(usize, u8)> {
+        self.tuning
+            .strings
+            .iter()
+            .enumerate()
+            .flat_map(|(index, string)| {
+                (0..=self.frets)
+                    .filter(move |fret| self.mark(string, *fret).is_some())
+                    .map(move |fret| (index, fret))
+            })
+            .collect()
+    }
+
+    /// What to draw at `fret` on the open `string`, if it plays one of the tones
+    fn mark(&self, string: &NoteWithOctave, fret: u8) -> Option<char> {
+        let note = string.saturating_add(fret as i32).note;
+        if self.root == Some(note) {
+            Some(ROOT_MARK)
+        } else if self.tones.contains(&note) {
+            Some(TONE_MARK)
+        } else {
+            None
+        }
+    }
+}
+
+/// Which staff notes are written on
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Clef {
+    #[default]
+    Treble,
+    Bass,
+}
+
+impl fmt::Display for Clef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Clef::Treble => write!(f, "treble"),
+            Clef::Bass => write!(f, "bass"),
+        }
+    }
+}
+
+impl FromStr for Clef {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "treble" | "g" => Ok(Clef::Treble),
+            "bass" | "f" => Ok(Clef::Bass),
+            _ => Err(format!("Invalid clef: {} (try treble or bass)", s)),
+        }
+    }
+}
+
+impl Clef {
+    /// The clef that suits `notes`: bass when they sit below middle C on average
+    pub fn for_notes(notes: &[SpelledPitch]) -> Clef {
+        let middle_c = 4 * 7;
+        let total: i32 = notes.iter().map(step).sum();
+        if !notes.is_empty() && total < middle_c * notes.len() as i32 {
+            Clef::Bass
+        } else {
+            Clef::Treble
+        }
+    }
+
+    /// Step of the bottom line: E4 on the treble staff, G2 on the bass staff
+    fn bottom_line(&self) -> i32 {
+        match self {
+            Clef::Treble => 4 * 7 + 2,
+            Clef::Bass => 2 * 7 + 4,
+        }
+    }
+
+    /// The line the clef is named after, with its letter: G4 on the treble staff and F3
+    /// on the bass staff
+    fn marked_line(&self) -> (i32, char) {
+        match self {
+            Clef::Treble => (self.bottom_line() + 2, 'G'),
+            Clef::Bass => (self.bottom_line() + 6, 'F'),
+        }
+    }
+}
+
+/// A five-line staff in the terminal with a key signature and a row of notes, e.g. a
+/// dictated melody or a scale to sing
+#[derive(Debug, PartialEq, Clone)]
+pub struct Staff {
+    pub clef: Clef,
+    pub key: KeySignature,
+    /// Notes from left to right, with `None` for a rest
+    pub notes: Vec<Option<SpelledPitch>>,
+}
+
+impl Default for Staff {
+    fn default() -> Self {
+        Self {
+            clef: Clef::default(),
+            key: KeySignature::new(0).expect("C major has a key signature"),
+            notes: Vec::new(),
+        }
+    }
+}
+
+/// Drawn with the clef's letter on its line, the key signature, then a notehead per note
+/// with ledger lines beyond the staff. Accidentals are written whenever a note differs
+/// from the key signature, e.g. G4 A4 B4 C5 in F major:
+///
+/// ```text
+/// |----------------------|
+/// |                      |
+/// |----------------------|
+/// |                    o |
+/// |---b-----------no-----|
+/// |            o         |
+/// |G-------o-------------|
+/// |                      |
+/// |----------------------|
+/// ```
+impl fmt::Display for Staff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bottom = self.clef.bottom_line();
+        let top = bottom + STAFF_HEIGHT;
+        let heads = self.notes.iter().flatten().map(step);
+        let low = heads.clone().fold(bottom, i32::min);
+        let high = heads.fold(top, i32::max);
+        let rows: Vec<String> = (low..=high).rev().map(|row| self.row(row)).collect();
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+impl Staff {
+    pub fn with_clef(mut self, clef: Clef) -> Self {
+        self.clef = clef;
+        self
+    }
+
+    pub fn with_key(mut self, key: KeySignature) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn with_notes(mut self, notes: &[SpelledPitch]) -> Self {
+        self.notes = notes.iter().copied().map(Some).collect();
+        self
+    }
+
+    /// The melody's notes and rests, spelled in `key` on the clef that suits them
+    pub fn for_melody(melody: &Melody, key: KeySignature) -> Self {
+        let notes: Vec<Option<SpelledPitch>> = melody
+            .events()
+            .iter()
+            .map(|(event, _)| match event {
+                Event::Note(note) => Some(key.spell(note)),
+                Event::Rest => None,
+            })
+            .collect();
+        let spelled: Vec<SpelledPitch> = notes.iter().flatten().copied().collect();
+        Self {
+            clef: Clef::for_notes(&spelled),
+            key,
+            notes,
+        }
+    }
+
+    /// The scale's notes going up from the root, in its own key signature where it has one
+    pub fn for_scale(scale: &Scale) -> Self {
+        let notes = scale.spelled_notes();
+        let key = KeySignature::for_scale(scale);
+        Self::default()
+            .with_clef(Clef::for_notes(&notes))
+            .with_key(key)
+            .with_notes(&notes)
+    }
+
+    /// One line of the drawing, for the line or space at `row`
+    fn row(&self, row: i32) -> String {
+        let bottom = self.clef.bottom_line();
+        let top = bottom + STAFF_HEIGHT;
+        let on_staff = (bottom..=top).contains(&row);
+        let is_line = (row - bottom).rem_euclid(2) == 0;
+        let fill = if on_staff && is_line { '-' } else { ' ' };
+        let edge = if on_staff { '|' } else { ' ' };
+
+        let mut line = String::from(edge);
+        let (clef_line, clef_letter) = self.clef.marked_line();
+        line.push(if row == clef_line { clef_letter } else { fill });
+        for (accidental_row, accidental) in self.signature_rows() {
+            let mark = if accidental_row == row {
+                accidental.to_string()
+            } else {
+                String::new()
+            };
+            line.push_str(&cell(fill, &mark, fill));
+        }
+        for note in &self.notes {
+            match note {
+                None => {
+                    let head = if row == bottom + STAFF_HEIGHT / 2 {
+                        REST_MARK
+                    } else {
+                        fill
+                    };
+                    line.push_str(&cell(fill, "", head));
+                }
+                Some(pitch) => {
+                    let head = step(pitch);
+                    let ledger = !on_staff
+                        && is_line
+                        && ((head..bottom).contains(&row) || (top + 1..=head).contains(&row));
+                    let fill = if ledger { '-' } else { fill };
+                    if row == head {
+                        line.push_str(&cell(fill, &self.accidental_mark(pitch), NOTEHEAD));
+                    } else {
+                        line.push_str(&cell(fill, "", fill));
+                    }
+                }
+            }
+        }
+        line.push(fill);
+        line.push(edge);
+        line.trim_end().to_string()
+    }
+
+    /// Row and accidental of each sharp or flat in the key signature, in the order written
+    fn signature_rows(&self) -> Vec<(i32, Accidental)> {
+        let rows = if self.key.fifths() >= 0 {
+            TREBLE_SHARPS
+        } else {
+            TREBLE_FLATS
+        };
+        // The bass staff's signature sits two octaves lower, e.g. its first sharp on F3
+        let shift = match self.clef {
+            Clef::Treble => 0,
+            Clef::Bass => -2 * 7,
+        };
+        self.key
+            .accidentals()
+            .iter()
+            .zip(rows)
+            .map(|(note, row)| (row + shift, note.accidental))
+            .collect()
+    }
+
+    /// The accidental written before `pitch`, empty when the key signature already says it
+    fn accidental_mark(&self, pitch: &SpelledPitch) -> String {
+        match pitch.note.accidental {
+            accidental if accidental == self.key.accidental_for(pitch.note.name) => String::new(),
+            Accidental::Natural => "n".to_string(),
+            accidental => accidental.to_string(),
+        }
+    }
+}
+
+/// Letter-name steps above C0, so each line and space of a staff is one step
+fn step(pitch: &SpelledPitch) -> i32 {
+    pitch.octave as i32 * 7 + pitch.note.name.index()
+}
+
+/// A note's column on a staff: up to two characters of accidental right before `head`,
+/// padded with `fill`
+fn cell(fill: char, accidental: &str, head: char) -> String {
+    let mut cell: String = std::iter::repeat_n(fill, 3 - accidental.len()).collect();
+    cell.push_str(accidental);
+    cell.push(head);
+    cell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tempo::NoteValue;
+
+    fn notes(names: &[&str]) -> Vec<NoteWithOctave> {
+        names.iter().map(|n| n.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_render_one_octave() {
+        let keyboard = Keyboard::new(4, 1).with_highlighted(&notes(&["C4", "E4", "G#4"]));
+        assert_eq!(
+            keyboard.to_string(),
+            "|  |#| |#|  |  |#| |*| |#|  |\n\
+             |  |#| |#|  |  |#| |*| |#|  |\n\
+             | * |   | * |   |   |   |   |\n\
+             |___|___|___|___|___|___|___|\n  \
+             C       E\n                    \
+             G#"
+        );
+        assert_eq!(Keyboard::new(4, 1).to_string().lines().count(), 4);
+    }
+
+    #[test]
+    fn test_spanning() {
+        let keyboard = Keyboard::spanning(&notes(&["A3", "C#5"]));
+        assert_eq!((keyboard.first_octave, keyboard.octaves), (3, 3));
+        let lines: Vec<String> = keyboard.to_string().lines().map(String::from).collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines.iter().take(4).all(|line| line.len() == 3 * 28 + 1));
+        // A3 is the sixth white key, C#5 the first black key of the third octave
+        assert_eq!(lines[2].find('*'), Some(5 * 4 + 2));
+        assert_eq!(lines[0].find('*'), Some(14 * 4 + 4));
+        assert_eq!(lines[4].trim(), "A");
+        assert_eq!(lines[5].find("C#"), Some(14 * 4 + 4));
+
+        assert_eq!(Keyboard::new(9, 5).octaves, 2);
+        assert_eq!(Keyboard::spanning(&[]).first_octave, 4);
+    }
+
+    #[test]
+    fn test_string_tunings() {
+        assert_eq!(StringTuning::default().to_string(), "E2 A2 D3 G3 B3 E4");
+        assert_eq!(
+            "Drop-D".parse::<StringTuning>().unwrap().strings[0],
+            notes(&["D2"])[0]
+        );
+        assert_eq!("bass".parse::<StringTuning>().unwrap().strings.len(), 4);
+        let ukulele: StringTuning = "G4, C4, E4, A4".parse().unwrap();
+        assert_eq!(ukulele.to_string(), "G4 C4 E4 A4");
+        assert!("".parse::<StringTuning>().is_err());
+        assert!("E2 X2".parse::<StringTuning>().is_err());
+    }
+
+    #[test]
+    fn test_fretboard() {
+        let e_minor = notes(&["E3", "G3", "B3"]);
+        let fretboard = Fretboard::default()
+            .with_frets(5)
+            .with_notes(&e_minor, Note::E);
+        let drawn = fretboard.to_string();
+        let lines: Vec<&str> = drawn.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "     0    1   2   3   4   5");
+        assert_eq!(lines[1], "E    R ||---|---|-o-|---|---|");
+        assert_eq!(lines[2], "B    o ||---|---|---|---|-R-|");
+        assert_eq!(lines[6], "E    R ||---|---|-o-|---|---|");
+
+        // Open strings plus three frets on the low E and the first fret on the B
+        let positions = fretboard.positions();
+        assert!(positions.contains(&(0, 0)));
+        assert!(positions.contains(&(4, 0)));
+        assert!(!positions.contains(&(4, 1)));
+        assert_eq!(
+            positions.iter().filter(|(string, _)| *string == 0).count(),
+            2
+        );
+
+        assert_eq!(Fretboard::default().with_frets(40).frets, MAX_FRETS);
+    }
+
+    #[test]
+    fn test_labels_dont_overlap() {
+        let keyboard = Keyboard::new(4, 1).with_highlighted(&notes(&["C4", "C#4", "D4"]));
+        let lines: Vec<String> = keyboard.to_string().lines().map(String::from).collect();
+        assert_eq!(lines[4], "  C   D");
+        assert_eq!(lines[5], "    C#");
+    }
+
+    #[test]
+    fn test_staff() {
+        let melody = Melody::from_notes(&notes(&["G4", "A4", "B4", "C5"]), NoteValue::Quarter);
+        let staff = Staff::for_melody(&melody, KeySignature::new(-1).unwrap());
+        assert_eq!(staff.clef, Clef::Treble);
+        assert_eq!(
+            staff.to_string(),
+            "|----------------------|\n\
+             |                      |\n\
+             |----------------------|\n\
+             |                    o |\n\
+             |---b-----------no-----|\n\
+             |            o         |\n\
+             |G-------o-------------|\n\
+             |                      |\n\
+             |----------------------|"
+        );
+    }
+
+    #[test]
+    fn test_staff_ledger_lines_and_rests() {
+        let melody = Melody::from_notes(&notes(&["C4", "A5"]), NoteValue::Quarter)
+            .with_rest(NoteValue::Quarter);
+        let drawn = Staff::for_melody(&melody, KeySignature::new(0).unwrap()).to_string();
+        let lines: Vec<&str> = drawn.lines().collect();
+        // A5 and C4 each sit on a ledger line just beyond the staff
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "      ---o");
+        assert_eq!(lines[1], "");
+        assert_eq!(lines[6], "|------------r-|");
+        assert_eq!(lines[12], "  ---o");
+    }
+
+    #[test]
+    fn test_staff_for_scale() {
+        let d_major: Scale = "D4 major".parse().unwrap();
+        let staff = Staff::for_scale(&d_major);
+        assert_eq!(staff.key.sharps(), 2);
+        let drawn = staff.to_string();
+        // F# and C# come from the key signature, so no note needs its own sharp
+        assert_eq!(drawn.matches('#').count(), 2);
+        assert_eq!(drawn.matches('o').count(), 7);
+
+        let low: Scale = "E2 minor".parse().unwrap();
+        let staff = Staff::for_scale(&low);
+        assert_eq!(staff.clef, Clef::Bass);
+        assert_eq!(
+            staff.to_string().lines().nth(2),
+            Some("|F--#------------------------------|")
+        );
+        assert_eq!(Clef::for_notes(&[]), Clef::Treble);
+        assert_eq!("Bass".parse::<Clef>(), Ok(Clef::Bass));
+        assert!("alto".parse::<Clef>().is_err());
+    }
+}
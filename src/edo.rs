@@ -0,0 +1,165 @@
+use crate::notes::{Note, NoteWithOctave, Tuning};
+use std::fmt;
+use std::str::FromStr;
+
+/// Most divisions of the octave an EDO can have
+pub const MAX_EDO_DIVISIONS: u16 = 72;
+
+/// An equal division of the octave (EDO) into any number of steps, like 19-EDO or 24-EDO.
+/// 12-EDO is ordinary equal temperament.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Edo {
+    divisions: u16,
+}
+
+impl fmt::Display for Edo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-EDO", self.divisions)
+    }
+}
+
+/// Parse "19-EDO", "24-TET", "31edo" or just "19"
+impl FromStr for Edo {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let number = ["-edo", "edo", "-tet", "tet"]
+            .iter()
+            .find_map(|suffix| lower.strip_suffix(suffix))
+            .unwrap_or(&lower);
+        let divisions = number
+            .trim()
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid EDO: {}", s))?;
+        Edo::new(divisions)
+    }
+}
+
+impl Edo {
+    /// Quarter tones, the EDO of the quarter-tone interval quiz
+    pub const QUARTER_TONES: Edo = Edo { divisions: 24 };
+
+    /// `divisions` equal steps per octave, from 1 to `MAX_EDO_DIVISIONS`
+    pub fn new(divisions: u16) -> Result<Self, String> {
+        if !(1..=MAX_EDO_DIVISIONS).contains(&divisions) {
+            return Err(format!(
+                "An EDO needs 1 to {} divisions, not {}",
+                MAX_EDO_DIVISIONS, divisions
+            ));
+        }
+        Ok(Self { divisions })
+    }
+
+    pub fn divisions(&self) -> u16 {
+        self.divisions
+    }
+
+    /// Size of one step in cents, 50 for quarter tones
+    pub fn step_cents(&self) -> f64 {
+        1200.0 / self.divisions as f64
+    }
+
+    /// Frequency ratio of `steps` steps, which may be more than an octave or negative
+    pub fn ratio(&self, steps: i32) -> f64 {
+        2.0_f64.powf(steps as f64 / self.divisions as f64)
+    }
+
+    /// The step closest to `semitones` equal-tempered semitones, e.g. the 19-EDO fifth is 11 steps
+    pub fn steps_for(&self, semitones: i32) -> i32 {
+        (semitones as f64 * self.divisions as f64 / 12.0).round() as i32
+    }
+
+    /// Frequency of the step `steps` above (or below) C4, counted from the tuning's C4 like
+    /// `step_name`
+    pub fn frequency(&self, steps: i32, tuning: &Tuning) -> f64 {
+        let c4 = NoteWithOctave {
+            note: Note::C,
+            octave: 4,
+        };
+        tuning.frequency_of(&c4) * self.ratio(steps)
+    }
+
+    /// Name of the step `step` above (or below) C4, as the 12-EDO note at or below it with
+    /// an up (^) for each step higher, e.g. "^C4" for the 24-EDO quarter tone above C4.
+    /// Steps that are also 12-EDO notes keep their usual name, like "D4" for step 3 of 19-EDO.
+    pub fn step_name(&self, step: i32) -> String {
+        let semitones = step as f64 * 12.0 / self.divisions as f64;
+        let above = semitones.ceil() as i32;
+        let semitone = if self.steps_for(above) == step {
+            above
+        } else {
+            semitones.floor() as i32
+        };
+        let ups = (step - self.steps_for(semitone)).max(0) as usize;
+        let note = Note::from_semitone(semitone.rem_euclid(12) as u8)
+            .expect("semitones modulo 12 are always a note");
+        format!("{}{}{}", "^".repeat(ups), note, 4 + semitone.div_euclid(12))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_edo() {
+        assert_eq!("19-EDO".parse::<Edo>().unwrap().divisions(), 19);
+        assert_eq!("24-tet".parse::<Edo>().unwrap(), Edo::QUARTER_TONES);
+        assert_eq!("31edo".parse::<Edo>().unwrap().divisions(), 31);
+        assert_eq!("53".parse::<Edo>().unwrap().to_string(), "53-EDO");
+        assert!("0-edo".parse::<Edo>().is_err());
+        assert!("200-edo".parse::<Edo>().is_err());
+        assert!("many-edo".parse::<Edo>().is_err());
+    }
+
+    #[test]
+    fn test_edo_steps() {
+        let nineteen = Edo::new(19).unwrap();
+        assert_eq!(nineteen.steps_for(7), 11);
+        assert_eq!(nineteen.steps_for(4), 6);
+        assert_eq!(nineteen.steps_for(12), 19);
+        assert!((nineteen.ratio(19) - 2.0).abs() < 1e-9);
+        assert!((Edo::QUARTER_TONES.step_cents() - 50.0).abs() < 1e-9);
+
+        let tuning = Tuning::STANDARD;
+        let c4 = 261.6255653005986;
+        assert!((Edo::QUARTER_TONES.frequency(0, &tuning) - c4).abs() < 1e-9);
+        assert!((Edo::QUARTER_TONES.frequency(-24, &tuning) - c4 / 2.0).abs() < 1e-9);
+        assert!((Edo::QUARTER_TONES.frequency(18, &tuning) - 440.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_step_names_match_frequencies() {
+        // Each 24-EDO step sounds at the note it's named after, a step higher for an up
+        let quarter = Edo::QUARTER_TONES;
+        let tuning = Tuning::STANDARD;
+        for step in -48..=48 {
+            let name = quarter.step_name(step);
+            let note = name.trim_start_matches('^');
+            let ups = (name.len() - note.len()) as i32;
+            let note: NoteWithOctave = note.parse().unwrap();
+            let expected = tuning.frequency_of(&note) * quarter.ratio(ups);
+            assert!(
+                (quarter.frequency(step, &tuning) - expected).abs() < 1e-6,
+                "{} is step {}",
+                name,
+                step
+            );
+        }
+    }
+
+    #[test]
+    fn test_step_names() {
+        let quarter = Edo::QUARTER_TONES;
+        let names: Vec<String> = (0..5).map(|step| quarter.step_name(step)).collect();
+        assert_eq!(names, vec!["C4", "^C4", "C#4", "^C#4", "D4"]);
+        assert_eq!(quarter.step_name(-1), "^B3");
+        assert_eq!(quarter.step_name(24), "C5");
+
+        let nineteen = Edo::new(19).unwrap();
+        assert_eq!(nineteen.step_name(3), "D4");
+        assert_eq!(nineteen.step_name(11), "G4");
+        assert_eq!(nineteen.step_name(1), "^C4");
+    }
+}
@@ -0,0 +1,346 @@
+use crate::intervals::Interval;
+use crate::melody::Melody;
+use crate::notes::{Note, NoteWithOctave, Scale, ScaleType};
+use crate::progression::Progression;
+use crate::render::{Render, RenderConfig, write_wav};
+use crate::rhythm::Rhythm;
+use crate::stats::{AnswerRecord, Exercise};
+use crate::tempo::Tempo;
+use crate::training::{Cadence, NOTE_QUIZ_REFERENCE};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// How missed questions are written out
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ExportFormat {
+    /// A spreadsheet with a header row
+    #[default]
+    Csv,
+    /// A tab-separated deck for Anki's File > Import, with sound tags for the audio
+    Anki,
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "csv"),
+            ExportFormat::Anki => write!(f, "anki"),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "anki" | "tsv" => Ok(ExportFormat::Anki),
+            _ => Err(format!("Invalid export format: {}", s)),
+        }
+    }
+}
+
+impl ExportFormat {
+    /// Name of the deck file inside the export directory
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "missed.csv",
+            ExportFormat::Anki => "missed.txt",
+        }
+    }
+}
+
+/// A missed question turned into a flashcard
+#[derive(Debug, PartialEq, Clone)]
+pub struct Flashcard {
+    pub exercise: Exercise,
+    /// What the quiz asked, e.g. "Interval quiz: name the interval"
+    pub front: String,
+    /// The right answer, e.g. "Major Third"
+    pub back: String,
+    /// Wrong answers given, most frequent first
+    pub wrong_answers: Vec<String>,
+    pub misses: u32,
+    /// WAV file of the question, relative to the export directory, once rendered
+    pub audio: Option<String>,
+}
+
+impl Flashcard {
+    /// The question rendered as audio, for the exercises whose questions can be rebuilt from
+    /// the stats alone. Intervals and notes are heard from C4.
+    pub fn render(&self, config: &RenderConfig) -> Option<Vec<f32>> {
+        let question = self.back.as_str();
+        match self.exercise {
+            Exercise::Intervals => {
+                let interval = question.parse::<Interval>().ok()?;
+                let top = interval.apply_to(&NOTE_QUIZ_REFERENCE)?;
+                Some([NOTE_QUIZ_REFERENCE, top].render(config))
+            }
+            Exercise::Notes => {
+                let note = NoteWithOctave {
+                    note: question.parse::<Note>().ok()?,
+                    octave: NOTE_QUIZ_REFERENCE.octave,
+                };
+                Some([NOTE_QUIZ_REFERENCE, note].render(config))
+            }
+            Exercise::Cadences => {
+                let cadence = question.parse::<Cadence>().ok()?;
+                let key = Scale::new(NOTE_QUIZ_REFERENCE, ScaleType::Major);
+                let progression = Progression::parse(key, cadence.numerals()).ok()?;
+                Some(
+                    progression
+                        .chords()
+                        .iter()
+                        .flat_map(|chord| chord.render(config))
+                        .collect(),
+                )
+            }
+            Exercise::SingBack | Exercise::MelodicDictation => {
+                Some(question.parse::<Melody>().ok()?.render(config))
+            }
+            Exercise::Rhythm => {
+                let beat = config.note_duration.as_secs_f64();
+                let tempo = Tempo((60.0 / beat).round() as u16);
+                Some(question.parse::<Rhythm>().ok()?.render(tempo))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Every question answered wrong at least once in `records`, most missed first
+pub fn missed_cards(records: &[AnswerRecord]) -> Vec<Flashcard> {
+    let mut missed: BTreeMap<(Exercise, &str), BTreeMap<&str, u32>> = BTreeMap::new();
+    for record in records.iter().filter(|record| !record.correct) {
+        *missed
+            .entry((record.exercise, &record.question))
+            .or_default()
+            .entry(&record.answer)
+            .or_default() += 1;
+    }
+
+    let mut cards: Vec<Flashcard> = missed
+        .into_iter()
+        .map(|((exercise, question), answers)| {
+            let mut answers: Vec<(&str, u32)> = answers.into_iter().collect();
+            answers.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+            Flashcard {
+                exercise,
+                front: format!("{}: {}", exercise, prompt(exercise)),
+                back: question.to_string(),
+                wrong_answers: answers
+                    .iter()
+                    .map(|(answer, _)| answer.to_string())
+                    .collect(),
+                misses: answers.iter().map(|(_, count)| count).sum(),
+                audio: None,
+            }
+        })
+        .collect();
+    cards.sort_by_key(|card| std::cmp::Reverse(card.misses));
+    cards
+}
+
+/// Write the missed questions in `records` to `dir` as a deck in `format`, with a WAV file
+/// for each question that can be rendered. Returns where the deck was written.
+pub fn export_missed(
+    records: &[AnswerRecord],
+    format: ExportFormat,
+    dir: impl AsRef<Path>,
+    config: &RenderConfig,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let mut cards = missed_cards(records);
+    for (i, card) in cards.iter_mut().enumerate() {
+        if let Some(samples) = card.render(config) {
+            // Anki keeps every media file in one folder, so the names need to be distinctive
+            let name = format!("ear-trainer-missed-{:03}.wav", i + 1);
+            write_wav(dir.join(&name), &samples)?;
+            card.audio = Some(name);
+        }
+    }
+
+    let deck = match format {
+        ExportFormat::Csv => to_csv(&cards),
+        ExportFormat::Anki => to_anki(&cards),
+    };
+    let path = dir.join(format.file_name());
+    fs::write(&path, deck)?;
+    Ok(path)
+}
+
+/// One row per card: exercise, front, back, wrong answers, misses and audio file
+pub fn to_csv(cards: &[Flashcard]) -> String {
+    let mut csv = String::from("exercise,front,back,wrong_answers,misses,audio\n");
+    for card in cards {
+        let fields = [
+            card.exercise.to_string(),
+            card.front.clone(),
+            card.back.clone(),
+            card.wrong_answers.join("; "),
+            card.misses.to_string(),
+            card.audio.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Anki's plain-text import format: the front with a sound tag, then the back
+pub fn to_anki(cards: &[Flashcard]) -> String {
+    let mut deck = String::from("#separator:tab\n#html:true\n#tags column:3\n");
+    for card in cards {
+        let mut front = card.front.clone();
+        if let Some(audio) = &card.audio {
+            front.push_str(&format!(" [sound:{}]", audio));
+        }
+        let back = format!(
+            "{}<br>Missed {} time{}, answered as {}",
+            card.back,
+            card.misses,
+            if card.misses == 1 { "" } else { "s" },
+            card.wrong_answers.join(", ")
+        );
+        let tag = format!("ear-trainer {:?}", card.exercise).to_lowercase();
+        deck.push_str(&format!(
+            "{}\t{}\t{}\n",
+            anki_field(&front),
+            anki_field(&back),
+            tag
+        ));
+    }
+    deck
+}
+
+/// What the user is asked to do on the front of a card
+fn prompt(exercise: Exercise) -> &'static str {
+    match exercise {
+        Exercise::Intervals | Exercise::QuarterTones => "name the interval",
+        Exercise::IntervalComparison => "which interval is larger?",
+        Exercise::EarComparison => "which ear hears the higher note?",
+        Exercise::Notes => "name the note after C4",
+        Exercise::Frequency => "name the nearest note",
+        Exercise::Cadences => "name the cadence",
+        Exercise::Inversions => "name the inversion",
+        Exercise::Degrees => "name the scale degree",
+        Exercise::SingBack => "sing the melody back",
+        Exercise::Intonation => "sharp, flat or in tune?",
+        Exercise::Rhythm => "write down the rhythm",
+        Exercise::MelodicDictation => "write down the melody",
+    }
+}
+
+/// Quote a CSV field if it holds a comma, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Tabs and line breaks would start a new field or card
+fn anki_field(field: &str) -> String {
+    field.replace('\t', " ").replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn answer(exercise: Exercise, question: &str, answer: &str) -> AnswerRecord {
+        AnswerRecord::new(
+            exercise,
+            question,
+            answer,
+            question == answer,
+            Duration::from_secs(2),
+        )
+    }
+
+    fn records() -> Vec<AnswerRecord> {
+        vec![
+            answer(Exercise::Intervals, "Minor Sixth", "Major Sixth"),
+            answer(Exercise::Intervals, "Minor Sixth", "Major Sixth"),
+            answer(Exercise::Intervals, "Minor Sixth", "Perfect Fifth"),
+            answer(Exercise::Intervals, "Perfect Fifth", "Perfect Fifth"),
+            answer(Exercise::Cadences, "Half", "Plagal"),
+            answer(Exercise::Inversions, "First inversion", "Root position"),
+        ]
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("csv".parse::<ExportFormat>(), Ok(ExportFormat::Csv));
+        assert_eq!("Anki".parse::<ExportFormat>(), Ok(ExportFormat::Anki));
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+        assert_eq!(ExportFormat::Anki.to_string(), "anki");
+    }
+
+    #[test]
+    fn test_missed_cards() {
+        let cards = missed_cards(&records());
+        assert_eq!(cards.len(), 3);
+        assert_eq!(cards[0].back, "Minor Sixth");
+        assert_eq!(cards[0].misses, 3);
+        assert_eq!(cards[0].wrong_answers, vec!["Major Sixth", "Perfect Fifth"]);
+        assert_eq!(cards[0].front, "Interval quiz: name the interval");
+
+        let config = RenderConfig::default();
+        assert_eq!(
+            cards[0].render(&config).map(|samples| samples.len()),
+            Some(2 * crate::audio::SAMPLE_RATE as usize / 2)
+        );
+        let cadence = cards.iter().find(|c| c.exercise == Exercise::Cadences);
+        assert!(cadence.unwrap().render(&config).is_some());
+        let inversion = cards.iter().find(|c| c.exercise == Exercise::Inversions);
+        assert_eq!(inversion.unwrap().render(&config), None);
+    }
+
+    #[test]
+    fn test_deck_formats() {
+        let mut cards = missed_cards(&records());
+        cards[0].audio = Some("ear-trainer-missed-001.wav".to_string());
+        cards[1].back = "Say \"hi\", twice".to_string();
+
+        let csv = to_csv(&cards);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "exercise,front,back,wrong_answers,misses,audio");
+        assert_eq!(
+            lines[1],
+            "Interval quiz,Interval quiz: name the interval,Minor Sixth,Major Sixth; Perfect Fifth,3,ear-trainer-missed-001.wav"
+        );
+        assert!(lines[2].contains("\"Say \"\"hi\"\", twice\""));
+
+        let anki = to_anki(&cards);
+        let first = anki.lines().nth(3).unwrap();
+        assert_eq!(
+            first,
+            "Interval quiz: name the interval [sound:ear-trainer-missed-001.wav]\t\
+             Minor Sixth<br>Missed 3 times, answered as Major Sixth, Perfect Fifth\t\
+             ear-trainer intervals"
+        );
+    }
+
+    #[test]
+    fn test_export_missed() {
+        let dir = std::env::temp_dir().join(format!("ear-trainer-export-{}", std::process::id()));
+        let config = RenderConfig {
+            note_duration: Duration::from_millis(50),
+            ..RenderConfig::default()
+        };
+        let path = export_missed(&records(), ExportFormat::Anki, &dir, &config).unwrap();
+        assert_eq!(path, dir.join("missed.txt"));
+        assert!(dir.join("ear-trainer-missed-001.wav").exists());
+        assert!(!dir.join("ear-trainer-missed-003.wav").exists());
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 6);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
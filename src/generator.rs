@@ -0,0 +1,283 @@
+use crate::chords::{Chord, ChordQuality};
+use crate::intervals::Interval;
+use crate::melody::Melody;
+use crate::notes::{NoteWithOctave, Scale};
+use crate::tempo::NoteValue;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+/// Steps between melody notes in scale degrees, with how likely each is
+const MELODY_STEPS: [(i32, u32); 6] = [(-2, 1), (-1, 3), (0, 1), (1, 3), (2, 1), (4, 1)];
+
+/// A random number generator that gives the same questions for the same seed
+pub fn seeded(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// Any note from `low` to `high`, inclusive
+pub fn random_note<R: Rng>(
+    rng: &mut R,
+    low: &NoteWithOctave,
+    high: &NoteWithOctave,
+) -> Result<NoteWithOctave, String> {
+    if low > high {
+        return Err(format!("{} is above {}", low, high));
+    }
+    let semitone = rng.gen_range(low.absolute_semitone()..=high.absolute_semitone());
+    NoteWithOctave::from_absolute_semitone(semitone)
+        .ok_or_else(|| format!("No notes between {} and {}", low, high))
+}
+
+/// One of the `allowed` intervals
+pub fn random_interval<R: Rng>(rng: &mut R, allowed: &[Interval]) -> Result<Interval, String> {
+    allowed
+        .choose(rng)
+        .copied()
+        .ok_or_else(|| "No intervals to choose from".to_string())
+}
+
+/// A chord of one of the `qualities`, with every tone between `low` and `high`
+pub fn random_chord<R: Rng>(
+    rng: &mut R,
+    qualities: &[ChordQuality],
+    low: &NoteWithOctave,
+    high: &NoteWithOctave,
+) -> Result<Chord, String> {
+    let quality = qualities
+        .choose(rng)
+        .cloned()
+        .ok_or("No chord qualities to choose from")?;
+    let span = quality.intervals().last().copied().unwrap_or(0) as i32;
+    let top_root = high
+        .checked_sub(span)
+        .filter(|top_root| top_root >= low)
+        .ok_or_else(|| {
+            format!(
+                "A {} chord doesn't fit between {} and {}",
+                quality, low, high
+            )
+        })?;
+    Ok(Chord::new(random_note(rng, low, &top_root)?, quality))
+}
+
+/// The chord's tones rearranged, lowest first: a random inversion,
+/// sometimes opened up by raising the middle tone an octave
+pub fn random_voicing<R: Rng>(rng: &mut R, chord: &Chord) -> Vec<NoteWithOctave> {
+    let mut notes = chord.notes();
+    let inversion = rng.gen_range(0..notes.len().max(1));
+    for note in notes.iter_mut().take(inversion) {
+        *note = note.transpose(12).unwrap_or(*note);
+    }
+    notes.sort();
+    if notes.len() >= 3 && rng.gen_bool(0.5) {
+        notes[1] = notes[1].transpose(12).unwrap_or(notes[1]);
+        notes.sort();
+    }
+    notes
+}
+
+/// A mostly stepwise melody of `length` notes in `key`, starting and ending on the tonic
+/// and staying within the octave above it. Non-heptatonic keys move through their own notes.
+pub fn random_melody<R: Rng>(
+    rng: &mut R,
+    key: &Scale,
+    length: usize,
+    value: NoteValue,
+) -> Result<Melody, String> {
+    let top = key.scale_type.intervals().len() as i32;
+    let steps = WeightedIndex::new(MELODY_STEPS.iter().map(|(_, weight)| weight))
+        .map_err(|e| e.to_string())?;
+
+    let mut degrees = vec![0];
+    while degrees.len() < length {
+        let last = *degrees.last().unwrap_or(&0);
+        let step = MELODY_STEPS[steps.sample(rng)].0;
+        // Bounce off the ends of the octave instead of leaving it
+        let next = if (0..=top).contains(&(last + step)) {
+            last + step
+        } else {
+            (last - step).clamp(0, top)
+        };
+        degrees.push(next);
+    }
+    if length > 1 {
+        let last = degrees.len() - 1;
+        degrees[last] = if degrees[last - 1] * 2 > top { top } else { 0 };
+    }
+
+    let notes = degrees
+        .into_iter()
+        .take(length)
+        .map(|degree| {
+            key.degree(degree as usize + 1)
+                .ok_or_else(|| format!("{} has no note on degree {}", key, degree + 1))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Melody::from_notes(&notes, value))
+}
+
+/// A melody of `length` notes in `key` that starts on the tonic, stays within `range` scale
+/// degrees above it and never leaps more than `max_leap` degrees. Small steps are likeliest,
+/// and the melody comes home to a tonic at the end when that's within a leap.
+pub fn random_diatonic_melody<R: Rng>(
+    rng: &mut R,
+    key: &Scale,
+    length: usize,
+    range: u8,
+    max_leap: u8,
+    value: NoteValue,
+) -> Result<Melody, String> {
+    if max_leap == 0 || range == 0 {
+        return Err("A melody needs room to move".to_string());
+    }
+    let (top, max_leap) = (range as i32, max_leap.min(range) as i32);
+    let steps: Vec<i32> = (-max_leap..=max_leap).filter(|&step| step != 0).collect();
+    let weights = WeightedIndex::new(steps.iter().map(|step| max_leap + 1 - step.abs()))
+        .map_err(|e| e.to_string())?;
+
+    let mut degrees = vec![0];
+    while degrees.len() < length {
+        let last = *degrees.last().unwrap_or(&0);
+        let step = steps[weights.sample(rng)];
+        // Bounce off the ends of the range instead of leaving it
+        let next = if (0..=top).contains(&(last + step)) {
+            last + step
+        } else {
+            (last - step).clamp(0, top)
+        };
+        degrees.push(next);
+    }
+    let per_octave = key.scale_type.intervals().len() as i32;
+    if length > 1 {
+        let last = degrees.len() - 1;
+        let home = (0..=top)
+            .step_by(per_octave.max(1) as usize)
+            .min_by_key(|tonic| (tonic - degrees[last - 1]).abs())
+            .unwrap_or(0);
+        if (home - degrees[last - 1]).abs() <= max_leap {
+            degrees[last] = home;
+        }
+    }
+
+    let notes = degrees
+        .into_iter()
+        .take(length)
+        .map(|degree| {
+            key.degree(degree as usize + 1)
+                .ok_or_else(|| format!("{} has no note on degree {}", key, degree + 1))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Melody::from_notes(&notes, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::ScaleType;
+
+    fn note(s: &str) -> NoteWithOctave {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_random_note_in_range() {
+        let mut rng = seeded(1);
+        for _ in 0..100 {
+            let n = random_note(&mut rng, &note("E3"), &note("G3")).unwrap();
+            assert!((note("E3")..=note("G3")).contains(&n));
+        }
+        assert_eq!(
+            random_note(&mut rng, &note("C4"), &note("C4")).unwrap(),
+            note("C4")
+        );
+        assert!(random_note(&mut rng, &note("C5"), &note("C4")).is_err());
+    }
+
+    #[test]
+    fn test_seeded_generators_repeat() {
+        let allowed: Vec<Interval> = (1..=12).map(Interval::from_semitones).collect();
+        let pick = |seed| {
+            let mut rng = seeded(seed);
+            (0..10)
+                .map(|_| random_interval(&mut rng, &allowed).unwrap().semitones())
+                .collect::<Vec<u8>>()
+        };
+        assert_eq!(pick(42), pick(42));
+        assert!(random_interval(&mut seeded(0), &[]).is_err());
+    }
+
+    #[test]
+    fn test_random_chord_fits_range() {
+        let mut rng = seeded(2);
+        let qualities = [ChordQuality::Major7, ChordQuality::Minor];
+        for _ in 0..50 {
+            let chord = random_chord(&mut rng, &qualities, &note("C4"), &note("C5")).unwrap();
+            assert!(chord.root >= note("C4"));
+            assert!(*chord.notes().last().unwrap() <= note("C5"));
+        }
+        assert!(random_chord(&mut rng, &qualities, &note("C4"), &note("E4")).is_err());
+        assert!(random_chord(&mut rng, &[], &note("C4"), &note("C5")).is_err());
+    }
+
+    #[test]
+    fn test_random_voicing_keeps_chord_tones() {
+        let mut rng = seeded(3);
+        let chord = Chord::new(note("C4"), ChordQuality::Dominant7);
+        for _ in 0..20 {
+            let voicing = random_voicing(&mut rng, &chord);
+            assert_eq!(voicing.len(), 4);
+            assert!(voicing.windows(2).all(|pair| pair[0] <= pair[1]));
+            let mut names: Vec<_> = voicing.iter().map(|n| n.note).collect();
+            names.sort();
+            let mut expected: Vec<_> = chord.notes().iter().map(|n| n.note).collect();
+            expected.sort();
+            assert_eq!(names, expected);
+        }
+    }
+
+    #[test]
+    fn test_random_melody_is_diatonic() {
+        let key = Scale::new(note("D4"), ScaleType::Major);
+        let in_key: Vec<_> = key.notes().iter().map(|n| n.note).collect();
+        let mut rng = seeded(4);
+        for _ in 0..20 {
+            let melody = random_melody(&mut rng, &key, 8, NoteValue::Quarter).unwrap();
+            let notes = melody.notes();
+            assert_eq!(notes.len(), 8);
+            assert_eq!(notes[0], note("D4"));
+            assert_eq!(notes[7].note, key.root.note);
+            assert!(notes.iter().all(|n| in_key.contains(&n.note)));
+            assert!(notes.iter().all(|n| (note("D4")..=note("D5")).contains(n)));
+        }
+        assert!(
+            random_melody(&mut rng, &key, 0, NoteValue::Quarter)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_random_diatonic_melody_keeps_its_limits() {
+        let key = Scale::new(note("A3"), ScaleType::Minor);
+        let mut rng = seeded(5);
+        for _ in 0..50 {
+            let melody =
+                random_diatonic_melody(&mut rng, &key, 6, 4, 2, NoteValue::Quarter).unwrap();
+            let degrees: Vec<usize> = melody
+                .notes()
+                .iter()
+                .map(|n| (1..=5).find(|&d| key.degree(d) == Some(*n)).unwrap())
+                .collect();
+            assert_eq!(degrees.len(), 6);
+            assert_eq!(degrees[0], 1);
+            assert!(
+                degrees
+                    .windows(2)
+                    .all(|pair| pair[0].abs_diff(pair[1]) <= 2)
+            );
+        }
+        assert!(random_diatonic_melody(&mut rng, &key, 4, 7, 0, NoteValue::Quarter).is_err());
+    }
+}
@@ -0,0 +1,534 @@
+use crate::display::StringTuning;
+use crate::notes::{NoteWithOctave, Pitch, Tuning};
+#[cfg(feature = "audio")]
+use crate::recording::lock;
+#[cfg(feature = "audio")]
+use rodio::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+#[cfg(feature = "audio")]
+use rodio::cpal::{self, FromSample, SampleFormat, SizedSample, StreamConfig};
+#[cfg(feature = "audio")]
+use std::collections::VecDeque;
+use std::fmt;
+#[cfg(feature = "audio")]
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "audio")]
+type SharedBuffer = Arc<Mutex<VecDeque<f32>>>;
+
+/// How much microphone audio `Microphone::open` keeps by default
+pub const DEFAULT_BUFFER: Duration = Duration::from_secs(2);
+
+/// Samples analysed per pitch reading, enough for two periods of the lowest default pitch
+pub const ANALYSIS_WINDOW: usize = 2048;
+
+/// Readings within this many cents of the note count as in tune
+pub const IN_TUNE_CENTS: f64 = 5.0;
+
+/// Readings in a row that must be in tune before the string tuner moves on,
+/// so a note passing through the target on its way doesn't count
+pub const TUNED_READINGS: u32 = 5;
+
+/// Live capture from the default microphone, downmixed to mono.
+/// Only the most recent audio is kept, like a tape loop.
+#[cfg(feature = "audio")]
+pub struct Microphone {
+    stream: cpal::Stream,
+    buffer: SharedBuffer,
+    capacity: usize,
+    sample_rate: u32,
+}
+
+#[cfg(feature = "audio")]
+impl Microphone {
+    /// Start capturing, keeping the last `DEFAULT_BUFFER` of audio
+    pub fn open() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::open_with_buffer(DEFAULT_BUFFER)
+    }
+
+    /// Start capturing, keeping the last `length` of audio
+    pub fn open_with_buffer(length: Duration) -> Result<Self, Box<dyn std::error::Error>> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or("No microphone input device available")?;
+        let supported = device.default_input_config()?;
+        let sample_format = supported.sample_format();
+        let config: StreamConfig = supported.into();
+        let sample_rate = config.sample_rate.0;
+        let capacity = (length.as_secs_f64() * sample_rate as f64).ceil() as usize;
+        let buffer: SharedBuffer = Arc::new(Mutex::new(VecDeque::with_capacity(capacity)));
+
+        let stream = match sample_format {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, &buffer, capacity)?,
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, &buffer, capacity)?,
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, &buffer, capacity)?,
+            other => return Err(format!("Unsupported microphone sample format: {}", other).into()),
+        };
+        stream.play()?;
+
+        Ok(Self {
+            stream,
+            buffer,
+            capacity,
+            sample_rate,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Most samples the buffer holds
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Copy of the latest `count` samples, or fewer if not that many have arrived
+    pub fn latest(&self, count: usize) -> Vec<f32> {
+        let buffer = lock(&self.buffer);
+        let skip = buffer.len().saturating_sub(count);
+        buffer.iter().skip(skip).copied().collect()
+    }
+
+    /// Everything captured since the last `take`, emptying the buffer
+    pub fn take(&self) -> Vec<f32> {
+        lock(&self.buffer).drain(..).collect()
+    }
+
+    /// Throw away what has been captured so far
+    pub fn clear(&self) {
+        lock(&self.buffer).clear();
+    }
+
+    /// The pitch of the latest `ANALYSIS_WINDOW` samples, if there is a clear one
+    pub fn read_pitch(&self, detector: &PitchDetector, tuning: &Tuning) -> Option<PitchReading> {
+        let frequency = detector.detect(&self.latest(ANALYSIS_WINDOW), self.sample_rate)?;
+        PitchReading::new(frequency, tuning).ok()
+    }
+
+    /// Stop capturing
+    pub fn close(self) -> Result<(), Box<dyn std::error::Error>> {
+        self.stream.pause()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "audio")]
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    buffer: &SharedBuffer,
+    capacity: usize,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    let buffer = Arc::clone(buffer);
+
+    device.build_input_stream(
+        config,
+        move |data: &[T], _| {
+            let mut buffer = lock(&buffer);
+            for frame in data.chunks(channels) {
+                if buffer.len() == capacity {
+                    buffer.pop_front();
+                }
+                buffer.push_back(
+                    frame.iter().map(|s| s.to_sample::<f32>()).sum::<f32>() / channels as f32,
+                );
+            }
+        },
+        |e| eprintln!("❌ Microphone error: {}", e),
+        None,
+    )
+}
+
+/// A detected pitch, with the nearest note and how far off it is
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PitchReading {
+    pub frequency: f64,
+    pub note: NoteWithOctave,
+    /// Offset from the nearest note, from -50 (flat) to +50 (sharp)
+    pub cents: f64,
+}
+
+impl fmt::Display for PitchReading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:+.0} cents ({:.2} Hz)",
+            self.note, self.cents, self.frequency
+        )
+    }
+}
+
+impl PitchReading {
+    pub fn new(frequency: f64, tuning: &Tuning) -> Result<Self, String> {
+        let Pitch { note, cents } = Pitch::from_frequency(frequency, tuning)?;
+        Ok(Self {
+            frequency,
+            note,
+            cents,
+        })
+    }
+
+    /// The reading as a note and cents offset, without the frequency
+    pub fn pitch(&self) -> Pitch {
+        Pitch::new(self.note, self.cents)
+    }
+
+    pub fn is_in_tune(&self) -> bool {
+        self.cents.abs() <= IN_TUNE_CENTS
+    }
+}
+
+/// A tuner needle `width` cells wide, centred when `cents` is zero and
+/// at either end for a quarter-tone flat or sharp
+pub fn needle(cents: f64, width: usize) -> String {
+    let width = width.max(3) | 1;
+    let centre = width / 2;
+    let offset = (cents / 50.0 * centre as f64).round() as i64;
+    let position = (centre as i64 + offset).clamp(0, width as i64 - 1) as usize;
+    let bar: String = (0..width)
+        .map(|i| {
+            if i == position {
+                '●'
+            } else if i == centre {
+                '│'
+            } else {
+                '─'
+            }
+        })
+        .collect();
+    format!("[{}]", bar)
+}
+
+/// Signed distance from `reference` to `frequency` in cents; positive when sharp
+pub fn cents_from(reference: f64, frequency: f64) -> f64 {
+    1200.0 * (frequency / reference).log2()
+}
+
+/// How a reading compares with the string being tuned
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StringReading {
+    /// No clear pitch was heard
+    Silent,
+    /// Cents from the string's target, positive when sharp
+    Off(f64),
+    /// The string has been in tune for `TUNED_READINGS` readings and the tuner has moved on
+    Tuned,
+}
+
+/// Guides through an instrument's open strings one at a time, lowest first, moving on by
+/// itself once the heard pitch settles within the threshold of the string's target
+#[derive(Debug, PartialEq, Clone)]
+pub struct StringTuner {
+    strings: Vec<NoteWithOctave>,
+    /// Index of the string being tuned, `strings.len()` once they're all done
+    current: usize,
+    /// Cents from the target that count as in tune
+    threshold: f64,
+    /// In-tune readings in a row for the current string
+    in_tune: u32,
+}
+
+impl StringTuner {
+    pub fn new(tuning: &StringTuning) -> Self {
+        Self {
+            strings: tuning.strings.clone(),
+            current: 0,
+            threshold: IN_TUNE_CENTS,
+            in_tune: 0,
+        }
+    }
+
+    /// Count strings as in tune within `cents` of their target rather than `IN_TUNE_CENTS`
+    pub fn with_threshold(mut self, cents: f64) -> Self {
+        self.threshold = cents.abs();
+        self
+    }
+
+    /// The string being tuned, or `None` once every string is done
+    pub fn target(&self) -> Option<NoteWithOctave> {
+        self.strings.get(self.current).copied()
+    }
+
+    /// Position of the string being tuned, counting the lowest as 0
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.current >= self.strings.len()
+    }
+
+    /// Move on to the next string without waiting for this one
+    pub fn skip(&mut self) {
+        self.current = (self.current + 1).min(self.strings.len());
+        self.in_tune = 0;
+    }
+
+    /// Compare a detected `frequency` with the target string as `tuning` pitches it,
+    /// moving on to the next string once it has stayed in tune
+    pub fn hear(&mut self, frequency: Option<f64>, tuning: &Tuning) -> StringReading {
+        let (Some(target), Some(frequency)) = (self.target(), frequency) else {
+            self.in_tune = 0;
+            return StringReading::Silent;
+        };
+        let cents = cents_from(tuning.frequency_of(&target), frequency);
+        if cents.abs() > self.threshold {
+            self.in_tune = 0;
+            return StringReading::Off(cents);
+        }
+        self.in_tune += 1;
+        if self.in_tune < TUNED_READINGS {
+            return StringReading::Off(cents);
+        }
+        self.skip();
+        StringReading::Tuned
+    }
+}
+
+/// YIN pitch detection, tuned for voices and instruments
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PitchDetector {
+    pub min_frequency: f64,
+    pub max_frequency: f64,
+    /// How aperiodic a signal may be and still count as pitched; lower is stricter
+    pub threshold: f64,
+    /// RMS level below which the input counts as silence
+    pub silence: f32,
+}
+
+impl Default for PitchDetector {
+    fn default() -> Self {
+        Self {
+            min_frequency: 50.0,
+            max_frequency: 2000.0,
+            threshold: 0.15,
+            silence: 0.01,
+        }
+    }
+}
+
+impl PitchDetector {
+    /// Fundamental frequency of `samples` in Hz, or `None` for silence and unpitched sound
+    pub fn detect(&self, samples: &[f32], sample_rate: u32) -> Option<f64> {
+        let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt();
+        if rms < self.silence {
+            return None;
+        }
+
+        let rate = sample_rate as f64;
+        let min_lag = (rate / self.max_frequency).floor().max(2.0) as usize;
+        let max_lag = (rate / self.min_frequency).ceil() as usize;
+        let window = samples.len() / 2;
+        if max_lag >= window || min_lag >= max_lag {
+            return None;
+        }
+
+        // Difference function, normalised by its running mean (steps 2 and 3 of YIN)
+        let mut normalized = vec![1.0; max_lag + 1];
+        let mut running_sum = 0.0;
+        for lag in 1..=max_lag {
+            let difference: f64 = (0..window)
+                .map(|i| {
+                    let delta = (samples[i] - samples[i + lag]) as f64;
+                    delta * delta
+                })
+                .sum();
+            running_sum += difference;
+            normalized[lag] = if running_sum > 0.0 {
+                difference * lag as f64 / running_sum
+            } else {
+                1.0
+            };
+        }
+
+        // First dip under the threshold, followed down to its lowest point
+        let mut lag = (min_lag..max_lag).find(|&lag| normalized[lag] < self.threshold)?;
+        while lag + 1 < max_lag && normalized[lag + 1] < normalized[lag] {
+            lag += 1;
+        }
+
+        // Parabolic interpolation between neighbouring lags for sub-sample accuracy
+        let (before, at, after) = (normalized[lag - 1], normalized[lag], normalized[lag + 1]);
+        let curvature = before - 2.0 * at + after;
+        let offset = if curvature.abs() > f64::EPSILON {
+            (0.5 * (before - after) / curvature).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        Some(rate / (lag as f64 + offset))
+    }
+
+    /// Pitch of every `ANALYSIS_WINDOW`-sample frame in a recording, one frame every `hop` samples
+    pub fn track(&self, samples: &[f32], sample_rate: u32, hop: usize) -> Vec<Option<f64>> {
+        samples
+            .windows(ANALYSIS_WINDOW)
+            .step_by(hop.max(1))
+            .map(|frame| self.detect(frame, sample_rate))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    const RATE: u32 = 44100;
+
+    fn tone(frequency: f32, count: usize) -> Vec<f32> {
+        (0..count)
+            .map(|i| 0.5 * (TAU * frequency * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_sine_pitch() {
+        let detector = PitchDetector::default();
+        for frequency in [82.41, 220.0, 440.0, 1046.5] {
+            let detected = detector
+                .detect(&tone(frequency, ANALYSIS_WINDOW * 2), RATE)
+                .unwrap();
+            assert!(
+                cents_from(frequency as f64, detected).abs() < 5.0,
+                "{} Hz detected as {} Hz",
+                frequency,
+                detected
+            );
+        }
+    }
+
+    #[test]
+    fn test_detect_harmonic_tone() {
+        // A strong second harmonic shouldn't fool the detector into an octave error
+        let samples: Vec<f32> = tone(196.0, ANALYSIS_WINDOW)
+            .iter()
+            .zip(tone(392.0, ANALYSIS_WINDOW))
+            .map(|(a, b)| a + 0.8 * b)
+            .collect();
+        let detected = PitchDetector::default().detect(&samples, RATE).unwrap();
+        assert!(cents_from(196.0, detected).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_no_pitch_in_silence() {
+        let detector = PitchDetector::default();
+        assert_eq!(detector.detect(&vec![0.0; ANALYSIS_WINDOW], RATE), None);
+        assert_eq!(detector.detect(&[], RATE), None);
+        // Too short to hold two periods of the lowest pitch
+        assert_eq!(detector.detect(&tone(440.0, 256), RATE), None);
+    }
+
+    #[test]
+    fn test_track_follows_pitch_changes() {
+        let mut samples = vec![0.0; ANALYSIS_WINDOW];
+        samples.extend(tone(220.0, ANALYSIS_WINDOW * 2));
+        samples.extend(tone(330.0, ANALYSIS_WINDOW * 2));
+        let track = PitchDetector::default().track(&samples, RATE, ANALYSIS_WINDOW);
+        assert_eq!(track.len(), 5);
+        assert_eq!(track[0], None);
+        assert!(cents_from(220.0, track[1].unwrap()).abs() < 5.0);
+        assert!(cents_from(330.0, track[4].unwrap()).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_pitch_reading() {
+        let tuning = Tuning::default();
+        let reading = PitchReading::new(445.0, &tuning).unwrap();
+        assert_eq!(reading.note.to_string(), "A4");
+        assert!((reading.cents - 19.56).abs() < 0.01);
+        assert_eq!(reading.pitch().to_string(), "A4 +20 cents");
+
+        let flat = PitchReading::new(258.0, &tuning).unwrap();
+        assert_eq!(flat.note.to_string(), "C4");
+        assert_eq!(flat.to_string(), "C4 -24 cents (258.00 Hz)");
+        assert!(PitchReading::new(0.0, &tuning).is_err());
+        assert!(PitchReading::new(441.0, &tuning).unwrap().is_in_tune());
+        assert!(!reading.is_in_tune());
+    }
+
+    #[test]
+    fn test_string_tuner() {
+        let tuning = Tuning::default();
+        let mut tuner = StringTuner::new(&"bass".parse().unwrap());
+        assert_eq!(tuner.len(), 4);
+        assert_eq!(tuner.target(), Some("E1".parse().unwrap()));
+        let e1 = tuning.frequency_of(&"E1".parse().unwrap());
+
+        assert_eq!(tuner.hear(None, &tuning), StringReading::Silent);
+        let StringReading::Off(cents) = tuner.hear(Some(e1 * 1.01), &tuning) else {
+            panic!("a sharp string isn't in tune");
+        };
+        assert!((cents - 17.2).abs() < 0.1);
+
+        // A passing reading in tune isn't enough, it has to stay there
+        assert!(matches!(
+            tuner.hear(Some(e1), &tuning),
+            StringReading::Off(_)
+        ));
+        tuner.hear(Some(e1 * 1.01), &tuning);
+        for _ in 1..TUNED_READINGS {
+            assert!(matches!(
+                tuner.hear(Some(e1), &tuning),
+                StringReading::Off(_)
+            ));
+        }
+        assert_eq!(tuner.hear(Some(e1), &tuning), StringReading::Tuned);
+        assert_eq!(tuner.position(), 1);
+        assert_eq!(tuner.target(), Some("A1".parse().unwrap()));
+
+        // An octave up is the wrong string, not in tune
+        let a1 = tuning.frequency_of(&"A1".parse().unwrap());
+        assert!(matches!(tuner.hear(Some(a1 * 2.0), &tuning), StringReading::Off(c) if c > 1199.0));
+
+        tuner.skip();
+        tuner.skip();
+        assert!(!tuner.is_done());
+        tuner.skip();
+        assert!(tuner.is_done());
+        assert_eq!(tuner.target(), None);
+        assert_eq!(tuner.hear(Some(a1), &tuning), StringReading::Silent);
+    }
+
+    #[test]
+    fn test_string_tuner_threshold() {
+        let tuning = Tuning::default();
+        let strings: StringTuning = "A4".parse().unwrap();
+        let sharp = 440.0 * 1.005;
+        let mut strict = StringTuner::new(&strings);
+        let mut loose = StringTuner::new(&strings).with_threshold(10.0);
+        for _ in 0..TUNED_READINGS {
+            assert!(matches!(
+                strict.hear(Some(sharp), &tuning),
+                StringReading::Off(_)
+            ));
+            loose.hear(Some(sharp), &tuning);
+        }
+        assert!(!strict.is_done());
+        assert!(loose.is_done());
+    }
+
+    #[test]
+    fn test_needle() {
+        assert_eq!(needle(0.0, 9), "[────●────]");
+        assert_eq!(needle(25.0, 9), "[────│─●──]");
+        assert_eq!(needle(-50.0, 9), "[●───│────]");
+        // Past a quarter tone the needle stays pinned to the end
+        assert_eq!(needle(80.0, 9), "[────│───●]");
+        // Even widths grow by one so there is a centre cell
+        assert_eq!(needle(0.0, 4), "[──●──]");
+    }
+}
@@ -0,0 +1,489 @@
+#[cfg(feature = "playback")]
+use crate::audio::AudioEngine;
+#[cfg(feature = "playback")]
+use crate::melody::Melody;
+use crate::notes::{MAX_OCTAVE, NoteWithOctave};
+#[cfg(feature = "playback")]
+use crate::tempo::{NoteValue, Tempo};
+use std::fmt;
+use std::str::FromStr;
+
+/// Widest interval number, enough to span every note in range from C0 to B10
+pub const MAX_INTERVAL_NUMBER: u8 = 7 * (MAX_OCTAVE + 1);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IntervalQuality {
+    Perfect,
+    Major,
+    Minor,
+    Augmented,
+    Diminished,
+}
+
+/// An interval described by quality and generic size, e.g. a minor third or a perfect fifth.
+/// `number` counts scale steps inclusively: 1 = unison, 5 = fifth, 8 = octave, 10 = tenth...
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Interval {
+    pub quality: IntervalQuality,
+    pub number: u8,
+}
+
+/// How the two notes of an interval are sounded
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IntervalPlayStyle {
+    /// One note after the other, root first
+    #[default]
+    MelodicAscending,
+    /// One note after the other, top note first
+    MelodicDescending,
+    /// Both notes at once
+    Harmonic,
+}
+
+impl fmt::Display for IntervalPlayStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let style = match self {
+            IntervalPlayStyle::MelodicAscending => "melodic ascending",
+            IntervalPlayStyle::MelodicDescending => "melodic descending",
+            IntervalPlayStyle::Harmonic => "harmonic",
+        };
+        write!(f, "{}", style)
+    }
+}
+
+impl FromStr for IntervalPlayStyle {
+    type Err = String;
+    /// "melodic" on its own means ascending, the way intervals are usually first learned
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ascending" | "a" | "melodic" | "m" => Ok(IntervalPlayStyle::MelodicAscending),
+            "descending" | "d" => Ok(IntervalPlayStyle::MelodicDescending),
+            "harmonic" | "h" => Ok(IntervalPlayStyle::Harmonic),
+            _ => Err(format!("Invalid play style: {}", s)),
+        }
+    }
+}
+
+impl IntervalPlayStyle {
+    pub const ALL: [IntervalPlayStyle; 3] = [
+        IntervalPlayStyle::MelodicAscending,
+        IntervalPlayStyle::MelodicDescending,
+        IntervalPlayStyle::Harmonic,
+    ];
+
+    /// The word `from_str` reads back, e.g. "ascending"
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            IntervalPlayStyle::MelodicAscending => "ascending",
+            IntervalPlayStyle::MelodicDescending => "descending",
+            IntervalPlayStyle::Harmonic => "harmonic",
+        }
+    }
+
+    /// The order `root` and `top` are sounded in; harmonic intervals list the root first
+    pub fn order(&self, root: NoteWithOctave, top: NoteWithOctave) -> [NoteWithOctave; 2] {
+        match self {
+            IntervalPlayStyle::MelodicDescending => [top, root],
+            IntervalPlayStyle::MelodicAscending | IntervalPlayStyle::Harmonic => [root, top],
+        }
+    }
+}
+
+impl fmt::Display for IntervalQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quality = match self {
+            IntervalQuality::Perfect => "Perfect",
+            IntervalQuality::Major => "Major",
+            IntervalQuality::Minor => "Minor",
+            IntervalQuality::Augmented => "Augmented",
+            IntervalQuality::Diminished => "Diminished",
+        };
+        write!(f, "{}", quality)
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Interval {
+    type Err = String;
+    /// Accepts shorthand ("m3", "P5", "A4", "TT") or full names ("minor third", "tritone")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let lower = trimmed.to_lowercase();
+        if lower == "tt" || lower == "tritone" {
+            return Ok(Interval::TRITONE);
+        }
+
+        // Shorthand is case-sensitive: "M3" is major, "m3" is minor
+        if let Some(symbol) = trimmed.chars().next()
+            && let Ok(number) = trimmed[symbol.len_utf8()..].parse::<u8>()
+        {
+            let quality = match symbol {
+                'P' => IntervalQuality::Perfect,
+                'M' => IntervalQuality::Major,
+                'm' => IntervalQuality::Minor,
+                'A' => IntervalQuality::Augmented,
+                'd' => IntervalQuality::Diminished,
+                _ => return Err(format!("Invalid interval: {}", s)),
+            };
+            return Interval::new(quality, number);
+        }
+
+        let (quality, number) = lower
+            .split_once(' ')
+            .ok_or_else(|| format!("Invalid interval: {}", s))?;
+        let quality = match quality {
+            "perfect" => IntervalQuality::Perfect,
+            "major" => IntervalQuality::Major,
+            "minor" => IntervalQuality::Minor,
+            "augmented" => IntervalQuality::Augmented,
+            "diminished" => IntervalQuality::Diminished,
+            _ => return Err(format!("Invalid interval: {}", s)),
+        };
+        let number = (1..=15)
+            .find(|&n| number_name(n).to_lowercase() == number.trim())
+            .ok_or_else(|| format!("Invalid interval: {}", s))?;
+        Interval::new(quality, number)
+    }
+}
+
+impl IntervalQuality {
+    /// Abbreviation used in interval shorthand (P5, m3, A4...)
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            IntervalQuality::Perfect => "P",
+            IntervalQuality::Major => "M",
+            IntervalQuality::Minor => "m",
+            IntervalQuality::Augmented => "A",
+            IntervalQuality::Diminished => "d",
+        }
+    }
+}
+
+impl Interval {
+    /// The augmented fourth, the usual spelling of the tritone
+    pub const TRITONE: Interval = Interval {
+        quality: IntervalQuality::Augmented,
+        number: 4,
+    };
+
+    /// Build an interval, rejecting impossible combinations like a "major fifth"
+    pub fn new(quality: IntervalQuality, number: u8) -> Result<Self, String> {
+        if number == 0 {
+            return Err("Interval number must be at least 1 (unison)".to_string());
+        }
+        if number > MAX_INTERVAL_NUMBER {
+            return Err(format!(
+                "Interval number must be at most {}, not {}",
+                MAX_INTERVAL_NUMBER, number
+            ));
+        }
+
+        let perfect_kind = Self::is_perfect_kind(number);
+        let valid = match quality {
+            IntervalQuality::Perfect => perfect_kind,
+            IntervalQuality::Major | IntervalQuality::Minor => !perfect_kind,
+            IntervalQuality::Augmented => true,
+            // A diminished unison would be negative
+            IntervalQuality::Diminished => number != 1,
+        };
+
+        if !valid {
+            return Err(format!("Invalid interval: {} {}", quality, number));
+        }
+
+        Ok(Self { quality, number })
+    }
+
+    /// The conventional interval for a number of semitones (6 is spelled as the tritone, A4)
+    pub fn from_semitones(semitones: u8) -> Self {
+        let octaves = semitones / 12;
+        let (quality, simple_number) = match semitones % 12 {
+            0 => (IntervalQuality::Perfect, 1),
+            1 => (IntervalQuality::Minor, 2),
+            2 => (IntervalQuality::Major, 2),
+            3 => (IntervalQuality::Minor, 3),
+            4 => (IntervalQuality::Major, 3),
+            5 => (IntervalQuality::Perfect, 4),
+            6 => (IntervalQuality::Augmented, 4),
+            7 => (IntervalQuality::Perfect, 5),
+            8 => (IntervalQuality::Minor, 6),
+            9 => (IntervalQuality::Major, 6),
+            10 => (IntervalQuality::Minor, 7),
+            11 => (IntervalQuality::Major, 7),
+            _ => unreachable!("Modulo 12 should only give 0-11"),
+        };
+
+        Self {
+            quality,
+            number: simple_number + 7 * octaves,
+        }
+    }
+
+    /// The interval between two notes, regardless of which one is higher
+    pub fn between(a: &NoteWithOctave, b: &NoteWithOctave) -> Self {
+        let distance = (b.absolute_semitone() - a.absolute_semitone()).unsigned_abs();
+        Self::from_semitones(distance.min(u8::MAX as u32) as u8)
+    }
+
+    /// Size of the interval in semitones
+    pub fn semitones(&self) -> u8 {
+        let octaves = (self.number as i32 - 1) / 7;
+        let simple_number = (self.number - 1) % 7 + 1;
+
+        // Major/perfect size of each simple interval, unison to seventh
+        let reference: i32 = [0, 2, 4, 5, 7, 9, 11][simple_number as usize - 1];
+        let perfect_kind = Self::is_perfect_kind(self.number);
+        let simple = match self.quality {
+            IntervalQuality::Perfect | IntervalQuality::Major => reference,
+            IntervalQuality::Minor => reference - 1,
+            IntervalQuality::Augmented => reference + 1,
+            IntervalQuality::Diminished if perfect_kind => reference - 1,
+            IntervalQuality::Diminished => reference - 2,
+        };
+
+        // A diminished octave dips below the reference octave, e.g. d8 = 11
+        (octaves * 12 + simple) as u8
+    }
+
+    /// Full name, e.g. "Minor Third" or "Perfect Fifth"
+    pub fn name(&self) -> String {
+        format!("{} {}", self.quality, number_name(self.number))
+    }
+
+    /// Shorthand name, e.g. "m3" or "P5"
+    pub fn short_name(&self) -> String {
+        format!("{}{}", self.quality.symbol(), self.number)
+    }
+
+    /// The note this interval above `note`, or `None` if it would leave the supported range
+    pub fn apply_to(&self, note: &NoteWithOctave) -> Option<NoteWithOctave> {
+        note.checked_add(*self)
+    }
+
+    /// Play this interval above `root`, one beat per note, or one beat together when harmonic
+    #[cfg(feature = "playback")]
+    pub fn play_from(
+        &self,
+        root: &NoteWithOctave,
+        engine: &AudioEngine,
+        style: IntervalPlayStyle,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let top = self.apply_to(root).ok_or("Interval is out of range")?;
+        let notes = style.order(*root, top);
+        match style {
+            IntervalPlayStyle::Harmonic => engine.play_notes(&notes, tempo.beat_duration()),
+            _ => Melody::from_notes(&notes, NoteValue::Quarter).play_on(engine, tempo),
+        }
+    }
+
+    /// Unisons, fourths, fifths and their compounds take perfect/augmented/diminished qualities
+    fn is_perfect_kind(number: u8) -> bool {
+        matches!((number - 1) % 7 + 1, 1 | 4 | 5)
+    }
+}
+
+fn number_name(number: u8) -> String {
+    match number {
+        1 => "Unison".to_string(),
+        2 => "Second".to_string(),
+        3 => "Third".to_string(),
+        4 => "Fourth".to_string(),
+        5 => "Fifth".to_string(),
+        6 => "Sixth".to_string(),
+        7 => "Seventh".to_string(),
+        8 => "Octave".to_string(),
+        9 => "Ninth".to_string(),
+        10 => "Tenth".to_string(),
+        11 => "Eleventh".to_string(),
+        12 => "Twelfth".to_string(),
+        13 => "Thirteenth".to_string(),
+        n => {
+            let suffix = match (n % 10, n % 100) {
+                (_, 11..=13) => "th",
+                (1, _) => "st",
+                (2, _) => "nd",
+                (3, _) => "rd",
+                _ => "th",
+            };
+            format!("{}{}", n, suffix)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::Note;
+
+    #[test]
+    fn test_interval_semitones() {
+        let minor_third = Interval::new(IntervalQuality::Minor, 3).unwrap();
+        assert_eq!(minor_third.semitones(), 3);
+
+        let perfect_fifth = Interval::new(IntervalQuality::Perfect, 5).unwrap();
+        assert_eq!(perfect_fifth.semitones(), 7);
+
+        let diminished_fifth = Interval::new(IntervalQuality::Diminished, 5).unwrap();
+        assert_eq!(diminished_fifth.semitones(), 6);
+        assert_eq!(Interval::TRITONE.semitones(), 6);
+
+        let diminished_seventh = Interval::new(IntervalQuality::Diminished, 7).unwrap();
+        assert_eq!(diminished_seventh.semitones(), 9);
+
+        let major_tenth = Interval::new(IntervalQuality::Major, 10).unwrap();
+        assert_eq!(major_tenth.semitones(), 16);
+
+        let diminished_octave = Interval::new(IntervalQuality::Diminished, 8).unwrap();
+        assert_eq!(diminished_octave.semitones(), 11);
+    }
+
+    #[test]
+    fn test_invalid_intervals() {
+        assert!(Interval::new(IntervalQuality::Major, 5).is_err());
+        assert!(Interval::new(IntervalQuality::Perfect, 3).is_err());
+        assert!(Interval::new(IntervalQuality::Diminished, 1).is_err());
+        assert!(Interval::new(IntervalQuality::Perfect, 0).is_err());
+
+        // Wider intervals would wrap around in semitones()
+        assert!("M200".parse::<Interval>().is_err());
+        assert!(Interval::new(IntervalQuality::Augmented, 255).is_err());
+        assert!(Interval::new(IntervalQuality::Perfect, MAX_INTERVAL_NUMBER + 1).is_err());
+        let widest = Interval::new(IntervalQuality::Augmented, MAX_INTERVAL_NUMBER).unwrap();
+        assert_eq!(widest.semitones(), 132);
+        assert_eq!("M77".parse::<Interval>().unwrap().semitones(), 131);
+    }
+
+    #[test]
+    fn test_from_semitones_round_trip() {
+        for semitones in 0..=36 {
+            assert_eq!(Interval::from_semitones(semitones).semitones(), semitones);
+        }
+        assert_eq!(Interval::from_semitones(6), Interval::TRITONE);
+        assert_eq!(Interval::from_semitones(12).name(), "Perfect Octave");
+    }
+
+    #[test]
+    fn test_interval_names() {
+        assert_eq!(Interval::from_semitones(3).name(), "Minor Third");
+        assert_eq!(Interval::from_semitones(7).name(), "Perfect Fifth");
+        assert_eq!(Interval::from_semitones(7).short_name(), "P5");
+        assert_eq!(Interval::from_semitones(14).name(), "Major Ninth");
+        assert_eq!(Interval::from_semitones(24).name(), "Perfect 15th");
+        assert_eq!(format!("{}", Interval::TRITONE), "Augmented Fourth");
+    }
+
+    #[test]
+    fn test_interval_between_notes() {
+        let c4 = NoteWithOctave {
+            note: Note::C,
+            octave: 4,
+        };
+        let g4 = NoteWithOctave {
+            note: Note::G,
+            octave: 4,
+        };
+        let e5 = NoteWithOctave {
+            note: Note::E,
+            octave: 5,
+        };
+
+        assert_eq!(Interval::between(&c4, &g4).short_name(), "P5");
+        // Direction doesn't matter
+        assert_eq!(Interval::between(&g4, &c4).short_name(), "P5");
+        assert_eq!(Interval::between(&c4, &e5).short_name(), "M10");
+    }
+
+    #[test]
+    fn test_apply_to() {
+        let b4 = NoteWithOctave {
+            note: Note::B,
+            octave: 4,
+        };
+        let minor_third = Interval::new(IntervalQuality::Minor, 3).unwrap();
+        let d5 = minor_third.apply_to(&b4).unwrap();
+        assert_eq!(d5.note, Note::D);
+        assert_eq!(d5.octave, 5);
+
+        let top = NoteWithOctave {
+            note: Note::B,
+            octave: 10,
+        };
+        assert!(minor_third.apply_to(&top).is_none());
+    }
+
+    #[test]
+    fn test_interval_parsing() {
+        assert_eq!(
+            "m3".parse::<Interval>().unwrap(),
+            Interval::from_semitones(3)
+        );
+        assert_eq!(
+            "M3".parse::<Interval>().unwrap(),
+            Interval::from_semitones(4)
+        );
+        assert_eq!(
+            "P5".parse::<Interval>().unwrap(),
+            Interval::from_semitones(7)
+        );
+        assert_eq!("tritone".parse::<Interval>().unwrap(), Interval::TRITONE);
+        assert_eq!("TT".parse::<Interval>().unwrap(), Interval::TRITONE);
+        assert_eq!(
+            "Minor Sixth".parse::<Interval>().unwrap(),
+            Interval::from_semitones(8)
+        );
+        assert_eq!(
+            "perfect octave".parse::<Interval>().unwrap(),
+            Interval::from_semitones(12)
+        );
+        assert_eq!("d5".parse::<Interval>().unwrap().semitones(), 6);
+
+        assert!("M5".parse::<Interval>().is_err());
+        assert!("x3".parse::<Interval>().is_err());
+        assert!("major fifth".parse::<Interval>().is_err());
+        assert!("".parse::<Interval>().is_err());
+    }
+
+    #[test]
+    fn test_play_style_parsing() {
+        assert_eq!(
+            "melodic".parse::<IntervalPlayStyle>().unwrap(),
+            IntervalPlayStyle::MelodicAscending
+        );
+        assert_eq!(
+            "d".parse::<IntervalPlayStyle>().unwrap(),
+            IntervalPlayStyle::MelodicDescending
+        );
+        assert_eq!(
+            "Harmonic".parse::<IntervalPlayStyle>().unwrap(),
+            IntervalPlayStyle::Harmonic
+        );
+        assert!("sideways".parse::<IntervalPlayStyle>().is_err());
+        for style in IntervalPlayStyle::ALL {
+            assert_eq!(style.keyword().parse::<IntervalPlayStyle>(), Ok(style));
+        }
+        assert_eq!(
+            IntervalPlayStyle::MelodicDescending.to_string(),
+            "melodic descending"
+        );
+    }
+
+    #[test]
+    fn test_play_style_order() {
+        let c4: NoteWithOctave = "C4".parse().unwrap();
+        let g4: NoteWithOctave = "G4".parse().unwrap();
+        assert_eq!(IntervalPlayStyle::MelodicAscending.order(c4, g4), [c4, g4]);
+        assert_eq!(IntervalPlayStyle::MelodicDescending.order(c4, g4), [g4, c4]);
+        assert_eq!(IntervalPlayStyle::Harmonic.order(c4, g4), [c4, g4]);
+    }
+}
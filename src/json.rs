@@ -0,0 +1,274 @@
+use crate::naming::NoteNaming;
+use crate::notes::{Scale, ScaleDirection, Tuning};
+use crate::report::SessionReport;
+use crate::spelling::SpelledPitch;
+use crate::stats::{ConfusedPair, Exercise, StatsStore};
+use serde::Serialize;
+
+/// A note and how it sounds, as printed by `note --json`
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct NoteOutput {
+    pub note: String,
+    /// Hz in the tuning in use
+    pub frequency: f64,
+    /// `null` above G9, the highest note MIDI has
+    pub midi: Option<u8>,
+}
+
+impl NoteOutput {
+    /// `None` when the note is outside the supported octaves
+    pub fn new(note: &SpelledPitch, tuning: &Tuning) -> Option<Self> {
+        let pitch = note.pitch()?;
+        Some(Self {
+            note: note.to_string(),
+            frequency: tuning.frequency_of(&pitch),
+            midi: pitch.to_midi(),
+        })
+    }
+
+    /// The same note written another way, e.g. in German or solfège
+    pub fn with_name(mut self, name: String) -> Self {
+        self.note = name;
+        self
+    }
+}
+
+/// The notes of a scale run in the order they're played, as printed by `scale --json`
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct ScaleOutput {
+    pub scale: String,
+    pub direction: String,
+    pub notes: Vec<NoteOutput>,
+}
+
+impl ScaleOutput {
+    /// Notes are named in `naming`, with movable do counted from the scale's root
+    pub fn new(
+        scale: &Scale,
+        octaves: u8,
+        direction: ScaleDirection,
+        naming: &NoteNaming,
+        tuning: &Tuning,
+    ) -> Self {
+        Self {
+            scale: scale.to_string(),
+            direction: direction.to_string(),
+            notes: scale
+                .spelled_run(octaves, direction)
+                .iter()
+                .filter_map(|note| {
+                    let name = naming.name_spelled_pitch(note, &scale.root.note);
+                    Some(NoteOutput::new(note, tuning)?.with_name(name))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Saved answers summed up, as printed by `stats --json`
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct StatsOutput {
+    /// Where the stats are kept, if they're saved at all
+    pub path: Option<String>,
+    /// Practice sessions the report covers
+    pub sessions: usize,
+    pub report: SessionReport,
+}
+
+impl StatsOutput {
+    /// Every answer, or only those from the last `sessions` practice sessions
+    pub fn new(stats: &StatsStore, sessions: Option<usize>) -> Self {
+        let all = stats.sessions();
+        let recent = &all[all.len() - sessions.unwrap_or(all.len()).min(all.len())..];
+        Self {
+            path: stats.path().map(|path| path.display().to_string()),
+            sessions: recent.len(),
+            report: SessionReport::from_records(&recent.concat()),
+        }
+    }
+}
+
+/// Answers taken for each other in recent sessions, as printed by
+/// `stats confusions --json`
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct ConfusionsOutput {
+    /// Practice sessions looked at
+    pub sessions: usize,
+    /// Exercises with at least one mix-up
+    pub exercises: Vec<ExerciseConfusions>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct ExerciseConfusions {
+    pub exercise: Exercise,
+    /// Most mixed up first
+    pub pairs: Vec<PairOutput>,
+}
+
+/// Two answers taken for each other, counting both ways round
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct PairOutput {
+    pub first: String,
+    pub second: String,
+    pub first_as_second: u32,
+    pub second_as_first: u32,
+    pub total: u32,
+}
+
+impl From<&ConfusedPair> for PairOutput {
+    fn from(pair: &ConfusedPair) -> Self {
+        Self {
+            first: pair.first.clone(),
+            second: pair.second.clone(),
+            first_as_second: pair.first_as_second,
+            second_as_first: pair.second_as_first,
+            total: pair.total(),
+        }
+    }
+}
+
+impl ConfusionsOutput {
+    /// Mix-ups over the last `sessions` practice sessions
+    pub fn new(stats: &StatsStore, sessions: usize) -> Self {
+        let exercises = stats
+            .exercises()
+            .into_iter()
+            .map(|exercise| ExerciseConfusions {
+                exercise,
+                pairs: stats
+                    .confusions(exercise, sessions)
+                    .pairs()
+                    .iter()
+                    .map(PairOutput::from)
+                    .collect(),
+            })
+            .filter(|confusions| !confusions.pairs.is_empty())
+            .collect();
+        Self {
+            sessions: stats.sessions().len().min(sessions),
+            exercises,
+        }
+    }
+}
+
+/// A failed command, printed in place of its output
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct ErrorOutput {
+    pub error: String,
+}
+
+impl ErrorOutput {
+    pub fn new(error: impl ToString) -> Self {
+        Self {
+            error: error.to_string(),
+        }
+    }
+}
+
+/// `value` as indented JSON
+pub fn to_json(value: &impl Serialize) -> String {
+    serde_json::to_string_pretty(value).expect("output types serialize to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naming::NamingScheme;
+    use crate::notes::Note;
+    use crate::stats::AnswerRecord;
+    use std::time::Duration;
+
+    #[test]
+    fn test_note_and_scale_output() {
+        let tuning = Tuning::default();
+        let a4 = NoteOutput::new(&"A4".parse().unwrap(), &tuning).unwrap();
+        assert_eq!(
+            to_json(&a4),
+            "{\n  \"note\": \"A4\",\n  \"frequency\": 440.0,\n  \"midi\": 69\n}"
+        );
+        let a9 = NoteOutput::new(&"A9".parse().unwrap(), &tuning).unwrap();
+        assert!(to_json(&a9).contains("\"midi\": null"));
+
+        let scale: Scale = "F major".parse().unwrap();
+        let letters = NoteNaming::default();
+        let run = ScaleOutput::new(&scale, 1, ScaleDirection::Ascending, &letters, &tuning);
+        assert_eq!(run.notes.len(), 8);
+        assert_eq!(run.notes[3].note, "Bb4");
+        assert_eq!(run.notes[7].midi, Some(77));
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&run)).unwrap();
+        assert_eq!(json["notes"][0]["note"], "F4");
+
+        let german = NoteNaming::from(NamingScheme::German);
+        let scale: Scale = german.to_letters("H minor", &Note::C).parse().unwrap();
+        let run = ScaleOutput::new(&scale, 1, ScaleDirection::Ascending, &german, &tuning);
+        assert_eq!(run.scale, "B4 Minor");
+        assert_eq!(run.notes[0].note, "H4");
+        assert_eq!(run.notes[0].midi, Some(71));
+        assert_eq!(run.notes[2].note, "D5");
+        assert_eq!(
+            to_json(&ErrorOutput::new("Invalid scale")),
+            "{\n  \"error\": \"Invalid scale\"\n}"
+        );
+    }
+
+    #[test]
+    fn test_stats_output() {
+        let mut stats = StatsStore::in_memory();
+        stats.record(AnswerRecord::new(
+            Exercise::Intervals,
+            "Major 3rd",
+            "Minor 3rd",
+            false,
+            Duration::from_secs(2),
+        ));
+        let output = StatsOutput::new(&stats, None);
+        assert_eq!(output.path, None);
+        assert_eq!(output.sessions, 1);
+        assert_eq!(output.report.total, 1);
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&output)).unwrap();
+        assert_eq!(json["report"]["exercises"][0]["exercise"], "intervals");
+        assert_eq!(
+            StatsOutput::new(&StatsStore::in_memory(), Some(5)).sessions,
+            0
+        );
+    }
+
+    #[test]
+    fn test_confusions_output() {
+        let mut stats = StatsStore::in_memory();
+        for (question, answer) in [
+            ("Major 3rd", "Minor 3rd"),
+            ("Minor 3rd", "Major 3rd"),
+            ("Major 3rd", "Minor 3rd"),
+            ("Perfect 5th", "Perfect 5th"),
+        ] {
+            stats.record(AnswerRecord::new(
+                Exercise::Intervals,
+                question,
+                answer,
+                question == answer,
+                Duration::from_secs(2),
+            ));
+        }
+        let output = ConfusionsOutput::new(&stats, 10);
+        assert_eq!(output.sessions, 1);
+        assert_eq!(output.exercises.len(), 1);
+        let pair = &output.exercises[0].pairs[0];
+        assert_eq!(
+            (pair.first.as_str(), pair.second.as_str()),
+            ("Major 3rd", "Minor 3rd")
+        );
+        assert_eq!((pair.first_as_second, pair.second_as_first), (2, 1));
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&output)).unwrap();
+        assert_eq!(json["exercises"][0]["exercise"], "intervals");
+        assert_eq!(json["exercises"][0]["pairs"][0]["total"], 3);
+        assert!(
+            ConfusionsOutput::new(&StatsStore::in_memory(), 10)
+                .exercises
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,442 @@
+use crate::notes::{Note, NoteWithOctave, Scale, ScaleType};
+use crate::spelling::{Accidental, NoteName, SpelledNote, SpelledPitch};
+use std::fmt;
+
+/// Largest number of sharps or flats in a standard key signature
+pub const MAX_ACCIDENTALS: i8 = 7;
+
+/// Largest offset for theoretical keys like G# major, where double sharps or flats appear
+const MAX_FIFTHS: i8 = 2 * MAX_ACCIDENTALS;
+
+/// Krumhansl-Kessler ratings of how well each pitch class, counted up from the tonic,
+/// fits a major key
+const MAJOR_PROFILE: [f32; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+
+/// Krumhansl-Kessler ratings for a minor key
+const MINOR_PROFILE: [f32; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Sharps or flats at the start of the staff, stored as steps around the circle of fifths.
+/// Positive values count sharps, negative values count flats.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeySignature {
+    fifths: i8,
+}
+
+impl fmt::Display for KeySignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.fifths {
+            0 => write!(f, "no sharps or flats"),
+            1 => write!(f, "1 sharp"),
+            -1 => write!(f, "1 flat"),
+            n if n > 0 => write!(f, "{} sharps", n),
+            n => write!(f, "{} flats", -n),
+        }
+    }
+}
+
+impl KeySignature {
+    /// Key signature with `fifths` sharps (or flats when negative).
+    /// Beyond 7 the key is theoretical: G# major (8) double-sharps F on top of the seven sharps.
+    pub fn new(fifths: i8) -> Result<Self, String> {
+        if !(-MAX_FIFTHS..=MAX_FIFTHS).contains(&fifths) {
+            return Err(format!(
+                "A key signature has at most {} sharps or flats",
+                MAX_FIFTHS
+            ));
+        }
+        Ok(Self { fifths })
+    }
+
+    /// Whether the signature needs double sharps or flats
+    pub fn is_theoretical(&self) -> bool {
+        self.fifths.abs() > MAX_ACCIDENTALS
+    }
+
+    /// Key signature for a tonic and mode, e.g. D dorian has no sharps or flats.
+    /// Enharmonic keys prefer flats up to five (Db over C#) and sharps from six (F# over Gb).
+    pub fn for_key(tonic: &Note, scale_type: &ScaleType) -> Self {
+        let tonic_fifths = tonic.to_semitone() * 7;
+        let fifths = (tonic_fifths + mode_offset(scale_type)).rem_euclid(12);
+        let fifths = if fifths > 6 { fifths - 12 } else { fifths };
+        Self {
+            fifths: fifths as i8,
+        }
+    }
+
+    /// Key signature for a written tonic, telling C# major (7 sharps) from Db major (5 flats)
+    pub fn for_spelled_key(tonic: &SpelledNote, scale_type: &ScaleType) -> Result<Self, String> {
+        let fifths = tonic.fifths() + mode_offset(scale_type);
+        let fifths = i8::try_from(fifths).map_err(|e| e.to_string())?;
+        KeySignature::new(fifths).map_err(|_| {
+            format!(
+                "{} {} has no key signature; try its enharmonic equivalent",
+                tonic, scale_type
+            )
+        })
+    }
+
+    /// The scale's own key signature, from its written root where that has one
+    /// (Db major rather than C# major)
+    pub fn for_scale(scale: &Scale) -> Self {
+        KeySignature::for_spelled_key(&scale.spelled_root().note, &scale.scale_type)
+            .unwrap_or_else(|_| KeySignature::for_key(&scale.root.note, &scale.scale_type))
+    }
+
+    /// Sharps are positive, flats negative
+    pub fn fifths(&self) -> i8 {
+        self.fifths
+    }
+
+    pub fn sharps(&self) -> u8 {
+        self.fifths.max(0) as u8
+    }
+
+    pub fn flats(&self) -> u8 {
+        (-self.fifths).max(0) as u8
+    }
+
+    /// The sharped or flatted notes in the order they are written (F# C# G#... or Bb Eb Ab...).
+    /// In theoretical keys the first letters are doubled, e.g. Fx C# G#... for G# major.
+    pub fn accidentals(&self) -> Vec<SpelledNote> {
+        let count = self.fifths.unsigned_abs() as i32;
+        (0..count.min(MAX_ACCIDENTALS as i32))
+            .map(|i| {
+                // Letters that come round the circle again get a second accidental
+                let doubled = 7 * ((count - 1 - i) / 7);
+                if self.fifths >= 0 {
+                    SpelledNote::from_fifths(6 + i + doubled)
+                } else {
+                    SpelledNote::from_fifths(-2 - i - doubled)
+                }
+            })
+            .collect()
+    }
+
+    /// The accidental the signature puts on every `name`, e.g. Sharp for F in D major
+    pub fn accidental_for(&self, name: NoteName) -> Accidental {
+        self.accidentals()
+            .into_iter()
+            .find(|note| note.name == name)
+            .map_or(Accidental::Natural, |note| note.accidental)
+    }
+
+    /// Spell a sounding note the way it's written in this key: with the key's own letter
+    /// when it belongs to the key, otherwise with sharps in sharp keys and flats in flat keys
+    pub fn spell(&self, pitch: &NoteWithOctave) -> SpelledPitch {
+        let diatonic = (0..7)
+            .map(NoteName::from_index)
+            .find(|name| SpelledNote::new(*name, self.accidental_for(*name)).pitch() == pitch.note);
+        if let Some(spelled) = diatonic.and_then(|name| SpelledPitch::spell(pitch, name)) {
+            return spelled;
+        }
+        let sharp = SpelledPitch::from_pitch(pitch);
+        if self.fifths >= 0 || sharp.note.accidental == Accidental::Natural {
+            return sharp;
+        }
+        SpelledPitch::spell(pitch, NoteName::from_index(sharp.note.name.index() + 1))
+            .unwrap_or(sharp)
+    }
+
+    /// Tonic of the major key with this signature
+    pub fn major_tonic(&self) -> SpelledNote {
+        SpelledNote::from_fifths(self.fifths as i32)
+    }
+
+    /// Tonic of the relative minor key, three fifths above the major tonic
+    pub fn minor_tonic(&self) -> SpelledNote {
+        SpelledNote::from_fifths(self.fifths as i32 + 3)
+    }
+
+    /// One step clockwise round the circle: one more sharp or one fewer flat
+    pub fn sharpened(&self) -> Option<KeySignature> {
+        KeySignature::new(self.fifths + 1).ok()
+    }
+
+    /// One step anticlockwise round the circle: one more flat or one fewer sharp
+    pub fn flattened(&self) -> Option<KeySignature> {
+        KeySignature::new(self.fifths - 1).ok()
+    }
+
+    /// All fifteen key signatures, from seven flats to seven sharps
+    pub fn all() -> impl Iterator<Item = KeySignature> {
+        (-MAX_ACCIDENTALS..=MAX_ACCIDENTALS).map(|fifths| KeySignature { fifths })
+    }
+}
+
+/// The twelve keys clockwise from C: C G D A E B F# Db Ab Eb Bb F
+pub fn circle_of_fifths() -> impl Iterator<Item = KeySignature> {
+    (0..12).map(|step| KeySignature {
+        fifths: if step > 6 { step - 12 } else { step },
+    })
+}
+
+/// Rank the key signatures `notes` most likely belong to, best first, with each one's
+/// score from -1 to 1. A signature scores as well as the better of its major and
+/// relative minor key; see `keys_of` to tell them apart.
+pub fn key_of(notes: &[NoteWithOctave]) -> Vec<(KeySignature, f32)> {
+    let mut signatures: Vec<(KeySignature, f32)> = Vec::new();
+    for (key, score) in keys_of(notes) {
+        let signature = KeySignature::for_key(&key.root.note, &key.scale_type);
+        if !signatures.iter().any(|(s, _)| *s == signature) {
+            signatures.push((signature, score));
+        }
+    }
+    signatures
+}
+
+/// Rank the 24 major and minor keys for `notes`, best first, using the
+/// Krumhansl-Schmuckler method: how often each pitch class occurs is correlated
+/// with each key's profile. Empty when the notes don't favour any pitch class.
+pub fn keys_of(notes: &[NoteWithOctave]) -> Vec<(Scale, f32)> {
+    let mut counts = [0.0_f32; 12];
+    for note in notes {
+        counts[note.note.to_semitone() as usize] += 1.0;
+    }
+
+    let mut keys = Vec::new();
+    for tonic in (0..12).filter_map(Note::from_semitone) {
+        for (scale_type, profile) in [
+            (ScaleType::Major, &MAJOR_PROFILE),
+            (ScaleType::Minor, &MINOR_PROFILE),
+        ] {
+            let rotated: Vec<f32> = (0..12)
+                .map(|i| counts[(i + tonic.to_semitone() as usize) % 12])
+                .collect();
+            let Some(score) = correlation(&rotated, profile) else {
+                return Vec::new();
+            };
+            let signature = KeySignature::for_key(&tonic, &scale_type);
+            let spelled_tonic = match scale_type {
+                ScaleType::Major => signature.major_tonic(),
+                _ => signature.minor_tonic(),
+            };
+            let root = SpelledPitch {
+                note: spelled_tonic,
+                octave: 4,
+            };
+            if let Ok(key) = Scale::spelled(root, scale_type) {
+                keys.push((key, score));
+            }
+        }
+    }
+    keys.sort_by(|a, b| b.1.total_cmp(&a.1));
+    keys
+}
+
+/// Pearson correlation of two equally long series, or `None` if either is flat
+fn correlation(xs: &[f32], ys: &[f32]) -> Option<f32> {
+    let n = xs.len() as f32;
+    let mean_x = xs.iter().sum::<f32>() / n;
+    let mean_y = ys.iter().sum::<f32>() / n;
+    let (mut covariance, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        covariance += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some(covariance / (var_x * var_y).sqrt())
+}
+
+/// Fifths between the mode's tonic and the tonic of the major key sharing its signature
+fn mode_offset(scale_type: &ScaleType) -> i32 {
+    match scale_type {
+        ScaleType::Major | ScaleType::MajorPentatonic => 0,
+        ScaleType::Lydian => 1,
+        ScaleType::Mixolydian => -1,
+        ScaleType::Dorian => -2,
+        ScaleType::Minor
+        | ScaleType::Aeolian
+        | ScaleType::HarmonicMinor
+        | ScaleType::MelodicMinor
+        | ScaleType::MinorPentatonic
+        | ScaleType::Blues => -3,
+        ScaleType::Phrygian => -4,
+        ScaleType::Locrian => -5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(notes: &[SpelledNote]) -> Vec<String> {
+        notes.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_major_key_signatures() {
+        let d_major = KeySignature::for_key(&Note::D, &ScaleType::Major);
+        assert_eq!(d_major.sharps(), 2);
+        assert_eq!(names(&d_major.accidentals()), vec!["F#", "C#"]);
+
+        let e_flat_major = KeySignature::for_key(&Note::DSharp, &ScaleType::Major);
+        assert_eq!(e_flat_major.flats(), 3);
+        assert_eq!(names(&e_flat_major.accidentals()), vec!["Bb", "Eb", "Ab"]);
+        assert_eq!(e_flat_major.major_tonic().to_string(), "Eb");
+
+        let f_sharp_major = KeySignature::for_key(&Note::FSharp, &ScaleType::Major);
+        assert_eq!(f_sharp_major.sharps(), 6);
+        assert_eq!(f_sharp_major.accidentals()[5].to_string(), "E#");
+    }
+
+    #[test]
+    fn test_mode_key_signatures() {
+        let d_dorian = KeySignature::for_key(&Note::D, &ScaleType::Dorian);
+        assert_eq!(d_dorian.fifths(), 0);
+
+        let a_minor = KeySignature::for_key(&Note::A, &ScaleType::Minor);
+        assert_eq!(a_minor.to_string(), "no sharps or flats");
+
+        let c_sharp_minor = KeySignature::for_key(&Note::CSharp, &ScaleType::HarmonicMinor);
+        assert_eq!(c_sharp_minor.to_string(), "4 sharps");
+        assert_eq!(c_sharp_minor.minor_tonic().to_string(), "C#");
+
+        let f_lydian = KeySignature::for_key(&Note::F, &ScaleType::Lydian);
+        assert_eq!(f_lydian.fifths(), 0);
+
+        let g_phrygian = KeySignature::for_key(&Note::G, &ScaleType::Phrygian);
+        assert_eq!(g_phrygian.to_string(), "3 flats");
+    }
+
+    #[test]
+    fn test_extreme_key_signatures() {
+        let seven_sharps = KeySignature::new(7).unwrap();
+        assert_eq!(seven_sharps.major_tonic().to_string(), "C#");
+        assert_eq!(seven_sharps.minor_tonic().to_string(), "A#");
+        assert_eq!(seven_sharps.accidentals().last().unwrap().to_string(), "B#");
+        assert!(seven_sharps.sharpened().unwrap().is_theoretical());
+
+        let seven_flats = KeySignature::new(-7).unwrap();
+        assert_eq!(seven_flats.major_tonic().to_string(), "Cb");
+        assert_eq!(seven_flats.accidentals().last().unwrap().to_string(), "Fb");
+        assert!(seven_flats.flattened().unwrap().is_theoretical());
+
+        assert!(KeySignature::new(15).is_err());
+        assert!(KeySignature::new(14).unwrap().flattened().is_some());
+        assert!(KeySignature::new(14).unwrap().sharpened().is_none());
+    }
+
+    #[test]
+    fn test_circle_of_fifths() {
+        let tonics: Vec<String> = circle_of_fifths()
+            .map(|key| key.major_tonic().to_string())
+            .collect();
+        assert_eq!(
+            tonics,
+            vec![
+                "C", "G", "D", "A", "E", "B", "F#", "Db", "Ab", "Eb", "Bb", "F"
+            ]
+        );
+        assert_eq!(KeySignature::all().count(), 15);
+
+        let g = KeySignature::new(0).unwrap().sharpened().unwrap();
+        assert_eq!(g.major_tonic().to_string(), "G");
+        assert_eq!(g.flattened().unwrap().fifths(), 0);
+    }
+
+    fn pitches(names: &str) -> Vec<NoteWithOctave> {
+        names
+            .split_whitespace()
+            .map(|n| n.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_key_of_melody() {
+        // Twinkle, twinkle in D major
+        let melody = pitches("D4 D4 A4 A4 B4 B4 A4 G4 G4 F#4 F#4 E4 E4 D4");
+        let keys = keys_of(&melody);
+        assert_eq!(keys.len(), 24);
+        assert_eq!(keys[0].0.to_string(), "D4 Major");
+        assert!(keys.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+        let signatures = key_of(&melody);
+        assert_eq!(signatures[0].0.sharps(), 2);
+        assert_eq!(signatures.len(), 12);
+        assert!(signatures[0].1 > 0.5);
+    }
+
+    #[test]
+    fn test_key_of_minor_melody() {
+        let melody = pitches("A3 C4 E4 A4 G#4 A4 E4 C4 B3 A3 D4 C4 B3 A3");
+        let keys = keys_of(&melody);
+        assert_eq!(keys[0].0.to_string(), "A4 Minor");
+        assert_eq!(key_of(&melody)[0].0.fifths(), 0);
+
+        let e_flat = keys_of(&pitches("Eb4 G4 Bb4 Eb5 Bb4 G4 Eb4"));
+        assert_eq!(e_flat[0].0.to_string(), "Eb4 Major");
+    }
+
+    #[test]
+    fn test_key_of_without_a_centre() {
+        assert!(key_of(&[]).is_empty());
+        let chromatic: Vec<NoteWithOctave> = (60..72)
+            .map(|n| NoteWithOctave::from_midi(n).unwrap())
+            .collect();
+        assert!(keys_of(&chromatic).is_empty());
+    }
+
+    #[test]
+    fn test_spelled_key_signatures() {
+        let c_sharp: SpelledNote = "C#".parse().unwrap();
+        let d_flat: SpelledNote = "Db".parse().unwrap();
+        let major = ScaleType::Major;
+        assert_eq!(
+            KeySignature::for_spelled_key(&c_sharp, &major)
+                .unwrap()
+                .sharps(),
+            7
+        );
+        assert_eq!(
+            KeySignature::for_spelled_key(&d_flat, &major)
+                .unwrap()
+                .flats(),
+            5
+        );
+
+        let a_sharp_minor =
+            KeySignature::for_spelled_key(&"A#".parse().unwrap(), &ScaleType::Minor);
+        assert_eq!(a_sharp_minor.unwrap().sharps(), 7);
+
+        // G# major is a theoretical key with a double sharp
+        let g_sharp = KeySignature::for_spelled_key(&"G#".parse().unwrap(), &major).unwrap();
+        assert!(g_sharp.is_theoretical());
+        assert_eq!(g_sharp.accidentals()[0].to_string(), "Fx");
+        assert_eq!(g_sharp.accidentals().len(), 7);
+        assert_eq!(g_sharp.major_tonic().to_string(), "G#");
+
+        let f_flat = KeySignature::for_spelled_key(&"Fb".parse().unwrap(), &major).unwrap();
+        assert_eq!(f_flat.flats(), 8);
+        assert_eq!(f_flat.accidentals()[0].to_string(), "Bbb");
+        assert!(!KeySignature::new(-7).unwrap().is_theoretical());
+    }
+
+    #[test]
+    fn test_spelling_in_a_key() {
+        let pitch = |s: &str| s.parse::<NoteWithOctave>().unwrap();
+        let d_major = KeySignature::new(2).unwrap();
+        assert_eq!(d_major.accidental_for(NoteName::F), Accidental::Sharp);
+        assert_eq!(d_major.accidental_for(NoteName::B), Accidental::Natural);
+        assert_eq!(d_major.spell(&pitch("F#4")).to_string(), "F#4");
+        assert_eq!(d_major.spell(&pitch("A#4")).to_string(), "A#4");
+
+        let e_flat = KeySignature::new(-3).unwrap();
+        assert_eq!(e_flat.spell(&pitch("D#4")).to_string(), "Eb4");
+        assert_eq!(e_flat.spell(&pitch("C#5")).to_string(), "Db5");
+        assert_eq!(e_flat.spell(&pitch("E4")).to_string(), "E4");
+
+        // The key's own letters win over the usual spelling
+        let c_sharp = KeySignature::new(7).unwrap();
+        assert_eq!(c_sharp.spell(&pitch("C4")).to_string(), "B#3");
+        let c_flat = KeySignature::new(-7).unwrap();
+        assert_eq!(c_flat.spell(&pitch("B3")).to_string(), "Cb4");
+    }
+}
@@ -1 +1,42 @@
-pub mod notes;
\ No newline at end of file
+pub mod answer;
+pub mod arpeggio;
+pub mod audio;
+pub mod chords;
+pub mod config;
+pub mod difficulty;
+pub mod display;
+pub mod edo;
+pub mod export;
+pub mod generator;
+pub mod input;
+pub mod intervals;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod keys;
+pub mod lilypond;
+pub mod live;
+pub mod melody;
+pub mod metronome;
+#[cfg(feature = "midi")]
+pub mod midi;
+pub mod musicxml;
+pub mod naming;
+pub mod notes;
+pub mod progression;
+#[cfg(feature = "audio")]
+pub mod recording;
+pub mod render;
+pub mod report;
+pub mod rhythm;
+pub mod scala;
+pub mod script;
+pub mod smf;
+pub mod spelling;
+pub mod srs;
+pub mod stats;
+pub mod tempo;
+pub mod training;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "web")]
+pub mod web;
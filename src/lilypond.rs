@@ -0,0 +1,182 @@
+use crate::chords::Chord;
+use crate::melody::Melody;
+use crate::musicxml::{Score, ToMusicXml, Written};
+use crate::notes::Scale;
+use crate::spelling::{Accidental, SpelledNote, SpelledPitch};
+use crate::tempo::NoteValue;
+
+/// LilyPond release the fragments are written for
+pub const LILYPOND_VERSION: &str = "2.24.0";
+
+/// Something that can be typeset with LilyPond
+pub trait ToLilyPond {
+    /// A fragment that compiles on its own or can be pasted into a larger file
+    fn to_lilypond(&self) -> String;
+}
+
+/// The melody in rhythm and in its time signature, spelled in the key it fits best
+impl ToLilyPond for Melody {
+    fn to_lilypond(&self) -> String {
+        self.to_score().to_lilypond()
+    }
+}
+
+/// One octave of the scale up to the top tonic in quarter notes, in its own key signature
+impl ToLilyPond for Scale {
+    fn to_lilypond(&self) -> String {
+        self.to_score().to_lilypond()
+    }
+}
+
+/// The chord as a whole-note block chord
+impl ToLilyPond for Chord {
+    fn to_lilypond(&self) -> String {
+        self.to_score().to_lilypond()
+    }
+}
+
+impl Score {
+    /// The score as LilyPond, one bar of the time signature per line. Notes crossing a
+    /// barline are tied and the last bar is filled up with rests, as in MusicXML.
+    pub fn to_lilypond(&self) -> String {
+        let mut ly = format!(
+            "\\version \"{}\"\n{{\n  \\clef {}\n  \\key {} \\major\n  \\time {}\n",
+            LILYPOND_VERSION,
+            self.clef,
+            note_name(&self.key.major_tonic()),
+            self.time_signature
+        );
+        for measure in self.measures() {
+            ly.push_str(&format!("  {} |\n", bar(&measure)));
+        }
+        ly.push_str("}\n");
+        ly
+    }
+}
+
+/// The notes and rests of one bar, with triplets grouped under `\tuplet`
+fn bar(measure: &[Written]) -> String {
+    let mut tokens = Vec::new();
+    let mut triplets: Vec<String> = Vec::new();
+    for written in measure {
+        if is_triplet(written.value) {
+            triplets.push(event(written));
+            continue;
+        }
+        if !triplets.is_empty() {
+            tokens.push(format!("\\tuplet 3/2 {{ {} }}", triplets.join(" ")));
+            triplets.clear();
+        }
+        tokens.push(event(written));
+    }
+    if !triplets.is_empty() {
+        tokens.push(format!("\\tuplet 3/2 {{ {} }}", triplets.join(" ")));
+    }
+    tokens.join(" ")
+}
+
+/// A note, rest or chord with its duration, e.g. "fis'8.", "r4" or "<c' e' g'>1~"
+fn event(written: &Written) -> String {
+    let sound = match written.notes.as_slice() {
+        [] => "r".to_string(),
+        [note] => pitch(note),
+        notes => {
+            let pitches: Vec<String> = notes.iter().map(pitch).collect();
+            format!("<{}>", pitches.join(" "))
+        }
+    };
+    let tie = if written.tied_to_next { "~" } else { "" };
+    format!("{}{}{}", sound, duration(written.value), tie)
+}
+
+/// A note in LilyPond's Dutch names with octave marks, where c' is middle C
+fn pitch(pitch: &SpelledPitch) -> String {
+    let marks = match pitch.octave {
+        octave if octave >= 3 => "'".repeat(octave as usize - 3),
+        octave => ",".repeat(3 - octave as usize),
+    };
+    format!("{}{}", note_name(&pitch.note), marks)
+}
+
+/// A letter with "is" for each sharp and "es" for each flat, e.g. "fis" or "bes"
+fn note_name(note: &SpelledNote) -> String {
+    let suffix = match note.accidental {
+        Accidental::DoubleFlat => "eses",
+        Accidental::Flat => "es",
+        Accidental::Natural => "",
+        Accidental::Sharp => "is",
+        Accidental::DoubleSharp => "isis",
+    };
+    format!("{}{}", note.name.to_string().to_lowercase(), suffix)
+}
+
+/// LilyPond's duration for a value; triplets are written at their normal length
+/// inside `\tuplet`
+fn duration(value: NoteValue) -> &'static str {
+    match value {
+        NoteValue::Whole => "1",
+        NoteValue::DottedHalf => "2.",
+        NoteValue::Half | NoteValue::HalfTriplet => "2",
+        NoteValue::DottedQuarter => "4.",
+        NoteValue::Quarter | NoteValue::QuarterTriplet => "4",
+        NoteValue::DottedEighth => "8.",
+        NoteValue::Eighth | NoteValue::EighthTriplet => "8",
+        NoteValue::Sixteenth => "16",
+    }
+}
+
+fn is_triplet(value: NoteValue) -> bool {
+    matches!(
+        value,
+        NoteValue::HalfTriplet | NoteValue::QuarterTriplet | NoteValue::EighthTriplet
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_names() {
+        let name = |s: &str| pitch(&s.parse().unwrap());
+        assert_eq!(name("C4"), "c'");
+        assert_eq!(name("Bb3"), "bes");
+        assert_eq!(name("F#5"), "fis''");
+        assert_eq!(name("Ebb2"), "eeses,");
+        assert_eq!(name("Cx1"), "cisis,,");
+    }
+
+    #[test]
+    fn test_scale_and_chord() {
+        let scale: Scale = "F major".parse().unwrap();
+        assert_eq!(
+            scale.to_lilypond(),
+            "\\version \"2.24.0\"\n{\n  \\clef treble\n  \\key f \\major\n  \\time 4/4\n  \
+             f'4 g'4 a'4 bes'4 |\n  c''4 d''4 e''4 f''4 |\n}\n"
+        );
+
+        let chord: Chord = "G7".parse().unwrap();
+        let ly = chord.to_lilypond();
+        assert!(ly.contains("\\key c \\major"));
+        assert!(ly.contains("  <g' b' d'' f''>1 |\n"));
+
+        let low: Scale = "C2 major".parse().unwrap();
+        assert!(low.to_lilypond().contains("\\clef bass"));
+        assert!(low.to_lilypond().contains("c,4 d,4"));
+    }
+
+    #[test]
+    fn test_melody_rhythm() {
+        let melody = "C4:h D4:h E4:e3 F#4:e3 G4:e3 A4:q."
+            .parse::<Melody>()
+            .unwrap()
+            .with_time_signature("3/4".parse().unwrap());
+        let ly = melody.to_lilypond();
+        // The C is written without the key's sharp, so LilyPond prints a natural sign
+        assert!(ly.contains("\\key d \\major"));
+        assert!(ly.contains("\\time 3/4"));
+        assert!(ly.contains("  c'2 d'4~ |\n"));
+        assert!(ly.contains("  d'4 \\tuplet 3/2 { e'8 fis'8 g'8 } a'4~ |\n"));
+        assert!(ly.contains("  a'8 r2 r8 |\n"));
+    }
+}
@@ -0,0 +1,254 @@
+use crate::display::Keyboard;
+use crate::notes::{MAX_OCTAVE, Note, NoteWithOctave};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Computer keys played like a piano, with the semitones each one is above the lowest C:
+/// the home row holds the white keys and the row above it the black keys, as on a DAW
+const QWERTY_KEYS: [(char, i32); 18] = [
+    ('a', 0),
+    ('w', 1),
+    ('s', 2),
+    ('e', 3),
+    ('d', 4),
+    ('f', 5),
+    ('t', 6),
+    ('g', 7),
+    ('y', 8),
+    ('h', 9),
+    ('u', 10),
+    ('j', 11),
+    ('k', 12),
+    ('o', 13),
+    ('l', 14),
+    ('p', 15),
+    (';', 16),
+    ('\'', 17),
+];
+
+/// Octave of the lowest C unless shifted
+pub const DEFAULT_LIVE_OCTAVE: u8 = 4;
+
+/// Notes that can sound at once; pressing another lets go of the oldest
+pub const MAX_VOICES: usize = 8;
+
+/// How long a note keeps sounding after its key was last seen. Terminals only report
+/// presses, so a held key is told apart by its auto-repeat, which starts within about
+/// half a second.
+pub const RELEASE_AFTER: Duration = Duration::from_millis(700);
+
+/// Keys that shift the whole keyboard down or up an octave
+const OCTAVE_DOWN: char = 'z';
+const OCTAVE_UP: char = 'x';
+
+/// The letter rows of a computer keyboard mapped onto a piano from a C, a=C, w=C#, s=D...
+/// up to '=F an octave and a half higher. z and x shift it down and up an octave.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct QwertyKeyboard {
+    /// Octave of the C under the a key
+    pub octave: u8,
+}
+
+impl Default for QwertyKeyboard {
+    fn default() -> Self {
+        Self {
+            octave: DEFAULT_LIVE_OCTAVE,
+        }
+    }
+}
+
+impl QwertyKeyboard {
+    pub fn with_octave(mut self, octave: u8) -> Self {
+        self.octave = octave.min(MAX_OCTAVE - 1);
+        self
+    }
+
+    /// The note `key` plays, if it's one of the piano keys
+    pub fn note_for(&self, key: char) -> Option<NoteWithOctave> {
+        let key = key.to_ascii_lowercase();
+        let (_, semitones) = QWERTY_KEYS.iter().find(|(k, _)| *k == key)?;
+        let c = NoteWithOctave {
+            note: Note::C,
+            octave: self.octave,
+        };
+        c.checked_add(*semitones)
+    }
+
+    /// Handle an octave key, returning whether `key` was one
+    pub fn shift(&mut self, key: char) -> bool {
+        match key.to_ascii_lowercase() {
+            OCTAVE_DOWN => self.octave = self.octave.saturating_sub(1),
+            OCTAVE_UP => self.octave = (self.octave + 1).min(MAX_OCTAVE - 1),
+            _ => return false,
+        }
+        true
+    }
+
+    /// The piano keys the computer keys cover, for drawing
+    pub fn keyboard(&self) -> Keyboard {
+        Keyboard::new(self.octave, 2)
+    }
+}
+
+/// A note being held, with whatever keeps it sounding, e.g. an audio `Drone`
+#[derive(Debug)]
+struct Voice<V> {
+    note: NoteWithOctave,
+    // Kept only so the note sounds until the voice is dropped
+    _sound: V,
+    started: Instant,
+    last_pressed: Instant,
+}
+
+/// The notes sounding in live play. Each one is let go once its key hasn't been seen for
+/// `RELEASE_AFTER`, and the oldest makes way when more than `MAX_VOICES` are pressed.
+/// Dropping a voice's sound is what silences it.
+#[derive(Debug)]
+pub struct Voices<V> {
+    voices: Vec<Voice<V>>,
+    max: usize,
+}
+
+impl<V> Default for Voices<V> {
+    fn default() -> Self {
+        Self {
+            voices: Vec::new(),
+            max: MAX_VOICES,
+        }
+    }
+}
+
+/// The held notes, lowest first, e.g. "C4 E4 G4"
+impl<V> fmt::Display for Voices<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self.notes().iter().map(|n| n.to_string()).collect();
+        write!(f, "{}", names.join(" "))
+    }
+}
+
+impl<V> Voices<V> {
+    /// Allow `max` notes at once, at least one
+    pub fn with_max(mut self, max: usize) -> Self {
+        self.max = max.max(1);
+        self
+    }
+
+    /// The key for `note` went down at `now`. A note that's already sounding is kept
+    /// going; otherwise `start` is called to sound it. Returns whether a new note started.
+    pub fn press<E>(
+        &mut self,
+        note: NoteWithOctave,
+        now: Instant,
+        start: impl FnOnce() -> Result<V, E>,
+    ) -> Result<bool, E> {
+        if let Some(voice) = self.voices.iter_mut().find(|voice| voice.note == note) {
+            voice.last_pressed = now;
+            return Ok(false);
+        }
+        let sound = start()?;
+        if self.voices.len() >= self.max
+            && let Some(oldest) = (0..self.voices.len()).min_by_key(|&i| self.voices[i].started)
+        {
+            self.voices.remove(oldest);
+        }
+        self.voices.push(Voice {
+            note,
+            _sound: sound,
+            started: now,
+            last_pressed: now,
+        });
+        Ok(true)
+    }
+
+    /// Let go of every note whose key hasn't been seen for `RELEASE_AFTER`, returning them
+    pub fn release_expired(&mut self, now: Instant) -> Vec<NoteWithOctave> {
+        let (expired, held): (Vec<Voice<V>>, Vec<Voice<V>>) = self
+            .voices
+            .drain(..)
+            .partition(|voice| now.saturating_duration_since(voice.last_pressed) >= RELEASE_AFTER);
+        self.voices = held;
+        expired.into_iter().map(|voice| voice.note).collect()
+    }
+
+    /// Let go of everything
+    pub fn release_all(&mut self) {
+        self.voices.clear();
+    }
+
+    /// The notes sounding, lowest first
+    pub fn notes(&self) -> Vec<NoteWithOctave> {
+        let mut notes: Vec<NoteWithOctave> = self.voices.iter().map(|voice| voice.note).collect();
+        notes.sort();
+        notes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.voices.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(s: &str) -> NoteWithOctave {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_qwerty_keys() {
+        let mut keys = QwertyKeyboard::default();
+        assert_eq!(keys.note_for('a'), Some(note("C4")));
+        assert_eq!(keys.note_for('w'), Some(note("C#4")));
+        assert_eq!(keys.note_for('S'), Some(note("D4")));
+        assert_eq!(keys.note_for('k'), Some(note("C5")));
+        assert_eq!(keys.note_for('\''), Some(note("F5")));
+        assert_eq!(keys.note_for('q'), None);
+
+        assert!(keys.shift('z'));
+        assert_eq!(keys.note_for('a'), Some(note("C3")));
+        assert!(!keys.shift('a'));
+        let top = QwertyKeyboard::default().with_octave(20);
+        assert_eq!(top.octave, MAX_OCTAVE - 1);
+    }
+
+    #[test]
+    fn test_voices_hold_and_release() {
+        let start = Instant::now();
+        let later = |ms| start + Duration::from_millis(ms);
+        let mut voices: Voices<u8> = Voices::default();
+        assert_eq!(voices.press(note("E4"), start, || Ok::<_, ()>(1)), Ok(true));
+        assert_eq!(
+            voices.press(note("C4"), later(100), || Ok::<_, ()>(2)),
+            Ok(true)
+        );
+        // Auto-repeat keeps E4 going without starting it again
+        assert_eq!(
+            voices.press(note("E4"), later(600), || Err("restarted")),
+            Ok(false)
+        );
+        assert_eq!(voices.to_string(), "C4 E4");
+
+        assert_eq!(voices.release_expired(later(800)), [note("C4")]);
+        assert_eq!(voices.notes(), [note("E4")]);
+        assert_eq!(voices.release_expired(later(1300)), [note("E4")]);
+        assert!(voices.is_empty());
+    }
+
+    #[test]
+    fn test_voices_steal_the_oldest() {
+        let now = Instant::now();
+        let mut voices: Voices<()> = Voices::default().with_max(2);
+        for (i, name) in ["C4", "E4", "G4"].iter().enumerate() {
+            let pressed = now + Duration::from_millis(i as u64);
+            voices
+                .press(note(name), pressed, || Ok::<_, ()>(()))
+                .unwrap();
+        }
+        assert_eq!(voices.notes(), [note("E4"), note("G4")]);
+        assert!(voices.press(note("A4"), now, || Err(())).is_err());
+        assert_eq!(voices.notes().len(), 2);
+        voices.release_all();
+        assert!(voices.is_empty());
+    }
+}
@@ -1,102 +1,3097 @@
-use ear_trainer::notes::{NoteWithOctave, Scale, ScaleType};
-use std::io;
+use clap::builder::{PossibleValue, StringValueParser, TypedValueParser};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use ear_trainer::answer::{AnswerInput, TerminalInput};
+use ear_trainer::arpeggio::{Arpeggio, ArpeggioPattern, MAX_ARPEGGIO_OCTAVES};
+#[cfg(feature = "tui")]
+use ear_trainer::audio::Drone;
+use ear_trainer::audio::{AudioEngine, Playable, PlaybackOptions, Waveform};
+use ear_trainer::chords::{Chord, ChordQuality, Inversion};
+use ear_trainer::config::{QuizConfig, default_path as default_config_path};
+use ear_trainer::difficulty::DifficultyController;
+use ear_trainer::display::{
+    Clef, DEFAULT_FRETS, Fretboard, Keyboard, MAX_FRETS, Staff, StringTuning,
+};
+use ear_trainer::export::{ExportFormat, export_missed, missed_cards};
+use ear_trainer::generator::random_interval;
+use ear_trainer::input::{
+    ANALYSIS_WINDOW, IN_TUNE_CENTS, Microphone, PitchDetector, StringReading, StringTuner, needle,
+};
+use ear_trainer::intervals::{Interval, IntervalPlayStyle};
+use ear_trainer::json::{
+    ConfusionsOutput, ErrorOutput, NoteOutput, ScaleOutput, StatsOutput, to_json,
+};
+use ear_trainer::keys::{KeySignature, key_of};
+#[cfg(feature = "tui")]
+use ear_trainer::live::{DEFAULT_LIVE_OCTAVE, QwertyKeyboard, Voices};
+use ear_trainer::melody::Melody;
+use ear_trainer::metronome::{MAX_SUBDIVISIONS, Metronome};
+use ear_trainer::naming::{NamingScheme, NoteNaming, PitchNotation};
+use ear_trainer::notes::{
+    MAX_SCALE_OCTAVES, Note, NoteWithOctave, Scale, ScaleDirection, Tuning, TuningSystem,
+};
+use ear_trainer::progression::Progression;
+use ear_trainer::recording::SessionRecorder;
+use ear_trainer::render::{Render, RenderConfig};
+use ear_trainer::report::SessionReport;
+use ear_trainer::rhythm::{RHYTHM_BEATS, Rhythm, count_in, read_taps};
+use ear_trainer::scala::{KeyboardMapping, ScalaScale, ScalaTuning};
+use ear_trainer::script::Script;
+use ear_trainer::smf::read_melody;
+use ear_trainer::spelling::SpelledPitch;
+use ear_trainer::srs::Scheduler;
+use ear_trainer::stats::{AnswerRecord, Exercise, StatsStore};
+use ear_trainer::tempo::{NoteValue, Tempo, TimeSignature, bpm_from_taps};
+use ear_trainer::training::{
+    Cadence, CadenceAccuracy, CadenceQuestion, ComparisonAnswer, DICTATION_DEFAULT_LENGTH,
+    DICTATION_DEFAULT_MAX_LEAP, DICTATION_DEFAULT_RANGE, DegreeAccuracy, DegreeQuestion,
+    DictationOptions, ESTIMATION_MAX_HZ, ESTIMATION_MIN_HZ, EarAnswer, EarComparison, GuessGrade,
+    INTONATION_DEFAULT_CENTS, IntervalComparison, IntervalQuestion, IntonationAnswer,
+    IntonationQuestion, InversionQuestion, MelodicDictationQuestion, NoteAccuracy, NoteQuestion,
+    QuarterToneInterval, QuarterToneQuestion, QuizScore, SING_BACK_TOLERANCE_CENTS, SOLFEGE,
+    SingBackQuestion, default_quiz_intervals, grade_frequency_guess, is_nearest_note,
+    parse_note_range, parse_scale_degree, random_frequency,
+};
+#[cfg(feature = "tui")]
+use ear_trainer::tui::{Action, MAX_CHOICES, RawTerminal, TuiScreen, pick_choices};
+#[cfg(feature = "tui")]
+use ratatui::widgets::Paragraph;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-fn main() {
-    println!("🎵 Music Note Frequency Calculator 🎵");
-    println!("=====================================\n");
+/// Ear training, note frequencies and practice tools
+#[derive(Parser)]
+#[command(name = "ear-trainer", version, about)]
+struct Cli {
+    /// Record the microphone to session-<timestamp>.wav
+    #[arg(long, global = true)]
+    record: bool,
+
+    /// Record the microphone and mix in the notes the trainer plays
+    #[arg(long, global = true)]
+    record_with_playback: bool,
+
+    /// Timbre of every tone
+    #[arg(long, global = true, value_name = "NAME", value_parser = named::<Waveform>(&["sine", "square", "triangle", "sawtooth"]))]
+    waveform: Option<Waveform>,
+
+    /// Loudness of every tone in percent of full scale, 30 by default;
+    /// turn it up on laptop speakers or down on headphones
+    #[arg(long, global = true, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(0..=100))]
+    volume: Option<u8>,
+
+    /// Reference pitch for A4 in Hz
+    #[arg(long, global = true, value_name = "HZ", value_parser = parse_tuning)]
+    a4: Option<Tuning>,
+
+    /// Tune notes in equal, just, pythagorean or meantone temperament, or an EDO like 31-edo
+    #[arg(long, global = true, value_name = "SYSTEM")]
+    temperament: Option<TuningSystem>,
+
+    /// Note the temperament is tuned from
+    #[arg(long, global = true, value_name = "NOTE", default_value_t = Note::C)]
+    temperament_tonic: Note,
+
+    /// Retune every note with a Scala scale (.scl) file
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        conflicts_with = "temperament"
+    )]
+    scl: Option<PathBuf>,
+
+    /// Scala keyboard mapping (.kbm) for the --scl scale; without one the scale's tonic
+    /// is middle C and A4 keeps its reference pitch
+    #[arg(long, global = true, value_name = "FILE", requires = "scl")]
+    kbm: Option<PathBuf>,
+
+    /// Speed of scales and arpeggios in BPM
+    #[arg(long, global = true, value_name = "BPM")]
+    tempo: Option<Tempo>,
+
+    /// Show and type notes as letters, german (H is B natural, B is B flat),
+    /// fixed-do or latin (Do is always C) or movable-do (Do is the tonic of the key)
+    #[arg(long, global = true, value_name = "SCHEME", default_value_t = NamingScheme::Letters, value_parser = named::<NamingScheme>(&["letters", "german", "fixed-do", "movable-do"]))]
+    naming: NamingScheme,
+
+    /// Write octaves in scientific (C4 is middle C) or helmholtz (c' is middle C) notation
+    #[arg(long, global = true, value_name = "NOTATION", default_value_t = PitchNotation::Scientific, value_parser = named::<PitchNotation>(&["scientific", "helmholtz"]))]
+    notation: PitchNotation,
+
+    /// Print `note`, `scale` and `stats` output as JSON for scripts; status messages go
+    /// to stderr
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Play notes on a MIDI synth; picks the first port whose name contains PORT,
+    /// or the first port of all when left empty
+    #[cfg(feature = "midi")]
+    #[arg(long, global = true, value_name = "PORT", num_args = 0..=1, default_missing_value = "")]
+    midi_out: Option<String>,
+
+    /// Answer quizzes by playing a MIDI keyboard; picks the first port whose name contains
+    /// PORT, or the first port of all when left empty
+    #[cfg(feature = "midi")]
+    #[arg(long, global = true, value_name = "PORT", num_args = 0..=1, default_missing_value = "")]
+    midi_in: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Pick exercises from a menu (the default)
+    Interactive,
+    /// Print a completion script, e.g. `completions bash > ~/.local/share/bash-completion/completions/ear-trainer`
+    Completions {
+        /// Shell to complete for
+        shell: Shell,
+    },
+    /// Print a note's frequency and play it, e.g. `note C4`
+    Note {
+        note: String,
+        /// How long to play the note, in seconds
+        #[arg(long, default_value_t = 1.0)]
+        seconds: f64,
+    },
+    /// Play a scale, e.g. `scale "F# minor"` or `scale "A melodic minor" --direction up-down`
+    Scale {
+        scale: String,
+        /// How many octaves to cover
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=MAX_SCALE_OCTAVES as i64))]
+        octaves: u8,
+        /// Which way to play the scale
+        #[arg(long, default_value_t = ScaleDirection::Ascending, value_parser = named::<ScaleDirection>(&["ascending", "descending", "up-down"]))]
+        direction: ScaleDirection,
+    },
+    /// Arpeggiate and then sound a chord, e.g. `chord "G dom7"` or `chord F#m7b5`
+    Chord { chord: String },
+    /// Show where a scale or chord lies on a guitar neck,
+    /// e.g. `fretboard "A minor pentatonic"` or `fretboard "G dom7" --tuning drop-d`
+    Fretboard {
+        scale_or_chord: String,
+        /// standard, drop-d, dadgad, open-g, bass, or the open strings from lowest to
+        /// highest like "D2 A2 D3 G3 B3 E4"
+        #[arg(long, default_value = "standard")]
+        tuning: StringTuning,
+        /// How many frets to show past the open strings
+        #[arg(long, default_value_t = DEFAULT_FRETS, value_parser = clap::value_parser!(u8).range(1..=MAX_FRETS as i64))]
+        frets: u8,
+    },
+    /// Write a scale or melody on a staff, e.g. `staff "Eb major"` or `staff "G4 A4 B4:h"`
+    Staff {
+        scale_or_melody: String,
+        /// Picked from the notes if left out
+        #[arg(long, value_parser = named::<Clef>(&["treble", "bass"]))]
+        clef: Option<Clef>,
+    },
+    /// Play a chord one note at a time, e.g. `arpeggio "C maj7" --pattern up-down`
+    Arpeggio {
+        chord: String,
+        /// Order to play the chord tones in
+        #[arg(long, default_value_t = ArpeggioPattern::Up, value_parser = named::<ArpeggioPattern>(&["up", "down", "up-down", "random"]))]
+        pattern: ArpeggioPattern,
+        /// How many octaves to cover
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=MAX_ARPEGGIO_OCTAVES as i64))]
+        octaves: u8,
+    },
+    /// Hear a chord in equal temperament and then in another tuning system,
+    /// e.g. `temperament "C major" --system just`
+    Temperament {
+        chord: String,
+        /// just, pythagorean, meantone or an EDO like 19-edo
+        #[arg(long, default_value_t = TuningSystem::JustIntonation)]
+        system: TuningSystem,
+    },
+    /// Play Roman-numeral chords in a key, e.g. `progression "C major" ii-V-I`
+    Progression { key: String, numerals: String },
+    /// Play a scale over a drone on its tonic
+    Drone {
+        scale: String,
+        /// Add the fifth to the drone
+        #[arg(long)]
+        fifth: bool,
+    },
+    /// Click a steady pulse until Enter is pressed, e.g. `metronome 120 4/4`
+    Metronome {
+        bpm: Tempo,
+        /// Accents the first beat of each bar, and every third pulse in 6/8, 9/8 or 12/8
+        #[arg(default_value_t = TimeSignature::COMMON)]
+        time_signature: TimeSignature,
+        /// Clicks per pulse: 2 for eighths in 4/4, 3 for triplets, 4 for sixteenths
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=MAX_SUBDIVISIONS as i64))]
+        subdivisions: u8,
+    },
+    /// Play the melody from a MIDI file to write down by ear, e.g. `transcribe riff.mid`
+    Transcribe {
+        file: PathBuf,
+        /// Bars of clicks before each playing, in the file's time signature
+        #[arg(long, value_name = "BARS", default_value_t = 0)]
+        count_in: u8,
+        /// Keep the metronome clicking under the melody
+        #[arg(long)]
+        click: bool,
+    },
+    /// Play notes, chords, rests and tempo changes written as text, from a file or `-`
+    /// for stdin, e.g. `play song.txt` or `echo "tempo 90 C4 E4:e G4:e Am:h" | play -`
+    #[command(alias = "run")]
+    Play {
+        /// Script file, or - to read it from stdin
+        script: PathBuf,
+        /// Render the script to a WAV file instead of playing it
+        #[arg(long, value_name = "FILE")]
+        wav: Option<PathBuf>,
+    },
+    /// Show accuracy from past quiz sessions
+    Stats {
+        #[command(subcommand)]
+        view: Option<StatsView>,
+    },
+    /// Turn missed quiz questions into flashcards, with a WAV file of each question,
+    /// e.g. `export --format anki --out deck`
+    Export {
+        /// csv for a spreadsheet, or anki for a deck to import with File > Import
+        #[arg(long, default_value_t = ExportFormat::Csv, value_parser = named::<ExportFormat>(&["csv", "anki"]))]
+        format: ExportFormat,
+        /// Directory to write the deck and its audio to
+        #[arg(long, value_name = "DIR", default_value = "ear-trainer-export")]
+        out: PathBuf,
+        /// Only look at the most recent practice sessions
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        sessions: Option<u64>,
+    },
+    /// Show or change which intervals, chords, keys, notes and directions the quizzes use,
+    /// e.g. `config --intervals M3,P5 --range C4-C5 --directions ascending`
+    Config {
+        /// Intervals to ask about, e.g. M3,P5
+        #[arg(long, value_delimiter = ',')]
+        intervals: Option<Vec<Interval>>,
+        /// Chord qualities to play, e.g. major,minor,dom7
+        #[arg(long, value_delimiter = ',')]
+        chords: Option<Vec<ChordQuality>>,
+        /// Tonics of the major keys to play in, e.g. C,G,F
+        #[arg(long, value_delimiter = ',')]
+        keys: Option<Vec<Note>>,
+        /// Lowest and highest note to play, e.g. C4-C5
+        #[arg(long, value_parser = parse_note_range)]
+        range: Option<(NoteWithOctave, NoteWithOctave)>,
+        /// Ways to sound intervals: ascending, descending and/or harmonic
+        #[arg(long, value_delimiter = ',')]
+        directions: Option<Vec<IntervalPlayStyle>>,
+        /// Lift every limit before applying the others
+        #[arg(long)]
+        reset: bool,
+    },
+    /// Tune an instrument or voice with the microphone; Ctrl+C to stop
+    Tune {
+        /// Tune these strings one at a time, playing each one's pitch and moving on once it's
+        /// in tune: standard, drop-d, dadgad, open-g, bass, or notes like "D2 A2 D3 G3 B3 E4"
+        #[arg(long, value_name = "TUNING")]
+        strings: Option<StringTuning>,
+        /// How close to its pitch, in cents, a string has to stay to count as in tune
+        #[arg(long, default_value_t = IN_TUNE_CENTS, requires = "strings")]
+        cents: f64,
+        /// Only listen, without playing each string's pitch
+        #[arg(long, requires = "strings")]
+        quiet: bool,
+    },
+    /// Play notes from the computer keyboard: a w s e d f t g y h u j k from C upwards,
+    /// z and x to change octave, q to quit
+    #[cfg(feature = "tui")]
+    Live {
+        /// Octave of the C under the a key
+        #[arg(long, default_value_t = DEFAULT_LIVE_OCTAVE)]
+        octave: u8,
+    },
+    /// Full-screen interval drill: 1-9 to answer, r to replay, space for the next
+    /// question and q to quit
+    #[cfg(feature = "tui")]
+    Tui {
+        /// How to play each interval; otherwise the config's directions
+        #[arg(long, value_parser = named::<IntervalPlayStyle>(PLAY_STYLES))]
+        style: Option<IntervalPlayStyle>,
+    },
+    /// Run an ear-training quiz
+    Quiz {
+        /// Also write the end-of-session report to FILE as JSON
+        #[arg(long, global = true, value_name = "FILE")]
+        report: Option<PathBuf>,
+        #[command(subcommand)]
+        quiz: Quiz,
+    },
+}
+
+#[derive(Subcommand)]
+enum StatsView {
+    /// Show which intervals, chords or notes you mix up most, to practise them side by side
+    Confusions {
+        /// How many recent practice sessions to look at
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+        sessions: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum Quiz {
+    /// Name intervals by ear
+    Intervals {
+        /// How to play each interval; asked for when left out
+        #[arg(long, alias = "mode", value_parser = named::<IntervalPlayStyle>(PLAY_STYLES))]
+        style: Option<IntervalPlayStyle>,
+        /// Add intervals, widen the range and speed up as your accuracy rises
+        #[arg(long)]
+        adaptive: bool,
+    },
+    /// Interval quiz that brings back missed intervals sooner, using your saved answers
+    Smart {
+        /// How to play each interval; asked for when left out
+        #[arg(long, alias = "mode", value_parser = named::<IntervalPlayStyle>(PLAY_STYLES))]
+        style: Option<IntervalPlayStyle>,
+        /// Add intervals, widen the range and speed up as your accuracy rises
+        #[arg(long)]
+        adaptive: bool,
+    },
+    /// Tell which of two intervals is larger
+    Compare,
+    /// Tell whether the note in your left or right ear is higher
+    Ears,
+    /// Tell whether a note is sharp, flat or in tune against a reference
+    Intonation {
+        /// How many cents out of tune the notes may be; smaller is harder
+        #[arg(long, value_name = "CENTS", default_value_t = INTONATION_DEFAULT_CENTS)]
+        cents: f64,
+    },
+    /// Name intervals of whole and half semitones, played in 24-EDO quarter tones
+    QuarterTones,
+    /// Write down or tap back a one-bar rhythm played as clicks, at --tempo (90 BPM by default)
+    Rhythm,
+    /// Name notes played after a C4 reference
+    Notes {
+        /// Range of mystery notes, e.g. C4-B4; asked for when left out
+        #[arg(long, value_parser = parse_note_range, conflicts_with = "adaptive")]
+        range: Option<(NoteWithOctave, NoteWithOctave)>,
+        /// Widen the range and speed up as your accuracy rises
+        #[arg(long)]
+        adaptive: bool,
+    },
+    /// Guess frequencies and name the nearest notes
+    Frequency,
+    /// Name the cadence that ends a short chord progression
+    Cadences,
+    /// Tell which chord tone is in the bass of a block chord
+    Inversions,
+    /// Name scale degrees (do, re, mi...) heard after a cadence sets up the key
+    Degrees,
+    /// Sing back a note or short melody, scored on pitch through the microphone
+    Sing {
+        /// Notes in each melody; 1 sings back single notes
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=8))]
+        notes: u8,
+        /// How many cents off a note may be and still count as correct
+        #[arg(long, value_name = "CENTS", default_value_t = SING_BACK_TOLERANCE_CENTS)]
+        tolerance: f64,
+    },
+    /// Write down a short melody in a major key as note names, at --tempo (80 BPM by default)
+    Dictation {
+        /// Notes in each melody
+        #[arg(long, default_value_t = DICTATION_DEFAULT_LENGTH as u8, value_parser = clap::value_parser!(u8).range(2..=12))]
+        notes: u8,
+        /// How many scale degrees above the tonic the melody may climb
+        #[arg(long, value_name = "DEGREES", default_value_t = DICTATION_DEFAULT_RANGE, value_parser = clap::value_parser!(u8).range(1..=14))]
+        range: u8,
+        /// Biggest leap between notes in scale degrees, 1 for stepwise melodies
+        #[arg(long, value_name = "DEGREES", default_value_t = DICTATION_DEFAULT_MAX_LEAP, value_parser = clap::value_parser!(u8).range(1..=7))]
+        max_leap: u8,
+        /// How many times each melody is played before you answer
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=5))]
+        plays: u8,
+    },
+}
+
+/// Save the quiz settings and show what the quizzes will now ask about
+fn update_config(config: &QuizConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let path = default_config_path().ok_or("Could not find a config directory")?;
+    config.save(&path)?;
+    println!("⚙️ Quiz settings in {}:", path.display());
+    println!("{}", config);
+    Ok(())
+}
+
+/// The tuning picked on the command line, shared by playback and offline rendering: the
+/// reference pitch, then a temperament or a Scala scale tuned from it
+fn cli_tuning(cli: &Cli) -> Result<Tuning, String> {
+    let mut tuning = cli.a4.clone().unwrap_or_default();
+    if let Some(system) = &cli.temperament {
+        tuning = tuning.with_system(system.clone(), cli.temperament_tonic);
+    }
+    if let Some(scl) = &cli.scl {
+        let scala = load_scala(scl, cli.kbm.as_deref(), tuning.a4())
+            .map_err(|e| format!("Could not load {}: {}", scl.display(), e))?;
+        tuning = tuning.with_system(TuningSystem::Scala(Arc::new(scala)), Note::C);
+    }
+    Ok(tuning)
+}
+
+/// Load a Scala scale, laid out by the keyboard mapping if there is one
+fn load_scala(
+    scl: &Path,
+    kbm: Option<&Path>,
+    a4: f64,
+) -> Result<ScalaTuning, Box<dyn std::error::Error>> {
+    let scale = ScalaScale::load(scl)?;
+    Ok(match kbm {
+        Some(kbm) => ScalaTuning::with_keyboard(scale, KeyboardMapping::load(kbm)?),
+        None => ScalaTuning::new(scale, a4),
+    })
+}
+
+fn parse_tuning(s: &str) -> Result<Tuning, String> {
+    let a4 = s.parse::<f64>().map_err(|e| e.to_string())?;
+    Tuning::new(a4)
+}
+
+const PLAY_STYLES: &[&str] = &["ascending", "descending", "harmonic"];
+
+/// Parses a value with its `FromStr`, aliases included, but offers `names` to shell
+/// completion and `--help`
+#[derive(Clone)]
+struct Named<T> {
+    names: &'static [&'static str],
+    parsed: PhantomData<T>,
+}
+
+fn named<T>(names: &'static [&'static str]) -> Named<T> {
+    Named {
+        names,
+        parsed: PhantomData,
+    }
+}
+
+impl<T> TypedValueParser for Named<T>
+where
+    T: FromStr<Err = String> + Clone + Send + Sync + 'static,
+{
+    type Value = T;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<T, clap::Error> {
+        StringValueParser::new()
+            .try_map(|s| s.parse::<T>())
+            .parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(self.names.iter().copied().map(PossibleValue::new)))
+    }
+}
+
+fn main() -> ExitCode {
+    let mut cli = Cli::parse();
+    let command = cli.command.take().unwrap_or(Command::Interactive);
+    let interactive = matches!(command, Command::Interactive);
+    let json = cli.json;
+
+    if let Command::Completions { shell } = command {
+        clap_complete::generate(shell, &mut Cli::command(), "ear-trainer", &mut io::stdout());
+        return ExitCode::SUCCESS;
+    }
+
+    // Every quiz answer is saved so progress carries across sessions
+    let mut stats = StatsStore::open_default().unwrap_or_else(|e| {
+        status(
+            json,
+            format!("❌ Could not load stats, answers won't be saved: {}", e),
+        );
+        StatsStore::in_memory()
+    });
+    if let Command::Stats { view } = command {
+        match view {
+            None if json => println!("{}", to_json(&StatsOutput::new(&stats, None))),
+            None => print_stats(&stats),
+            Some(StatsView::Confusions { sessions }) if json => println!(
+                "{}",
+                to_json(&ConfusionsOutput::new(&stats, sessions as usize))
+            ),
+            Some(StatsView::Confusions { sessions }) => print_confusions(&stats, sessions as usize),
+        }
+        return ExitCode::SUCCESS;
+    }
+    if let Command::Export {
+        format,
+        out,
+        sessions,
+    } = &command
+    {
+        let render = match render_config(&cli) {
+            Ok(render) => render,
+            Err(e) => {
+                report_error(json, e);
+                return ExitCode::FAILURE;
+            }
+        };
+        return match export_missed_questions(&stats, *format, out, *sessions, &render) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                report_error(json, format!("Could not export missed questions: {}", e));
+                ExitCode::FAILURE
+            }
+        };
+    }
+    // Rendering a script writes a file instead of playing it
+    if let Command::Play {
+        script,
+        wav: Some(wav),
+    } = &command
+    {
+        let render = match render_config(&cli) {
+            Ok(render) => RenderConfig {
+                note_duration: cli.tempo.unwrap_or_default().beat_duration(),
+                ..render
+            },
+            Err(e) => {
+                report_error(json, e);
+                return ExitCode::FAILURE;
+            }
+        };
+        return match render_script(script, wav, &render) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                report_error(
+                    json,
+                    format!("Could not render {}: {}", script.display(), e),
+                );
+                ExitCode::FAILURE
+            }
+        };
+    }
+    // Diagrams don't play anything
+    if let Command::Fretboard {
+        scale_or_chord,
+        tuning,
+        frets,
+    } = &command
+    {
+        return if show_fretboard(scale_or_chord, tuning, *frets) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    }
+    if let Command::Staff {
+        scale_or_melody,
+        clef,
+    } = &command
+    {
+        return if show_staff(scale_or_melody, *clef) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    }
+    let config = QuizConfig::open_default().unwrap_or_else(|e| {
+        status(
+            json,
+            format!(
+                "❌ Could not load quiz settings, asking about everything: {}",
+                e
+            ),
+        );
+        QuizConfig::default()
+    });
+    if let Command::Config {
+        intervals,
+        chords,
+        keys,
+        range,
+        directions,
+        reset,
+    } = command
+    {
+        let mut config = if reset { QuizConfig::default() } else { config };
+        if let Some(intervals) = intervals {
+            config = config.with_intervals(intervals);
+        }
+        if let Some(chords) = chords {
+            config = config.with_chords(chords);
+        }
+        if let Some(keys) = keys {
+            config = config.with_keys(keys);
+        }
+        if let Some((low, high)) = range {
+            config = config.with_range(low, high);
+        }
+        if let Some(directions) = directions {
+            config = config.with_directions(directions);
+        }
+        return match update_config(&config) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                report_error(json, format!("Could not save quiz settings: {}", e));
+                ExitCode::FAILURE
+            }
+        };
+    }
+    // The tuner only listens, so it only opens the output device to play strings' pitches
+    if let Command::Tune {
+        strings,
+        cents,
+        quiet,
+    } = command
+    {
+        let tuning = match cli_tuning(&cli) {
+            Ok(tuning) => tuning,
+            Err(e) => {
+                report_error(json, e);
+                return ExitCode::FAILURE;
+            }
+        };
+        let engine = match &strings {
+            Some(_) if !quiet => match AudioEngine::new() {
+                Ok(mut engine) => {
+                    engine.set_tuning(tuning.clone());
+                    Some(engine)
+                }
+                Err(e) => {
+                    report_error(json, format!("Could not open audio output: {}", e));
+                    return ExitCode::FAILURE;
+                }
+            },
+            _ => None,
+        };
+        let result = match strings {
+            Some(strings) => run_string_tuner(engine.as_ref(), &strings, cents, &tuning),
+            None => run_tuner(tuning),
+        };
+        return match result {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                report_error(json, format!("Tuner error: {}", e));
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    if interactive {
+        println!("🎵 Music Note Frequency Calculator 🎵");
+        println!("=====================================\n");
+    }
+
+    // `--record` captures the microphone, `--record-with-playback` also mixes in played notes
+    let mix_playback = cli.record_with_playback;
+    let recorder = if mix_playback || cli.record {
+        match SessionRecorder::start(".", mix_playback) {
+            Ok(recorder) => {
+                status(
+                    json,
+                    format!("🎙️ Recording session to {}", recorder.path().display()),
+                );
+                Some(recorder)
+            }
+            Err(e) => {
+                status(json, format!("❌ Could not start recording: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Open the output device once and share it across the whole session
+    let mut engine = match AudioEngine::new() {
+        Ok(engine) => engine,
+        Err(e) => {
+            report_error(json, format!("Could not open audio output: {}", e));
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let tuning = match cli_tuning(&cli) {
+        Ok(tuning) => tuning,
+        Err(e) => {
+            report_error(json, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(a4) = &cli.a4 {
+        status(json, format!("🎚️ Tuning A4 to {:.1} Hz", a4.a4()));
+    }
+    if let Some(system) = &cli.temperament {
+        status(
+            json,
+            format!("🎚️ Tuning in {} from {}", system, cli.temperament_tonic),
+        );
+    }
+    if cli.scl.is_some() {
+        status(json, format!("🎚️ Tuning to {}", tuning.system()));
+    }
+    engine.set_tuning(tuning);
+    if let Some(waveform) = cli.waveform {
+        status(json, format!("🎛️ Using a {} wave", waveform));
+        engine.set_waveform(waveform);
+    }
+    if let Some(volume) = cli.volume {
+        status(json, format!("🔉 Volume at {}%", volume));
+        engine.set_gain(volume as f32 / 100.0);
+    }
+    if let Some(tempo) = cli.tempo {
+        status(json, format!("🥁 Playing at {}", tempo));
+    }
+    #[cfg(feature = "midi")]
+    if let Some(port) = &cli.midi_out {
+        let port = Some(port.as_str()).filter(|port| !port.is_empty());
+        match ear_trainer::midi::MidiOut::connect(port) {
+            Ok(midi) => {
+                status(json, format!("🎹 Sending notes to {}", midi.port_name()));
+                engine.set_midi_output(Some(midi));
+            }
+            Err(e) => {
+                report_error(json, format!("Could not open MIDI output: {}", e));
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let tempo = cli.tempo;
+
+    // Quizzes that can be answered by playing take answers from here
+    let mut input: Box<dyn AnswerInput> = Box::new(TerminalInput);
+    #[cfg(feature = "midi")]
+    if let Some(port) = &cli.midi_in {
+        let port = Some(port.as_str()).filter(|port| !port.is_empty());
+        match ear_trainer::midi::KeyboardInput::connect(port) {
+            Ok(keyboard) => {
+                status(
+                    json,
+                    format!("🎹 Play your answers on {}", keyboard.port_name()),
+                );
+                input = Box::new(keyboard);
+            }
+            Err(e) => {
+                report_error(json, format!("Could not open MIDI input: {}", e));
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let input = input.as_mut();
+
+    let naming = NoteNaming::from(cli.naming).with_notation(cli.notation);
+
+    // Answers logged from here on make up the session report
+    let session_start = stats.records().len();
+    let report_path = match &command {
+        Command::Quiz { report, .. } => report.clone(),
+        _ => None,
+    };
+    let ok = match command {
+        Command::Interactive => {
+            run_interactive(&engine, tempo, naming, &config, &mut stats, input);
+            true
+        }
+        Command::Note { note, seconds } => {
+            match (
+                naming.parse_spelled_pitch(&note, &Note::C),
+                Duration::try_from_secs_f64(seconds),
+            ) {
+                (Ok(note), Ok(duration)) => play_note(&engine, &note, duration, naming, json),
+                (Err(e), _) => {
+                    report_error(json, e);
+                    false
+                }
+                (_, Err(_)) => {
+                    report_error(json, format!("Invalid duration: {} seconds", seconds));
+                    false
+                }
+            }
+        }
+        Command::Scale {
+            scale,
+            octaves,
+            direction,
+        } => match parse_scale(&naming.to_letters(&scale, &Note::C)) {
+            Ok(scale) => play_scale_run(
+                &engine,
+                &scale,
+                octaves,
+                direction,
+                tempo.unwrap_or_default(),
+                naming,
+                json,
+            ),
+            Err(e) => {
+                report_error(json, e);
+                false
+            }
+        },
+        Command::Chord { chord } => match parse_chord(&naming.to_letters(&chord, &Note::C)) {
+            Ok(chord) => play(&engine, &chord, tempo.unwrap_or_default()),
+            Err(e) => {
+                report_error(json, e);
+                false
+            }
+        },
+        Command::Arpeggio {
+            chord,
+            pattern,
+            octaves,
+        } => match parse_chord(&naming.to_letters(&chord, &Note::C)) {
+            Ok(chord) => {
+                let arpeggio = Arpeggio::new(chord, pattern).with_octaves(octaves);
+                play(&engine, &arpeggio, tempo.unwrap_or_default())
+            }
+            Err(e) => {
+                report_error(json, e);
+                false
+            }
+        },
+        Command::Temperament { chord, system } => {
+            match parse_chord(&naming.to_letters(&chord, &Note::C)) {
+                Ok(chord) => play_in_temperaments(&engine, &chord, system),
+                Err(e) => {
+                    report_error(json, e);
+                    false
+                }
+            }
+        }
+        Command::Progression { key, numerals } => {
+            let key = parse_scale(&naming.to_letters(&key, &Note::C));
+            match key.and_then(|key| Progression::parse(key, &numerals)) {
+                Ok(progression) => play(&engine, &progression, tempo.unwrap_or_default()),
+                Err(e) => {
+                    report_error(json, e);
+                    false
+                }
+            }
+        }
+        Command::Drone { scale, fifth } => {
+            match parse_scale(&naming.to_letters(&scale, &Note::C)) {
+                Ok(scale) => play_drone(&engine, &scale, tempo.unwrap_or(Tempo(75)), fifth),
+                Err(e) => {
+                    report_error(json, e);
+                    false
+                }
+            }
+        }
+        Command::Metronome {
+            bpm,
+            time_signature,
+            subdivisions,
+        } => run_metronome(
+            &engine,
+            Metronome::new(bpm, time_signature).with_subdivisions(subdivisions),
+        ),
+        Command::Transcribe {
+            file,
+            count_in,
+            click,
+        } => handle_transcribe_path(&engine, &file, tempo, count_in, click),
+        Command::Play { script, .. } => play_script(&engine, &script, tempo.unwrap_or_default()),
+        #[cfg(feature = "tui")]
+        Command::Tui { style } => run_tui(&engine, style, &config, &mut stats),
+        #[cfg(feature = "tui")]
+        Command::Live { octave } => run_live(&engine, octave),
+        // Handled before the audio device is opened
+        Command::Stats { .. }
+        | Command::Export { .. }
+        | Command::Fretboard { .. }
+        | Command::Staff { .. }
+        | Command::Config { .. }
+        | Command::Completions { .. }
+        | Command::Tune { .. } => true,
+        Command::Quiz { quiz, .. } => {
+            let stats = &mut stats;
+            match quiz {
+                Quiz::Intervals { style, adaptive } => handle_interval_quiz_path(
+                    &engine, style, false, adaptive, &config, stats, input,
+                ),
+                Quiz::Smart { style, adaptive } => {
+                    handle_interval_quiz_path(&engine, style, true, adaptive, &config, stats, input)
+                }
+                Quiz::Compare => handle_interval_comparison_path(&engine, &config, stats),
+                Quiz::Ears => handle_ear_drill_path(&engine, &config, stats),
+                Quiz::Intonation { cents } => {
+                    handle_intonation_drill_path(&engine, cents, &config, stats)
+                }
+                Quiz::QuarterTones => handle_quarter_tone_quiz_path(&engine, &config, stats),
+                Quiz::Rhythm => {
+                    handle_rhythm_dictation_path(&engine, tempo.unwrap_or(Tempo(90)), stats)
+                }
+                Quiz::Notes { range, adaptive } => handle_note_identification_path(
+                    &engine, range, adaptive, naming, &config, stats, input,
+                ),
+                Quiz::Frequency => handle_frequency_game_path(&engine, naming, stats),
+                Quiz::Cadences => handle_cadence_quiz_path(&engine, &config, stats),
+                Quiz::Inversions => handle_inversion_quiz_path(&engine, &config, stats),
+                Quiz::Degrees => handle_degree_quiz_path(&engine, &config, stats, input),
+                Quiz::Sing { notes, tolerance } => {
+                    handle_sing_back_path(&engine, notes as usize, tolerance, &config, stats)
+                }
+                Quiz::Dictation {
+                    notes,
+                    range,
+                    max_leap,
+                    plays,
+                } => {
+                    let options = DictationOptions::default()
+                        .with_length(notes as usize)
+                        .with_range(range)
+                        .with_max_leap(max_leap);
+                    let tempo = tempo.unwrap_or(Tempo(80));
+                    handle_melodic_dictation_path(
+                        &engine, options, plays, tempo, naming, &config, stats,
+                    )
+                }
+            }
+            true
+        }
+    };
+
+    let report = SessionReport::from_records(&stats.records()[session_start..]);
+    if !report.is_empty() {
+        println!("\n📋 Session report: {}", report);
+    }
+    if let Some(path) = report_path {
+        match report.save(&path) {
+            Ok(()) => println!("💾 Report saved to {}", path.display()),
+            Err(e) => println!("❌ Could not save the report: {}", e),
+        }
+    }
+
+    if let Some(recorder) = recorder {
+        match recorder.finish() {
+            Ok(path) => println!("💾 Session saved to {}", path.display()),
+            Err(e) => println!("❌ Error saving recording: {}", e),
+        }
+    }
+
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// The original menu-driven session
+fn run_interactive(
+    engine: &AudioEngine,
+    tempo: Option<Tempo>,
+    naming: NoteNaming,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+    answers: &mut dyn AnswerInput,
+) {
+    loop {
+        println!(
+            "Play a note, a scale, a chord, a chord progression, a scale over a drone, the frequency game, compare intervals, compare pitches between your ears, judge intonation, take the interval quiz, name notes against a reference, identify cadences, name chord inversions, or name scale degrees? [n/s/c/p/d/f/i/e/t/q/r/k/v/g]"
+        );
+
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+        let input = input.trim();
+
+        if input == "n" {
+            handle_note_path(engine, naming);
+            break;
+        }
+
+        if input == "s" {
+            handle_scale_path(engine, tempo.unwrap_or_default());
+            break;
+        }
+
+        if input == "c" {
+            handle_chord_path(engine, tempo.unwrap_or_default());
+            break;
+        }
+
+        if input == "p" {
+            handle_progression_path(engine, tempo.unwrap_or_default());
+            break;
+        }
+
+        if input == "d" {
+            handle_drone_path(engine, tempo.unwrap_or(Tempo(75)));
+            break;
+        }
+
+        if input == "f" {
+            handle_frequency_game_path(engine, naming, stats);
+            break;
+        }
+
+        if input == "i" {
+            handle_interval_comparison_path(engine, config, stats);
+            break;
+        }
+
+        if input == "e" {
+            handle_ear_drill_path(engine, config, stats);
+            break;
+        }
+
+        if input == "t" {
+            handle_intonation_drill_path(engine, INTONATION_DEFAULT_CENTS, config, stats);
+            break;
+        }
+
+        if input == "q" {
+            handle_interval_quiz_path(engine, None, false, false, config, stats, answers);
+            break;
+        }
+
+        if input == "r" {
+            handle_note_identification_path(engine, None, false, naming, config, stats, answers);
+            break;
+        }
+
+        if input == "k" {
+            handle_cadence_quiz_path(engine, config, stats);
+            break;
+        }
+
+        if input == "v" {
+            handle_inversion_quiz_path(engine, config, stats);
+            break;
+        }
+
+        if input == "g" {
+            handle_degree_quiz_path(engine, config, stats, answers);
+            break;
+        }
+    }
+}
+
+fn handle_note_path(engine: &AudioEngine, naming: NoteNaming) {
+    loop {
+        println!("\n🎵 Enter a note (e.g. C4, A#3, Bb2) or 'q' to quit:");
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+        let input = input.trim();
+
+        if input == "q" {
+            println!("👋 Goodbye!");
+            break;
+        }
+
+        let note = match naming.parse_spelled_pitch(input, &Note::C) {
+            Ok(note) => note,
+            Err(_) => {
+                println!("❌ Invalid input. Please enter a valid note (e.g. C4, A#3, Bb2).");
+                continue;
+            }
+        };
+
+        play_note(engine, &note, Duration::from_secs(1), naming, false);
+    }
+}
+
+/// Print a note's frequency and play it, returning whether playback worked
+fn play_note(
+    engine: &AudioEngine,
+    note: &SpelledPitch,
+    duration: Duration,
+    naming: NoteNaming,
+    json: bool,
+) -> bool {
+    let name = naming.name_spelled_pitch(note, &Note::C);
+    let Some(pitch) = note.pitch() else {
+        report_error(json, format!("{} is out of range", name));
+        return false;
+    };
+    match NoteOutput::new(note, engine.tuning()) {
+        Some(output) if json => println!("{}", to_json(&output.with_name(name.clone()))),
+        _ => println!(
+            "📊 {} = {:.2} Hz",
+            name,
+            engine.tuning().frequency_of(&pitch)
+        ),
+    }
+
+    match pitch.play_on(engine, duration) {
+        Ok(_) => {
+            status(json, format!("🎶 Playing {}...", name));
+            true
+        }
+        Err(e) => {
+            status(json, format!("❌ Error playing {}: {}", name, e));
+            false
+        }
+    }
+}
+
+/// Print a progress or settings message, on stderr when stdout is kept for JSON
+fn status(json: bool, message: impl fmt::Display) {
+    if json {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// Print why a command failed, as JSON in JSON mode
+fn report_error(json: bool, message: impl fmt::Display) {
+    if json {
+        println!("{}", to_json(&ErrorOutput::new(message)));
+    } else {
+        println!("❌ {}", message);
+    }
+}
+
+/// Play a scale, chord, progression or melody, reporting any playback error
+fn play(engine: &AudioEngine, item: &(impl Playable + fmt::Display), tempo: Tempo) -> bool {
+    println!("🎶 Playing {}...", item);
+    match item.play(engine, &PlaybackOptions::new().with_tempo(tempo)) {
+        Ok(_) => true,
+        Err(e) => {
+            println!("❌ Error playing {}: {}", item, e);
+            false
+        }
+    }
+}
+
+fn handle_scale_path(engine: &AudioEngine, mut tempo: Tempo) {
+    loop {
+        let scale = read_scale(&mut tempo);
+        play(engine, &scale, tempo);
+    }
+}
+
+/// Play a MIDI file's melody until the user asks to see the answer or quits.
+/// Uses the file's own tempo unless `--tempo` was given, and its time signature
+/// for the count-in and click when the notes fit its bars.
+fn handle_transcribe_path(
+    engine: &AudioEngine,
+    path: &Path,
+    tempo: Option<Tempo>,
+    count_in: u8,
+    click: bool,
+) -> bool {
+    let imported = match read_melody(path) {
+        Ok(imported) => imported,
+        Err(e) => {
+            println!("❌ Could not read {}: {}", path.display(), e);
+            return false;
+        }
+    };
+    let tempo = tempo.or(imported.tempo).unwrap_or_default();
+    let melody = match imported.time_signature {
+        Some(time_signature) => {
+            let barred = imported.melody.clone().with_time_signature(time_signature);
+            if barred.bars().is_ok() {
+                barred
+            } else {
+                imported.melody
+            }
+        }
+        None => imported.melody,
+    };
+    println!(
+        "🎧 Transcribe the melody from {} ({} notes at {})",
+        path.display(),
+        melody.notes().len(),
+        tempo
+    );
+
+    loop {
+        if let Err(e) = melody.play_in_time(engine, tempo, count_in, click) {
+            println!("❌ Error playing the melody: {}", e);
+            return false;
+        }
+        println!("📝 Press Enter to replay, 's' to show the notes, or 'q' to quit:");
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+
+        match input.trim() {
+            "q" => return true,
+            "s" => {
+                println!("🎼 {}", melody);
+                if let Err(e) = melody.play_announced_on(engine, tempo) {
+                    println!("❌ Error playing the melody: {}", e);
+                    return false;
+                }
+                return true;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn handle_chord_path(engine: &AudioEngine, tempo: Tempo) {
+    loop {
+        println!("\n🎵 Enter a chord (e.g. C major, G dom7, Cmaj7, F#m7b5, Bbsus4, G/B):");
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+        let chord = match parse_chord(&input) {
+            Ok(chord) => chord,
+            Err(e) => {
+                println!("❌ {}", e);
+                continue;
+            }
+        };
+
+        play(engine, &chord, tempo);
+    }
+}
+
+/// Parse a chord like "C major", "G4 dom7" or a symbol like "Cmaj7" or "G/B"
+fn parse_chord(input: &str) -> Result<Chord, String> {
+    let Some((note, quality)) = input.trim().split_once(" ") else {
+        return input.trim().parse::<Chord>().map_err(|_| {
+            "Invalid input. Please enter a valid chord (e.g. C major, G dom7, Cmaj7, F#m7b5, G/B)."
+                .to_string()
+        });
+    };
+    let note = note
+        .parse::<SpelledPitch>()
+        .map_err(|_| "Invalid input. Please enter a valid note (e.g. C4, A#3, Bb2).")?;
+    let quality = quality.trim().parse::<ChordQuality>().map_err(|_| {
+        "Invalid input. Please enter a valid chord quality (e.g. major, minor, dim, aug, maj7, m7, 7)."
+    })?;
+    Chord::spelled(note, quality)
+}
+
+fn handle_progression_path(engine: &AudioEngine, mut tempo: Tempo) {
+    loop {
+        let key = read_scale(&mut tempo);
+
+        println!("🎵 Enter Roman numerals (e.g. ii-V-I, I-vi-IV-V, i-iv-V7):");
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+        let progression = match Progression::parse(key, &input) {
+            Ok(progression) => progression,
+            Err(e) => {
+                println!("❌ {}", e);
+                continue;
+            }
+        };
+
+        play(engine, &progression, tempo);
+    }
+}
+
+fn handle_drone_path(engine: &AudioEngine, mut tempo: Tempo) {
+    loop {
+        let scale = read_scale(&mut tempo);
+
+        println!("🎵 Add the fifth to the drone? [y/n]");
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+        let with_fifth = input.trim() == "y";
+
+        play_drone(engine, &scale, tempo, with_fifth);
+    }
+}
+
+fn play_scale_run(
+    engine: &AudioEngine,
+    scale: &Scale,
+    octaves: u8,
+    direction: ScaleDirection,
+    tempo: Tempo,
+    naming: NoteNaming,
+    json: bool,
+) -> bool {
+    if json {
+        let output = ScaleOutput::new(scale, octaves, direction, &naming, engine.tuning());
+        println!("{}", to_json(&output));
+    }
+    status(json, format!("🎶 Playing {} ({})...", scale, direction));
+    match scale.play_run(engine, octaves, direction, tempo) {
+        Ok(_) => true,
+        Err(e) => {
+            status(json, format!("❌ Error playing {}: {}", scale, e));
+            false
+        }
+    }
+}
+
+/// Play `chord` in the engine's tuning, then tuned in `system` from its root
+fn play_in_temperaments(engine: &AudioEngine, chord: &Chord, system: TuningSystem) -> bool {
+    let first = engine.tuning().clone();
+    println!(
+        "🎶 Playing {} in {}, then in {}...",
+        chord,
+        first.system(),
+        system
+    );
+    let second = first.clone().with_system(system, chord.root.note);
+    match engine.play_in_tunings(&chord.notes(), &[first, second], Duration::from_secs(2)) {
+        Ok(_) => true,
+        Err(e) => {
+            println!("❌ Error playing {}: {}", chord, e);
+            false
+        }
+    }
+}
+
+fn play_drone(engine: &AudioEngine, scale: &Scale, tempo: Tempo, with_fifth: bool) -> bool {
+    match scale.play_with_drone(engine, tempo, with_fifth) {
+        Ok(_) => {
+            println!("🎶 Playing {} over a drone...", scale);
+            true
+        }
+        Err(e) => {
+            println!("❌ Error playing {}: {}", scale, e);
+            false
+        }
+    }
+}
+
+/// Click in the background until the user presses Enter
+fn run_metronome(engine: &AudioEngine, metronome: Metronome) -> bool {
+    let clicks = match metronome.start(engine) {
+        Ok(clicks) => clicks,
+        Err(e) => {
+            println!("❌ Error starting the metronome: {}", e);
+            return false;
+        }
+    };
+    println!("🥁 Metronome at {}, press Enter to stop", metronome);
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .expect("Failed to read line");
+    clicks.stop();
+    true
+}
+
+fn handle_frequency_game_path(engine: &AudioEngine, naming: NoteNaming, stats: &mut StatsStore) {
+    let mut rng = rand::thread_rng();
+    let mut score = 0;
+    let mut rounds = 0;
+
+    loop {
+        println!(
+            "\n🎯 Guess a tone's frequency (g), name the nearest note to a frequency (n), or 'q' to quit:"
+        );
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+        let frequency = random_frequency(&mut rng, ESTIMATION_MIN_HZ, ESTIMATION_MAX_HZ);
+        let nearest = engine.tuning().nearest_note(frequency).ok();
+        let nearest_name = nearest
+            .map(|note| naming.name_pitch(&note, &Note::C))
+            .unwrap_or_default();
+        let nearest = nearest.map(|note| note.to_string()).unwrap_or_default();
+
+        match input.trim() {
+            "q" => {
+                println!("🏁 Score: {} points over {} rounds", score, rounds);
+                println!("👋 Goodbye!");
+                break;
+            }
+            "g" => {
+                if let Err(e) = engine.play_frequency(frequency as f32, Duration::from_secs(1)) {
+                    println!("❌ Error playing tone: {}", e);
+                    continue;
+                }
+                println!("📊 What frequency was that, in Hz?");
+                let asked = Instant::now();
+                let mut input = String::new();
+                io::stdin()
+                    .read_line(&mut input)
+                    .expect("Failed to read line");
+                let grade = match input
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|e| e.to_string())
+                    .and_then(|guess| grade_frequency_guess(frequency, guess))
+                {
+                    Ok(grade) => grade,
+                    Err(_) => {
+                        println!("❌ Invalid input. Please enter a frequency (e.g. 440).");
+                        continue;
+                    }
+                };
+                rounds += 1;
+                score += grade.points();
+                println!("🎶 {}! It was {:.2} Hz", grade, frequency);
+                // Within a semitone counts as correct
+                let correct = matches!(grade, GuessGrade::Perfect | GuessGrade::Close);
+                log_answer(
+                    stats,
+                    AnswerRecord::new(
+                        Exercise::Frequency,
+                        nearest,
+                        format!("{} Hz", input.trim()),
+                        correct,
+                        asked.elapsed(),
+                    ),
+                );
+            }
+            "n" => {
+                println!("📊 Which note is nearest to {:.2} Hz?", frequency);
+                let asked = Instant::now();
+                let mut input = String::new();
+                io::stdin()
+                    .read_line(&mut input)
+                    .expect("Failed to read line");
+                let answer = match naming.parse_pitch(input.trim(), &Note::C) {
+                    Ok(note) => note,
+                    Err(_) => {
+                        println!(
+                            "❌ Invalid input. Please enter a valid note (e.g. {}).",
+                            naming.examples(&Note::C)
+                        );
+                        continue;
+                    }
+                };
+                rounds += 1;
+                let correct = match is_nearest_note(frequency, &answer, engine.tuning()) {
+                    Ok(true) => {
+                        score += 1;
+                        println!("✅ Correct!");
+                        true
+                    }
+                    Ok(false) => {
+                        println!("❌ Nope, the nearest note was {}", nearest_name);
+                        false
+                    }
+                    Err(e) => {
+                        println!("❌ {}", e);
+                        continue;
+                    }
+                };
+                log_answer(
+                    stats,
+                    AnswerRecord::new(
+                        Exercise::Frequency,
+                        nearest,
+                        answer.to_string(),
+                        correct,
+                        asked.elapsed(),
+                    ),
+                );
+            }
+            _ => continue,
+        }
+    }
+}
+
+fn handle_interval_comparison_path(
+    engine: &AudioEngine,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+) {
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+
+    loop {
+        let comparison = match IntervalComparison::random(&mut rng, config) {
+            Ok(comparison) => comparison,
+            Err(e) => {
+                println!("❌ {}", e);
+                break;
+            }
+        };
+        println!("\n🎧 Listen to two intervals...");
+        if let Err(e) = comparison.play(engine, Tempo(86)) {
+            println!("❌ Error playing intervals: {}", e);
+            break;
+        }
+
+        let asked = Instant::now();
+        let answer = loop {
+            println!("📊 Which was larger? [1/2/same] or 'q' to quit:");
+            let mut input = String::new();
+            io::stdin()
+                .read_line(&mut input)
+                .expect("Failed to read line");
+            let input = input.trim();
+            if input == "q" {
+                break None;
+            }
+            match input.parse::<ComparisonAnswer>() {
+                Ok(answer) => break Some(answer),
+                Err(_) => println!("❌ Invalid input. Please enter 1, 2, or same."),
+            }
+        };
+
+        let Some(answer) = answer else {
+            println!("🏁 Score: {}", score);
+            println!("👋 Goodbye!");
+            break;
+        };
+
+        let correct = comparison.is_correct(&answer);
+        score.record(correct);
+        log_answer(
+            stats,
+            AnswerRecord::new(
+                Exercise::IntervalComparison,
+                format!(
+                    "{} vs {}",
+                    comparison.first.semitones, comparison.second.semitones
+                ),
+                format!("{:?}", answer),
+                correct,
+                asked.elapsed(),
+            ),
+        );
+        if correct {
+            println!("✅ Correct, {}!", comparison.answer());
+        } else {
+            println!(
+                "❌ Not quite, {} ({} vs {} semitones)",
+                comparison.answer(),
+                comparison.first.semitones,
+                comparison.second.semitones
+            );
+        }
+    }
+}
+
+/// Play a note in each ear and ask which one was higher
+fn handle_ear_drill_path(engine: &AudioEngine, config: &QuizConfig, stats: &mut StatsStore) {
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+
+    loop {
+        let comparison = EarComparison::random(&mut rng, config);
+        println!("\n🎧 Listen to a note in your left ear, then one in your right...");
+        if let Err(e) = comparison.play(engine, Tempo(86)) {
+            println!("❌ Error playing notes: {}", e);
+            break;
+        }
+
+        let asked = Instant::now();
+        let answer = loop {
+            println!("📊 Which was higher? [left/right/same] or 'q' to quit:");
+            let mut input = String::new();
+            io::stdin()
+                .read_line(&mut input)
+                .expect("Failed to read line");
+            let input = input.trim();
+            if input == "q" {
+                break None;
+            }
+            match input.parse::<EarAnswer>() {
+                Ok(answer) => break Some(answer),
+                Err(_) => println!("❌ Invalid input. Please enter left, right, or same."),
+            }
+        };
+
+        let Some(answer) = answer else {
+            println!("🏁 Score: {}", score);
+            println!("👋 Goodbye!");
+            break;
+        };
+
+        let correct = comparison.is_correct(&answer);
+        score.record(correct);
+        log_answer(
+            stats,
+            AnswerRecord::new(
+                Exercise::EarComparison,
+                format!("{} vs {}", comparison.left, comparison.right),
+                format!("{:?}", answer),
+                correct,
+                asked.elapsed(),
+            ),
+        );
+        if correct {
+            println!("✅ Correct, {}!", comparison.answer());
+        } else {
+            println!(
+                "❌ Not quite, {} ({} left, {} right)",
+                comparison.answer(),
+                comparison.left,
+                comparison.right
+            );
+        }
+    }
+}
+
+fn handle_intonation_drill_path(
+    engine: &AudioEngine,
+    max_cents: f64,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+) {
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+
+    loop {
+        let question = IntonationQuestion::random(&mut rng, max_cents, config);
+        println!("\n🎧 Listen to a note in tune, then the same note again...");
+        if let Err(e) = question.play(engine, Tempo(80)) {
+            println!("❌ Error playing notes: {}", e);
+            break;
+        }
+
+        let asked = Instant::now();
+        let answer = loop {
+            println!("📊 Was the second note flat, sharp or in tune? [f/s/i] or 'q' to quit:");
+            let mut input = String::new();
+            io::stdin()
+                .read_line(&mut input)
+                .expect("Failed to read line");
+            let input = input.trim();
+            if input == "q" {
+                break None;
+            }
+            match input.parse::<IntonationAnswer>() {
+                Ok(answer) => break Some(answer),
+                Err(_) => println!("❌ Invalid input. Please enter flat, sharp, or in tune."),
+            }
+        };
+
+        let Some(answer) = answer else {
+            println!("🏁 Score: {}", score);
+            println!("👋 Goodbye!");
+            break;
+        };
+
+        let correct = question.is_correct(&answer);
+        score.record(correct);
+        log_answer(
+            stats,
+            AnswerRecord::new(
+                Exercise::Intonation,
+                question.pitch.to_string(),
+                answer.to_string(),
+                correct,
+                asked.elapsed(),
+            ),
+        );
+        if correct {
+            println!(
+                "✅ Correct, it was {} ({})!",
+                question.answer(),
+                question.pitch
+            );
+        } else {
+            println!(
+                "❌ Not quite, it was {} ({})",
+                question.answer(),
+                question.pitch
+            );
+        }
+    }
+}
+
+fn handle_rhythm_dictation_path(engine: &AudioEngine, tempo: Tempo, stats: &mut StatsStore) {
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+
+    loop {
+        let rhythm = Rhythm::random(&mut rng, RHYTHM_BEATS);
+        println!("\n🥁 Listen to a bar of clicks, then the rhythm...");
+        if let Err(e) = rhythm.play_with_count_in(engine, tempo) {
+            println!("❌ Error playing the rhythm: {}", e);
+            break;
+        }
+
+        let asked = Instant::now();
+        let answer = loop {
+            println!(
+                "📝 Type the rhythm (e.g. q q ee q), 't' to tap it, 'r' to replay, or 'q' to quit:"
+            );
+            let mut input = String::new();
+            io::stdin()
+                .read_line(&mut input)
+                .expect("Failed to read line");
+            match input.trim() {
+                "q" => break None,
+                "r" => {
+                    if let Err(e) = rhythm.play_with_count_in(engine, tempo) {
+                        println!("❌ Error playing the rhythm: {}", e);
+                    }
+                }
+                "t" => {
+                    let notes = rhythm.values.len();
+                    println!(
+                        "🥁 After the count-in, press Enter on each of the {} notes",
+                        notes
+                    );
+                    if let Err(e) = count_in(engine, tempo) {
+                        println!("❌ Error playing the count-in: {}", e);
+                        continue;
+                    }
+                    let taps =
+                        read_taps(&mut io::stdin().lock(), notes).expect("Failed to read line");
+                    let taps = rhythm.score_taps(&taps, tempo);
+                    break Some((format!("tapped ({})", taps), taps.is_correct()));
+                }
+                text => match text.parse::<Rhythm>() {
+                    Ok(answer) => break Some((answer.to_string(), answer == rhythm)),
+                    Err(e) => println!("❌ {}", e),
+                },
+            }
+        };
+
+        let Some((answer, correct)) = answer else {
+            println!("🏁 Score: {}", score);
+            println!("👋 Goodbye!");
+            break;
+        };
+
+        score.record(correct);
+        log_answer(
+            stats,
+            AnswerRecord::new(
+                Exercise::Rhythm,
+                rhythm.to_string(),
+                answer.clone(),
+                correct,
+                asked.elapsed(),
+            ),
+        );
+        if correct {
+            println!("✅ Correct, {}: {}", rhythm, answer);
+        } else {
+            println!(
+                "❌ Not quite, the rhythm was {} and you gave {}",
+                rhythm, answer
+            );
+        }
+    }
+}
+
+fn handle_melodic_dictation_path(
+    engine: &AudioEngine,
+    options: DictationOptions,
+    plays: u8,
+    tempo: Tempo,
+    naming: NoteNaming,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+) {
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+
+    loop {
+        let question = match MelodicDictationQuestion::random(&mut rng, &options, config) {
+            Ok(question) => question,
+            Err(e) => {
+                println!("❌ Error making a melody: {}", e);
+                break;
+            }
+        };
+        let notes = question.melody.notes();
+        println!(
+            "\n🎼 A melody of {} notes in {}, starting on {}. Listen {} times...",
+            notes.len(),
+            question.key,
+            naming.name_pitch(&question.key.root, &question.key.root.note),
+            plays
+        );
+        if let Err(e) = question.play(engine, tempo) {
+            println!("❌ Error playing the melody: {}", e);
+            break;
+        }
+        for _ in 1..plays {
+            if let Err(e) = question.play_melody(engine, tempo) {
+                println!("❌ Error playing the melody: {}", e);
+            }
+        }
+
+        let tonic = question.key.root.note;
+        let example: Vec<String> = question.key.notes_spanning(1)[..3]
+            .iter()
+            .chain([&question.key.root])
+            .map(|note| naming.name_pitch(note, &tonic))
+            .collect();
+        let asked = Instant::now();
+        let answer = loop {
+            println!(
+                "📝 Write the notes (e.g. {}), 'r' to replay, or 'q' to quit:",
+                example.join(" ")
+            );
+            let mut input = String::new();
+            io::stdin()
+                .read_line(&mut input)
+                .expect("Failed to read line");
+            match input.trim() {
+                "q" => break None,
+                "r" => {
+                    if let Err(e) = question.play_melody(engine, tempo) {
+                        println!("❌ Error playing the melody: {}", e);
+                    }
+                }
+                text => match question.score(text, naming) {
+                    Ok(result) => break Some((text.to_string(), result)),
+                    Err(e) => println!("❌ {}", e),
+                },
+            }
+        };
+
+        let Some((answer, result)) = answer else {
+            println!("🏁 Score: {}", score);
+            println!("👋 Goodbye!");
+            break;
+        };
+
+        let correct = result.is_perfect();
+        score.record(correct);
+        log_answer(
+            stats,
+            AnswerRecord::new(
+                Exercise::MelodicDictation,
+                question.melody.to_string(),
+                answer,
+                correct,
+                asked.elapsed(),
+            ),
+        );
+        for note in &result.notes {
+            println!("   {}", note.named(naming, &tonic));
+        }
+        if correct {
+            println!("✅ Correct, every note right!");
+        } else {
+            println!("❌ {}", result);
+        }
+        let key = KeySignature::for_key(&tonic, &question.key.scale_type);
+        println!("\n{}\n", Staff::for_melody(&question.melody, key));
+    }
+}
+
+fn handle_quarter_tone_quiz_path(
+    engine: &AudioEngine,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+) {
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
 
     loop {
-        println!("Play a note or a scale? [n/s]");
+        let question = QuarterToneQuestion::random(&mut rng, config);
+        println!("\n🎧 Listen to an interval in quarter tones...");
+        if let Err(e) = question.play(engine, Tempo(80)) {
+            println!("❌ Error playing notes: {}", e);
+            break;
+        }
 
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read line");
-        let input = input.trim();
+        let asked = Instant::now();
+        let answer = loop {
+            println!("📊 How many semitones apart? (e.g. 3.5, neutral third, P5) or 'q' to quit:");
+            let mut input = String::new();
+            io::stdin()
+                .read_line(&mut input)
+                .expect("Failed to read line");
+            let input = input.trim();
+            if input == "q" {
+                break None;
+            }
+            match input.parse::<QuarterToneInterval>() {
+                Ok(answer) => break Some(answer),
+                Err(e) => println!("❌ {}", e),
+            }
+        };
 
-        if input == "n" {
-            handle_note_path();
+        let Some(answer) = answer else {
+            println!("🏁 Score: {}", score);
+            println!("👋 Goodbye!");
             break;
+        };
+
+        let correct = question.is_correct(&answer);
+        score.record(correct);
+        log_answer(
+            stats,
+            AnswerRecord::new(
+                Exercise::QuarterTones,
+                question.interval.to_string(),
+                answer.to_string(),
+                correct,
+                asked.elapsed(),
+            ),
+        );
+        if correct {
+            println!("✅ Correct, it was a {}!", question.interval);
+        } else {
+            println!(
+                "❌ Not quite, it was a {} ({} to {})",
+                question.interval,
+                question.root,
+                question.top_name()
+            );
         }
+    }
+}
 
-        if input == "s" {
-            handle_scale_path();
+/// Run the interval quiz, asking for the play style unless one was given.
+/// In smart practice the spaced-repetition schedule picks each interval;
+/// when adaptive, the intervals, range and tempo follow the difficulty level.
+fn handle_interval_quiz_path(
+    engine: &AudioEngine,
+    style: Option<IntervalPlayStyle>,
+    smart: bool,
+    adaptive: bool,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+    input: &mut dyn AnswerInput,
+) {
+    // With directions in the config, each question picks one of them instead of asking
+    let style = match style {
+        Some(style) => Some(style),
+        None if !config.directions.is_empty() => None,
+        None => loop {
+            println!(
+                "\n🎧 Ascending, descending (one note after the other) or harmonic (together)? [a/d/h]"
+            );
+            let Some(answer) = input.next_answer() else {
+                return;
+            };
+            match answer
+                .text()
+                .unwrap_or_default()
+                .parse::<IntervalPlayStyle>()
+            {
+                Ok(style) => break Some(style),
+                Err(_) => println!("❌ Invalid input. Please enter a, d or h."),
+            }
+        },
+    };
+
+    let mut rng = rand::thread_rng();
+    let mut scheduler = smart.then(|| Scheduler::from_stats(stats));
+    let mut controller = adaptive.then(DifficultyController::default);
+    let mut score = QuizScore::default();
+
+    'questions: loop {
+        let difficulty = controller.as_ref().map(|c| c.difficulty());
+        let allowed = config
+            .filter_intervals(&difficulty.map_or_else(default_quiz_intervals, |d| d.intervals()));
+        let tempo = difficulty.map_or(Tempo(75), |d| d.tempo(Tempo(75)));
+        let interval = match &scheduler {
+            Some(scheduler) => {
+                let names: Vec<String> = allowed.iter().map(|i| i.to_string()).collect();
+                scheduler
+                    .pick_now(&mut rng, Exercise::Intervals, &names)
+                    .map(|i| allowed[i])
+            }
+            None => random_interval(&mut rng, &allowed).ok(),
+        };
+        let Some(interval) = interval else {
+            println!("❌ No intervals to choose from");
+            break;
+        };
+        let range = difficulty.map(|d| d.note_range()).or(config.range);
+        let question = match range {
+            Some((low, high)) => IntervalQuestion::for_interval_in(&mut rng, interval, &low, &high),
+            None => IntervalQuestion::for_interval(&mut rng, interval),
+        };
+        let style = style
+            .or_else(|| config.random_direction(&mut rng))
+            .unwrap_or_default();
+
+        loop {
+            if let Err(e) = question.play(engine, style, tempo) {
+                println!("❌ Error playing interval: {}", e);
+                break 'questions;
+            }
+
+            println!("📊 Name the interval (e.g. m3, P5, tritone), 'r' to replay, or 'q' to quit:");
+            let asked = Instant::now();
+            let Some(answer) = input.next_answer() else {
+                break 'questions;
+            };
+
+            let answer = match answer.text() {
+                Some("q") => {
+                    println!("🏁 Score: {}", score);
+                    println!("👋 Goodbye!");
+                    break 'questions;
+                }
+                Some("r") => continue,
+                Some(text) => match text.parse::<Interval>() {
+                    Ok(answer) => answer,
+                    Err(_) => {
+                        println!(
+                            "❌ Invalid input. Please enter an interval (e.g. m3, P5, tritone)."
+                        );
+                        continue;
+                    }
+                },
+                None => match answer.interval() {
+                    Some(answer) => answer,
+                    None => {
+                        println!("❌ Play both notes of the interval.");
+                        continue;
+                    }
+                },
+            };
+
+            let correct = question.is_correct(&answer);
+            score.record(correct);
+            let record = AnswerRecord::new(
+                Exercise::Intervals,
+                question.interval.to_string(),
+                answer.to_string(),
+                correct,
+                asked.elapsed(),
+            );
+            if let Some(scheduler) = scheduler.as_mut() {
+                scheduler.review(&record);
+            }
+            log_answer(stats, record);
+            if correct {
+                println!("✅ Correct, it was a {}!", question.interval);
+            } else {
+                println!("❌ Not quite, it was a {}", question.interval);
+            }
+            let played: Vec<NoteWithOctave> = [Some(question.root), question.top()]
+                .into_iter()
+                .flatten()
+                .collect();
+            println!("{}", Keyboard::spanning(&played));
+            println!("📈 Score: {}", score);
+            report_level_change(controller.as_mut(), correct);
+            break;
+        }
+    }
+}
+
+/// Sound notes while their computer keys are held, until q or Esc
+#[cfg(feature = "tui")]
+fn run_live(engine: &AudioEngine, octave: u8) -> bool {
+    // How often held notes are checked for release between key presses
+    const POLL: Duration = Duration::from_millis(10);
+
+    let mut terminal = match RawTerminal::enter() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+    let mut qwerty = QwertyKeyboard::default().with_octave(octave);
+    let mut voices: Voices<Drone> = Voices::default();
+    let mut error: Option<String> = None;
+    let mut changed = true;
+
+    loop {
+        if changed {
+            let mut screen = format!(
+                "🎹 Play with a w s e d f t g y h u j k o l p ; ' · z/x octave down/up · q quit\n\n{}\n\nOctave {}  Holding: {}",
+                qwerty.keyboard().with_highlighted(&voices.notes()),
+                qwerty.octave,
+                voices
+            );
+            if let Some(e) = &error {
+                screen.push_str(&format!("\n❌ Error playing a note: {}", e));
+            }
+            let _ = terminal.draw(Paragraph::new(screen));
+            changed = false;
+        }
+        match terminal.key_within(POLL) {
+            Ok(Some('q' | 'Q' | '\x1b')) | Err(_) => break,
+            Ok(Some(key)) if qwerty.shift(key) => {
+                voices.release_all();
+                changed = true;
+            }
+            Ok(Some(key)) => {
+                if let Some(note) = qwerty.note_for(key) {
+                    match voices.press(note, Instant::now(), || engine.start_note(&note)) {
+                        Ok(started) => changed |= started,
+                        Err(e) => {
+                            error = Some(e.to_string());
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            Ok(None) => {}
+        }
+        changed |= !voices.release_expired(Instant::now()).is_empty();
+    }
+    voices.release_all();
+    drop(terminal);
+    println!("👋 Goodbye!");
+    true
+}
+
+/// Drill intervals on a full screen, answering with a single key press
+#[cfg(feature = "tui")]
+fn run_tui(
+    engine: &AudioEngine,
+    style: Option<IntervalPlayStyle>,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+) -> bool {
+    let allowed = config.filter_intervals(&default_quiz_intervals());
+    if allowed.is_empty() {
+        println!("❌ No intervals to choose from");
+        return false;
+    }
+    let mut terminal = match RawTerminal::enter() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+    let mut rng = rand::thread_rng();
+    let mut screen = TuiScreen {
+        title: "Interval drill".to_string(),
+        ..TuiScreen::default()
+    };
+    let tempo = Tempo(75);
+
+    'questions: while let Ok(question) = match config.range {
+        Some((low, high)) => random_interval(&mut rng, &allowed)
+            .map(|interval| IntervalQuestion::for_interval_in(&mut rng, interval, &low, &high)),
+        None => IntervalQuestion::random(&mut rng, &allowed),
+    } {
+        let style = style
+            .or_else(|| config.random_direction(&mut rng))
+            .unwrap_or_default();
+        let choices = pick_choices(&mut rng, &question.interval, &allowed, MAX_CHOICES);
+        let played: Vec<NoteWithOctave> = [Some(question.root), question.top()]
+            .into_iter()
+            .flatten()
+            .collect();
+        screen.ask(
+            format!("🎧 Which interval was that? ({})", style),
+            choices
+                .iter()
+                .map(|interval| interval.to_string())
+                .collect(),
+            // Marked once answered, so the keyboard doesn't give it away
+            Keyboard::spanning(&played).with_highlighted(&[]),
+        );
+        let _ = terminal.draw(&screen);
+        let mut asked = Instant::now();
+        let mut answered = false;
+        if let Err(e) = question.play(engine, style, tempo) {
+            screen.feedback = Some(format!("❌ Error playing interval: {}", e));
+        }
+
+        loop {
+            let _ = terminal.draw(&screen);
+            match terminal.next_action() {
+                None | Some(Action::Quit) => break 'questions,
+                Some(Action::Replay) => {
+                    if let Err(e) = question.play(engine, style, tempo) {
+                        screen.feedback = Some(format!("❌ Error playing interval: {}", e));
+                    }
+                    if !answered {
+                        asked = Instant::now();
+                    }
+                }
+                Some(Action::Next) if answered => break,
+                Some(Action::Choose(index)) if !answered && index < choices.len() => {
+                    let answer = choices[index];
+                    let correct = question.is_correct(&answer);
+                    let record = AnswerRecord::new(
+                        Exercise::Intervals,
+                        question.interval.to_string(),
+                        answer.to_string(),
+                        correct,
+                        asked.elapsed(),
+                    );
+                    screen.records.push(record.clone());
+                    stats.record(record);
+                    screen.feedback = Some(if correct {
+                        format!("✅ Correct, it was a {}!", question.interval)
+                    } else {
+                        format!("❌ Not quite, it was a {}", question.interval)
+                    });
+                    screen.keyboard = Keyboard::spanning(&played);
+                    answered = true;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    drop(terminal);
+
+    let report = SessionReport::from_records(&screen.records);
+    if !report.is_empty() {
+        println!("📋 Session report: {}", report);
+    }
+    if let Err(e) = stats.save() {
+        println!("❌ Could not save stats: {}", e);
+    }
+    println!("👋 Goodbye!");
+    true
+}
+
+/// Run the note identification quiz, asking for the range unless one was given
+fn handle_note_identification_path(
+    engine: &AudioEngine,
+    range: Option<(NoteWithOctave, NoteWithOctave)>,
+    adaptive: bool,
+    naming: NoteNaming,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+    input: &mut dyn AnswerInput,
+) {
+    let mut controller = adaptive.then(DifficultyController::default);
+    let range = range
+        .or_else(|| controller.as_ref().map(|c| c.difficulty().note_range()))
+        .or(config.range);
+    let (mut low, mut high) = match range {
+        Some(range) => range,
+        None => loop {
+            println!(
+                "\n🎹 Enter a range for the mystery notes (e.g. C4-B4), or press enter for C4-B4:"
+            );
+            let Some(answer) = input.next_answer() else {
+                return;
+            };
+            let range = match answer.text().unwrap_or_default() {
+                "" => "C4-B4",
+                range => range,
+            };
+            match parse_note_range(range) {
+                Ok(range) => break range,
+                Err(e) => println!("❌ {}", e),
+            }
+        },
+    };
+
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+    let mut accuracy = NoteAccuracy::default();
+
+    'questions: loop {
+        let difficulty = controller.as_ref().map(|c| c.difficulty());
+        if let Some(difficulty) = difficulty {
+            (low, high) = difficulty.note_range();
+        }
+        let tempo = difficulty.map_or(Tempo(75), |d| d.tempo(Tempo(75)));
+        let question = match NoteQuestion::random(&mut rng, &low, &high, &accuracy) {
+            Ok(question) => question,
+            Err(e) => {
+                println!("❌ {}", e);
+                break;
+            }
+        };
+
+        loop {
+            println!(
+                "\n🎧 Reference {}, then the mystery note...",
+                naming.name_pitch(&question.reference, &question.reference.note)
+            );
+            if let Err(e) = question.play(engine, tempo) {
+                println!("❌ Error playing notes: {}", e);
+                break 'questions;
+            }
+
+            println!(
+                "📊 Name the mystery note (e.g. {}), 'r' to replay, or 'q' to quit:",
+                naming.examples(&question.reference.note)
+            );
+            let asked = Instant::now();
+            let Some(answer) = input.next_answer() else {
+                break 'questions;
+            };
+
+            let answer = match answer.text() {
+                Some("q") => break 'questions,
+                Some("r") => continue,
+                Some(text) => match naming.parse_pitch(text, &question.reference.note) {
+                    Ok(answer) => answer.note,
+                    Err(_) => {
+                        println!(
+                            "❌ Invalid input. Please enter a note (e.g. {}).",
+                            naming.examples(&question.reference.note)
+                        );
+                        continue;
+                    }
+                },
+                None => match answer.note() {
+                    Some(answer) => answer,
+                    None => {
+                        println!("❌ Play a single note.");
+                        continue;
+                    }
+                },
+            };
+
+            let correct = question.is_correct(&answer);
+            score.record(correct);
+            accuracy.record(&question.mystery.note, correct);
+            log_answer(
+                stats,
+                AnswerRecord::new(
+                    Exercise::Notes,
+                    question.mystery.note.to_string(),
+                    answer.to_string(),
+                    correct,
+                    asked.elapsed(),
+                )
+                .in_key(question.reference.to_string()),
+            );
+            let mystery = naming.name_pitch(&question.mystery, &question.reference.note);
+            if correct {
+                println!("✅ Correct, it was {}!", mystery);
+            } else {
+                println!("❌ Not quite, it was {}", mystery);
+            }
+            println!(
+                "{}",
+                Keyboard::spanning(&[question.reference, question.mystery])
+            );
+            println!("📈 Score: {}", score);
+            report_level_change(controller.as_mut(), correct);
             break;
         }
     }
 
-    fn handle_note_path() {
+    println!("🏁 Score: {}", score);
+    for (note, note_score) in accuracy.results() {
+        println!("   {}: {}", note, note_score);
+    }
+    println!("👋 Goodbye!");
+}
+
+fn handle_cadence_quiz_path(engine: &AudioEngine, config: &QuizConfig, stats: &mut StatsStore) {
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+    let mut accuracy = CadenceAccuracy::default();
+
+    'questions: loop {
+        let question = match CadenceQuestion::random(&mut rng, config) {
+            Ok(question) => question,
+            Err(e) => {
+                println!("❌ {}", e);
+                break;
+            }
+        };
+
         loop {
-            println!("\n🎵 Enter a note (e.g. C4, A#3, Bb2) or 'q' to quit:");
+            println!("\n🎧 Listen to how the phrase ends...");
+            if let Err(e) = question.play(engine, Tempo(90)) {
+                println!("❌ Error playing chords: {}", e);
+                break 'questions;
+            }
+
+            println!(
+                "📊 Which cadence? [authentic/plagal/deceptive/half], 'r' to replay, or 'q' to quit:"
+            );
+            let asked = Instant::now();
             let mut input = String::new();
             io::stdin()
                 .read_line(&mut input)
                 .expect("Failed to read line");
-            let input = input.trim();
 
-            if input == "q" {
-                println!("👋 Goodbye!");
+            let answer = match input.trim() {
+                "q" => break 'questions,
+                "r" => continue,
+                answer => match answer.parse::<Cadence>() {
+                    Ok(answer) => answer,
+                    Err(_) => {
+                        println!(
+                            "❌ Invalid input. Please enter authentic, plagal, deceptive, or half."
+                        );
+                        continue;
+                    }
+                },
+            };
+
+            let correct = question.is_correct(&answer);
+            score.record(correct);
+            accuracy.record(&question.cadence, correct);
+            log_answer(
+                stats,
+                AnswerRecord::new(
+                    Exercise::Cadences,
+                    question.cadence.to_string(),
+                    answer.to_string(),
+                    correct,
+                    asked.elapsed(),
+                )
+                .in_key(question.progression.key.to_string()),
+            );
+            if correct {
+                println!("✅ Correct, it was {}!", question.progression);
+            } else {
+                println!(
+                    "❌ Not quite, it was a {} cadence: {}",
+                    question.cadence, question.progression
+                );
+            }
+            println!("📈 Score: {}", score);
+            break;
+        }
+    }
+
+    println!("🏁 Score: {}", score);
+    for (cadence, cadence_score) in accuracy.results() {
+        println!("   {}: {}", cadence, cadence_score);
+    }
+    println!("👋 Goodbye!");
+}
+
+fn handle_inversion_quiz_path(engine: &AudioEngine, config: &QuizConfig, stats: &mut StatsStore) {
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+
+    'questions: loop {
+        let question = match InversionQuestion::random(&mut rng, config) {
+            Ok(question) => question,
+            Err(e) => {
+                println!("❌ {}", e);
                 break;
             }
+        };
 
-            let note = match input.parse::<NoteWithOctave>() {
-                Ok(note) => note,
-                Err(_) => {
-                    println!("❌ Invalid input. Please enter a valid note (e.g. C4, A#3, Bb2).");
-                    continue;
-                }
+        loop {
+            println!("\n🎧 Listen to the chord...");
+            if let Err(e) = question.play(engine, Tempo(90)) {
+                println!("❌ Error playing chord: {}", e);
+                break 'questions;
+            }
+
+            println!("📊 Which inversion? [root/1st/2nd/3rd], 'r' to replay, or 'q' to quit:");
+            let asked = Instant::now();
+            let mut input = String::new();
+            io::stdin()
+                .read_line(&mut input)
+                .expect("Failed to read line");
+
+            let answer = match input.trim() {
+                "q" => break 'questions,
+                "r" => continue,
+                answer => match answer.parse::<Inversion>() {
+                    Ok(answer) => answer,
+                    Err(_) => {
+                        println!("❌ Invalid input. Please enter root, 1st, 2nd, or 3rd.");
+                        continue;
+                    }
+                },
+            };
+
+            let correct = question.is_correct(&answer);
+            score.record(correct);
+            log_answer(
+                stats,
+                AnswerRecord::new(
+                    Exercise::Inversions,
+                    question.chord.inversion.to_string(),
+                    answer.to_string(),
+                    correct,
+                    asked.elapsed(),
+                ),
+            );
+            let notes: Vec<String> = question
+                .chord
+                .spelled_notes()
+                .iter()
+                .map(|n| n.to_string())
+                .collect();
+            if correct {
+                println!(
+                    "✅ Correct, it was {} ({})!",
+                    question.chord.name(),
+                    notes.join(" ")
+                );
+            } else {
+                println!(
+                    "❌ Not quite, it was {} ({})",
+                    question.chord.name(),
+                    notes.join(" ")
+                );
+            }
+            println!("{}", Keyboard::spanning(&question.chord.notes()));
+            println!("📈 Score: {}", score);
+            break;
+        }
+    }
+
+    println!("🏁 Score: {}", score);
+    println!("👋 Goodbye!");
+}
+
+fn handle_degree_quiz_path(
+    engine: &AudioEngine,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+    input: &mut dyn AnswerInput,
+) {
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+    let mut accuracy = DegreeAccuracy::default();
+
+    'questions: loop {
+        let question = match DegreeQuestion::random(&mut rng, &accuracy, config) {
+            Ok(question) => question,
+            Err(e) => {
+                println!("❌ {}", e);
+                break;
+            }
+        };
+
+        println!(
+            "\n🎧 Listen to the cadence in {}, then the mystery note...",
+            question.cadence.key
+        );
+        if let Err(e) = question.play(engine, Tempo(90)) {
+            println!("❌ Error playing the question: {}", e);
+            break;
+        }
+
+        let mut asked = Instant::now();
+        loop {
+            println!(
+                "📊 Which degree was it? [1-7 or do/re/mi/fa/sol/la/ti], 'r' to replay the note, 'c' to replay the cadence, or 'q' to quit:"
+            );
+            let Some(answer) = input.next_answer() else {
+                break 'questions;
             };
 
-            println!("📊 {} = {:.2} Hz", note, note.frequency());
+            let replay = match answer.text() {
+                Some("q") => break 'questions,
+                Some("r") => Some(question.play_note(engine, Tempo(90))),
+                Some("c") => Some(question.play(engine, Tempo(90))),
+                _ => None,
+            };
+            if let Some(replay) = replay {
+                if let Err(e) = replay {
+                    println!("❌ Error playing the question: {}", e);
+                    break 'questions;
+                }
+                asked = Instant::now();
+                continue;
+            }
+
+            let key = &question.cadence.key;
+            let answer = match answer.text() {
+                Some(text) => match parse_scale_degree(text) {
+                    Ok(answer) => answer,
+                    Err(_) => {
+                        println!(
+                            "❌ Invalid input. Please enter a degree from 1 to 7 or a syllable."
+                        );
+                        continue;
+                    }
+                },
+                None => match answer.note().and_then(|note| key.degree_of(&note)) {
+                    Some(answer) => answer,
+                    None => {
+                        println!("❌ Play a single note from {}.", key);
+                        continue;
+                    }
+                },
+            };
 
-            match note.play_default() {
-                Ok(_) => println!("🎶 Playing {}...", note),
-                Err(e) => println!("❌ Error playing {}: {}", note, e),
+            let correct = question.is_correct(answer);
+            score.record(correct);
+            accuracy.record(question.degree, correct);
+            log_answer(
+                stats,
+                AnswerRecord::new(
+                    Exercise::Degrees,
+                    question.degree.to_string(),
+                    answer.to_string(),
+                    correct,
+                    asked.elapsed(),
+                )
+                .in_key(question.cadence.key.to_string()),
+            );
+            if correct {
+                println!("✅ Correct, it was {}!", question.answer());
+            } else {
+                println!("❌ Not quite, it was {}", question.answer());
             }
+            println!("📈 Score: {}", score);
+            break;
         }
     }
 
-    fn handle_scale_path() {
+    println!("🏁 Score: {}", score);
+    for (degree, degree_score) in accuracy.results() {
+        println!("   {} ({}): {}", degree, SOLFEGE[degree - 1], degree_score);
+    }
+    println!("👋 Goodbye!");
+}
+
+fn handle_sing_back_path(
+    engine: &AudioEngine,
+    notes: usize,
+    tolerance: f64,
+    config: &QuizConfig,
+    stats: &mut StatsStore,
+) {
+    let tempo = Tempo(90);
+    // Allow time to take a breath before singing and to trail off afterwards
+    let grace = Duration::from_secs(2);
+    let listen_for = tempo.duration_of(NoteValue::Half) * notes as u32 + grace;
+    let microphone = match Microphone::open_with_buffer(listen_for) {
+        Ok(microphone) => microphone,
+        Err(e) => {
+            println!("❌ Could not open the microphone: {}", e);
+            return;
+        }
+    };
+    let detector = PitchDetector::default();
+    let tuning = engine.tuning().clone();
+    let mut rng = rand::thread_rng();
+    let mut score = QuizScore::default();
+    let mut note_score = QuizScore::default();
+
+    'questions: loop {
+        let question = match SingBackQuestion::random(&mut rng, notes, config) {
+            Ok(question) => question,
+            Err(e) => {
+                println!("❌ {}", e);
+                break;
+            }
+        };
+
+        println!("\n🎧 Listen to the melody in {}...", question.key);
+        if let Err(e) = question.play(engine, tempo) {
+            println!("❌ Error playing the melody: {}", e);
+            break;
+        }
+
+        let asked = Instant::now();
         loop {
-            println!("\n🎵 Enter a scale (e.g. C major, A minor, F# major):");
+            println!("🎤 Press Enter and sing it back, 'r' to replay the melody, or 'q' to quit:");
             let mut input = String::new();
             io::stdin()
                 .read_line(&mut input)
                 .expect("Failed to read line");
-            let (note, scale_type) = match input.split_once(" ") {
-                Some((note, scale_type)) => (note, scale_type.trim()),
-                None => {
-                    println!(
-                        "❌ Invalid input. Please enter a valid scale (e.g. C major, A minor, F# major)."
-                    );
+
+            match input.trim() {
+                "q" => break 'questions,
+                "r" => {
+                    if let Err(e) = question.play(engine, tempo) {
+                        println!("❌ Error playing the melody: {}", e);
+                        break 'questions;
+                    }
                     continue;
                 }
-            };
-
-            let note = match note.parse::<NoteWithOctave>() {
-                Ok(note) => note,
-                Err(_) => {
-                    println!("❌ Invalid input. Please enter a valid note (e.g. C4, A#3, Bb2).");
+                "" => {}
+                _ => {
+                    println!("❌ Invalid input. Press Enter to sing, 'r' or 'q'.");
                     continue;
                 }
-            };
+            }
 
-            let scale_type = match scale_type.parse::<ScaleType>() {
-                Ok(scale_type) => scale_type,
-                Err(_) => {
-                    println!(
-                        "❌ Invalid input. Please enter a valid scale type (e.g. major, minor)."
-                    );
-                    continue;
+            microphone.clear();
+            println!("🎶 Sing now!");
+            std::thread::sleep(question.duration(tempo) + grace);
+            let samples = microphone.take();
+
+            let sung = question.score(
+                &samples,
+                microphone.sample_rate(),
+                tempo,
+                &detector,
+                &tuning,
+            );
+            let mut answers = Vec::new();
+            for note in &sung {
+                let correct = note.is_correct(tolerance);
+                note_score.record(correct);
+                match (note.cents, note.frequency.map(|f| tuning.nearest_note(f))) {
+                    (Some(cents), Some(Ok(heard))) => {
+                        let mark = if correct { "✅" } else { "❌" };
+                        println!(
+                            "{} {}: {:+.0} cents (heard {})",
+                            mark, note.target, cents, heard
+                        );
+                        answers.push(heard.to_string());
+                    }
+                    _ => {
+                        println!("🔇 {}: no pitch heard", note.target);
+                        answers.push("-".to_string());
+                    }
                 }
-            };
+            }
+
+            let correct = !sung.is_empty() && sung.iter().all(|note| note.is_correct(tolerance));
+            score.record(correct);
+            log_answer(
+                stats,
+                AnswerRecord::new(
+                    Exercise::SingBack,
+                    question.melody.to_string(),
+                    answers.join(" "),
+                    correct,
+                    asked.elapsed(),
+                )
+                .in_key(question.key.to_string()),
+            );
+            if correct {
+                println!("✅ Every note within {:.0} cents!", tolerance);
+            } else {
+                println!("❌ Not quite, the melody was {}", question.melody);
+            }
+            println!("📈 Score: {}", score);
+            break;
+        }
+    }
+
+    println!("🏁 Score: {}", score);
+    println!("🎯 Notes in tune: {}", note_score);
+    println!("👋 Goodbye!");
+}
+
+/// Feed an answer to an adaptive quiz's controller and announce any level change
+fn report_level_change(controller: Option<&mut DifficultyController>, correct: bool) {
+    if let Some(controller) = controller
+        && let Some(change) = controller.record(correct)
+    {
+        println!("🎚️ Moving {} to level {}", change, controller.level());
+    }
+}
+
+/// Add an answer to the stats store and save it straight away, so quitting mid-quiz loses nothing
+fn log_answer(stats: &mut StatsStore, record: AnswerRecord) {
+    stats.record(record);
+    if let Err(e) = stats.save() {
+        println!("❌ Could not save stats: {}", e);
+    }
+}
+
+/// Print overall and per-question accuracy for every exercise with answers
+fn print_stats(stats: &StatsStore) {
+    if let Some(path) = stats.path() {
+        println!("📁 Stats from {}", path.display());
+    }
+    let exercises = stats.exercises();
+    if exercises.is_empty() {
+        println!("📈 No answers yet. Take a quiz to start tracking progress!");
+        return;
+    }
+    for exercise in exercises {
+        println!("\n📈 {}: {}", exercise, stats.accuracy(exercise));
+        if let Some(time) = stats.average_response_time(exercise) {
+            println!("   ⏱️ Average answer time: {:.1}s", time.as_secs_f64());
+        }
+        for (question, score) in stats.accuracy_by_question(exercise) {
+            println!("   {}: {}", question, score);
+        }
+    }
+}
+
+/// Print the answers most often taken for each other in the last `sessions` sessions
+fn print_confusions(stats: &StatsStore, sessions: usize) {
+    let found = stats.sessions().len().min(sessions);
+    println!(
+        "🔀 Mix-ups over the last {} practice session{}",
+        found,
+        if found == 1 { "" } else { "s" }
+    );
+    let mut any = false;
+    for exercise in stats.exercises() {
+        let pairs = stats.confusions(exercise, sessions).pairs();
+        if pairs.is_empty() {
+            continue;
+        }
+        any = true;
+        println!("\n📈 {}", exercise);
+        for pair in pairs.iter().take(10) {
+            println!("   {}", pair);
+        }
+    }
+    if !any {
+        println!("🎉 No mix-ups yet. Keep practising!");
+    }
+}
+
+/// Offline rendering settings with the tone and tuning picked on the command line
+fn render_config(cli: &Cli) -> Result<RenderConfig, String> {
+    let mut render = RenderConfig {
+        tuning: cli_tuning(cli)?,
+        ..RenderConfig::default()
+    };
+    if let Some(waveform) = cli.waveform {
+        render.tone.waveform = waveform;
+    }
+    if let Some(volume) = cli.volume {
+        render.tone.gain = volume as f32 / 100.0;
+    }
+    Ok(render)
+}
+
+/// Read a script from `path`, or from stdin when it's `-`
+fn read_script(path: &Path) -> Result<Script, Box<dyn std::error::Error>> {
+    let text = if path == Path::new("-") {
+        io::read_to_string(io::stdin())?
+    } else {
+        std::fs::read_to_string(path)?
+    };
+    Ok(text.parse::<Script>()?)
+}
+
+fn play_script(engine: &AudioEngine, path: &Path, tempo: Tempo) -> bool {
+    let script = match read_script(path) {
+        Ok(script) => script,
+        Err(e) => {
+            println!("❌ Could not read {}: {}", path.display(), e);
+            return false;
+        }
+    };
+    if let Err(e) = script.play_on(engine, tempo) {
+        println!("❌ Error playing the script: {}", e);
+        return false;
+    }
+    true
+}
+
+fn render_script(
+    path: &Path,
+    wav: &Path,
+    render: &RenderConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let script = read_script(path)?;
+    script.render_to_wav(wav, render)?;
+    println!("💾 Rendered {} to {}", path.display(), wav.display());
+    Ok(())
+}
+
+/// Write the questions missed in the last `sessions` practice sessions (or ever) to `out`,
+/// rendering the audio with the tone and tuning picked on the command line
+fn export_missed_questions(
+    stats: &StatsStore,
+    format: ExportFormat,
+    out: &Path,
+    sessions: Option<u64>,
+    render: &RenderConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let all = stats.sessions();
+    let recent = &all[all
+        .len()
+        .saturating_sub(sessions.map_or(all.len(), |n| n as usize))..];
+    let records: Vec<AnswerRecord> = recent.concat();
+    let cards = missed_cards(&records);
+    if cards.is_empty() {
+        println!("🎉 No missed questions to export. Keep practising!");
+        return Ok(());
+    }
+
+    let path = export_missed(&records, format, out, render)?;
+    println!(
+        "📤 Exported {} missed question{} to {}",
+        cards.len(),
+        if cards.len() == 1 { "" } else { "s" },
+        path.display()
+    );
+    Ok(())
+}
+
+/// Show the nearest note, its target frequency and a cents needle, redrawn on one line
+fn run_tuner(tuning: Tuning) -> Result<(), Box<dyn std::error::Error>> {
+    let microphone = Microphone::open()?;
+    let detector = PitchDetector::default();
+    println!(
+        "🎸 Tuner listening (A4 = {:.1} Hz). Press Ctrl+C to stop.",
+        tuning.a4()
+    );
+
+    let mut stdout = io::stdout();
+    loop {
+        let line = match microphone.read_pitch(&detector, &tuning) {
+            Some(reading) => format!(
+                "{} {:<4} target {:>7.2} Hz  heard {:>7.2} Hz  {} {:+3.0} cents",
+                if reading.is_in_tune() { "✅" } else { "🎯" },
+                reading.note.to_string(),
+                tuning.frequency_of(&reading.note),
+                reading.frequency,
+                needle(reading.cents, 21),
+                reading.cents
+            ),
+            None => "🔇 Listening...".to_string(),
+        };
+        // Clear the rest of the line so a shorter reading doesn't leave old text behind
+        write!(stdout, "\r{}\x1b[K", line)?;
+        stdout.flush()?;
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Tune `strings` one at a time: play a string's pitch, listen until it stays within
+/// `threshold` cents of it, then move on to the next. The pitch plays again every
+/// `REFERENCE_REPEAT` until the string is in tune; with no engine the tuner only listens.
+fn run_string_tuner(
+    engine: Option<&AudioEngine>,
+    strings: &StringTuning,
+    threshold: f64,
+    tuning: &Tuning,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // How long each string's pitch sounds, and how long to listen before playing it again
+    const REFERENCE_TONE: Duration = Duration::from_millis(1500);
+    const REFERENCE_REPEAT: Duration = Duration::from_secs(6);
+
+    let microphone = Microphone::open()?;
+    let detector = PitchDetector::default();
+    let mut tuner = StringTuner::new(strings).with_threshold(threshold);
+    println!(
+        "🎸 Tuning {} one string at a time (A4 = {:.1} Hz). Press Ctrl+C to stop.",
+        strings,
+        tuning.a4()
+    );
+
+    let mut stdout = io::stdout();
+    while let Some(target) = tuner.target() {
+        println!(
+            "\n🎯 String {} of {}: {} ({:.2} Hz)",
+            tuner.position() + 1,
+            tuner.len(),
+            target,
+            tuning.frequency_of(&target)
+        );
+        'string: loop {
+            if let Some(engine) = engine {
+                engine.play_note(&target, REFERENCE_TONE)?;
+            }
+            // Don't mistake the reference tone for the string
+            microphone.clear();
+            let listening = Instant::now();
+            while engine.is_none() || listening.elapsed() < REFERENCE_REPEAT {
+                let heard = detector.detect(
+                    &microphone.latest(ANALYSIS_WINDOW),
+                    microphone.sample_rate(),
+                );
+                let line = match tuner.hear(heard, tuning) {
+                    StringReading::Tuned => {
+                        writeln!(stdout, "\r✅ {} is in tune\x1b[K", target)?;
+                        break 'string;
+                    }
+                    StringReading::Off(cents) => format!(
+                        "{} {:<4} heard {:>7.2} Hz  {} {:+5.0} cents",
+                        if cents.abs() <= threshold {
+                            "✅"
+                        } else {
+                            "🎯"
+                        },
+                        target.to_string(),
+                        heard.unwrap_or_default(),
+                        needle(cents, 21),
+                        cents
+                    ),
+                    StringReading::Silent => "🔇 Listening...".to_string(),
+                };
+                write!(stdout, "\r{}\x1b[K", line)?;
+                stdout.flush()?;
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+    println!("\n🎉 All {} strings are in tune!", tuner.len());
+    Ok(())
+}
 
-            let scale = Scale::new(note, scale_type);
+/// Keep prompting until the user enters a valid scale.
+/// Entering 't' taps a new tempo, which updates `tempo`.
+fn read_scale(tempo: &mut Tempo) -> Scale {
+    loop {
+        println!("\n🎵 Enter a scale (e.g. C major, A minor, D dorian) or 't' to tap a tempo:");
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
 
-            match scale.play_default() {
-                Ok(_) => println!("🎶 Playing {}...", scale),
-                Err(e) => println!("❌ Error playing {}: {}", scale, e),
+        if input.trim() == "t" {
+            match tap_tempo() {
+                Some(tapped) => {
+                    *tempo = tapped;
+                    println!("🥁 Tempo set to {}", tapped);
+                }
+                None => println!("❌ Tap at least twice to set a tempo."),
             }
+            continue;
+        }
+        match parse_scale(&input) {
+            Ok(scale) => return scale,
+            Err(e) => println!("❌ {}", e),
+        }
+    }
+}
+
+/// Print the frets that play a scale, or failing that a chord, on strings tuned to `tuning`
+fn show_fretboard(input: &str, tuning: &StringTuning, frets: u8) -> bool {
+    let (name, notes, root) = match (parse_scale(input), parse_chord(input)) {
+        (Ok(scale), _) => (scale.to_string(), scale.notes(), scale.root.note),
+        (_, Ok(chord)) => (chord.name(), chord.notes(), chord.root.note),
+        (Err(e), Err(_)) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+    let fretboard = Fretboard::default()
+        .with_tuning(tuning.clone())
+        .with_frets(frets)
+        .with_notes(&notes, root);
+    println!(
+        "🎸 {} on strings tuned {} (R marks the root)\n",
+        name, tuning
+    );
+    println!("{}", fretboard);
+    true
+}
+
+/// Print a scale in its key signature, or failing that a melody in the key it's most
+/// likely in, on a staff
+fn show_staff(input: &str, clef: Option<Clef>) -> bool {
+    let staff = match (parse_scale(input), input.parse::<Melody>()) {
+        (Ok(scale), _) => {
+            let staff = Staff::for_scale(&scale);
+            println!("🎼 {} ({})\n", scale, staff.key);
+            staff
+        }
+        (_, Ok(melody)) => {
+            let key = key_of(&melody.notes())
+                .first()
+                .map_or(Staff::default().key, |(key, _)| *key);
+            println!("🎼 {} ({})\n", melody, key);
+            Staff::for_melody(&melody, key)
+        }
+        (Err(e), Err(_)) => {
+            println!("❌ {} Or write a melody like \"C4 D4:e E4:e F4:h\".", e);
+            return false;
+        }
+    };
+    let staff = match clef {
+        Some(clef) => staff.with_clef(clef),
+        None => staff,
+    };
+    println!("{}", staff);
+    true
+}
+
+/// Parse a scale like "C major" or "F#3 harmonic minor"
+fn parse_scale(input: &str) -> Result<Scale, String> {
+    input.parse::<Scale>().map_err(|_| {
+        "Invalid input. Please enter a valid scale (e.g. C major, Bb3 dorian, C# harmonic minor)."
+            .to_string()
+    })
+}
+
+/// Record Enter presses as beat taps until the user types 'd'
+fn tap_tempo() -> Option<Tempo> {
+    println!("🥁 Press Enter on each beat, then type 'd' and Enter when done:");
+    let mut taps = Vec::new();
+    loop {
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+        if input.trim() == "d" {
+            break;
         }
+        taps.push(Instant::now());
     }
+    bpm_from_taps(&taps).and_then(Tempo::from_bpm)
 }
@@ -0,0 +1,525 @@
+#[cfg(feature = "playback")]
+use crate::audio::{AudioEngine, Playable, PlaybackOptions};
+use crate::chords::Chord;
+#[cfg(feature = "playback")]
+use crate::metronome::Metronome;
+use crate::notes::{NoteWithOctave, Scale};
+use crate::tempo::{NoteValue, Tempo, TimeSignature};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Something that takes up time in a melody: a sounding note or a rest
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Event {
+    Note(NoteWithOctave),
+    Rest,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Note(note) => write!(f, "{}", note),
+            Event::Rest => write!(f, "r"),
+        }
+    }
+}
+
+/// Slack allowed when adding up beats, so triplets still fill a bar exactly
+const BEAT_EPSILON: f64 = 1e-9;
+
+/// Heaviest swing, the first eighth of each beat three times as long as the second
+pub const MAX_SWING: f64 = 3.0;
+
+/// A sequence of notes and rests, each with its own rhythmic value,
+/// optionally divided into bars by a time signature
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Melody {
+    events: Vec<(Event, NoteValue)>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    time_signature: Option<TimeSignature>,
+    /// How much longer the first eighth of each beat is than the second, e.g. 2.0 for a
+    /// triplet shuffle; `None` plays the rhythm straight
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    swing: Option<f64>,
+}
+
+/// With a time signature, it leads and bars are separated by "|", e.g. "3/4 C4:h D4:q | E4:h."
+impl fmt::Display for Melody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens: Vec<String> = Vec::new();
+        let bars = match self.time_signature {
+            Some(time_signature) => {
+                tokens.push(time_signature.to_string());
+                self.bars().unwrap_or_else(|_| vec![self.clone()])
+            }
+            None => vec![self.clone()],
+        };
+        for (i, bar) in bars.iter().enumerate() {
+            if i > 0 {
+                tokens.push("|".to_string());
+            }
+            for (event, value) in &bar.events {
+                tokens.push(format!("{}:{}", event, value.symbol()));
+            }
+        }
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+/// Parse space-separated events like "C4:q D4:e r:e E4:h".
+/// A missing value means a quarter note, and "r" or "rest" is a rest.
+/// A leading time signature like "3/4" divides the melody into bars, and "|" marks
+/// a barline that the notes before it must reach exactly.
+impl FromStr for Melody {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut melody = Melody::new();
+        for (i, token) in s.split_whitespace().enumerate() {
+            if i == 0 && token.contains('/') {
+                melody = melody.with_time_signature(token.parse()?);
+                continue;
+            }
+            if token == "|" {
+                let bar = melody.time_signature.unwrap_or_default().bar_beats();
+                let beats = melody.beats();
+                let past_barline = beats - (beats / bar).round() * bar;
+                if beats < BEAT_EPSILON || past_barline.abs() > BEAT_EPSILON {
+                    return Err(format!(
+                        "Bar {} doesn't fill {} beats",
+                        (beats / bar).floor() as usize + 1,
+                        bar
+                    ));
+                }
+                continue;
+            }
+            let (event, value) = match token.split_once(':') {
+                Some((event, value)) => (event, value.parse::<NoteValue>()?),
+                None => (token, NoteValue::Quarter),
+            };
+            melody = match event {
+                "r" | "rest" => melody.with_rest(value),
+                note => melody.with_note(note.parse()?, value),
+            };
+        }
+        if melody.time_signature.is_some() {
+            melody.bars()?;
+        }
+        Ok(melody)
+    }
+}
+
+impl Melody {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The notes one after another, all with the same value
+    pub fn from_notes(notes: &[NoteWithOctave], value: NoteValue) -> Self {
+        Self {
+            events: notes.iter().map(|&n| (Event::Note(n), value)).collect(),
+            time_signature: None,
+            swing: None,
+        }
+    }
+
+    /// The scale ascending from the root
+    pub fn from_scale(scale: &Scale, value: NoteValue) -> Self {
+        Self::from_notes(&scale.notes(), value)
+    }
+
+    /// The chord as an arpeggio from the bass up
+    pub fn from_chord(chord: &Chord, value: NoteValue) -> Self {
+        Self::from_notes(&chord.notes(), value)
+    }
+
+    pub fn with_note(mut self, note: NoteWithOctave, value: NoteValue) -> Self {
+        self.events.push((Event::Note(note), value));
+        self
+    }
+
+    pub fn with_rest(mut self, value: NoteValue) -> Self {
+        self.events.push((Event::Rest, value));
+        self
+    }
+
+    /// Divide the melody into bars of `time_signature`
+    pub fn with_time_signature(mut self, time_signature: TimeSignature) -> Self {
+        self.time_signature = Some(time_signature);
+        self
+    }
+
+    /// Swing the eighth notes by `ratio`, clamped to 1.0 (straight) to `MAX_SWING`
+    pub fn with_swing(mut self, ratio: f64) -> Self {
+        self.swing = Some(ratio.clamp(1.0, MAX_SWING));
+        self
+    }
+
+    /// This melody followed by `other`
+    pub fn then(mut self, other: Melody) -> Self {
+        self.events.extend(other.events);
+        self
+    }
+
+    pub fn events(&self) -> &[(Event, NoteValue)] {
+        &self.events
+    }
+
+    pub fn time_signature(&self) -> Option<TimeSignature> {
+        self.time_signature
+    }
+
+    pub fn swing(&self) -> Option<f64> {
+        self.swing
+    }
+
+    /// The melody split into bars of its time signature (4/4 if it has none). The last bar
+    /// may be short; a note that would cross a barline is an error.
+    pub fn bars(&self) -> Result<Vec<Melody>, String> {
+        let time_signature = self.time_signature.unwrap_or_default();
+        let bar_beats = time_signature.bar_beats();
+        let mut bars = Vec::new();
+        let mut bar = Melody::new().with_time_signature(time_signature);
+        let mut position = 0.0;
+        for &(event, value) in &self.events {
+            position += value.beats();
+            if position > bar_beats + BEAT_EPSILON {
+                return Err(format!(
+                    "{}:{} crosses the barline at the end of bar {} in {}",
+                    event,
+                    value.symbol(),
+                    bars.len() + 1,
+                    time_signature
+                ));
+            }
+            bar.events.push((event, value));
+            if position > bar_beats - BEAT_EPSILON {
+                bars.push(std::mem::replace(
+                    &mut bar,
+                    Melody::new().with_time_signature(time_signature),
+                ));
+                position = 0.0;
+            }
+        }
+        if !bar.is_empty() {
+            bars.push(bar);
+        }
+        Ok(bars)
+    }
+
+    /// Rests to fill up the last bar, longest first
+    pub fn with_filled_bars(mut self) -> Self {
+        let bar_beats = self.time_signature.unwrap_or_default().bar_beats();
+        let into_bar = self.beats() % bar_beats;
+        if into_bar < BEAT_EPSILON || bar_beats - into_bar < BEAT_EPSILON {
+            return self;
+        }
+        let mut remaining = bar_beats - into_bar;
+        while let Some(rest) = NoteValue::ALL
+            .iter()
+            .find(|value| value.beats() <= remaining + BEAT_EPSILON)
+        {
+            self.events.push((Event::Rest, *rest));
+            remaining -= rest.beats();
+        }
+        self
+    }
+
+    /// The sounding notes in order, without rests
+    pub fn notes(&self) -> Vec<NoteWithOctave> {
+        self.events
+            .iter()
+            .filter_map(|(event, _)| match event {
+                Event::Note(note) => Some(*note),
+                Event::Rest => None,
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Total length in quarter-note beats
+    pub fn beats(&self) -> f64 {
+        self.events.iter().map(|(_, value)| value.beats()).sum()
+    }
+
+    /// How long the melody lasts at `tempo`
+    pub fn duration(&self, tempo: Tempo) -> Duration {
+        tempo.beat_duration().mul_f64(self.beats())
+    }
+
+    /// How many beats each event lasts when played with `swing`. The first half of every
+    /// beat is stretched to `swing` times the length of the second, so a pair of eighths
+    /// plays long-short while quarter notes and the bars keep their length.
+    pub fn swung_beats(&self, swing: Option<f64>) -> Vec<f64> {
+        let Some(ratio) = swing else {
+            return self.events.iter().map(|(_, value)| value.beats()).collect();
+        };
+        let long = ratio / (1.0 + ratio);
+        let mut start = 0.0;
+        self.events
+            .iter()
+            .map(|(_, value)| {
+                let end = start + value.beats();
+                let beats = swung_position(end, long) - swung_position(start, long);
+                start = end;
+                beats
+            })
+            .collect()
+    }
+
+    /// Play the melody on a one-off engine at `tempo`
+    #[cfg(feature = "playback")]
+    pub fn play(&self, tempo: Tempo) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_on(&AudioEngine::new()?, tempo)
+    }
+
+    /// Play the melody on an already open audio engine
+    #[cfg(feature = "playback")]
+    pub fn play_on(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_events(engine, &PlaybackOptions::new().with_tempo(tempo), None)
+    }
+
+    /// Play the melody, printing each note as it sounds
+    #[cfg(feature = "playback")]
+    pub fn play_announced_on(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let labels: Vec<String> = self.notes().iter().map(|n| n.to_string()).collect();
+        self.play_events(
+            engine,
+            &PlaybackOptions::new().with_tempo(tempo),
+            Some(&labels),
+        )
+    }
+
+    /// Play the melody, printing `labels[i]` as the i-th note sounds,
+    /// e.g. to show spelled names like "E#4" instead of "F4"
+    #[cfg(feature = "playback")]
+    pub fn play_labelled_on(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+        labels: &[String],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_events(
+            engine,
+            &PlaybackOptions::new().with_tempo(tempo),
+            Some(labels),
+        )
+    }
+
+    /// Play the melody after `count_in` bars of clicks in its time signature,
+    /// with `click` keeping the metronome going underneath
+    #[cfg(feature = "playback")]
+    pub fn play_in_time(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+        count_in: u8,
+        click: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let metronome = Metronome::new(tempo, self.time_signature.unwrap_or_default());
+        for _ in 0..count_in {
+            engine.play_buffer(metronome.render_bar())?;
+        }
+        let clicks = if click {
+            Some(metronome.start(engine)?)
+        } else {
+            None
+        };
+        self.play_on(engine, tempo)?;
+        drop(clicks);
+        Ok(())
+    }
+
+    /// Play the melody shaped by `opts`, printing `labels[i]` as the i-th note sounds
+    #[cfg(feature = "playback")]
+    pub(crate) fn play_events(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+        labels: Option<&[String]>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut note_index = 0;
+        let beats = self.swung_beats(opts.swing.or(self.swing));
+        for ((event, _), beats) in self.events.iter().zip(beats) {
+            let duration = opts.tempo.beat_duration().mul_f64(beats);
+            match event {
+                Event::Note(note) => {
+                    if let Some(label) = labels.and_then(|labels| labels.get(note_index)) {
+                        println!("🎵 {}", label);
+                    }
+                    note_index += 1;
+                    engine.play_note_with(note, duration, opts)?;
+                }
+                Event::Rest => engine.rest(duration)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where `position` beats into a melody falls once the first half of each beat is
+/// stretched to `long` of it
+fn swung_position(position: f64, long: f64) -> f64 {
+    let beat = position.floor();
+    let into_beat = position - beat;
+    if into_beat <= 0.5 {
+        beat + into_beat * 2.0 * long
+    } else {
+        beat + long + (into_beat - 0.5) * 2.0 * (1.0 - long)
+    }
+}
+
+#[cfg(feature = "playback")]
+impl Playable for Melody {
+    fn play(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_events(engine, opts, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chords::ChordQuality;
+    use crate::notes::{Note, ScaleType};
+
+    #[test]
+    fn test_parse_melody() {
+        let melody: Melody = "C4:q D4:e r:e E4:h".parse().unwrap();
+        assert_eq!(melody.len(), 4);
+        assert_eq!(melody.events()[2], (Event::Rest, NoteValue::Eighth));
+        assert_eq!(melody.beats(), 4.0);
+        assert_eq!(melody.to_string(), "C4:q D4:e r:e E4:h");
+
+        // Values default to quarter notes
+        let melody: Melody = "A4 rest".parse().unwrap();
+        assert_eq!(melody.to_string(), "A4:q r:q");
+
+        assert!("C4:x".parse::<Melody>().is_err());
+        assert!("H4:q".parse::<Melody>().is_err());
+    }
+
+    #[test]
+    fn test_melody_from_scale_and_chord() {
+        let scale = Scale::new("C4".parse().unwrap(), ScaleType::Major);
+        let melody = Melody::from_scale(&scale, NoteValue::Eighth);
+        assert_eq!(melody.notes(), scale.notes());
+        assert_eq!(melody.beats(), scale.notes().len() as f64 * 0.5);
+
+        let chord = Chord::new("A3".parse().unwrap(), ChordQuality::Minor);
+        let melody = Melody::from_chord(&chord, NoteValue::Quarter);
+        assert_eq!(melody.notes()[1].note, Note::C);
+        assert_eq!(melody.notes()[1].octave, 4);
+    }
+
+    #[test]
+    fn test_melody_builders() {
+        let c4: NoteWithOctave = "C4".parse().unwrap();
+        let melody = Melody::new()
+            .with_note(c4, NoteValue::Half)
+            .with_rest(NoteValue::Quarter)
+            .then("G4:q".parse().unwrap());
+        assert_eq!(melody.to_string(), "C4:h r:q G4:q");
+        assert_eq!(melody.notes().len(), 2);
+        assert_eq!(melody.duration(Tempo(120)), Duration::from_secs(2));
+        assert!(Melody::new().is_empty());
+    }
+
+    #[test]
+    fn test_bars() {
+        let melody: Melody = "3/4 C4:h D4:q | E4:h. | F4:q G4:e".parse().unwrap();
+        assert_eq!(melody.time_signature(), Some("3/4".parse().unwrap()));
+        let bars = melody.bars().unwrap();
+        assert_eq!(bars.len(), 3);
+        assert_eq!(bars[1].to_string(), "3/4 E4:h.");
+        assert_eq!(melody.to_string(), "3/4 C4:h D4:q | E4:h. | F4:q G4:e");
+
+        let filled = melody.with_filled_bars();
+        assert_eq!(filled.to_string(), "3/4 C4:h D4:q | E4:h. | F4:q G4:e r:q.");
+
+        let triplets: Melody = "2/4 C4:e3 D4:e3 E4:e3 F4:q | G4:h".parse().unwrap();
+        assert_eq!(triplets.bars().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_bars() {
+        assert!("3/4 C4:h D4:h".parse::<Melody>().is_err());
+        assert!("4/4 C4:w | D4:w".parse::<Melody>().is_ok());
+        assert!("4/4 C4:h | D4:w".parse::<Melody>().is_err());
+        assert!("4/4 C4:q | D4:h.".parse::<Melody>().is_err());
+        assert!("| C4:q".parse::<Melody>().is_err());
+        assert!("5/3 C4:q".parse::<Melody>().is_err());
+
+        // Without a time signature bars are 4/4, but nothing is checked until asked
+        let long: Melody = "C4:h. D4:h".parse().unwrap();
+        assert!(long.bars().is_err());
+        assert_eq!(long.to_string(), "C4:h. D4:h");
+    }
+
+    #[test]
+    fn test_swing() {
+        let melody: Melody = "C4:e D4:e E4:q F4:e. G4:s".parse().unwrap();
+        assert_eq!(melody.swing(), None);
+        assert_eq!(melody.swung_beats(None), [0.5, 0.5, 1.0, 0.75, 0.25]);
+
+        let shuffle = melody.clone().with_swing(2.0);
+        assert_eq!(shuffle.swing(), Some(2.0));
+        let beats = shuffle.swung_beats(shuffle.swing());
+        let expected = [2.0 / 3.0, 1.0 / 3.0, 1.0, 5.0 / 6.0, 1.0 / 6.0];
+        assert!(
+            beats
+                .iter()
+                .zip(expected)
+                .all(|(a, b)| (a - b).abs() < 1e-9)
+        );
+        // Swing moves notes within the beat but not the total length
+        assert!((beats.iter().sum::<f64>() - melody.beats()).abs() < 1e-9);
+
+        assert_eq!(melody.clone().with_swing(0.5).swing(), Some(1.0));
+        assert_eq!(melody.with_swing(10.0).swing(), Some(MAX_SWING));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let melody: Melody = "C4:q r:e D4:e3 E4:h.".parse().unwrap();
+        let json = serde_json::to_string(&melody).unwrap();
+        assert_eq!(serde_json::from_str::<Melody>(&json).unwrap(), melody);
+        assert!(serde_json::from_str::<Melody>(r#"{"events":[["rest","eighth"]]}"#).is_ok());
+
+        let waltz: Melody = "3/4 C4:h. | D4:q E4:h".parse().unwrap();
+        let json = serde_json::to_string(&waltz).unwrap();
+        assert_eq!(serde_json::from_str::<Melody>(&json).unwrap(), waltz);
+
+        let shuffle = melody.with_swing(2.0);
+        let json = serde_json::to_string(&shuffle).unwrap();
+        assert_eq!(serde_json::from_str::<Melody>(&json).unwrap(), shuffle);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::audio::SAMPLE_RATE;
+#[cfg(feature = "playback")]
+use crate::audio::{AudioEngine, Drone};
+use crate::tempo::{Tempo, TimeSignature};
+use std::f32::consts::TAU;
+use std::fmt;
+use std::time::Duration;
+
+/// Most clicks a metronome can split each pulse into
+pub const MAX_SUBDIVISIONS: u8 = 4;
+
+/// How long each click rings
+const CLICK_LENGTH: Duration = Duration::from_millis(30);
+
+/// How strongly a click is played
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Click {
+    /// The first pulse of the bar
+    Accent,
+    /// The start of a felt beat
+    Beat,
+    /// A pulse inside a compound beat, or a subdivision of a pulse
+    Subdivision,
+}
+
+impl Click {
+    fn frequency(&self) -> f32 {
+        match self {
+            Click::Accent => 1760.0,
+            Click::Beat => 1320.0,
+            Click::Subdivision => 880.0,
+        }
+    }
+
+    fn gain(&self) -> f32 {
+        match self {
+            Click::Accent => 0.8,
+            Click::Beat => 0.6,
+            Click::Subdivision => 0.35,
+        }
+    }
+
+    /// A short sine blip that dies away quickly, at `SAMPLE_RATE`
+    pub fn render(&self) -> Vec<f32> {
+        let length = (CLICK_LENGTH.as_secs_f64() * SAMPLE_RATE as f64) as usize;
+        (0..length)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                (TAU * self.frequency() * t).sin() * self.gain() * (-t / 0.008).exp()
+            })
+            .collect()
+    }
+}
+
+/// A click track at a steady tempo, accenting the first beat of each bar
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Metronome {
+    pub tempo: Tempo,
+    pub time_signature: TimeSignature,
+    /// Clicks per pulse, from 1 to `MAX_SUBDIVISIONS`
+    pub subdivisions: u8,
+}
+
+impl fmt::Display for Metronome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in {}", self.tempo, self.time_signature)?;
+        if self.subdivisions > 1 {
+            write!(f, ", {} clicks per pulse", self.subdivisions)?;
+        }
+        Ok(())
+    }
+}
+
+impl Metronome {
+    /// One click per pulse of `time_signature`, where a quarter note lasts a beat of `tempo`
+    pub fn new(tempo: Tempo, time_signature: TimeSignature) -> Self {
+        Self {
+            tempo,
+            time_signature,
+            subdivisions: 1,
+        }
+    }
+
+    /// Split each pulse into `subdivisions` clicks, clamped to 1-`MAX_SUBDIVISIONS`
+    pub fn with_subdivisions(mut self, subdivisions: u8) -> Self {
+        self.subdivisions = subdivisions.clamp(1, MAX_SUBDIVISIONS);
+        self
+    }
+
+    /// Every click in one bar, with when it sounds after the bar starts
+    pub fn clicks(&self) -> Vec<(Duration, Click)> {
+        let pulse = self
+            .time_signature
+            .pulse_duration(self.tempo.beat_duration());
+        let step = pulse / self.subdivisions as u32;
+        let pulses_per_beat = self.time_signature.pulses_per_beat();
+        (0..self.time_signature.beats)
+            .flat_map(|pulse_index| {
+                (0..self.subdivisions).map(move |sub| {
+                    let click = if sub > 0 {
+                        Click::Subdivision
+                    } else if pulse_index == 0 {
+                        Click::Accent
+                    } else if pulse_index.is_multiple_of(pulses_per_beat) {
+                        Click::Beat
+                    } else {
+                        Click::Subdivision
+                    };
+                    (pulse * pulse_index as u32 + step * sub as u32, click)
+                })
+            })
+            .collect()
+    }
+
+    /// One bar of clicks as a mono buffer at `SAMPLE_RATE`, ready to loop
+    pub fn render_bar(&self) -> Vec<f32> {
+        let bar = self.time_signature.bar_duration(self.tempo);
+        let mut samples = vec![0.0; (bar.as_secs_f64() * SAMPLE_RATE as f64) as usize];
+        for (offset, click) in self.clicks() {
+            let start = (offset.as_secs_f64() * SAMPLE_RATE as f64) as usize;
+            for (sample, value) in samples.iter_mut().skip(start).zip(click.render()) {
+                *sample += value;
+            }
+        }
+        samples
+    }
+
+    /// Click in the background, alongside anything else the engine plays,
+    /// until the returned `Drone` is stopped or dropped
+    #[cfg(feature = "playback")]
+    pub fn start(&self, engine: &AudioEngine) -> Result<Drone, Box<dyn std::error::Error>> {
+        engine.start_loop(self.render_bar())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(metronome: &Metronome) -> Vec<Click> {
+        metronome.clicks().iter().map(|(_, click)| *click).collect()
+    }
+
+    #[test]
+    fn test_accents_in_simple_and_compound_time() {
+        let common = Metronome::new(Tempo(120), TimeSignature::COMMON);
+        assert_eq!(
+            kinds(&common),
+            vec![Click::Accent, Click::Beat, Click::Beat, Click::Beat]
+        );
+        let offsets: Vec<Duration> = common.clicks().iter().map(|(at, _)| *at).collect();
+        assert_eq!(offsets[3], Duration::from_millis(1500));
+
+        let jig = Metronome::new(Tempo(120), "6/8".parse().unwrap());
+        assert_eq!(
+            kinds(&jig),
+            vec![
+                Click::Accent,
+                Click::Subdivision,
+                Click::Subdivision,
+                Click::Beat,
+                Click::Subdivision,
+                Click::Subdivision,
+            ]
+        );
+        assert_eq!(jig.clicks()[1].0, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_subdivisions() {
+        let triplets = Metronome::new(Tempo(60), "2/4".parse().unwrap()).with_subdivisions(3);
+        let clicks = triplets.clicks();
+        assert_eq!(clicks.len(), 6);
+        assert_eq!(clicks[3], (Duration::from_secs(1), Click::Beat));
+        assert_eq!(clicks[4].1, Click::Subdivision);
+        assert_eq!(triplets.to_string(), "60 BPM in 2/4, 3 clicks per pulse");
+
+        let clamped = Metronome::new(Tempo(60), TimeSignature::COMMON).with_subdivisions(9);
+        assert_eq!(clamped.subdivisions, MAX_SUBDIVISIONS);
+    }
+
+    #[test]
+    fn test_render_bar() {
+        let metronome = Metronome::new(Tempo(120), "3/4".parse().unwrap());
+        let bar = metronome.render_bar();
+        assert_eq!(bar.len(), (1.5 * SAMPLE_RATE as f64) as usize);
+
+        let peak = |range: std::ops::Range<usize>| {
+            bar[range].iter().fold(0.0_f32, |max, s| max.max(s.abs()))
+        };
+        let beat = SAMPLE_RATE as usize / 2;
+        let accent = peak(0..beat / 4);
+        let second = peak(beat..beat + beat / 4);
+        assert!(accent > second && second > 0.0);
+        assert_eq!(peak(beat / 4..beat), 0.0);
+    }
+}
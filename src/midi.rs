@@ -0,0 +1,305 @@
+use crate::answer::{Answer, AnswerInput, NoteGrouper};
+use crate::notes::NoteWithOctave;
+use crate::smf::DEFAULT_VELOCITY;
+use midir::{MidiInput, MidiInputConnection, MidiOutput, MidiOutputConnection};
+use std::io;
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Name the trainer shows up as in other MIDI software
+pub const CLIENT_NAME: &str = "ear-trainer";
+
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+const CONTROL_CHANGE: u8 = 0xB0;
+const ALL_NOTES_OFF: u8 = 123;
+
+/// Note-on message for `note` on `channel` (0-15)
+pub fn note_on(channel: u8, note: u8, velocity: u8) -> [u8; 3] {
+    [NOTE_ON | (channel & 0x0F), note & 0x7F, velocity & 0x7F]
+}
+
+/// Note-off message for `note` on `channel` (0-15)
+pub fn note_off(channel: u8, note: u8) -> [u8; 3] {
+    [NOTE_OFF | (channel & 0x0F), note & 0x7F, 0]
+}
+
+/// Names of the MIDI output ports that can be played through
+pub fn output_ports() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = MidiOutput::new(CLIENT_NAME)?;
+    output
+        .ports()
+        .iter()
+        .map(|port| Ok(output.port_name(port)?))
+        .collect()
+}
+
+/// Names of the MIDI input ports, e.g. connected keyboards
+pub fn input_ports() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let input = MidiInput::new(CLIENT_NAME)?;
+    input
+        .ports()
+        .iter()
+        .map(|port| Ok(input.port_name(port)?))
+        .collect()
+}
+
+/// Index of the first port whose name contains `wanted` (ignoring case),
+/// or of the first port when `wanted` is `None`
+fn find_port(names: &[String], wanted: Option<&str>, direction: &str) -> Result<usize, String> {
+    let lowered = wanted.map(str::to_lowercase);
+    names
+        .iter()
+        .position(|name| {
+            lowered
+                .as_ref()
+                .is_none_or(|wanted| name.to_lowercase().contains(wanted))
+        })
+        .ok_or_else(|| {
+            if names.is_empty() {
+                format!("No MIDI {} ports found", direction)
+            } else {
+                format!(
+                    "No MIDI {} port matches \"{}\". Available: {}",
+                    direction,
+                    wanted.unwrap_or_default(),
+                    names.join(", ")
+                )
+            }
+        })
+}
+
+/// A connection to a hardware or software synth, so notes sound in its timbre
+/// (e.g. a piano) instead of the built-in oscillators
+pub struct MidiOut {
+    connection: Mutex<MidiOutputConnection>,
+    port_name: String,
+    channel: u8,
+    velocity: u8,
+}
+
+impl MidiOut {
+    /// Connect to the first output port whose name contains `port` (ignoring case),
+    /// or to the first port of all when `port` is `None`
+    pub fn connect(port: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let output = MidiOutput::new(CLIENT_NAME)?;
+        let ports = output.ports();
+        let mut names = Vec::new();
+        for candidate in &ports {
+            names.push(output.port_name(candidate)?);
+        }
+
+        let index = find_port(&names, port, "output")?;
+        let connection = output.connect(&ports[index], CLIENT_NAME)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+            port_name: names.swap_remove(index),
+            channel: 0,
+            velocity: DEFAULT_VELOCITY,
+        })
+    }
+
+    pub fn port_name(&self) -> &str {
+        &self.port_name
+    }
+
+    /// Send on `channel`, from 0 to 15 (shown as 1-16 in most synths)
+    pub fn set_channel(&mut self, channel: u8) {
+        self.channel = channel.min(15);
+    }
+
+    pub fn set_velocity(&mut self, velocity: u8) {
+        self.velocity = velocity.min(127);
+    }
+
+    /// Sound `notes` together for `duration`, waiting until they are released. Notes
+    /// above G9 have no MIDI number and are left out.
+    pub fn play_notes(
+        &self,
+        notes: &[NoteWithOctave],
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let numbers: Vec<u8> = notes.iter().filter_map(NoteWithOctave::to_midi).collect();
+        for &number in &numbers {
+            self.send(&note_on(self.channel, number, self.velocity))?;
+        }
+        thread::sleep(duration);
+        for &number in &numbers {
+            self.send(&note_off(self.channel, number))?;
+        }
+        Ok(())
+    }
+
+    /// Silence anything still sounding on the channel
+    pub fn all_notes_off(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.send(&[CONTROL_CHANGE | self.channel, ALL_NOTES_OFF, 0])
+    }
+
+    fn send(&self, message: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        self.connection
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .send(message)?;
+        Ok(())
+    }
+}
+
+impl Drop for MidiOut {
+    fn drop(&mut self) {
+        // Don't leave notes hanging on the synth if playback was interrupted
+        let _ = self.all_notes_off();
+    }
+}
+
+/// The note number of a note-on message; note-ons with zero velocity are note-offs
+fn pressed_key(message: &[u8]) -> Option<u8> {
+    match *message {
+        [status, note, velocity] if status & 0xF0 == NOTE_ON && velocity > 0 => Some(note),
+        _ => None,
+    }
+}
+
+enum Message {
+    Line(String),
+    Key(u8),
+}
+
+/// Answers played on a MIDI keyboard, with typed answers still accepted
+/// so 'r' and 'q' keep working
+pub struct KeyboardInput {
+    // Dropping the connection stops the callback
+    _connection: MidiInputConnection<()>,
+    port_name: String,
+    sender: Sender<Message>,
+    messages: Receiver<Message>,
+    grouper: NoteGrouper,
+    reading_stdin: bool,
+}
+
+impl KeyboardInput {
+    /// Listen to the first input port whose name contains `port` (ignoring case),
+    /// or to the first port of all when `port` is `None`
+    pub fn connect(port: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let input = MidiInput::new(CLIENT_NAME)?;
+        let ports = input.ports();
+        let mut names = Vec::new();
+        for candidate in &ports {
+            names.push(input.port_name(candidate)?);
+        }
+        let index = find_port(&names, port, "input")?;
+
+        let (sender, messages) = mpsc::channel();
+        let keys = sender.clone();
+        let connection = input.connect(
+            &ports[index],
+            CLIENT_NAME,
+            move |_, message, _| {
+                if let Some(note) = pressed_key(message) {
+                    let _ = keys.send(Message::Key(note));
+                }
+            },
+            (),
+        )?;
+        Ok(Self {
+            _connection: connection,
+            port_name: names.swap_remove(index),
+            sender,
+            messages,
+            grouper: NoteGrouper::default(),
+            reading_stdin: false,
+        })
+    }
+
+    pub fn port_name(&self) -> &str {
+        &self.port_name
+    }
+
+    /// Read typed lines on a background thread, started on the first answer
+    /// so prompts before the quiz can still read the terminal themselves
+    fn read_stdin(&mut self) {
+        if self.reading_stdin {
+            return;
+        }
+        self.reading_stdin = true;
+        let lines = self.sender.clone();
+        thread::spawn(move || {
+            for line in io::stdin().lines() {
+                let Ok(line) = line else { break };
+                if lines.send(Message::Line(line)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+impl AnswerInput for KeyboardInput {
+    fn next_answer(&mut self) -> Option<Answer> {
+        self.read_stdin();
+        loop {
+            let message = match self.grouper.deadline() {
+                Some(deadline) => {
+                    match self
+                        .messages
+                        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    {
+                        Ok(message) => message,
+                        Err(RecvTimeoutError::Timeout) => {
+                            if let Some(notes) = self.grouper.take_if_done(Instant::now()) {
+                                return Some(Answer::from_midi(&notes));
+                            }
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => return None,
+                    }
+                }
+                None => self.messages.recv().ok()?,
+            };
+            match message {
+                Message::Line(line) => {
+                    self.grouper.clear();
+                    return Some(Answer::Text(line.trim().to_string()));
+                }
+                Message::Key(note) => self.grouper.press(note, Instant::now()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_messages() {
+        let a4: NoteWithOctave = "A4".parse().unwrap();
+        assert_eq!(note_on(0, a4.to_midi().unwrap(), 90), [0x90, 69, 90]);
+        assert_eq!(note_off(0, a4.to_midi().unwrap()), [0x80, 69, 0]);
+        assert_eq!(note_on(9, 36, 127), [0x99, 36, 127]);
+        // Out-of-range values are masked rather than corrupting the status byte
+        assert_eq!(note_on(16, 200, 255), [0x90, 72, 127]);
+    }
+
+    #[test]
+    fn test_pressed_key() {
+        assert_eq!(pressed_key(&note_on(3, 64, 100)), Some(64));
+        assert_eq!(pressed_key(&note_on(0, 64, 0)), None);
+        assert_eq!(pressed_key(&note_off(0, 64)), None);
+        assert_eq!(pressed_key(&[0xF8]), None);
+    }
+
+    #[test]
+    fn test_find_port() {
+        let names = vec!["Midi Through".to_string(), "USB Keystation 49".to_string()];
+        assert_eq!(find_port(&names, None, "input"), Ok(0));
+        assert_eq!(find_port(&names, Some("keystation"), "input"), Ok(1));
+        assert!(
+            find_port(&names, Some("piano"), "input")
+                .unwrap_err()
+                .contains("Keystation")
+        );
+        assert!(find_port(&[], None, "output").is_err());
+    }
+}
@@ -0,0 +1,458 @@
+use crate::chords::Chord;
+use crate::display::Clef;
+use crate::keys::{KeySignature, key_of};
+use crate::melody::{Event, Melody};
+use crate::notes::Scale;
+use crate::progression::Progression;
+use crate::spelling::SpelledPitch;
+use crate::tempo::{NoteValue, TimeSignature};
+use std::fs;
+use std::path::Path;
+
+/// Length of a quarter note in MusicXML duration units; divides evenly into every note
+/// value, triplets included
+pub const DIVISIONS: u32 = 12;
+
+/// Length of a note value in divisions
+pub fn divisions(value: NoteValue) -> u32 {
+    (value.beats() * DIVISIONS as f64).round() as u32
+}
+
+/// Written notes and rests with the key, meter and clef to print them in, ready to be
+/// saved as a single-part MusicXML score
+#[derive(Debug, PartialEq, Clone)]
+pub struct Score {
+    /// Printed above the music; left out when empty
+    pub title: String,
+    pub key: KeySignature,
+    pub time_signature: TimeSignature,
+    pub clef: Clef,
+    /// Notes sounding together with their value; no notes is a rest
+    events: Vec<(Vec<SpelledPitch>, NoteValue)>,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            key: KeySignature::new(0).expect("C major has a key signature"),
+            time_signature: TimeSignature::default(),
+            clef: Clef::default(),
+            events: Vec::new(),
+        }
+    }
+}
+
+/// A note or rest as printed in one bar, tied to its neighbours when it was split
+/// across a barline
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Written {
+    pub(crate) notes: Vec<SpelledPitch>,
+    pub(crate) value: NoteValue,
+    pub(crate) tied_from_previous: bool,
+    pub(crate) tied_to_next: bool,
+}
+
+impl Score {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_key(mut self, key: KeySignature) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn with_time_signature(mut self, time_signature: TimeSignature) -> Self {
+        self.time_signature = time_signature;
+        self
+    }
+
+    pub fn with_clef(mut self, clef: Clef) -> Self {
+        self.clef = clef;
+        self
+    }
+
+    /// Add notes sounding together, or a single note
+    pub fn push_notes(&mut self, notes: &[SpelledPitch], value: NoteValue) {
+        self.events.push((notes.to_vec(), value));
+    }
+
+    pub fn push_rest(&mut self, value: NoteValue) {
+        self.events.push((Vec::new(), value));
+    }
+
+    pub fn events(&self) -> &[(Vec<SpelledPitch>, NoteValue)] {
+        &self.events
+    }
+
+    /// The events laid out in bars of the time signature. Notes crossing a barline are
+    /// split and tied, and the last bar is filled up with rests.
+    pub(crate) fn measures(&self) -> Vec<Vec<Written>> {
+        let bar = (self.time_signature.bar_beats() * DIVISIONS as f64).round() as u32;
+        let mut measures = vec![Vec::new()];
+        let mut position = 0;
+        for (notes, value) in &self.events {
+            let mut remaining = divisions(*value);
+            let mut pieces = Vec::new();
+            while remaining > 0 {
+                let length = remaining.min(bar - position);
+                let values = if length == divisions(*value) {
+                    vec![*value]
+                } else {
+                    values_filling(length)
+                };
+                pieces.extend(values.into_iter().map(|value| (measures.len() - 1, value)));
+                remaining -= length;
+                position += length;
+                if position == bar {
+                    measures.push(Vec::new());
+                    position = 0;
+                }
+            }
+            let count = pieces.len();
+            for (i, (measure, value)) in pieces.into_iter().enumerate() {
+                let tied = !notes.is_empty();
+                measures[measure].push(Written {
+                    notes: notes.clone(),
+                    value,
+                    tied_from_previous: tied && i > 0,
+                    tied_to_next: tied && i + 1 < count,
+                });
+            }
+        }
+        if measures.len() > 1 && position == 0 {
+            measures.pop();
+        } else if let Some(last) = measures.last_mut() {
+            last.extend(
+                values_filling(bar - position)
+                    .into_iter()
+                    .map(|value| Written {
+                        notes: Vec::new(),
+                        value,
+                        tied_from_previous: false,
+                        tied_to_next: false,
+                    }),
+            );
+        }
+        measures
+    }
+
+    /// The score as a partwise MusicXML document
+    pub fn to_musicxml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+             <!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" \
+             \"http://www.musicxml.org/dtds/partwise.dtd\">\n\
+             <score-partwise version=\"4.0\">\n",
+        );
+        if !self.title.is_empty() {
+            xml.push_str(&format!(
+                "  <work>\n    <work-title>{}</work-title>\n  </work>\n",
+                escape(&self.title)
+            ));
+        }
+        xml.push_str(
+            "  <identification>\n    <encoding>\n      <software>ear-trainer</software>\n    \
+             </encoding>\n  </identification>\n  <part-list>\n    <score-part id=\"P1\">\n      \
+             <part-name>Music</part-name>\n    </score-part>\n  </part-list>\n  <part id=\"P1\">\n",
+        );
+        for (i, measure) in self.measures().iter().enumerate() {
+            xml.push_str(&format!("    <measure number=\"{}\">\n", i + 1));
+            if i == 0 {
+                self.write_attributes(&mut xml);
+            }
+            for written in measure {
+                write_note(&mut xml, written);
+            }
+            xml.push_str("    </measure>\n");
+        }
+        xml.push_str("  </part>\n</score-partwise>\n");
+        xml
+    }
+
+    fn write_attributes(&self, xml: &mut String) {
+        let (sign, line) = match self.clef {
+            Clef::Treble => ('G', 2),
+            Clef::Bass => ('F', 4),
+        };
+        xml.push_str(&format!("      <attributes>\n        <divisions>{}</divisions>\n        \
+             <key>\n          <fifths>{}</fifths>\n        </key>\n        \
+             <time>\n          <beats>{}</beats>\n          <beat-type>{}</beat-type>\n        \
+             </time>\n        <clef>\n          <sign>{}</sign>\n          <line>{}</line>\n        \
+             </clef>\n      </attributes>\n",
+            DIVISIONS,
+            self.key.fifths(),
+            self.time_signature.beats,
+            self.time_signature.unit,
+            sign,
+            line));
+    }
+
+    /// Write the score to `path` as MusicXML
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, self.to_musicxml())?;
+        Ok(())
+    }
+}
+
+/// Note values adding up to `length` divisions, longest first
+fn values_filling(mut length: u32) -> Vec<NoteValue> {
+    let mut values = Vec::new();
+    while let Some(value) = NoteValue::ALL
+        .iter()
+        .filter(|value| divisions(**value) <= length)
+        .max_by_key(|value| divisions(**value))
+    {
+        values.push(*value);
+        length -= divisions(*value);
+    }
+    values
+}
+
+/// The MusicXML note type of a value, whether it's dotted, and whether it's a triplet
+fn note_type(value: NoteValue) -> (&'static str, bool, bool) {
+    match value {
+        NoteValue::Whole => ("whole", false, false),
+        NoteValue::DottedHalf => ("half", true, false),
+        NoteValue::Half => ("half", false, false),
+        NoteValue::HalfTriplet => ("half", false, true),
+        NoteValue::DottedQuarter => ("quarter", true, false),
+        NoteValue::Quarter => ("quarter", false, false),
+        NoteValue::QuarterTriplet => ("quarter", false, true),
+        NoteValue::DottedEighth => ("eighth", true, false),
+        NoteValue::Eighth => ("eighth", false, false),
+        NoteValue::EighthTriplet => ("eighth", false, true),
+        NoteValue::Sixteenth => ("16th", false, false),
+    }
+}
+
+/// One `<note>` per pitch, the later ones marked as sounding with the first
+fn write_note(xml: &mut String, written: &Written) {
+    let (kind, dotted, triplet) = note_type(written.value);
+    let rest = [None];
+    let pitches: Vec<Option<&SpelledPitch>> = if written.notes.is_empty() {
+        rest.to_vec()
+    } else {
+        written.notes.iter().map(Some).collect()
+    };
+    for (i, pitch) in pitches.into_iter().enumerate() {
+        xml.push_str("      <note>\n");
+        if i > 0 {
+            xml.push_str("        <chord/>\n");
+        }
+        match pitch {
+            Some(pitch) => {
+                xml.push_str(&format!(
+                    "        <pitch>\n          <step>{}</step>\n",
+                    pitch.note.name
+                ));
+                let alter = pitch.note.accidental.semitones();
+                if alter != 0 {
+                    xml.push_str(&format!("          <alter>{}</alter>\n", alter));
+                }
+                xml.push_str(&format!(
+                    "          <octave>{}</octave>\n        </pitch>\n",
+                    pitch.octave
+                ));
+            }
+            None => xml.push_str("        <rest/>\n"),
+        }
+        xml.push_str(&format!(
+            "        <duration>{}</duration>\n",
+            divisions(written.value)
+        ));
+        if written.tied_from_previous {
+            xml.push_str("        <tie type=\"stop\"/>\n");
+        }
+        if written.tied_to_next {
+            xml.push_str("        <tie type=\"start\"/>\n");
+        }
+        xml.push_str(&format!("        <type>{}</type>\n", kind));
+        if dotted {
+            xml.push_str("        <dot/>\n");
+        }
+        if triplet {
+            xml.push_str(
+                "        <time-modification>\n          <actual-notes>3</actual-notes>\n          \
+                 <normal-notes>2</normal-notes>\n        </time-modification>\n",
+            );
+        }
+        if written.tied_from_previous || written.tied_to_next {
+            xml.push_str("        <notations>\n");
+            if written.tied_from_previous {
+                xml.push_str("          <tied type=\"stop\"/>\n");
+            }
+            if written.tied_to_next {
+                xml.push_str("          <tied type=\"start\"/>\n");
+            }
+            xml.push_str("        </notations>\n");
+        }
+        xml.push_str("      </note>\n");
+    }
+}
+
+/// `text` with the characters XML reserves replaced by entities
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Something that can be written out as sheet music for MuseScore, Finale or Sibelius
+pub trait ToMusicXml {
+    fn to_score(&self) -> Score;
+
+    /// Write a partwise MusicXML file
+    fn to_musicxml_file(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        self.to_score().write(path)
+    }
+}
+
+/// The melody in rhythm and in its time signature, spelled in the key it fits best
+impl ToMusicXml for Melody {
+    fn to_score(&self) -> Score {
+        let key = key_of(&self.notes())
+            .first()
+            .map_or(Score::default().key, |(key, _)| *key);
+        let spelled: Vec<SpelledPitch> = self.notes().iter().map(|n| key.spell(n)).collect();
+        let mut score = Score::new()
+            .with_key(key)
+            .with_time_signature(self.time_signature().unwrap_or_default())
+            .with_clef(Clef::for_notes(&spelled));
+        for (event, value) in self.events() {
+            match event {
+                Event::Note(note) => score.push_notes(&[key.spell(note)], *value),
+                Event::Rest => score.push_rest(*value),
+            }
+        }
+        score
+    }
+}
+
+/// One octave of the scale up to the top tonic in quarter notes, in its own key signature
+impl ToMusicXml for Scale {
+    fn to_score(&self) -> Score {
+        let notes = self.spelled_notes_spanning(1);
+        let mut score = Score::new()
+            .with_title(self.to_string())
+            .with_key(KeySignature::for_scale(self))
+            .with_clef(Clef::for_notes(&notes));
+        for note in &notes {
+            score.push_notes(&[*note], NoteValue::Quarter);
+        }
+        score
+    }
+}
+
+/// The chord as a whole-note block chord, as `ToMidi` writes it
+impl ToMusicXml for Chord {
+    fn to_score(&self) -> Score {
+        let notes = self.spelled_notes();
+        let mut score = Score::new()
+            .with_title(self.to_string())
+            .with_clef(Clef::for_notes(&notes));
+        score.push_notes(&notes, NoteValue::Whole);
+        score
+    }
+}
+
+/// Each chord as a half-note block chord, as it plays, in the key's signature
+impl ToMusicXml for Progression {
+    fn to_score(&self) -> Score {
+        let chords: Vec<Vec<SpelledPitch>> = self
+            .chords()
+            .iter()
+            .map(|chord| chord.spelled_notes())
+            .collect();
+        let mut score = Score::new()
+            .with_title(self.to_string())
+            .with_key(KeySignature::for_scale(&self.key))
+            .with_clef(Clef::for_notes(&chords.concat()));
+        for chord in &chords {
+            score.push_notes(chord, NoteValue::Half);
+        }
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text of every `<tag>` element in order
+    fn elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+        let open = format!("<{}>", tag);
+        let close = format!("</{}>", tag);
+        xml.split(open.as_str())
+            .skip(1)
+            .filter_map(|rest| rest.split(close.as_str()).next())
+            .collect()
+    }
+
+    #[test]
+    fn test_scale_score() {
+        let scale: Scale = "F major".parse().unwrap();
+        let xml = scale.to_score().to_musicxml();
+        assert!(xml.starts_with("<?xml version=\"1.0\""));
+        assert!(xml.contains("<score-partwise version=\"4.0\">"));
+        assert!(xml.contains("<work-title>F4 Major</work-title>"));
+        assert_eq!(elements(&xml, "fifths"), ["-1"]);
+        assert_eq!(elements(&xml, "sign"), ["G"]);
+        assert_eq!(
+            elements(&xml, "step"),
+            ["F", "G", "A", "B", "C", "D", "E", "F"]
+        );
+        // Bb, not A#
+        assert_eq!(elements(&xml, "alter"), ["-1"]);
+        assert_eq!(elements(&xml, "octave")[7], "5");
+        assert_eq!(xml.matches("<measure ").count(), 2);
+        assert!(xml.trim_end().ends_with("</score-partwise>"));
+    }
+
+    #[test]
+    fn test_melody_ties_and_rests() {
+        // Bars aren't checked when the time signature is added afterwards
+        let melody = "C4:h D4:h E4:e3 F#4:e3 G4:e3"
+            .parse::<Melody>()
+            .unwrap()
+            .with_time_signature("3/4".parse().unwrap());
+        let score = melody.to_score();
+        assert_eq!(score.time_signature.to_string(), "3/4");
+        let measures = score.measures();
+        assert_eq!(measures.len(), 2);
+        // D4 crosses the first barline and is tied over it
+        assert_eq!(measures[0][1].value, NoteValue::Quarter);
+        assert!(measures[0][1].tied_to_next);
+        assert!(measures[1][0].tied_from_previous);
+        // The last bar is filled up with a rest
+        assert_eq!(measures[1].last().unwrap().notes, []);
+        assert_eq!(measures[1].last().unwrap().value, NoteValue::Quarter);
+
+        let xml = score.to_musicxml();
+        assert_eq!(xml.matches("<tie type=\"start\"/>").count(), 1);
+        assert_eq!(xml.matches("<actual-notes>3</actual-notes>").count(), 3);
+        assert_eq!(elements(&xml, "beats"), ["3"]);
+    }
+
+    #[test]
+    fn test_progression_score() {
+        let key: Scale = "D major".parse().unwrap();
+        let progression = Progression::parse(key, "ii-V-I").unwrap();
+        let xml = progression.to_score().to_musicxml();
+        assert_eq!(elements(&xml, "fifths"), ["2"]);
+        assert_eq!(xml.matches("<chord/>").count(), 6);
+        // and the second bar is filled up with a half rest
+        assert_eq!(xml.matches("<type>half</type>").count(), 10);
+        // E minor, A major, D major: the C# and F# come out sharp
+        assert_eq!(elements(&xml, "alter"), ["1", "1"]);
+        assert_eq!(xml.matches("<measure ").count(), 2);
+        assert!(escape("Tom & Jerry <3").contains("&amp;"));
+    }
+}
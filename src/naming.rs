@@ -0,0 +1,553 @@
+use crate::notes::{Note, NoteWithOctave, helmholtz, is_helmholtz, split_helmholtz};
+use crate::spelling::{Accidental, NoteName, SpelledNote, SpelledPitch};
+use std::fmt;
+use std::str::FromStr;
+
+/// Fixed-do syllables for the twelve pitch classes from C, sharps for the black keys
+const FIXED_DO: [&str; 12] = [
+    "Do", "Do#", "Re", "Re#", "Mi", "Fa", "Fa#", "Sol", "Sol#", "La", "La#", "Si",
+];
+
+/// Chromatic movable-do syllables for each semitone above the tonic, raised on the way up
+const MOVABLE_DO: [&str; 12] = [
+    "Do", "Di", "Re", "Ri", "Mi", "Fa", "Fi", "Sol", "Si", "La", "Li", "Ti",
+];
+
+/// German names, where H is B natural and B is B flat, with -is for sharps
+const GERMAN: [&str; 12] = [
+    "C", "Cis", "D", "Dis", "E", "F", "Fis", "G", "Gis", "A", "B", "H",
+];
+
+/// Lowered movable-do syllables that are also accepted as answers, e.g. "Te" for a flat 7th
+const MOVABLE_DO_LOWERED: [(&str, i32); 6] = [
+    ("ra", 1),
+    ("me", 3),
+    ("se", 6),
+    ("le", 8),
+    ("te", 10),
+    ("so", 7),
+];
+
+/// How notes are shown and typed: English or German letter names, or solfège syllables
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum NamingScheme {
+    /// C, D, E... with # and b
+    #[default]
+    Letters,
+    /// C, Cis, Des... with H for B natural and B for B flat
+    German,
+    /// Do is always C, as taught in Latin-based systems; the Latin note names
+    FixedDo,
+    /// Do is the tonic of the current key, with Di, Ri... and Ra, Me... for chromatic notes
+    MovableDo,
+}
+
+impl fmt::Display for NamingScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NamingScheme::Letters => "letters",
+            NamingScheme::German => "german",
+            NamingScheme::FixedDo => "fixed-do",
+            NamingScheme::MovableDo => "movable-do",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for NamingScheme {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "letters" | "letter" | "english" => Ok(NamingScheme::Letters),
+            "german" | "de" => Ok(NamingScheme::German),
+            "fixed-do" | "fixed" | "latin" => Ok(NamingScheme::FixedDo),
+            "movable-do" | "movable" | "moveable-do" => Ok(NamingScheme::MovableDo),
+            _ => Err(format!("Invalid naming scheme: {}", s)),
+        }
+    }
+}
+
+impl NamingScheme {
+    /// Name of a pitch class, with movable do counted from `tonic`
+    pub fn name(&self, note: &Note, tonic: &Note) -> String {
+        match self {
+            NamingScheme::Letters => note.to_string(),
+            NamingScheme::German => GERMAN[note.to_semitone() as usize].to_string(),
+            NamingScheme::FixedDo => FIXED_DO[note.to_semitone() as usize].to_string(),
+            NamingScheme::MovableDo => {
+                let degree = (note.to_semitone() - tonic.to_semitone()).rem_euclid(12);
+                MOVABLE_DO[degree as usize].to_string()
+            }
+        }
+    }
+
+    /// Name of a note followed by its octave, e.g. "Sol4"
+    pub fn name_pitch(&self, pitch: &NoteWithOctave, tonic: &Note) -> String {
+        format!("{}{}", self.name(&pitch.note, tonic), pitch.octave)
+    }
+
+    /// Names of a few notes to show in prompts, e.g. "Mi, Fa#"
+    pub fn examples(&self, tonic: &Note) -> String {
+        let (first, second) = match self {
+            NamingScheme::MovableDo => (above(tonic, 4), above(tonic, 6)),
+            _ => (Note::E, Note::FSharp),
+        };
+        format!(
+            "{}, {}",
+            self.name(&first, tonic),
+            self.name(&second, tonic)
+        )
+    }
+
+    /// Read a pitch class typed in this scheme, in any case. English letter names are
+    /// understood too, except that a German B is B flat.
+    pub fn parse_note(&self, s: &str, tonic: &Note) -> Result<Note, String> {
+        if *self == NamingScheme::German
+            && let Some(note) = parse_german(s)
+        {
+            return Ok(note);
+        }
+        if let Ok(note) = s.parse::<Note>() {
+            return Ok(note);
+        }
+        let invalid = || format!("Invalid note: {}", s);
+        let syllable = s.to_lowercase();
+        match self {
+            NamingScheme::Letters | NamingScheme::German => Err(invalid()),
+            NamingScheme::FixedDo => spell_fixed_do(&syllable)
+                .map(|note| note.pitch())
+                .ok_or_else(invalid),
+            NamingScheme::MovableDo => {
+                let degree = MOVABLE_DO
+                    .iter()
+                    .position(|movable| movable.to_lowercase() == syllable)
+                    .map(|degree| degree as i32)
+                    .or_else(|| {
+                        MOVABLE_DO_LOWERED
+                            .iter()
+                            .find(|(lowered, _)| *lowered == syllable)
+                            .map(|(_, degree)| *degree)
+                    })
+                    .ok_or_else(invalid)?;
+                Ok(above(tonic, degree))
+            }
+        }
+    }
+
+    /// Read a pitch class typed in this scheme as the letter name it stands for, e.g.
+    /// German "Es" as Eb rather than D#. Movable-do syllables are spelled with sharps.
+    pub fn parse_spelled_note(&self, s: &str, tonic: &Note) -> Result<SpelledNote, String> {
+        let spelled = match self {
+            NamingScheme::German => spell_german(s),
+            NamingScheme::FixedDo => spell_fixed_do(&s.to_lowercase()),
+            _ => None,
+        };
+        match spelled.or_else(|| s.parse().ok()) {
+            Some(note) => Ok(note),
+            None => self
+                .parse_note(s, tonic)
+                .map(|note| SpelledNote::from_note(&note)),
+        }
+    }
+
+    /// Read a note with an optional octave like "Sol4", defaulting to octave 4. The octave
+    /// belongs to the written note, so "Dob4" (Cb4) sounds as B3, as in `SpelledPitch`.
+    pub fn parse_pitch(&self, s: &str, tonic: &Note) -> Result<NoteWithOctave, String> {
+        let (note_str, octave_str) = match s.find(|c: char| c.is_ascii_digit()) {
+            Some(pos) => (&s[..pos], &s[pos..]),
+            None => (s, "4"),
+        };
+        let note = self.parse_spelled_note(note_str, tonic)?;
+        let octave = octave_str.parse::<u8>().map_err(|e| e.to_string())?;
+        SpelledPitch { note, octave }
+            .pitch()
+            .ok_or_else(|| format!("{} is out of range", s))
+    }
+}
+
+/// How octaves are written
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum PitchNotation {
+    /// A number after the note, with middle C as C4
+    #[default]
+    Scientific,
+    /// Lowercase from the octave below middle C, with primes above and commas below it,
+    /// so middle C is c' and C2 is C
+    Helmholtz,
+}
+
+impl fmt::Display for PitchNotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PitchNotation::Scientific => "scientific",
+            PitchNotation::Helmholtz => "helmholtz",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for PitchNotation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "scientific" | "spn" => Ok(PitchNotation::Scientific),
+            "helmholtz" => Ok(PitchNotation::Helmholtz),
+            _ => Err(format!("Invalid pitch notation: {}", s)),
+        }
+    }
+}
+
+/// Names of notes and how their octaves are written, for showing and reading answers
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct NoteNaming {
+    pub scheme: NamingScheme,
+    pub notation: PitchNotation,
+}
+
+impl From<NamingScheme> for NoteNaming {
+    fn from(scheme: NamingScheme) -> Self {
+        Self {
+            scheme,
+            notation: PitchNotation::default(),
+        }
+    }
+}
+
+impl NoteNaming {
+    pub fn with_notation(mut self, notation: PitchNotation) -> Self {
+        self.notation = notation;
+        self
+    }
+
+    /// Name of a pitch class, with movable do counted from `tonic`
+    pub fn name(&self, note: &Note, tonic: &Note) -> String {
+        self.scheme.name(note, tonic)
+    }
+
+    /// Name of a note with its octave, e.g. "Sol4", or "sol'" in Helmholtz notation
+    pub fn name_pitch(&self, pitch: &NoteWithOctave, tonic: &Note) -> String {
+        match self.notation {
+            PitchNotation::Scientific => self.scheme.name_pitch(pitch, tonic),
+            PitchNotation::Helmholtz => helmholtz(&self.name(&pitch.note, tonic), pitch.octave),
+        }
+    }
+
+    /// Names of a few notes to show in prompts, e.g. "Mi, Fa#"
+    pub fn examples(&self, tonic: &Note) -> String {
+        self.scheme.examples(tonic)
+    }
+
+    /// Whether `s` says which octave the note is in, rather than naming a pitch class
+    pub fn has_octave(&self, s: &str) -> bool {
+        self.notation == PitchNotation::Helmholtz
+            || s.contains(|c: char| c.is_ascii_digit())
+            || self.is_helmholtz(s)
+    }
+
+    /// Read a pitch class typed in this scheme
+    pub fn parse_note(&self, s: &str, tonic: &Note) -> Result<Note, String> {
+        self.scheme.parse_note(s, tonic)
+    }
+
+    /// Read a note with its octave. Helmholtz notes are understood in either notation
+    /// when they are marked with primes or commas.
+    pub fn parse_pitch(&self, s: &str, tonic: &Note) -> Result<NoteWithOctave, String> {
+        self.parse_spelled_pitch(s, tonic)?
+            .pitch()
+            .ok_or_else(|| format!("{} is out of range", s))
+    }
+
+    /// Read a note with its octave as the letter name it stands for, so German "Es4" is
+    /// Eb4 and "H4" is B4. Without an octave the note is in octave 4.
+    pub fn parse_spelled_pitch(&self, s: &str, tonic: &Note) -> Result<SpelledPitch, String> {
+        let (name, octave) = if self.notation == PitchNotation::Helmholtz || self.is_helmholtz(s) {
+            split_helmholtz(s)?
+        } else {
+            match s.find(|c: char| c.is_ascii_digit()) {
+                Some(pos) => {
+                    let octave = s[pos..].parse::<u8>().map_err(|e| e.to_string())?;
+                    (s[..pos].to_string(), octave)
+                }
+                None => (s.to_string(), 4),
+            }
+        };
+        let note = self.scheme.parse_spelled_note(&name, tonic)?;
+        Ok(SpelledPitch { note, octave })
+    }
+
+    /// Name of a spelled note with its octave. Letter names keep their spelling, e.g.
+    /// "Bb4" rather than "A#4"; other schemes name the note that sounds.
+    pub fn name_spelled_pitch(&self, pitch: &SpelledPitch, tonic: &Note) -> String {
+        match (self.scheme, pitch.pitch()) {
+            (NamingScheme::Letters, _) | (_, None) => match self.notation {
+                PitchNotation::Scientific => pitch.to_string(),
+                PitchNotation::Helmholtz => helmholtz(&pitch.note.to_string(), pitch.octave),
+            },
+            (_, Some(sounding)) => self.name_pitch(&sounding, tonic),
+        }
+    }
+
+    /// A scale or chord with the note it starts with, and a bass note after "/", in
+    /// letter names, e.g. German "H minor" is "B minor" and "Fism7/Cis" is "F#m7/C#"
+    pub fn to_letters(&self, s: &str, tonic: &Note) -> String {
+        if self.scheme == NamingScheme::Letters {
+            return s.to_string();
+        }
+        let s = s.trim();
+        let (body, bass) = match s.split_once('/') {
+            Some((body, bass)) => (body, Some(bass)),
+            None => (s, None),
+        };
+        // The longest name that reads as a note, so "Fis" rather than "F" in "Fism7"
+        let root = (1..=body.len())
+            .rev()
+            .filter(|&end| body.is_char_boundary(end) && !body[..end].contains(char::is_whitespace))
+            .find_map(|end| {
+                let note = self.scheme.parse_spelled_note(&body[..end], tonic).ok()?;
+                Some(format!("{}{}", note, &body[end..]))
+            });
+        let mut letters = root.unwrap_or_else(|| body.to_string());
+        if let Some(bass) = bass {
+            let bass = match self.scheme.parse_spelled_note(bass.trim(), tonic) {
+                Ok(note) => note.to_string(),
+                Err(_) => bass.to_string(),
+            };
+            letters = format!("{}/{}", letters, bass);
+        }
+        letters
+    }
+
+    /// Syllables and German names are often typed in lowercase, so only letter names
+    /// can be told apart from Helmholtz notation without primes or commas
+    fn is_helmholtz(&self, s: &str) -> bool {
+        match self.scheme {
+            NamingScheme::Letters => is_helmholtz(s),
+            _ => s.contains(['\'', ',', '’', '′']),
+        }
+    }
+}
+
+/// A German note name like "Fis", "Es", "B" or "H"
+fn parse_german(s: &str) -> Option<Note> {
+    spell_german(s).map(|note| note.pitch())
+}
+
+/// The letter name a German note name stands for, e.g. Eb for "Es" and B for "H"
+fn spell_german(s: &str) -> Option<SpelledNote> {
+    let name = s.to_lowercase();
+    // Vowels take a bare -s for flat, and B is already H flat
+    let (letter, shift) = match name.as_str() {
+        "b" => (NoteName::B, -1),
+        "es" => (NoteName::E, -1),
+        "as" => (NoteName::A, -1),
+        "eses" => (NoteName::E, -2),
+        "ases" => (NoteName::A, -2),
+        "heses" => (NoteName::B, -2),
+        _ => {
+            let mut chars = name.chars();
+            let letter = match chars.next()? {
+                'c' => NoteName::C,
+                'd' => NoteName::D,
+                'e' => NoteName::E,
+                'f' => NoteName::F,
+                'g' => NoteName::G,
+                'a' => NoteName::A,
+                'h' => NoteName::B,
+                _ => return None,
+            };
+            let shift = match chars.as_str() {
+                "" => 0,
+                "is" => 1,
+                "isis" => 2,
+                "es" => -1,
+                "eses" => -2,
+                _ => return None,
+            };
+            (letter, shift)
+        }
+    };
+    Some(SpelledNote::new(letter, Accidental::from_semitones(shift)?))
+}
+
+/// The letter name a lowercase fixed-do syllable stands for, e.g. Eb for "mib"
+fn spell_fixed_do(syllable: &str) -> Option<SpelledNote> {
+    let (name, accidental) = match syllable.strip_suffix('#') {
+        Some(name) => (name, Accidental::Sharp),
+        None => match syllable.strip_suffix('b') {
+            Some(name) => (name, Accidental::Flat),
+            None => (syllable, Accidental::Natural),
+        },
+    };
+    let letter = match name {
+        "do" => NoteName::C,
+        "re" => NoteName::D,
+        "mi" => NoteName::E,
+        "fa" => NoteName::F,
+        "sol" | "so" => NoteName::G,
+        "la" => NoteName::A,
+        "si" | "ti" => NoteName::B,
+        _ => return None,
+    };
+    Some(SpelledNote::new(letter, accidental))
+}
+
+/// The pitch class `semitones` above `note`
+fn above(note: &Note, semitones: i32) -> Note {
+    Note::from_semitone((note.to_semitone() + semitones).rem_euclid(12) as u8)
+        .expect("semitone is within 0-11")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scheme() {
+        assert_eq!("fixed-do".parse(), Ok(NamingScheme::FixedDo));
+        assert_eq!("Movable".parse(), Ok(NamingScheme::MovableDo));
+        assert!("numbers".parse::<NamingScheme>().is_err());
+        assert_eq!(NamingScheme::MovableDo.to_string(), "movable-do");
+    }
+
+    #[test]
+    fn test_fixed_do() {
+        let fixed = NamingScheme::FixedDo;
+        assert_eq!(fixed.name(&Note::G, &Note::D), "Sol");
+        assert_eq!(fixed.name(&Note::FSharp, &Note::D), "Fa#");
+        assert_eq!(fixed.examples(&Note::D), "Mi, Fa#");
+        assert_eq!(fixed.parse_note("sol", &Note::D), Ok(Note::G));
+        assert_eq!(fixed.parse_note("Si", &Note::D), Ok(Note::B));
+        assert_eq!(fixed.parse_note("Mib", &Note::D), Ok(Note::DSharp));
+        assert_eq!(fixed.parse_note("Dob", &Note::D), Ok(Note::B));
+        assert_eq!(fixed.parse_note("F#", &Note::D), Ok(Note::FSharp));
+        assert!(fixed.parse_note("Di", &Note::D).is_err());
+
+        // The octave goes with the written note, so Dob4 is Cb4, a semitone below C4
+        assert_eq!(fixed.parse_pitch("Dob4", &Note::C), "B3".parse());
+        assert_eq!(fixed.parse_pitch("Si#3", &Note::C), "C4".parse());
+        let naming = NoteNaming::from(fixed);
+        assert_eq!(naming.parse_pitch("Dob4", &Note::C), "B3".parse());
+        assert_eq!(naming.parse_pitch("dob'", &Note::C), "B3".parse());
+
+        let pitch = fixed.parse_pitch("La3", &Note::C).unwrap();
+        assert_eq!(pitch, "A3".parse().unwrap());
+        assert_eq!(fixed.name_pitch(&pitch, &Note::C), "La3");
+    }
+
+    #[test]
+    fn test_movable_do() {
+        let movable = NamingScheme::MovableDo;
+        assert_eq!(movable.name(&Note::D, &Note::D), "Do");
+        assert_eq!(movable.name(&Note::FSharp, &Note::D), "Mi");
+        assert_eq!(movable.name(&Note::C, &Note::D), "Li");
+        assert_eq!(movable.name(&Note::CSharp, &Note::D), "Ti");
+        assert_eq!(movable.examples(&Note::G), "Mi, Fi");
+        assert_eq!(movable.parse_note("mi", &Note::D), Ok(Note::FSharp));
+        assert_eq!(movable.parse_note("Te", &Note::D), Ok(Note::C));
+        assert_eq!(movable.parse_note("Si", &Note::C), Ok(Note::GSharp));
+        assert_eq!(movable.parse_note("So", &Note::F), Ok(Note::C));
+        assert_eq!(movable.parse_note("Bb", &Note::F), Ok(Note::ASharp));
+        assert!(movable.parse_note("Mi#", &Note::F).is_err());
+
+        for semitone in 0..12 {
+            let note = Note::from_semitone(semitone).unwrap();
+            let name = movable.name(&note, &Note::A);
+            assert_eq!(movable.parse_note(&name, &Note::A), Ok(note));
+            let name = NamingScheme::FixedDo.name(&note, &Note::A);
+            assert_eq!(NamingScheme::FixedDo.parse_note(&name, &Note::A), Ok(note));
+        }
+    }
+
+    #[test]
+    fn test_german() {
+        let german = NamingScheme::German;
+        assert_eq!("Latin".parse(), Ok(NamingScheme::FixedDo));
+        assert_eq!(german.name(&Note::B, &Note::C), "H");
+        assert_eq!(german.name(&Note::ASharp, &Note::C), "B");
+        assert_eq!(german.name_pitch(&"C#5".parse().unwrap(), &Note::C), "Cis5");
+        assert_eq!(german.examples(&Note::C), "E, Fis");
+        assert_eq!(german.parse_note("H", &Note::C), Ok(Note::B));
+        assert_eq!(german.parse_note("b", &Note::C), Ok(Note::ASharp));
+        assert_eq!(german.parse_note("Es", &Note::C), Ok(Note::DSharp));
+        assert_eq!(german.parse_note("as", &Note::C), Ok(Note::GSharp));
+        assert_eq!(german.parse_note("Des", &Note::C), Ok(Note::CSharp));
+        assert_eq!(german.parse_note("Ces", &Note::C), Ok(Note::B));
+        assert_eq!(german.parse_note("Fisis", &Note::C), Ok(Note::G));
+        assert_eq!(german.parse_note("F#", &Note::C), Ok(Note::FSharp));
+        assert_eq!(german.parse_pitch("H3", &Note::C), "B3".parse());
+        assert!(german.parse_note("Xis", &Note::C).is_err());
+        assert!(NamingScheme::Letters.parse_note("H", &Note::C).is_err());
+
+        // Spelled as the letter names they stand for, so H4 is B4 and B4 is Bb4
+        let naming = NoteNaming::from(german);
+        let spelled = |s: &str| naming.parse_spelled_pitch(s, &Note::C).unwrap().to_string();
+        assert_eq!(spelled("H4"), "B4");
+        assert_eq!(spelled("B4"), "Bb4");
+        assert_eq!(spelled("Es3"), "Eb3");
+        assert_eq!(spelled("Fis,"), "F#1");
+        assert_eq!(spelled("Ces"), "Cb4");
+        let h4 = naming.parse_spelled_pitch("H4", &Note::C).unwrap();
+        assert_eq!(naming.name_spelled_pitch(&h4, &Note::C), "H4");
+        assert_eq!(h4.pitch().and_then(|pitch| pitch.to_midi()), Some(71));
+
+        assert_eq!(naming.to_letters("H minor", &Note::C), "B minor");
+        assert_eq!(naming.to_letters("B major", &Note::C), "Bb major");
+        assert_eq!(naming.to_letters("Fis3 dorian", &Note::C), "F#3 dorian");
+        assert_eq!(naming.to_letters("Hm7/Fis", &Note::C), "Bm7/F#");
+        assert_eq!(naming.to_letters("Es7", &Note::C), "Eb7");
+        assert_eq!(naming.to_letters("C6/9", &Note::C), "C6/9");
+
+        for semitone in 0..12 {
+            let note = Note::from_semitone(semitone).unwrap();
+            assert_eq!(
+                german.parse_note(&german.name(&note, &Note::C), &Note::C),
+                Ok(note)
+            );
+        }
+    }
+
+    #[test]
+    fn test_helmholtz_naming() {
+        assert_eq!("Helmholtz".parse(), Ok(PitchNotation::Helmholtz));
+        let naming = NoteNaming::default().with_notation(PitchNotation::Helmholtz);
+        let c4: NoteWithOctave = "C4".parse().unwrap();
+        assert_eq!(naming.name_pitch(&c4, &Note::C), "c'");
+        assert_eq!(naming.parse_pitch("c''", &Note::C), "C5".parse());
+        assert_eq!(naming.parse_pitch("G", &Note::C), "G2".parse());
+        assert!(naming.has_octave("g"));
+
+        let german = NoteNaming::from(NamingScheme::German).with_notation(PitchNotation::Helmholtz);
+        assert_eq!(german.name_pitch(&"A#2".parse().unwrap(), &Note::C), "B");
+        assert_eq!(german.name_pitch(&"B4".parse().unwrap(), &Note::C), "h'");
+        assert_eq!(german.parse_pitch("fis'", &Note::C), "F#4".parse());
+
+        let solfege = NoteNaming::from(NamingScheme::FixedDo);
+        assert!(!solfege.has_octave("sol"));
+        assert!(solfege.has_octave("sol'"));
+        assert_eq!(solfege.parse_pitch("sol'", &Note::C), "G4".parse());
+        assert_eq!(solfege.parse_pitch("sol", &Note::C), "G4".parse());
+        assert_eq!(solfege.name_pitch(&c4, &Note::C), "Do4");
+
+        let letters = NoteNaming::default();
+        assert_eq!(letters.parse_pitch("e", &Note::C), "E3".parse());
+        assert_eq!(letters.parse_pitch("E", &Note::C), "E4".parse());
+        assert!(letters.has_octave("e"));
+        assert!(!letters.has_octave("E"));
+    }
+
+    #[test]
+    fn test_letters() {
+        let letters = NamingScheme::Letters;
+        assert_eq!(letters.name(&Note::ASharp, &Note::F), "A#");
+        assert_eq!(letters.parse_pitch("Bb2", &Note::F), "A#2".parse());
+        assert!(letters.parse_note("Do", &Note::C).is_err());
+
+        let naming = NoteNaming::from(letters);
+        let bb = naming.parse_spelled_pitch("Bb2", &Note::F).unwrap();
+        assert_eq!(naming.name_spelled_pitch(&bb, &Note::F), "Bb2");
+        assert_eq!(naming.to_letters("Hm7", &Note::C), "Hm7");
+        let fixed = NoteNaming::from(NamingScheme::FixedDo);
+        assert_eq!(fixed.to_letters("Solm7", &Note::C), "Gm7");
+        assert_eq!(fixed.to_letters("Sib major", &Note::C), "Bb major");
+    }
+}
@@ -1,9 +1,32 @@
-use rodio::{OutputStream, Sink, Source};
+#[cfg(feature = "playback")]
+use crate::audio::{AudioEngine, Playable, PlaybackOptions};
+use crate::chords::{Chord, ChordQuality};
+use crate::edo::Edo;
+use crate::intervals::Interval;
+use crate::keys::KeySignature;
+#[cfg(feature = "playback")]
+use crate::melody::Melody;
+use crate::scala::ScalaTuning;
+use crate::spelling::{NoteName, SpelledNote, SpelledPitch, letter_steps, transposition_fifths};
+#[cfg(feature = "playback")]
+use crate::tempo::{NoteValue, Tempo};
 use std::fmt;
+use std::ops::{Add, Sub};
 use std::str::FromStr;
+use std::sync::Arc;
+#[cfg(feature = "playback")]
 use std::time::Duration;
 
-#[derive(Debug, PartialEq)]
+/// Highest octave considered in range for conversions
+pub const MAX_OCTAVE: u8 = 10;
+
+/// Most octaves a scale can be played over
+pub const MAX_SCALE_OCTAVES: u8 = 4;
+
+/// Note names in pitch order within an octave, so `Note::C < Note::B`
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Note {
     C,
     CSharp,
@@ -19,12 +42,26 @@ pub enum Note {
     B,
 }
 
-#[derive(Debug, PartialEq)]
+/// A note in a specific octave, ordered by pitch (B3 < C4)
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NoteWithOctave {
     pub note: Note,
     pub octave: u8,
 }
 
+impl Ord for NoteWithOctave {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.absolute_semitone().cmp(&other.absolute_semitone())
+    }
+}
+
+impl PartialOrd for NoteWithOctave {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl fmt::Display for Note {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let note_str = match self {
@@ -83,9 +120,16 @@ impl FromStr for Note {
     }
 }
 
+/// Parse scientific pitch notation like "A#2", defaulting to octave 4, or Helmholtz
+/// notation like "c'" or "C," when the note is lowercase or marked with primes or commas
 impl FromStr for NoteWithOctave {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if is_helmholtz(s) {
+            let (name, octave) = split_helmholtz(s)?;
+            let note = name.parse::<Note>()?;
+            return Ok(NoteWithOctave { note, octave });
+        }
         let (note_str, octave_str) = match s.find(|c: char| c.is_ascii_digit()) {
             Some(pos) => (&s[..pos], &s[pos..]),
             None => (s, "4"),
@@ -93,147 +137,501 @@ impl FromStr for NoteWithOctave {
 
         let note = note_str.parse::<Note>()?;
         let octave = octave_str.parse::<u8>().map_err(|e| e.to_string())?;
+        if octave > MAX_OCTAVE {
+            return Err(format!(
+                "Octave {} is out of range (0-{})",
+                octave, MAX_OCTAVE
+            ));
+        }
 
         Ok(NoteWithOctave { note, octave })
     }
 }
 
+/// Octave of the unmarked lowercase notes in Helmholtz notation, c to b below middle C
+const HELMHOLTZ_SMALL_OCTAVE: u8 = 3;
+
+/// Whether `s` is clearly written in Helmholtz notation: marked with primes or commas,
+/// or starting with a lowercase letter
+pub fn is_helmholtz(s: &str) -> bool {
+    s.contains(['\'', ',', '’', '′']) || s.starts_with(|c: char| c.is_ascii_lowercase())
+}
+
+/// Split Helmholtz notation into the note name, capitalised, and its octave in scientific
+/// pitch notation: "C," is C1, "C" is C2, "c" is C3, "c'" is C4 (middle C) and "c''" is C5
+pub fn split_helmholtz(s: &str) -> Result<(String, u8), String> {
+    let invalid = || format!("Invalid Helmholtz note: {}", s);
+    let name = s.trim_end_matches(['\'', ',', '’', '′']);
+    let marks = &s[name.len()..];
+    let primes = marks.chars().filter(|c| *c != ',').count() as u8;
+    let commas = marks.chars().filter(|c| *c == ',').count() as u8;
+    let mut chars = name.chars();
+    let first = chars.next().ok_or_else(invalid)?;
+    let octave = if first.is_lowercase() && commas == 0 {
+        HELMHOLTZ_SMALL_OCTAVE + primes
+    } else if first.is_uppercase() && primes == 0 {
+        (HELMHOLTZ_SMALL_OCTAVE - 1)
+            .checked_sub(commas)
+            .ok_or_else(invalid)?
+    } else {
+        return Err(invalid());
+    };
+    if octave > MAX_OCTAVE {
+        return Err(invalid());
+    }
+    Ok((first.to_uppercase().chain(chars).collect(), octave))
+}
+
+/// Write a note name in Helmholtz notation for `octave`, e.g. "F#" in octave 5 is "f#''"
+pub fn helmholtz(name: &str, octave: u8) -> String {
+    if octave >= HELMHOLTZ_SMALL_OCTAVE {
+        let primes = "'".repeat((octave - HELMHOLTZ_SMALL_OCTAVE) as usize);
+        format!("{}{}", name.to_lowercase(), primes)
+    } else {
+        let commas = ",".repeat((HELMHOLTZ_SMALL_OCTAVE - 1 - octave) as usize);
+        format!("{}{}", name, commas)
+    }
+}
+
+/// Something a note can be moved by: an `Interval`, or a number of semitones
+pub trait PitchShift: Copy + fmt::Display {
+    /// Semitones upwards
+    fn semitones_up(self) -> i32;
+}
+
+impl PitchShift for Interval {
+    fn semitones_up(self) -> i32 {
+        self.semitones() as i32
+    }
+}
+
+impl PitchShift for i32 {
+    fn semitones_up(self) -> i32 {
+        self
+    }
+}
+
+/// The note `shift` higher, e.g. `c4 + Interval::from_semitones(7)` or `c4 + 7`.
+/// Panics if it would leave octaves 0 to `MAX_OCTAVE`; see `checked_add`.
+impl<T: PitchShift> Add<T> for NoteWithOctave {
+    type Output = NoteWithOctave;
+    fn add(self, shift: T) -> NoteWithOctave {
+        self.checked_add(shift)
+            .unwrap_or_else(|| panic!("{} + {} is out of range", self, shift))
+    }
+}
+
+/// The note `shift` lower. Panics if it would leave octaves 0 to `MAX_OCTAVE`;
+/// see `checked_sub`.
+impl<T: PitchShift> Sub<T> for NoteWithOctave {
+    type Output = NoteWithOctave;
+    fn sub(self, shift: T) -> NoteWithOctave {
+        self.checked_sub(shift)
+            .unwrap_or_else(|| panic!("{} - {} is out of range", self, shift))
+    }
+}
+
 impl NoteWithOctave {
-    /// Convenience method to get frequency directly from NoteWithOctave
+    /// Lowest note in range, C0
+    pub const MIN: NoteWithOctave = NoteWithOctave {
+        note: Note::C,
+        octave: 0,
+    };
+
+    /// Highest note in range, B in octave `MAX_OCTAVE`
+    pub const MAX: NoteWithOctave = NoteWithOctave {
+        note: Note::B,
+        octave: MAX_OCTAVE,
+    };
+
+    /// The note `shift` higher, or `None` if it would leave octaves 0 to `MAX_OCTAVE`
+    pub fn checked_add(self, shift: impl PitchShift) -> Option<NoteWithOctave> {
+        NoteWithOctave::from_absolute_semitone(self.absolute_semitone() + shift.semitones_up())
+    }
+
+    /// The note `shift` lower, or `None` if it would leave octaves 0 to `MAX_OCTAVE`
+    pub fn checked_sub(self, shift: impl PitchShift) -> Option<NoteWithOctave> {
+        NoteWithOctave::from_absolute_semitone(self.absolute_semitone() - shift.semitones_up())
+    }
+
+    /// The note `shift` higher, stopping at `NoteWithOctave::MAX` (or `MIN` going down)
+    pub fn saturating_add(self, shift: impl PitchShift) -> NoteWithOctave {
+        self.saturated(self.absolute_semitone() + shift.semitones_up())
+    }
+
+    /// The note `shift` lower, stopping at `NoteWithOctave::MIN` (or `MAX` going up)
+    pub fn saturating_sub(self, shift: impl PitchShift) -> NoteWithOctave {
+        self.saturated(self.absolute_semitone() - shift.semitones_up())
+    }
+
+    fn saturated(self, semitone: i32) -> NoteWithOctave {
+        let highest = NoteWithOctave::MAX.absolute_semitone();
+        NoteWithOctave::from_absolute_semitone(semitone.clamp(0, highest))
+            .unwrap_or(NoteWithOctave::MAX)
+    }
+
+    /// The note in Helmholtz notation, e.g. "c'" for C4 and "A," for A1
+    pub fn to_helmholtz(&self) -> String {
+        helmholtz(&self.note.to_string(), self.octave)
+    }
+
+    /// Semitones above C0, used for pitch arithmetic across octaves
+    pub(crate) fn absolute_semitone(&self) -> i32 {
+        self.octave as i32 * 12 + self.note.to_semitone()
+    }
+
+    /// Inverse of `absolute_semitone`, or `None` outside octaves 0 to `MAX_OCTAVE`
+    pub(crate) fn from_absolute_semitone(semitone: i32) -> Option<NoteWithOctave> {
+        let octave = semitone.div_euclid(12);
+        if semitone < 0 || octave > MAX_OCTAVE as i32 {
+            return None;
+        }
+        Some(NoteWithOctave {
+            note: Note::from_semitone(semitone.rem_euclid(12) as u8)?,
+            octave: octave as u8,
+        })
+    }
+
+    /// Move the note by a number of semitones (negative is down), carrying into
+    /// neighbouring octaves. Fails if the result leaves octaves 0 to `MAX_OCTAVE`.
+    pub fn transpose(&self, semitones: i32) -> Result<NoteWithOctave, String> {
+        self.checked_add(semitones).ok_or_else(|| {
+            format!(
+                "Transposing {} by {} semitones is out of range (octaves 0-{})",
+                self, semitones, MAX_OCTAVE
+            )
+        })
+    }
+
+    /// Standard MIDI note number (C4 = 60, A4 = 69), or `None` above G9, the highest
+    /// note MIDI has
+    pub fn to_midi(&self) -> Option<u8> {
+        u8::try_from(self.absolute_semitone() + 12)
+            .ok()
+            .filter(|&midi| midi <= 127)
+    }
+
+    /// Note for a MIDI note number. MIDI 0-11 (octave -1) can't be represented.
+    pub fn from_midi(midi: u8) -> Result<NoteWithOctave, String> {
+        if midi > 127 {
+            return Err(format!("MIDI note {} is out of range (0-127)", midi));
+        }
+        NoteWithOctave::from_absolute_semitone(midi as i32 - 12)
+            .ok_or_else(|| format!("MIDI note {} is below octave 0", midi))
+    }
+
+    /// Convenience method to get frequency directly from NoteWithOctave (A4 = 440Hz)
     pub fn frequency(&self) -> f64 {
         self.note.to_frequency(self.octave)
     }
 
     /// Play the note as audio for the specified duration
+    #[cfg(feature = "playback")]
     pub fn play(&self, duration: Duration) -> Result<(), Box<dyn std::error::Error>> {
-        let frequency = self.frequency() as f32;
-        play_frequency(frequency, duration)
+        self.play_on(&AudioEngine::new()?, duration)
+    }
+
+    /// Play the note on an already open audio engine
+    #[cfg(feature = "playback")]
+    pub fn play_on(
+        &self,
+        engine: &AudioEngine,
+        duration: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        engine.play_note(self, duration)
     }
 
     /// Play the note as audio for 1 second (convenience method)
+    #[cfg(feature = "playback")]
     pub fn play_default(&self) -> Result<(), Box<dyn std::error::Error>> {
         self.play(Duration::from_secs(1))
     }
 }
 
-/// Generate a sine wave source at the specified frequency
-struct SineWave {
-    frequency: f32,
-    sample_rate: u32,
-    sample_index: usize,
+#[cfg(feature = "playback")]
+impl Playable for NoteWithOctave {
+    fn play(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        engine.play_note_with(self, opts.tempo.beat_duration(), opts)
+    }
 }
 
-impl SineWave {
-    fn new(frequency: f32) -> Self {
-        Self {
-            frequency,
-            sample_rate: 44100,
-            sample_index: 0,
+/// A note bent sharp or flat by some cents, for pitches between the keys
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pitch {
+    pub note: NoteWithOctave,
+    /// Offset from the note, positive when sharp; 100 cents is a semitone
+    pub cents: f64,
+}
+
+impl From<NoteWithOctave> for Pitch {
+    fn from(note: NoteWithOctave) -> Self {
+        Pitch::new(note, 0.0)
+    }
+}
+
+impl fmt::Display for Pitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cents == 0.0 {
+            write!(f, "{}", self.note)
+        } else {
+            write!(f, "{} {:+.0} cents", self.note, self.cents)
         }
     }
 }
 
-impl Iterator for SineWave {
-    type Item = f32;
+impl Pitch {
+    pub fn new(note: NoteWithOctave, cents: f64) -> Self {
+        Self { note, cents }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let sample = (self.sample_index as f32 * self.frequency * 2.0 * std::f32::consts::PI
-            / self.sample_rate as f32)
-            .sin();
-        self.sample_index = self.sample_index.wrapping_add(1);
-        Some(sample * 0.3) // Reduce volume to 30%
+    /// The nearest note to `frequency` and how far off it is, from -50 to +50 cents
+    pub fn from_frequency(frequency: f64, tuning: &Tuning) -> Result<Self, String> {
+        let note = tuning.nearest_note(frequency)?;
+        let cents = 1200.0 * (frequency / tuning.frequency_of(&note)).log2();
+        Ok(Self { note, cents })
     }
-}
 
-impl Source for SineWave {
-    fn current_frame_len(&self) -> Option<usize> {
-        None
+    pub fn frequency(&self, tuning: &Tuning) -> f64 {
+        tuning.frequency_of(&self.note) * 2.0_f64.powf(self.cents / 1200.0)
     }
 
-    fn channels(&self) -> u16 {
-        1
+    /// Whether the pitch is within `tolerance_cents` of its note
+    pub fn is_in_tune(&self, tolerance_cents: f64) -> bool {
+        self.cents.abs() <= tolerance_cents
     }
+}
 
-    fn sample_rate(&self) -> u32 {
-        self.sample_rate
+#[cfg(feature = "playback")]
+impl Playable for Pitch {
+    fn play(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        engine.play_pitch_with(self, opts.tempo.beat_duration(), opts)
     }
+}
 
-    fn total_duration(&self) -> Option<Duration> {
-        None
+/// 5-limit just ratios for each semitone above the tonic
+const JUST_RATIOS: [f64; 12] = [
+    1.0,
+    16.0 / 15.0,
+    9.0 / 8.0,
+    6.0 / 5.0,
+    5.0 / 4.0,
+    4.0 / 3.0,
+    45.0 / 32.0,
+    3.0 / 2.0,
+    8.0 / 5.0,
+    5.0 / 3.0,
+    9.0 / 5.0,
+    15.0 / 8.0,
+];
+
+/// How the notes are tuned: the twelve of the octave relative to a tonic, or every key
+/// from a Scala file
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TuningSystem {
+    /// Twelve equal semitones, as on a modern piano
+    #[default]
+    EqualTemperament,
+    /// Pure 5-limit ratios from the tonic, like 5/4 for the major third and 3/2 for the fifth
+    JustIntonation,
+    /// Pure 3/2 fifths stacked from the tonic, from Db to F#
+    Pythagorean,
+    /// Fifths narrowed by a quarter of a syntonic comma so major thirds are pure, from Eb to G#
+    QuarterCommaMeantone,
+    /// Any number of equal steps per octave, each note on the step nearest its
+    /// equal-tempered pitch above the tonic
+    Edo(Edo),
+    /// A scale and keyboard mapping loaded from Scala files. Keys the mapping leaves
+    /// silent fall back to equal temperament.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Scala(Arc<ScalaTuning>),
+}
+
+impl fmt::Display for TuningSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TuningSystem::EqualTemperament => "equal temperament",
+            TuningSystem::JustIntonation => "just intonation",
+            TuningSystem::Pythagorean => "Pythagorean tuning",
+            TuningSystem::QuarterCommaMeantone => "quarter-comma meantone",
+            TuningSystem::Edo(edo) => return write!(f, "{}", edo),
+            TuningSystem::Scala(tuning) => return write!(f, "{}", tuning),
+        };
+        write!(f, "{}", name)
     }
 }
 
-/// Play a frequency for the specified duration
-pub fn play_frequency(
-    frequency: f32,
-    duration: Duration,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Create output stream
-    let (_stream, stream_handle) = OutputStream::try_default()?;
-    let sink = Sink::try_new(&stream_handle)?;
+impl FromStr for TuningSystem {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "equal" | "et" | "12-tet" | "12tet" | "equal temperament" => {
+                Ok(TuningSystem::EqualTemperament)
+            }
+            "just" | "ji" | "just intonation" => Ok(TuningSystem::JustIntonation),
+            "pythagorean" | "pyth" => Ok(TuningSystem::Pythagorean),
+            "meantone" | "quarter-comma" | "quarter-comma meantone" => {
+                Ok(TuningSystem::QuarterCommaMeantone)
+            }
+            other => other
+                .parse::<Edo>()
+                .map(TuningSystem::Edo)
+                .map_err(|_| format!("Invalid tuning system: {}", s)),
+        }
+    }
+}
 
-    // Create sine wave source
-    let source = SineWave::new(frequency).take_duration(duration);
+impl TuningSystem {
+    /// Frequency ratio of the note `semitones` above the tonic, within one octave.
+    /// Scala tunings aren't built on a tonic, and give the equal-tempered ratio.
+    pub fn ratio(&self, semitones: u8) -> f64 {
+        let semitones = semitones % 12;
+        match self {
+            TuningSystem::EqualTemperament | TuningSystem::Scala(_) => {
+                2.0_f64.powf(semitones as f64 / 12.0)
+            }
+            TuningSystem::JustIntonation => JUST_RATIOS[semitones as usize],
+            TuningSystem::Pythagorean => stacked_fifths(1.5, semitones, -5),
+            TuningSystem::QuarterCommaMeantone => stacked_fifths(5.0_f64.powf(0.25), semitones, -3),
+            TuningSystem::Edo(edo) => edo.ratio(edo.steps_for(semitones as i32)),
+        }
+    }
+}
 
-    // Play the sound
-    sink.append(source);
-    sink.sleep_until_end();
+/// Ratio of the note `semitones` above the tonic when it's reached by stacking `fifth`s,
+/// taking the fifths from `lowest` to `lowest + 11` and folding the result into one octave
+fn stacked_fifths(fifth: f64, semitones: u8, lowest: i32) -> f64 {
+    // Each fifth climbs 7 semitones, so the note 1 semitone up is 7 fifths away
+    let fifths = (semitones as i32 * 7 - lowest).rem_euclid(12) + lowest;
+    let ratio = fifth.powi(fifths);
+    ratio / 2.0_f64.powf(ratio.log2().floor())
+}
 
-    Ok(())
+/// Reference pitch used to convert between notes and frequencies (A4 = 440Hz by default),
+/// and the tuning system the notes are tuned in
+#[derive(Debug, PartialEq, Clone)]
+pub struct Tuning {
+    a4: f64,
+    system: TuningSystem,
+    /// Note the tuning system's ratios are measured from
+    tonic: Note,
 }
 
-impl Note {
-    pub fn to_frequency(&self, octave: u8) -> f64 {
-        // A4 = 440Hz as reference
-        let semitones_from_a4 = self.semitones_from_a4(octave);
-        440.0 * 2.0_f64.powf(semitones_from_a4 as f64 / 12.0)
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning::STANDARD
     }
+}
 
-    /// Convert frequency back to closest note and octave
-    pub fn from_frequency(frequency: f64) -> Result<NoteWithOctave, String> {
-        if frequency <= 0.0 {
-            return Err("Frequency must be positive".to_string());
+impl Tuning {
+    /// International standard concert pitch, A4 = 440Hz
+    pub const STANDARD: Tuning = Tuning {
+        a4: 440.0,
+        system: TuningSystem::EqualTemperament,
+        tonic: Note::C,
+    };
+
+    /// Equal temperament with a custom A4, e.g. 432, 442 or 415 (baroque) Hz
+    pub fn new(a4: f64) -> Result<Self, String> {
+        if !a4.is_finite() || a4 <= 0.0 {
+            return Err("Reference pitch must be a positive frequency".to_string());
         }
+        Ok(Self {
+            a4,
+            ..Tuning::STANDARD
+        })
+    }
 
-        // Calculate semitones from A4
-        let semitones_from_a4 = 12.0 * (frequency / 440.0).log2();
-        let rounded_semitones = semitones_from_a4.round() as i32;
+    /// The same reference pitch with notes tuned in `system` from `tonic`
+    pub fn with_system(mut self, system: TuningSystem, tonic: Note) -> Self {
+        self.system = system;
+        self.tonic = tonic;
+        self
+    }
+
+    /// Frequency of A4 in equal temperament; other systems may move it slightly
+    pub fn a4(&self) -> f64 {
+        self.a4
+    }
 
-        // Calculate octave (A4 is octave 4)
-        let octave = 4 + (rounded_semitones / 12);
+    pub fn system(&self) -> &TuningSystem {
+        &self.system
+    }
 
-        // Handle negative octaves or very high octaves
-        if octave < 0 || octave > 10 {
-            return Err(format!(
-                "Octave {} is out of reasonable range (0-10)",
-                octave
-            ));
+    pub fn tonic(&self) -> Note {
+        self.tonic
+    }
+
+    /// The note's frequency. Outside equal temperament the tonic keeps its
+    /// equal-tempered pitch and every other note is tuned from the tonic below it.
+    pub fn frequency(&self, note: &Note, octave: u8) -> f64 {
+        let semitones_from_a4 = note.semitones_from_a4(octave);
+        let equal = self.a4 * 2.0_f64.powf(semitones_from_a4 as f64 / 12.0);
+        match &self.system {
+            TuningSystem::EqualTemperament => return equal,
+            TuningSystem::Scala(scala) => {
+                let key = NoteWithOctave {
+                    note: *note,
+                    octave,
+                };
+                return scala.frequency_of(&key).unwrap_or(equal);
+            }
+            _ => {}
         }
+        let above_tonic = (note.to_semitone() - self.tonic.to_semitone()).rem_euclid(12) as u8;
+        let tonic = equal / 2.0_f64.powf(above_tonic as f64 / 12.0);
+        tonic * self.system.ratio(above_tonic)
+    }
 
-        // Calculate note within octave
-        let note_index = ((rounded_semitones % 12) + 12) % 12; // Ensure positive
-
-        let note = match note_index {
-            0 => Note::A,
-            1 => Note::ASharp,
-            2 => Note::B,
-            3 => Note::C,
-            4 => Note::CSharp,
-            5 => Note::D,
-            6 => Note::DSharp,
-            7 => Note::E,
-            8 => Note::F,
-            9 => Note::FSharp,
-            10 => Note::G,
-            11 => Note::GSharp,
-            _ => unreachable!("Modulo 12 should only give 0-11"),
-        };
+    pub fn frequency_of(&self, note: &NoteWithOctave) -> f64 {
+        self.frequency(&note.note, note.octave)
+    }
 
-        Ok(NoteWithOctave {
-            note,
-            octave: octave as u8,
+    /// Convert frequency back to the closest equal-tempered note and octave
+    pub fn nearest_note(&self, frequency: f64) -> Result<NoteWithOctave, String> {
+        if frequency <= 0.0 {
+            return Err("Frequency must be positive".to_string());
+        }
+
+        // Calculate semitones from A4, then count from C0 so octaves split at C
+        let semitones_from_a4 = 12.0 * (frequency / self.a4).log2();
+        let rounded_semitones = semitones_from_a4.round() as i32;
+        let a4 = NoteWithOctave {
+            note: Note::A,
+            octave: 4,
+        };
+        let absolute = a4.absolute_semitone() + rounded_semitones;
+
+        NoteWithOctave::from_absolute_semitone(absolute).ok_or_else(|| {
+            format!(
+                "Octave {} is out of reasonable range (0-{})",
+                absolute.div_euclid(12),
+                MAX_OCTAVE
+            )
         })
     }
+}
+
+impl Note {
+    /// Frequency in standard tuning (A4 = 440Hz)
+    pub fn to_frequency(&self, octave: u8) -> f64 {
+        Tuning::STANDARD.frequency(self, octave)
+    }
+
+    /// Convert frequency back to closest note and octave in standard tuning
+    pub fn from_frequency(frequency: f64) -> Result<NoteWithOctave, String> {
+        Tuning::STANDARD.nearest_note(frequency)
+    }
 
     /// Calculate semitones from A4 (440Hz reference)
     /// A4 is our 0 point, so A4 returns 0 semitones
@@ -299,15 +697,66 @@ impl Note {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ScaleType {
     Major,
     Minor,
+    HarmonicMinor,
+    MelodicMinor,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Aeolian,
+    Locrian,
+}
+
+/// Which way a scale is played
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ScaleDirection {
+    #[default]
+    Ascending,
+    Descending,
+    /// Up to the top tonic and back down, without repeating it
+    UpAndDown,
+}
+
+impl fmt::Display for ScaleDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self {
+            ScaleDirection::Ascending => "ascending",
+            ScaleDirection::Descending => "descending",
+            ScaleDirection::UpAndDown => "up-down",
+        };
+        write!(f, "{}", direction)
+    }
+}
+
+impl FromStr for ScaleDirection {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ascending" | "up" | "a" => Ok(ScaleDirection::Ascending),
+            "descending" | "down" | "d" => Ok(ScaleDirection::Descending),
+            "up-down" | "updown" | "up-and-down" | "both" => Ok(ScaleDirection::UpAndDown),
+            _ => Err(format!("Invalid scale direction: {}", s)),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scale {
     pub root: NoteWithOctave,
     pub scale_type: ScaleType,
+    /// How the root is written, which decides the letter names of the other notes
+    root_spelling: SpelledNote,
 }
 
 impl fmt::Display for ScaleType {
@@ -315,6 +764,17 @@ impl fmt::Display for ScaleType {
         let scale_name = match self {
             ScaleType::Major => "Major",
             ScaleType::Minor => "Minor",
+            ScaleType::HarmonicMinor => "Harmonic Minor",
+            ScaleType::MelodicMinor => "Melodic Minor",
+            ScaleType::MajorPentatonic => "Major Pentatonic",
+            ScaleType::MinorPentatonic => "Minor Pentatonic",
+            ScaleType::Blues => "Blues",
+            ScaleType::Dorian => "Dorian",
+            ScaleType::Phrygian => "Phrygian",
+            ScaleType::Lydian => "Lydian",
+            ScaleType::Mixolydian => "Mixolydian",
+            ScaleType::Aeolian => "Aeolian",
+            ScaleType::Locrian => "Locrian",
         };
         write!(f, "{}", scale_name)
     }
@@ -322,33 +782,201 @@ impl fmt::Display for ScaleType {
 
 impl fmt::Display for Scale {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {}", self.root, self.scale_type)
+        write!(f, "{} {}", self.spelled_root(), self.scale_type)
     }
 }
 
+/// Parse a whole scale like "A minor", "Bb3 dorian" or "C# Harmonic Minor". The root's
+/// octave defaults to 4, and the scale name may be several words.
+impl FromStr for Scale {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (root, scale_type) = s
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("Invalid scale: {}", s))?;
+        Scale::spelled(root.parse()?, scale_type.parse()?)
+    }
+}
+
+/// Case-insensitive, with words separated by spaces, underscores or hyphens
 impl FromStr for ScaleType {
     type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "major" | "maj" => Ok(ScaleType::Major),
+        let name = s.split_whitespace().collect::<Vec<_>>().join(" ");
+        match name.to_lowercase().as_str() {
+            "major" | "maj" | "ionian" => Ok(ScaleType::Major),
             "minor" | "min" => Ok(ScaleType::Minor),
+            "harmonic minor" | "harmonic_minor" | "harmonic-minor" => Ok(ScaleType::HarmonicMinor),
+            "melodic minor" | "melodic_minor" | "melodic-minor" => Ok(ScaleType::MelodicMinor),
+            "major pentatonic" | "major_pentatonic" | "major-pentatonic" | "pentatonic" => {
+                Ok(ScaleType::MajorPentatonic)
+            }
+            "minor pentatonic" | "minor_pentatonic" | "minor-pentatonic" => {
+                Ok(ScaleType::MinorPentatonic)
+            }
+            "blues" => Ok(ScaleType::Blues),
+
+            // Diatonic modes
+            "dorian" => Ok(ScaleType::Dorian),
+            "phrygian" => Ok(ScaleType::Phrygian),
+            "lydian" => Ok(ScaleType::Lydian),
+            "mixolydian" => Ok(ScaleType::Mixolydian),
+            "aeolian" => Ok(ScaleType::Aeolian),
+            "locrian" => Ok(ScaleType::Locrian),
             _ => Err(format!("Invalid scale type: {}", s)),
         }
     }
 }
 
 impl ScaleType {
+    pub const ALL: [ScaleType; 13] = [
+        ScaleType::Major,
+        ScaleType::Minor,
+        ScaleType::HarmonicMinor,
+        ScaleType::MelodicMinor,
+        ScaleType::MajorPentatonic,
+        ScaleType::MinorPentatonic,
+        ScaleType::Blues,
+        ScaleType::Dorian,
+        ScaleType::Phrygian,
+        ScaleType::Lydian,
+        ScaleType::Mixolydian,
+        ScaleType::Aeolian,
+        ScaleType::Locrian,
+    ];
+
     pub fn intervals(&self) -> Vec<u8> {
         match self {
             ScaleType::Major => vec![0, 2, 4, 5, 7, 9, 11],
             ScaleType::Minor => vec![0, 2, 3, 5, 7, 8, 10],
+            ScaleType::HarmonicMinor => vec![0, 2, 3, 5, 7, 8, 11],
+            // Ascending form; descending it's the same as natural minor
+            ScaleType::MelodicMinor => vec![0, 2, 3, 5, 7, 9, 11],
+            ScaleType::MajorPentatonic => vec![0, 2, 4, 7, 9],
+            ScaleType::MinorPentatonic => vec![0, 3, 5, 7, 10],
+            ScaleType::Blues => vec![0, 3, 5, 6, 7, 10],
+            ScaleType::Dorian => vec![0, 2, 3, 5, 7, 9, 10],
+            ScaleType::Phrygian => vec![0, 1, 3, 5, 7, 8, 10],
+            ScaleType::Lydian => vec![0, 2, 4, 6, 7, 9, 11],
+            ScaleType::Mixolydian => vec![0, 2, 4, 5, 7, 9, 10],
+            ScaleType::Aeolian => vec![0, 2, 3, 5, 7, 8, 10],
+            ScaleType::Locrian => vec![0, 1, 3, 5, 6, 8, 10],
+        }
+    }
+
+    /// The intervals used coming down. Melodic minor lowers its sixth and seventh again,
+    /// descending as the natural minor; every other scale is the same both ways.
+    pub fn descending_intervals(&self) -> Vec<u8> {
+        match self {
+            ScaleType::MelodicMinor => ScaleType::Minor.intervals(),
+            _ => self.intervals(),
         }
     }
 }
 
 impl Scale {
+    /// Scale on `root`, spelled with sharps for a black-key root
     pub fn new(root: NoteWithOctave, scale_type: ScaleType) -> Self {
-        Self { root, scale_type }
+        Self {
+            root_spelling: SpelledNote::from_note(&root.note),
+            root,
+            scale_type,
+        }
+    }
+
+    /// Scale on a written root, so Db major is spelled with flats and C# major with sharps
+    pub fn spelled(root: SpelledPitch, scale_type: ScaleType) -> Result<Self, String> {
+        let pitch = root
+            .pitch()
+            .ok_or_else(|| format!("{} is out of range", root))?;
+        Ok(Self {
+            root: pitch,
+            scale_type,
+            root_spelling: root.note,
+        })
+    }
+
+    /// The root as written
+    pub fn spelled_root(&self) -> SpelledPitch {
+        SpelledPitch::spell(&self.root, self.root_spelling.name)
+            .unwrap_or_else(|| SpelledPitch::from_pitch(&self.root))
+    }
+
+    /// The scale's notes with their theoretically correct names.
+    /// Seven-note scales use each letter once, so F# major has E# rather than F.
+    pub fn spelled_notes(&self) -> Vec<SpelledPitch> {
+        let intervals = self.scale_type.intervals();
+        let heptatonic = intervals.len() == 7;
+        self.notes()
+            .iter()
+            .zip(intervals)
+            .enumerate()
+            .map(|(degree, (note, interval))| {
+                let steps = if heptatonic {
+                    degree as i32
+                } else {
+                    letter_steps(interval)
+                };
+                let name = NoteName::from_index(self.root_spelling.name.index() + steps);
+                SpelledPitch::spell(note, name).unwrap_or_else(|| SpelledPitch::from_pitch(note))
+            })
+            .collect()
+    }
+
+    /// The scale ascending over `octaves` octaves (1 to `MAX_SCALE_OCTAVES`), ending on
+    /// the top tonic. Notes past the highest octave are left out.
+    pub fn notes_spanning(&self, octaves: u8) -> Vec<NoteWithOctave> {
+        pitches(&self.spelled_notes_spanning(octaves))
+    }
+
+    /// Like `notes_spanning`, with the notes' theoretically correct names
+    pub fn spelled_notes_spanning(&self, octaves: u8) -> Vec<SpelledPitch> {
+        self.spelled_run(octaves, ScaleDirection::Ascending)
+    }
+
+    /// One octave from the top tonic down to the root, in the scale's descending form
+    pub fn notes_descending(&self) -> Vec<NoteWithOctave> {
+        pitches(&self.spelled_run(1, ScaleDirection::Descending))
+    }
+
+    /// The notes played over `octaves` octaves in `direction`, with their theoretically
+    /// correct names. Melodic minor comes down as the natural minor.
+    pub fn spelled_run(&self, octaves: u8, direction: ScaleDirection) -> Vec<SpelledPitch> {
+        let octaves = octaves.clamp(1, MAX_SCALE_OCTAVES);
+        match direction {
+            ScaleDirection::Ascending => self.spelled_span(octaves, &self.scale_type.intervals()),
+            ScaleDirection::Descending => {
+                let mut down = self.spelled_span(octaves, &self.scale_type.descending_intervals());
+                down.reverse();
+                down
+            }
+            ScaleDirection::UpAndDown => {
+                let mut run = self.spelled_run(octaves, ScaleDirection::Ascending);
+                let down = self.spelled_run(octaves, ScaleDirection::Descending);
+                run.extend(down.into_iter().skip(1));
+                run
+            }
+        }
+    }
+
+    /// The same scale starting on a root moved by `semitones`, in whichever enharmonic key
+    /// has the fewest sharps or flats (F major up 5 is Bb major, not A# major)
+    pub fn transpose(&self, semitones: i32) -> Result<Scale, String> {
+        let root = self.root.transpose(semitones)?;
+        let root_spelling = transposition_fifths(semitones)
+            .into_iter()
+            .filter_map(|fifths| self.root_spelling.shift_fifths(fifths))
+            .min_by_key(|tonic| {
+                KeySignature::for_spelled_key(tonic, &self.scale_type)
+                    .map_or(u8::MAX, |key| key.fifths().unsigned_abs())
+            })
+            .unwrap_or_else(|| SpelledNote::from_note(&root.note));
+        Ok(Scale {
+            root,
+            scale_type: self.scale_type.clone(),
+            root_spelling,
+        })
     }
 
     pub fn notes(&self) -> Vec<NoteWithOctave> {
@@ -364,6 +992,98 @@ impl Scale {
         scale_notes
     }
 
+    /// The note on a 1-based scale degree; degrees past the top continue into the next octave
+    /// (degree 8 of a major scale is the octave)
+    pub fn degree(&self, degree: usize) -> Option<NoteWithOctave> {
+        let (index, octaves) = self.degree_position(degree)?;
+        let note = *self.notes().get(index)?;
+        note.transpose(12 * octaves).ok()
+    }
+
+    /// The 1-based degree a note falls on in any octave, or `None` if it isn't in the scale
+    pub fn degree_of(&self, note: &Note) -> Option<usize> {
+        self.notes()
+            .iter()
+            .position(|n| n.note == *note)
+            .map(|index| index + 1)
+    }
+
+    /// Whether `note` is one of the scale's notes from the root up to the top tonic
+    pub fn contains(&self, note: &NoteWithOctave) -> bool {
+        self.notes_spanning(1).contains(note)
+    }
+
+    /// Whether `note` is in the scale in any octave
+    pub fn contains_pitch_class(&self, note: Note) -> bool {
+        self.degree_of(&note).is_some()
+    }
+
+    /// Like `degree`, with the note's theoretically correct name
+    pub fn spelled_degree(&self, degree: usize) -> Option<SpelledPitch> {
+        let (index, octaves) = self.degree_position(degree)?;
+        let note = *self.spelled_notes().get(index)?;
+        Some(SpelledPitch {
+            octave: u8::try_from(note.octave as i32 + octaves).ok()?,
+            ..note
+        })
+    }
+
+    /// The diatonic triad on a 1-based degree, stacking the scale's own thirds
+    pub fn triad(&self, degree: usize) -> Option<Chord> {
+        if self.scale_type.intervals().len() != 7 {
+            return None;
+        }
+        let root = self.degree(degree)?.absolute_semitone();
+        let third = self.degree(degree + 2)?.absolute_semitone();
+        let fifth = self.degree(degree + 4)?.absolute_semitone();
+        let quality =
+            ChordQuality::from_intervals(&[0, (third - root) as u8, (fifth - root) as u8])?;
+        Chord::spelled(self.spelled_degree(degree)?, quality).ok()
+    }
+
+    /// The triads on every degree (I, ii, iii... in major).
+    /// Empty for scales that don't have seven notes, like the pentatonics.
+    pub fn triads(&self) -> Vec<Chord> {
+        (1..=self.scale_type.intervals().len())
+            .map_while(|degree| self.triad(degree))
+            .collect()
+    }
+
+    /// `intervals` stacked from the root over `octaves` octaves, then the top tonic
+    fn spelled_span(&self, octaves: u8, intervals: &[u8]) -> Vec<SpelledPitch> {
+        let heptatonic = intervals.len() == 7;
+        let tonic = std::iter::once((0, 0, octaves as i32));
+        (0..octaves as i32)
+            .flat_map(|octave| {
+                intervals
+                    .iter()
+                    .enumerate()
+                    .map(move |(degree, &interval)| (degree, interval, octave))
+            })
+            .chain(tonic)
+            .filter_map(|(degree, interval, octave)| {
+                let note = self.root.transpose(interval as i32 + 12 * octave).ok()?;
+                let steps = if heptatonic {
+                    degree as i32
+                } else {
+                    letter_steps(interval)
+                };
+                let name = NoteName::from_index(self.root_spelling.name.index() + steps);
+                Some(
+                    SpelledPitch::spell(&note, name)
+                        .unwrap_or_else(|| SpelledPitch::from_pitch(&note)),
+                )
+            })
+            .collect()
+    }
+
+    /// Index into the scale's notes and octaves above the root for a 1-based degree
+    fn degree_position(&self, degree: usize) -> Option<(usize, i32)> {
+        let len = self.scale_type.intervals().len();
+        let steps = degree.checked_sub(1)?;
+        Some((steps % len, (steps / len) as i32))
+    }
+
     /// Get a specific note at the given interval (in semitones) from the root
     fn note_at_interval(&self, semitones: u8) -> Option<NoteWithOctave> {
         let root_semitone = self.root.note.to_semitone();
@@ -379,18 +1099,109 @@ impl Scale {
         })
     }
 
-    pub fn play(&self, note_duration: Duration) -> Result<(), Box<dyn std::error::Error>> {
-        let notes = self.notes();
-        for note in notes {
-            println!("🎵 {}", note);
-            note.play(note_duration)?;
+    /// Play the scale in quarter notes at `tempo`
+    #[cfg(feature = "playback")]
+    pub fn play(&self, tempo: Tempo) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_on(&AudioEngine::new()?, tempo)
+    }
+
+    /// Play the scale on an already open audio engine
+    #[cfg(feature = "playback")]
+    pub fn play_on(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Playable::play(self, engine, &PlaybackOptions::new().with_tempo(tempo))
+    }
+
+    /// Play the scale at the default tempo (120 BPM)
+    #[cfg(feature = "playback")]
+    pub fn play_default(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.play(Tempo::default())
+    }
+
+    /// Play the scale over `octaves` octaves in `direction`, in quarter notes at `tempo`
+    #[cfg(feature = "playback")]
+    pub fn play_run(
+        &self,
+        engine: &AudioEngine,
+        octaves: u8,
+        direction: ScaleDirection,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let run = self.spelled_run(octaves, direction);
+        let labels: Vec<String> = run.iter().map(|n| n.to_string()).collect();
+        Melody::from_notes(&pitches(&run), NoteValue::Quarter).play_events(
+            engine,
+            &PlaybackOptions::new().with_tempo(tempo),
+            Some(&labels),
+        )
+    }
+
+    /// Play the scale over a sustained tonic drone (an octave below the root),
+    /// optionally reinforced with the fifth above the drone
+    #[cfg(feature = "playback")]
+    pub fn play_with_drone(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+        with_fifth: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let drone_octave = self.root.octave.saturating_sub(1);
+        let mut drone_frequencies =
+            vec![engine.tuning().frequency(&self.root.note, drone_octave) as f32];
+        if with_fifth {
+            // A perfect fifth is 7 semitones above the tonic
+            drone_frequencies.push(drone_frequencies[0] * 2.0_f32.powf(7.0 / 12.0));
         }
+
+        // The engine mixes the drone voices with the melody
+        let drone = engine.start_drone(&drone_frequencies)?;
+        self.play_on(engine, tempo)?;
+        drone.stop();
         Ok(())
     }
+}
 
-    /// Play the scale with default timing (500ms per note)
-    pub fn play_default(&self) -> Result<(), Box<dyn std::error::Error>> {
-        self.play(Duration::from_millis(500))
+/// Every scale, on every root in octave 4, that has all of `notes` in some octave.
+/// Scales with the same notes as one listed earlier, like Aeolian and Minor, are left out.
+pub fn scales_containing(notes: &[Note]) -> Vec<Scale> {
+    let mut scales: Vec<Scale> = Vec::new();
+    for scale_type in ScaleType::ALL {
+        for root in (0..12).filter_map(Note::from_semitone) {
+            let scale = Scale::new(
+                NoteWithOctave {
+                    note: root,
+                    octave: 4,
+                },
+                scale_type.clone(),
+            );
+            let duplicate = scales.iter().any(|s| {
+                s.root == scale.root && s.scale_type.intervals() == scale_type.intervals()
+            });
+            if !duplicate && notes.iter().all(|&note| scale.contains_pitch_class(note)) {
+                scales.push(scale);
+            }
+        }
+    }
+    scales
+}
+
+/// The sounding notes of spelled ones, skipping any out of range
+fn pitches(notes: &[SpelledPitch]) -> Vec<NoteWithOctave> {
+    notes.iter().filter_map(|note| note.pitch()).collect()
+}
+
+#[cfg(feature = "playback")]
+impl Playable for Scale {
+    fn play(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let labels: Vec<String> = self.spelled_notes().iter().map(|n| n.to_string()).collect();
+        Melody::from_scale(self, NoteValue::Quarter).play_events(engine, opts, Some(&labels))
     }
 }
 
@@ -541,6 +1352,36 @@ mod tests {
         assert_eq!(note.octave, 3);
     }
 
+    #[test]
+    fn test_helmholtz() {
+        let note = |s: &str| NoteWithOctave::from_str(s).unwrap();
+        assert_eq!(note("c'"), note("C4"));
+        assert_eq!(note("c"), note("C3"));
+        assert_eq!(note("f#''"), note("F#5"));
+        assert_eq!(note("bb"), note("Bb3"));
+        assert_eq!(note("A,"), note("A1"));
+        assert_eq!(note("C,,"), note("C0"));
+        // Unmarked capitals stay scientific, in octave 4
+        assert_eq!(note("C"), note("C4"));
+        assert_eq!(split_helmholtz("C"), Ok(("C".to_string(), 2)));
+        assert!(NoteWithOctave::from_str("C,,,").is_err());
+        assert!(NoteWithOctave::from_str("c,").is_err());
+        assert!(NoteWithOctave::from_str("C'").is_err());
+
+        assert_eq!(note("C4").to_helmholtz(), "c'");
+        assert_eq!(note("G#5").to_helmholtz(), "g#''");
+        assert_eq!(note("E2").to_helmholtz(), "E");
+        assert_eq!(note("B0").to_helmholtz(), "B,,");
+        for octave in 0..=MAX_OCTAVE {
+            let pitch = NoteWithOctave {
+                note: Note::FSharp,
+                octave,
+            };
+            let (name, parsed) = split_helmholtz(&pitch.to_helmholtz()).unwrap();
+            assert_eq!((name.as_str(), parsed), ("F#", octave));
+        }
+    }
+
     #[test]
     fn test_note_with_octave_from_str_no_octave() {
         let note = NoteWithOctave::from_str("C").unwrap();
@@ -579,6 +1420,212 @@ mod tests {
         assert!("invalid".parse::<ScaleType>().is_err());
     }
 
+    #[test]
+    fn test_scale_parsing() {
+        let scale = "C# harmonic minor".parse::<Scale>().unwrap();
+        assert_eq!(scale.scale_type, ScaleType::HarmonicMinor);
+        assert_eq!(scale.to_string(), "C#4 Harmonic Minor");
+
+        let dorian = "Bb3 dorian".parse::<Scale>().unwrap();
+        assert_eq!(dorian.to_string(), "Bb3 Dorian");
+        assert_eq!(dorian.root, "A#3".parse().unwrap());
+
+        let spaced = "  A   Minor  Pentatonic ".parse::<Scale>().unwrap();
+        assert_eq!(spaced.scale_type, ScaleType::MinorPentatonic);
+        assert_eq!(spaced.to_string().parse::<Scale>().unwrap(), spaced);
+
+        assert!("C".parse::<Scale>().is_err());
+        assert!("H major".parse::<Scale>().is_err());
+        assert!("C major minor".parse::<Scale>().is_err());
+    }
+
+    #[test]
+    fn test_scale_spanning_octaves() {
+        let names = |notes: Vec<SpelledPitch>| -> Vec<String> {
+            notes.iter().map(|n| n.to_string()).collect()
+        };
+        let g_major = Scale::new("G3".parse().unwrap(), ScaleType::Major);
+        let two_octaves = g_major.spelled_notes_spanning(2);
+        assert_eq!(two_octaves.len(), 15);
+        assert_eq!(two_octaves[7].to_string(), "G4");
+        assert_eq!(two_octaves[13].to_string(), "F#5");
+        assert_eq!(two_octaves[14].to_string(), "G5");
+        assert_eq!(g_major.notes_spanning(2)[8], "A4".parse().unwrap());
+        assert_eq!(g_major.notes_spanning(0), g_major.notes_spanning(1));
+
+        let pentatonic = Scale::new("A4".parse().unwrap(), ScaleType::MinorPentatonic);
+        assert_eq!(
+            names(pentatonic.spelled_notes_spanning(2)),
+            vec![
+                "A4", "C5", "D5", "E5", "G5", "A5", "C6", "D6", "E6", "G6", "A6"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_descending_scales() {
+        let names = |notes: Vec<SpelledPitch>| -> Vec<String> {
+            notes.iter().map(|n| n.to_string()).collect()
+        };
+        let d_major = Scale::new("D4".parse().unwrap(), ScaleType::Major);
+        let down = d_major.notes_descending();
+        assert_eq!(down.first(), Some(&"D5".parse().unwrap()));
+        assert_eq!(down.last(), Some(&"D4".parse().unwrap()));
+        assert_eq!(down.len(), 8);
+
+        let melodic = Scale::spelled("A3".parse().unwrap(), ScaleType::MelodicMinor).unwrap();
+        assert_eq!(
+            names(melodic.spelled_run(1, ScaleDirection::UpAndDown)),
+            vec![
+                "A3", "B3", "C4", "D4", "E4", "F#4", "G#4", "A4", "G4", "F4", "E4", "D4", "C4",
+                "B3", "A3"
+            ]
+        );
+        assert_eq!(
+            "up-down".parse::<ScaleDirection>().unwrap(),
+            ScaleDirection::UpAndDown
+        );
+        assert!("sideways".parse::<ScaleDirection>().is_err());
+    }
+
+    #[test]
+    fn test_scale_contains() {
+        let c_major = Scale::new("C4".parse().unwrap(), ScaleType::Major);
+        assert!(c_major.contains(&"E4".parse().unwrap()));
+        assert!(c_major.contains(&"C5".parse().unwrap()));
+        assert!(!c_major.contains(&"E5".parse().unwrap()));
+        assert!(!c_major.contains(&"F#4".parse().unwrap()));
+        assert!(c_major.contains_pitch_class(Note::B));
+        assert!(!c_major.contains_pitch_class(Note::ASharp));
+    }
+
+    #[test]
+    fn test_scales_containing() {
+        let scales = scales_containing(&[Note::C, Note::E, Note::G, Note::ASharp]);
+        let names: Vec<String> = scales.iter().map(|s| s.to_string()).collect();
+        assert!(names.contains(&"F4 Major".to_string()));
+        assert!(names.contains(&"C4 Mixolydian".to_string()));
+        assert!(!names.contains(&"C4 Major".to_string()));
+        assert!(names.contains(&"D4 Minor".to_string()));
+        assert!(!names.contains(&"D4 Aeolian".to_string()));
+        assert!(scales.iter().all(|s| s.contains_pitch_class(Note::ASharp)));
+
+        assert_eq!(scales_containing(&[]).len(), 12 * 12);
+        let chromatic: Vec<Note> = (0..12).filter_map(Note::from_semitone).collect();
+        assert!(scales_containing(&chromatic).is_empty());
+    }
+
+    #[test]
+    fn test_note_arithmetic() {
+        let c4: NoteWithOctave = "C4".parse().unwrap();
+        let fifth = Interval::from_semitones(7);
+        assert_eq!(c4 + fifth, "G4".parse().unwrap());
+        assert_eq!(c4 - fifth, "F3".parse().unwrap());
+        assert_eq!(c4 + 14, "D5".parse().unwrap());
+        assert_eq!(c4 - 1, "B3".parse().unwrap());
+        assert_eq!(c4 + -1, c4 - 1);
+        assert_eq!(c4 + fifth - fifth, c4);
+
+        assert_eq!(NoteWithOctave::MIN.checked_sub(1), None);
+        assert_eq!(NoteWithOctave::MAX.checked_add(fifth), None);
+        assert_eq!(c4.checked_add(fifth), Some(c4 + 7));
+        assert_eq!(c4.saturating_sub(100), NoteWithOctave::MIN);
+        assert_eq!(c4.saturating_add(1000), NoteWithOctave::MAX);
+        assert_eq!(c4.saturating_add(fifth), c4 + fifth);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_note_arithmetic_overflow() {
+        let _ = NoteWithOctave::MIN - Interval::from_semitones(2);
+    }
+
+    #[test]
+    fn test_pitch_cents() {
+        let a4: NoteWithOctave = "A4".parse().unwrap();
+        let sharp = Pitch::new(a4, 100.0);
+        let b_flat = Tuning::STANDARD.frequency_of(&"A#4".parse().unwrap());
+        assert!((sharp.frequency(&Tuning::STANDARD) - b_flat).abs() < 1e-9);
+        assert_eq!(Pitch::from(a4).frequency(&Tuning::STANDARD), 440.0);
+
+        let heard = Pitch::from_frequency(445.0, &Tuning::STANDARD).unwrap();
+        assert_eq!(heard.note, a4);
+        assert!((heard.cents - 19.56).abs() < 0.01);
+        assert!(heard.is_in_tune(20.0));
+        assert!(!heard.is_in_tune(10.0));
+        assert_eq!(heard.to_string(), "A4 +20 cents");
+        assert_eq!(Pitch::new(a4, -7.4).to_string(), "A4 -7 cents");
+        assert_eq!(Pitch::from(a4).to_string(), "A4");
+        assert!(Pitch::from_frequency(0.0, &Tuning::STANDARD).is_err());
+    }
+
+    #[test]
+    fn test_tuning_system_ratios() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(TuningSystem::JustIntonation.ratio(4), 5.0 / 4.0));
+        assert!(close(TuningSystem::JustIntonation.ratio(19), 3.0 / 2.0));
+        assert!(close(TuningSystem::Pythagorean.ratio(4), 81.0 / 64.0));
+        assert!(close(TuningSystem::Pythagorean.ratio(1), 256.0 / 243.0));
+        assert!(close(TuningSystem::Pythagorean.ratio(6), 729.0 / 512.0));
+        assert!(close(
+            TuningSystem::QuarterCommaMeantone.ratio(4),
+            5.0 / 4.0
+        ));
+        assert!(close(
+            TuningSystem::QuarterCommaMeantone.ratio(7),
+            5.0_f64.powf(0.25)
+        ));
+        assert!(close(TuningSystem::EqualTemperament.ratio(12), 1.0));
+        assert_eq!(
+            "just".parse::<TuningSystem>().unwrap(),
+            TuningSystem::JustIntonation
+        );
+        assert!("wonky".parse::<TuningSystem>().is_err());
+    }
+
+    #[test]
+    fn test_tuning_in_a_system() {
+        let just = Tuning::STANDARD.with_system(TuningSystem::JustIntonation, Note::C);
+        let c4 = Tuning::STANDARD.frequency(&Note::C, 4);
+        assert!((just.frequency(&Note::C, 4) - c4).abs() < 1e-9);
+        assert!((just.frequency(&Note::A, 4) - c4 * 5.0 / 3.0).abs() < 1e-9);
+        assert!((just.frequency(&Note::E, 5) - c4 * 2.5).abs() < 1e-9);
+        assert_eq!(*just.system(), TuningSystem::JustIntonation);
+
+        // From D, the A above is a pure fifth
+        let from_d = Tuning::STANDARD.with_system(TuningSystem::Pythagorean, Note::D);
+        let d4 = Tuning::STANDARD.frequency(&Note::D, 4);
+        assert!((from_d.frequency(&Note::A, 4) - d4 * 1.5).abs() < 1e-9);
+        assert!((from_d.frequency(&Note::C, 4) - d4 / 2.0 * 16.0 / 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tuning_in_an_edo() {
+        let system: TuningSystem = "19-edo".parse().unwrap();
+        assert_eq!(system.to_string(), "19-EDO");
+        let nineteen = Tuning::STANDARD.with_system(system, Note::C);
+        let c4 = Tuning::STANDARD.frequency(&Note::C, 4);
+        // The 19-EDO major third is 6 steps and the fifth 11
+        assert!((nineteen.frequency(&Note::E, 4) - c4 * 2.0_f64.powf(6.0 / 19.0)).abs() < 1e-9);
+        assert!((nineteen.frequency(&Note::G, 4) - c4 * 2.0_f64.powf(11.0 / 19.0)).abs() < 1e-9);
+
+        let twelve = Tuning::STANDARD.with_system("12-edo".parse().unwrap(), Note::C);
+        assert!((twelve.frequency(&Note::A, 4) - 440.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tuning_with_a_scala_scale() {
+        let steps: Vec<String> = (1..=12).map(|i| format!("{}.0", i * 100)).collect();
+        let scl = format!("Twelve equal steps\n12\n{}\n", steps.join("\n"));
+        let scala = ScalaTuning::new(scl.parse().unwrap(), 440.0);
+        let tuning = Tuning::STANDARD.with_system(TuningSystem::Scala(Arc::new(scala)), Note::C);
+        for (note, octave) in [(Note::A, 4), (Note::C, 4), (Note::FSharp, 2), (Note::B, 6)] {
+            let expected = Tuning::STANDARD.frequency(&note, octave);
+            assert!((tuning.frequency(&note, octave) - expected).abs() < 1e-6);
+        }
+        assert_eq!(tuning.system().to_string(), "Twelve equal steps");
+    }
+
     #[test]
     fn test_scale_intervals() {
         let major_intervals = ScaleType::Major.intervals();
@@ -586,6 +1633,108 @@ mod tests {
 
         let minor_intervals = ScaleType::Minor.intervals();
         assert_eq!(minor_intervals, vec![0, 2, 3, 5, 7, 8, 10]);
+
+        assert_eq!(
+            ScaleType::HarmonicMinor.intervals(),
+            vec![0, 2, 3, 5, 7, 8, 11]
+        );
+        assert_eq!(
+            ScaleType::MelodicMinor.intervals(),
+            vec![0, 2, 3, 5, 7, 9, 11]
+        );
+        assert_eq!(ScaleType::MajorPentatonic.intervals(), vec![0, 2, 4, 7, 9]);
+        assert_eq!(ScaleType::MinorPentatonic.intervals(), vec![0, 3, 5, 7, 10]);
+        assert_eq!(ScaleType::Blues.intervals(), vec![0, 3, 5, 6, 7, 10]);
+    }
+
+    #[test]
+    fn test_extended_scale_type_parsing() {
+        assert_eq!(
+            "harmonic minor".parse::<ScaleType>().unwrap(),
+            ScaleType::HarmonicMinor
+        );
+        assert_eq!(
+            "melodic_minor".parse::<ScaleType>().unwrap(),
+            ScaleType::MelodicMinor
+        );
+        assert_eq!(
+            "pentatonic".parse::<ScaleType>().unwrap(),
+            ScaleType::MajorPentatonic
+        );
+        assert_eq!(
+            "minor-pentatonic".parse::<ScaleType>().unwrap(),
+            ScaleType::MinorPentatonic
+        );
+        assert_eq!("blues".parse::<ScaleType>().unwrap(), ScaleType::Blues);
+        assert_eq!(format!("{}", ScaleType::HarmonicMinor), "Harmonic Minor");
+    }
+
+    #[test]
+    fn test_modes_are_rotations_of_major() {
+        let major = ScaleType::Major.intervals();
+        let modes = [
+            ScaleType::Major,
+            ScaleType::Dorian,
+            ScaleType::Phrygian,
+            ScaleType::Lydian,
+            ScaleType::Mixolydian,
+            ScaleType::Aeolian,
+            ScaleType::Locrian,
+        ];
+
+        for (degree, mode) in modes.iter().enumerate() {
+            let start = major[degree];
+            let expected: Vec<u8> = (0..7)
+                .map(|i| (major[(degree + i) % 7] + 12 - start) % 12)
+                .collect();
+            assert_eq!(
+                mode.intervals(),
+                expected,
+                "{} is not mode {}",
+                mode,
+                degree + 1
+            );
+        }
+    }
+
+    #[test]
+    fn test_d_dorian_uses_white_keys() {
+        let scale = Scale::new("D4".parse().unwrap(), "dorian".parse().unwrap());
+        let notes = scale.notes();
+        let expected = [
+            Note::D,
+            Note::E,
+            Note::F,
+            Note::G,
+            Note::A,
+            Note::B,
+            Note::C,
+        ];
+
+        assert_eq!(notes.len(), 7);
+        for (note, expected) in notes.iter().zip(expected) {
+            assert_eq!(note.note, expected);
+        }
+        assert_eq!(format!("{}", scale), "D4 Dorian");
+    }
+
+    #[test]
+    fn test_a_blues_scale() {
+        let scale = Scale::new(
+            NoteWithOctave {
+                note: Note::A,
+                octave: 3,
+            },
+            ScaleType::Blues,
+        );
+        let notes = scale.notes();
+
+        assert_eq!(notes.len(), 6);
+        assert_eq!(notes[0].note, Note::A);
+        assert_eq!(notes[1].note, Note::C);
+        assert_eq!(notes[1].octave, 4);
+        assert_eq!(notes[3].note, Note::DSharp);
+        assert_eq!(notes[5].note, Note::G);
     }
 
     #[test]
@@ -632,4 +1781,292 @@ mod tests {
         assert_eq!(Note::from_semitone(11).unwrap(), Note::B);
         assert_eq!(Note::from_semitone(12).unwrap(), Note::C); // Wraps around
     }
+
+    #[test]
+    fn test_midi_reference_notes() {
+        assert_eq!(NoteWithOctave::from_str("C4").unwrap().to_midi(), Some(60));
+        assert_eq!(NoteWithOctave::from_str("A4").unwrap().to_midi(), Some(69));
+        assert_eq!(NoteWithOctave::from_str("C0").unwrap().to_midi(), Some(12));
+
+        let a4 = NoteWithOctave::from_midi(69).unwrap();
+        assert_eq!(a4.note, Note::A);
+        assert_eq!(a4.octave, 4);
+    }
+
+    #[test]
+    fn test_midi_round_trip() {
+        for midi in 12..=127 {
+            let note = NoteWithOctave::from_midi(midi).unwrap();
+            assert_eq!(note.to_midi(), Some(midi), "Round trip failed for {}", note);
+        }
+    }
+
+    #[test]
+    fn test_midi_out_of_range() {
+        assert!(NoteWithOctave::from_midi(11).is_err());
+        assert!(NoteWithOctave::from_midi(128).is_err());
+
+        // G9 is the top of MIDI's range; higher notes have no number rather than wrapping
+        assert_eq!(NoteWithOctave::from_str("G9").unwrap().to_midi(), Some(127));
+        assert_eq!(NoteWithOctave::from_str("G#9").unwrap().to_midi(), None);
+        assert_eq!(NoteWithOctave::MAX.to_midi(), None);
+        assert!(NoteWithOctave::from_str("C99").is_err());
+        assert!(NoteWithOctave::from_str("C11").is_err());
+    }
+
+    #[test]
+    fn test_transpose_note() {
+        let b3 = NoteWithOctave::from_str("B3").unwrap();
+        let c4 = b3.transpose(1).unwrap();
+        assert_eq!(c4, NoteWithOctave::from_str("C4").unwrap());
+
+        // Negative steps borrow from the octave below
+        let a2 = NoteWithOctave::from_str("C4")
+            .unwrap()
+            .transpose(-15)
+            .unwrap();
+        assert_eq!(a2, NoteWithOctave::from_str("A2").unwrap());
+
+        assert_eq!(b3.transpose(0).unwrap(), b3);
+    }
+
+    #[test]
+    fn test_transpose_out_of_range() {
+        let c0 = NoteWithOctave::from_str("C0").unwrap();
+        assert!(c0.transpose(-1).is_err());
+
+        let b10 = NoteWithOctave::from_str("B10").unwrap();
+        assert!(b10.transpose(1).is_err());
+        assert!(b10.transpose(-1).is_ok());
+    }
+
+    #[test]
+    fn test_transpose_scale() {
+        let scale = Scale::new(NoteWithOctave::from_str("C4").unwrap(), ScaleType::Major);
+        let d_major = scale.transpose(2).unwrap();
+        assert_eq!(format!("{}", d_major), "D4 Major");
+
+        let notes = d_major.notes();
+        assert_eq!(notes[2].note, Note::FSharp);
+        assert_eq!(notes[6].note, Note::CSharp);
+        assert_eq!(notes[6].octave, 5);
+    }
+
+    #[test]
+    fn test_transpose_keeps_spelling() {
+        let f_major = Scale::new("F4".parse().unwrap(), ScaleType::Major);
+        let b_flat = f_major.transpose(5).unwrap();
+        assert_eq!(b_flat.to_string(), "Bb4 Major");
+        let names: Vec<String> = b_flat
+            .spelled_notes()
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(names, ["Bb4", "C5", "D5", "Eb5", "F5", "G5", "A5"]);
+
+        let d_flat = Scale::spelled("Db4".parse().unwrap(), ScaleType::Major).unwrap();
+        assert_eq!(d_flat.transpose(0).unwrap(), d_flat);
+        assert_eq!(d_flat.transpose(12).unwrap().to_string(), "Db5 Major");
+        assert_eq!(d_flat.transpose(2).unwrap().to_string(), "Eb4 Major");
+        assert_eq!(d_flat.transpose(1).unwrap().to_string(), "D4 Major");
+
+        // A minor up a tritone has six flats as Eb minor and six sharps as D# minor
+        let e_minor = Scale::new("E4".parse().unwrap(), ScaleType::Minor);
+        assert_eq!(e_minor.transpose(6).unwrap().to_string(), "Bb4 Minor");
+    }
+
+    #[test]
+    fn test_from_frequency_octave_boundaries() {
+        // Octaves change at C, not at A
+        let c5 = Note::from_frequency(Note::C.to_frequency(5)).unwrap();
+        assert_eq!(c5, NoteWithOctave::from_str("C5").unwrap());
+
+        let b3 = Note::from_frequency(Note::B.to_frequency(3)).unwrap();
+        assert_eq!(b3, NoteWithOctave::from_str("B3").unwrap());
+
+        let a_sharp3 = Note::from_frequency(Note::ASharp.to_frequency(3)).unwrap();
+        assert_eq!(a_sharp3, NoteWithOctave::from_str("A#3").unwrap());
+
+        // C5 used to be reported as C4 when octaves were counted from A
+        let tuning = Tuning::STANDARD;
+        let c5 = NoteWithOctave::from_str("C5").unwrap();
+        let exact = tuning.frequency_of(&c5);
+        let flat = exact * 2.0_f64.powf(-20.0 / 1200.0);
+        assert_eq!(tuning.nearest_note(exact).unwrap(), c5);
+        assert_eq!(tuning.nearest_note(flat).unwrap(), c5);
+        let heard = Pitch::from_frequency(flat, &tuning).unwrap();
+        assert_eq!(heard.note.octave, 5);
+        assert!((heard.cents + 20.0).abs() < 1e-6);
+        assert_eq!(Pitch::from_frequency(exact, &tuning).unwrap().note, c5);
+    }
+
+    #[test]
+    fn test_custom_tuning() {
+        let baroque = Tuning::new(415.0).unwrap();
+        let a4 = NoteWithOctave::from_str("A4").unwrap();
+        assert!((baroque.frequency_of(&a4) - 415.0).abs() < 0.01);
+        assert!((baroque.frequency(&Note::A, 5) - 830.0).abs() < 0.01);
+
+        // 440Hz is a semitone above A4 in baroque tuning
+        assert_eq!(
+            baroque.nearest_note(440.0).unwrap(),
+            NoteWithOctave::from_str("A#4").unwrap()
+        );
+
+        let verdi = Tuning::new(432.0).unwrap();
+        let c4 = NoteWithOctave::from_str("C4").unwrap();
+        assert_eq!(verdi.nearest_note(verdi.frequency_of(&c4)).unwrap(), c4);
+    }
+
+    #[test]
+    fn test_invalid_tuning() {
+        assert!(Tuning::new(0.0).is_err());
+        assert!(Tuning::new(-440.0).is_err());
+        assert!(Tuning::new(f64::NAN).is_err());
+        assert_eq!(Tuning::default().a4(), 440.0);
+    }
+
+    #[test]
+    fn test_notes_order_by_pitch() {
+        assert!(Note::C < Note::CSharp);
+        assert!(Note::ASharp < Note::B);
+
+        let b3: NoteWithOctave = "B3".parse().unwrap();
+        let c4: NoteWithOctave = "C4".parse().unwrap();
+        assert!(b3 < c4);
+
+        let mut notes: Vec<NoteWithOctave> = ["G4", "C5", "B3", "C4"]
+            .iter()
+            .map(|n| n.parse().unwrap())
+            .collect();
+        notes.sort();
+        let sorted: Vec<String> = notes.iter().map(|n| n.to_string()).collect();
+        assert_eq!(sorted, vec!["B3", "C4", "G4", "C5"]);
+        assert_eq!(notes.iter().max(), Some(&"C5".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_notes_as_map_keys() {
+        use std::collections::{HashMap, HashSet};
+
+        let mut counts: HashMap<Note, u32> = HashMap::new();
+        for note in ["C4", "E4", "C5"] {
+            let note: NoteWithOctave = note.parse().unwrap();
+            *counts.entry(note.note).or_default() += 1;
+        }
+        assert_eq!(counts[&Note::C], 2);
+
+        // Copies compare equal to the original
+        let a4: NoteWithOctave = "A4".parse().unwrap();
+        let copy = a4;
+        let set: HashSet<NoteWithOctave> = [a4, copy].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_spelled_scales() {
+        let names = |scale: &Scale| -> Vec<String> {
+            scale
+                .spelled_notes()
+                .iter()
+                .map(|n| n.to_string())
+                .collect()
+        };
+
+        let f_sharp_major = Scale::new("F#4".parse().unwrap(), ScaleType::Major);
+        assert_eq!(
+            names(&f_sharp_major),
+            vec!["F#4", "G#4", "A#4", "B4", "C#5", "D#5", "E#5"]
+        );
+
+        let d_flat_major = Scale::spelled("Db4".parse().unwrap(), ScaleType::Major).unwrap();
+        assert_eq!(
+            names(&d_flat_major),
+            vec!["Db4", "Eb4", "F4", "Gb4", "Ab4", "Bb4", "C5"]
+        );
+        assert_eq!(format!("{}", d_flat_major), "Db4 Major");
+        // Same pitches as C# major
+        let c_sharp_major = Scale::new("C#4".parse().unwrap(), ScaleType::Major);
+        assert_eq!(d_flat_major.notes(), c_sharp_major.notes());
+
+        let g_sharp_major = Scale::new("G#3".parse().unwrap(), ScaleType::Major);
+        assert_eq!(names(&g_sharp_major)[6], "Fx4");
+
+        let c_blues = Scale::new("C4".parse().unwrap(), ScaleType::Blues);
+        assert_eq!(names(&c_blues), vec!["C4", "Eb4", "F4", "Gb4", "G4", "Bb4"]);
+    }
+
+    #[test]
+    fn test_scale_degrees() {
+        let e_major = Scale::new("E4".parse().unwrap(), ScaleType::Major);
+        assert_eq!(e_major.degree(5), Some("B4".parse().unwrap()));
+        assert_eq!(e_major.degree(8), Some("E5".parse().unwrap()));
+        assert_eq!(e_major.degree(10), Some("G#5".parse().unwrap()));
+        assert_eq!(e_major.degree(0), None);
+        assert_eq!(e_major.degree_of(&Note::GSharp), Some(3));
+        assert_eq!(e_major.degree_of(&Note::E), Some(1));
+        assert_eq!(e_major.degree_of(&Note::G), None);
+
+        let f_major = Scale::new("F4".parse().unwrap(), ScaleType::Major);
+        assert_eq!(f_major.spelled_degree(4).unwrap().to_string(), "Bb4");
+        assert_eq!(f_major.spelled_degree(11).unwrap().to_string(), "Bb5");
+    }
+
+    #[test]
+    fn test_major_scale_triads() {
+        let c_major = Scale::new("C4".parse().unwrap(), ScaleType::Major);
+        let qualities: Vec<ChordQuality> =
+            c_major.triads().into_iter().map(|c| c.quality).collect();
+        assert_eq!(
+            qualities,
+            vec![
+                ChordQuality::Major,
+                ChordQuality::Minor,
+                ChordQuality::Minor,
+                ChordQuality::Major,
+                ChordQuality::Major,
+                ChordQuality::Minor,
+                ChordQuality::Diminished,
+            ]
+        );
+
+        let d_flat_major = Scale::spelled("Db4".parse().unwrap(), ScaleType::Major).unwrap();
+        let ii = d_flat_major.triad(2).unwrap();
+        assert_eq!(ii.name(), "Eb4 Minor");
+        let names: Vec<String> = ii.spelled_notes().iter().map(|n| n.to_string()).collect();
+        assert_eq!(names, vec!["Eb4", "Gb4", "Bb4"]);
+    }
+
+    #[test]
+    fn test_minor_scale_triads() {
+        let a_harmonic = Scale::new("A3".parse().unwrap(), ScaleType::HarmonicMinor);
+        assert_eq!(
+            a_harmonic.triad(3).unwrap().quality,
+            ChordQuality::Augmented
+        );
+        assert_eq!(a_harmonic.triad(5).unwrap().quality, ChordQuality::Major);
+        assert_eq!(
+            a_harmonic.triad(7).unwrap().quality,
+            ChordQuality::Diminished
+        );
+
+        let pentatonic = Scale::new("C4".parse().unwrap(), ScaleType::MajorPentatonic);
+        assert!(pentatonic.triads().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let note: NoteWithOctave = "F#3".parse().unwrap();
+        let json = serde_json::to_string(&note).unwrap();
+        assert_eq!(json, r#"{"note":"f_sharp","octave":3}"#);
+        assert_eq!(serde_json::from_str::<NoteWithOctave>(&json).unwrap(), note);
+
+        // Scales keep their spelling, so Gb major still reads back with flats
+        let scale = Scale::spelled("Gb4".parse().unwrap(), ScaleType::Major).unwrap();
+        let json = serde_json::to_string(&scale).unwrap();
+        let read: Scale = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, scale);
+        assert_eq!(read.to_string(), scale.to_string());
+    }
 }
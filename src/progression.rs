@@ -0,0 +1,277 @@
+#[cfg(feature = "playback")]
+use crate::audio::{AudioEngine, Playable, PlaybackOptions};
+use crate::chords::{Chord, ChordQuality};
+use crate::notes::Scale;
+use crate::spelling::{Accidental, SpelledNote, SpelledPitch};
+#[cfg(feature = "playback")]
+use crate::tempo::{NoteValue, Tempo};
+use std::fmt;
+use std::str::FromStr;
+
+const NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
+
+/// A chord named by the scale degree of its root, like "ii", "V7" or "bVII".
+/// Upper case is major and lower case minor, whatever the key.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RomanNumeral {
+    /// Scale degree of the root, from 1 to 7
+    pub degree: usize,
+    /// Raises or lowers the root from the key, e.g. flat for bVII
+    pub accidental: Accidental,
+    pub quality: ChordQuality,
+}
+
+impl fmt::Display for RomanNumeral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let numeral = NUMERALS[self.degree - 1];
+        let (numeral, suffix) = match self.quality {
+            ChordQuality::Major => (numeral.to_string(), ""),
+            ChordQuality::Minor => (numeral.to_lowercase(), ""),
+            ChordQuality::Diminished => (numeral.to_lowercase(), "°"),
+            ChordQuality::Augmented => (numeral.to_string(), "+"),
+            ChordQuality::Dominant7 => (numeral.to_string(), "7"),
+            ChordQuality::Major7 => (numeral.to_string(), "maj7"),
+            ChordQuality::Minor7 => (numeral.to_lowercase(), "7"),
+            ChordQuality::Diminished7 => (numeral.to_lowercase(), "°7"),
+            ChordQuality::HalfDiminished7 => (numeral.to_lowercase(), "ø7"),
+            ChordQuality::MinorMajor7 => (numeral.to_lowercase(), "maj7"),
+        };
+        write!(f, "{}{}{}", self.accidental, numeral, suffix)
+    }
+}
+
+/// Parse numerals like "IV", "vi", "vii°" (or "viio"), "III+", "V7", "Imaj7", "ii7", "viiø7" or "bVII"
+impl FromStr for RomanNumeral {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (accidental, rest) = match s.strip_prefix('b') {
+            Some(rest) => (Accidental::Flat, rest),
+            None => match s.strip_prefix('#') {
+                Some(rest) => (Accidental::Sharp, rest),
+                None => (Accidental::Natural, s),
+            },
+        };
+        let split = rest
+            .find(|c: char| !matches!(c, 'I' | 'V' | 'i' | 'v'))
+            .unwrap_or(rest.len());
+        let (numeral, suffix) = rest.split_at(split);
+
+        let upper = numeral.to_uppercase();
+        let degree = NUMERALS
+            .iter()
+            .position(|&n| n == upper)
+            .ok_or_else(|| format!("Invalid Roman numeral: {}", s))?
+            + 1;
+        let major = if numeral == upper {
+            true
+        } else if numeral == numeral.to_lowercase() {
+            false
+        } else {
+            return Err(format!("Mixed-case Roman numeral: {}", s));
+        };
+
+        let quality = match (major, suffix) {
+            (true, "") => ChordQuality::Major,
+            (false, "") => ChordQuality::Minor,
+            (false, "°" | "o" | "dim") => ChordQuality::Diminished,
+            (true, "+" | "aug") => ChordQuality::Augmented,
+            (true, "7") => ChordQuality::Dominant7,
+            (true, "maj7" | "M7") => ChordQuality::Major7,
+            (false, "7") => ChordQuality::Minor7,
+            (false, "°7" | "o7" | "dim7") => ChordQuality::Diminished7,
+            (false, "ø7" | "ø") => ChordQuality::HalfDiminished7,
+            (false, "maj7" | "M7") => ChordQuality::MinorMajor7,
+            _ => return Err(format!("Invalid chord suffix in {}", s)),
+        };
+        Ok(Self {
+            degree,
+            accidental,
+            quality,
+        })
+    }
+}
+
+impl RomanNumeral {
+    /// The chord this numeral names in `key`, e.g. ii in C major is D minor
+    pub fn chord_in(&self, key: &Scale) -> Result<Chord, String> {
+        if key.scale_type.intervals().len() != 7 {
+            return Err(format!("{} has no Roman numeral chords", key));
+        }
+        let root = key
+            .spelled_degree(self.degree)
+            .ok_or_else(|| format!("{} has no degree {}", key, self.degree))?;
+        let accidental = Accidental::from_semitones(
+            root.note.accidental.semitones() + self.accidental.semitones(),
+        )
+        .ok_or_else(|| format!("{}{} needs a triple accidental", self.accidental, root))?;
+        let root = SpelledPitch {
+            note: SpelledNote {
+                accidental,
+                ..root.note
+            },
+            ..root
+        };
+        Chord::spelled(root, self.quality.clone())
+    }
+}
+
+/// A sequence of chords written as Roman numerals in a key, like ii-V-I in C major
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progression {
+    pub key: Scale,
+    pub numerals: Vec<RomanNumeral>,
+}
+
+impl fmt::Display for Progression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let numerals: Vec<String> = self.numerals.iter().map(|n| n.to_string()).collect();
+        write!(f, "{} in {}", numerals.join("-"), self.key)
+    }
+}
+
+impl Progression {
+    /// Progression in `key`, failing if a numeral has no chord there (e.g. in a pentatonic key)
+    pub fn new(key: Scale, numerals: Vec<RomanNumeral>) -> Result<Self, String> {
+        for numeral in &numerals {
+            numeral.chord_in(&key)?;
+        }
+        Ok(Self { key, numerals })
+    }
+
+    /// Parse numerals separated by dashes or spaces, e.g. "ii-V-I" or "I vi IV V"
+    pub fn parse(key: Scale, numerals: &str) -> Result<Self, String> {
+        let numerals = numerals
+            .split(|c: char| c == '-' || c.is_whitespace())
+            .filter(|n| !n.is_empty())
+            .map(|n| n.parse())
+            .collect::<Result<Vec<RomanNumeral>, String>>()?;
+        if numerals.is_empty() {
+            return Err("A progression needs at least one chord".to_string());
+        }
+        Progression::new(key, numerals)
+    }
+
+    /// The chords in order, with roots in the octave of the key
+    pub fn chords(&self) -> Vec<Chord> {
+        self.numerals
+            .iter()
+            .filter_map(|numeral| numeral.chord_in(&self.key).ok())
+            .collect()
+    }
+
+    /// Play each chord as a half-note block chord on a one-off engine
+    #[cfg(feature = "playback")]
+    pub fn play(&self, tempo: Tempo) -> Result<(), Box<dyn std::error::Error>> {
+        self.play_on(&AudioEngine::new()?, tempo)
+    }
+
+    /// Play each chord as a half-note block chord, printing its numeral and name
+    #[cfg(feature = "playback")]
+    pub fn play_on(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Playable::play(self, engine, &PlaybackOptions::new().with_tempo(tempo))
+    }
+}
+
+#[cfg(feature = "playback")]
+impl Playable for Progression {
+    fn play(
+        &self,
+        engine: &AudioEngine,
+        opts: &PlaybackOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for (numeral, chord) in self.numerals.iter().zip(self.chords()) {
+            println!("🎵 {}: {}", numeral, chord);
+            engine.play_notes_with(
+                &chord.notes(),
+                opts.tempo.duration_of(NoteValue::Half),
+                opts,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::ScaleType;
+
+    fn key(root: &str, scale_type: ScaleType) -> Scale {
+        Scale::spelled(root.parse().unwrap(), scale_type).unwrap()
+    }
+
+    fn chord_names(progression: &Progression) -> Vec<String> {
+        progression.chords().iter().map(|c| c.name()).collect()
+    }
+
+    #[test]
+    fn test_parse_roman_numerals() {
+        let ii: RomanNumeral = "ii".parse().unwrap();
+        assert_eq!(ii.degree, 2);
+        assert_eq!(ii.quality, ChordQuality::Minor);
+
+        assert_eq!(
+            "viio".parse::<RomanNumeral>().unwrap().quality,
+            ChordQuality::Diminished
+        );
+        assert_eq!(
+            "V7".parse::<RomanNumeral>().unwrap().quality,
+            ChordQuality::Dominant7
+        );
+        let flat_seven: RomanNumeral = "bVII".parse().unwrap();
+        assert_eq!(flat_seven.accidental, Accidental::Flat);
+        assert_eq!(flat_seven.to_string(), "bVII");
+        assert_eq!("vii°".parse::<RomanNumeral>().unwrap().to_string(), "vii°");
+
+        assert!("VIII".parse::<RomanNumeral>().is_err());
+        assert!("Vi".parse::<RomanNumeral>().is_err());
+        assert!("v+".parse::<RomanNumeral>().is_err());
+    }
+
+    #[test]
+    fn test_progression_in_major_key() {
+        let two_five_one = Progression::parse(key("C4", ScaleType::Major), "ii-V-I").unwrap();
+        assert_eq!(
+            chord_names(&two_five_one),
+            vec!["D4 Minor", "G4 Major", "C4 Major"]
+        );
+        assert_eq!(two_five_one.to_string(), "ii-V-I in C4 Major");
+
+        let pop = Progression::parse(key("Eb4", ScaleType::Major), "I vi IV V").unwrap();
+        assert_eq!(
+            chord_names(&pop),
+            vec!["Eb4 Major", "C5 Minor", "Ab4 Major", "Bb4 Major"]
+        );
+    }
+
+    #[test]
+    fn test_progression_in_minor_key() {
+        let progression = Progression::parse(key("A3", ScaleType::Minor), "i-iv-V7-VI").unwrap();
+        let chords = progression.chords();
+        assert_eq!(chords[2].name(), "E4 Dominant 7th");
+        let names: Vec<String> = chords[2]
+            .spelled_notes()
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(names, vec!["E4", "G#4", "B4", "D5"]);
+        assert_eq!(chords[3].name(), "F4 Major");
+
+        // Accidentals move the key's own degree, so bVII in C major is Bb
+        let mixolydian = Progression::parse(key("C4", ScaleType::Major), "I-bVII-IV").unwrap();
+        assert_eq!(mixolydian.chords()[1].name(), "Bb4 Major");
+    }
+
+    #[test]
+    fn test_invalid_progressions() {
+        assert!(Progression::parse(key("C4", ScaleType::Major), "").is_err());
+        assert!(Progression::parse(key("C4", ScaleType::Major), "ii-X-I").is_err());
+        assert!(Progression::parse(key("C4", ScaleType::MajorPentatonic), "I-IV").is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use hound::{WavSpec, WavWriter};
+use rodio::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use rodio::cpal::{self, FromSample, SampleFormat, SizedSample, StreamConfig};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+type SharedWriter = Arc<Mutex<Option<WavWriter<BufWriter<File>>>>>;
+type PendingPlayback = Arc<Mutex<VecDeque<f32>>>;
+
+/// Trainer playback waiting to be mixed into the active recording
+struct PlaybackTap {
+    sample_rate: u32,
+    pending: PendingPlayback,
+}
+
+static PLAYBACK_TAP: Mutex<Option<PlaybackTap>> = Mutex::new(None);
+
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Hand played samples to the active recorder, if it is mixing in playback
+pub(crate) fn tap_playback(samples: &[f32], sample_rate: u32) {
+    if let Some(tap) = lock(&PLAYBACK_TAP).as_ref() {
+        let resampled = resample(samples, sample_rate, tap.sample_rate);
+        lock(&tap.pending).extend(resampled);
+    }
+}
+
+/// Nearest-neighbour resampling, good enough for a practice recording
+fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate {
+        return samples.to_vec();
+    }
+    let len = (samples.len() as u64 * to_rate as u64 / from_rate as u64) as usize;
+    (0..len)
+        .map(|i| samples[(i as u64 * from_rate as u64 / to_rate as u64) as usize])
+        .collect()
+}
+
+/// File name for a session started at the given time, e.g. `session-1700000000.wav`
+pub fn session_file_name(started: SystemTime) -> String {
+    let secs = started
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!("session-{}.wav", secs)
+}
+
+/// Records the default microphone to a WAV file for later review
+pub struct SessionRecorder {
+    stream: cpal::Stream,
+    writer: SharedWriter,
+    path: PathBuf,
+    mix_playback: bool,
+}
+
+impl SessionRecorder {
+    /// Start recording into a timestamped WAV in `dir`.
+    /// With `mix_playback`, notes played by the trainer are mixed into the file too.
+    pub fn start(
+        dir: impl AsRef<Path>,
+        mix_playback: bool,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or("No microphone input device available")?;
+        let supported = device.default_input_config()?;
+        let sample_format = supported.sample_format();
+        let config: StreamConfig = supported.into();
+
+        let path = dir.as_ref().join(session_file_name(SystemTime::now()));
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: config.sample_rate.0,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let writer: SharedWriter = Arc::new(Mutex::new(Some(WavWriter::create(&path, spec)?)));
+        let pending: PendingPlayback = Arc::new(Mutex::new(VecDeque::new()));
+
+        let stream = match sample_format {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, &writer, &pending)?,
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, &writer, &pending)?,
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, &writer, &pending)?,
+            other => return Err(format!("Unsupported microphone sample format: {}", other).into()),
+        };
+        stream.play()?;
+
+        if mix_playback {
+            *lock(&PLAYBACK_TAP) = Some(PlaybackTap {
+                sample_rate: config.sample_rate.0,
+                pending,
+            });
+        }
+
+        Ok(Self {
+            stream,
+            writer,
+            path,
+            mix_playback,
+        })
+    }
+
+    /// Where the recording is being written
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop recording and finalize the WAV file, returning its path
+    pub fn finish(self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        self.stream.pause()?;
+        if let Some(writer) = lock(&self.writer).take() {
+            writer.finalize()?;
+        }
+        Ok(self.path.clone())
+    }
+}
+
+impl Drop for SessionRecorder {
+    fn drop(&mut self) {
+        if self.mix_playback {
+            *lock(&PLAYBACK_TAP) = None;
+        }
+        if let Some(writer) = lock(&self.writer).take() {
+            let _ = writer.finalize();
+        }
+    }
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    writer: &SharedWriter,
+    pending: &PendingPlayback,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    let writer = Arc::clone(writer);
+    let pending = Arc::clone(pending);
+
+    device.build_input_stream(
+        config,
+        move |data: &[T], _| {
+            let mut writer = lock(&writer);
+            let Some(writer) = writer.as_mut() else {
+                return;
+            };
+            let mut pending = lock(&pending);
+
+            // Downmix to mono and add whatever the trainer played in the meantime
+            for frame in data.chunks(channels) {
+                let mic = frame.iter().map(|s| s.to_sample::<f32>()).sum::<f32>() / channels as f32;
+                let played = pending.pop_front().unwrap_or(0.0);
+                let _ = writer.write_sample((mic + played).clamp(-1.0, 1.0));
+            }
+        },
+        |e| eprintln!("❌ Recording error: {}", e),
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_session_file_name() {
+        let started = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(session_file_name(started), "session-1700000000.wav");
+    }
+
+    #[test]
+    fn test_resample_length() {
+        let samples = vec![0.5; 44100];
+        assert_eq!(resample(&samples, 44100, 48000).len(), 48000);
+        assert_eq!(resample(&samples, 44100, 22050).len(), 22050);
+        assert_eq!(resample(&samples, 44100, 44100), samples);
+    }
+}
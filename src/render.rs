@@ -0,0 +1,218 @@
+use crate::arpeggio::Arpeggio;
+use crate::audio::{SAMPLE_RATE, ToneConfig, render_mix, render_tone};
+use crate::chords::Chord;
+use crate::melody::{Event, Melody};
+use crate::notes::{NoteWithOctave, Scale, Tuning};
+use hound::{WavSpec, WavWriter};
+use std::path::Path;
+use std::time::Duration;
+
+/// Settings for rendering music offline, without an audio device
+#[derive(Debug, PartialEq, Clone)]
+pub struct RenderConfig {
+    pub tone: ToneConfig,
+    pub tuning: Tuning,
+    /// Length of each note, or of a quarter note in a melody; block chords last this long too
+    pub note_duration: Duration,
+    /// Silence between consecutive notes
+    pub gap: Duration,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            tone: ToneConfig::default(),
+            tuning: Tuning::default(),
+            note_duration: Duration::from_millis(500),
+            gap: Duration::ZERO,
+        }
+    }
+}
+
+/// Something that can be synthesized into a mono PCM buffer at `SAMPLE_RATE`
+pub trait Render {
+    fn render(&self, config: &RenderConfig) -> Vec<f32>;
+
+    /// Render and write the result as a 16-bit mono WAV file
+    fn render_to_wav(
+        &self,
+        path: impl AsRef<Path>,
+        config: &RenderConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        write_wav(path, &self.render(config))
+    }
+}
+
+impl Render for NoteWithOctave {
+    fn render(&self, config: &RenderConfig) -> Vec<f32> {
+        let frequency = config.tuning.frequency_of(self) as f32;
+        render_tone(frequency, config.note_duration, config.tone)
+    }
+}
+
+/// A melody: the notes one after another, separated by `gap`
+impl Render for [NoteWithOctave] {
+    fn render(&self, config: &RenderConfig) -> Vec<f32> {
+        let gap = silence(config.gap);
+        let mut samples = Vec::new();
+        for (i, note) in self.iter().enumerate() {
+            if i > 0 {
+                samples.extend_from_slice(&gap);
+            }
+            samples.extend(note.render(config));
+        }
+        samples
+    }
+}
+
+/// The melody in rhythm, with a quarter note lasting `note_duration`
+impl Render for Melody {
+    fn render(&self, config: &RenderConfig) -> Vec<f32> {
+        let mut samples = Vec::new();
+        for (event, value) in self.events() {
+            let duration = value.duration(config.note_duration);
+            match event {
+                Event::Note(note) => samples.extend(render_tone(
+                    config.tuning.frequency_of(note) as f32,
+                    duration,
+                    config.tone,
+                )),
+                Event::Rest => samples.extend(silence(duration)),
+            }
+        }
+        samples
+    }
+}
+
+/// The scale ascending from the root
+impl Render for Scale {
+    fn render(&self, config: &RenderConfig) -> Vec<f32> {
+        self.notes().render(config)
+    }
+}
+
+/// The arpeggio's tones one after another, in playing order
+impl Render for Arpeggio {
+    fn render(&self, config: &RenderConfig) -> Vec<f32> {
+        self.notes().render(config)
+    }
+}
+
+/// The chord as a block chord; render `chord.notes()` for an arpeggio
+impl Render for Chord {
+    fn render(&self, config: &RenderConfig) -> Vec<f32> {
+        let frequencies: Vec<f32> = self
+            .notes()
+            .iter()
+            .map(|n| config.tuning.frequency_of(n) as f32)
+            .collect();
+        render_mix(&frequencies, config.note_duration, config.tone)
+    }
+}
+
+/// Write mono samples in -1.0..=1.0 to a 16-bit PCM WAV file
+pub fn write_wav(
+    path: impl AsRef<Path>,
+    samples: &[f32],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = WavWriter::create(path, spec)?;
+    for &sample in samples {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+pub(crate) fn silence(duration: Duration) -> Vec<f32> {
+    vec![0.0; (duration.as_secs_f64() * SAMPLE_RATE as f64).round() as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chords::ChordQuality;
+    use crate::notes::ScaleType;
+
+    fn samples_for(duration: Duration) -> usize {
+        (duration.as_secs_f64() * SAMPLE_RATE as f64).round() as usize
+    }
+
+    #[test]
+    fn test_render_note_length() {
+        let config = RenderConfig::default();
+        let a4: NoteWithOctave = "A4".parse().unwrap();
+        let samples = a4.render(&config);
+        assert_eq!(samples.len(), samples_for(config.note_duration));
+        assert!(samples.iter().any(|s| s.abs() > 0.1));
+    }
+
+    #[test]
+    fn test_render_melody_with_gaps() {
+        let config = RenderConfig {
+            note_duration: Duration::from_millis(200),
+            gap: Duration::from_millis(100),
+            ..RenderConfig::default()
+        };
+        let melody: Vec<NoteWithOctave> = ["C4", "E4", "G4"]
+            .iter()
+            .map(|n| n.parse().unwrap())
+            .collect();
+        let samples = melody.render(&config);
+        assert_eq!(
+            samples.len(),
+            3 * samples_for(config.note_duration) + 2 * samples_for(config.gap)
+        );
+    }
+
+    #[test]
+    fn test_render_melody_rhythm() {
+        let config = RenderConfig::default();
+        let melody: Melody = "C4:h r:q E4:e".parse().unwrap();
+        let samples = melody.render(&config);
+        // Each event can round to the nearest sample on its own
+        let expected = samples_for(config.note_duration.mul_f64(3.5));
+        assert!(samples.len().abs_diff(expected) <= 3);
+        // The rest is silent
+        let rest_start = samples_for(config.note_duration * 2) + 10;
+        assert!(
+            samples[rest_start..rest_start + 100]
+                .iter()
+                .all(|&s| s == 0.0)
+        );
+    }
+
+    #[test]
+    fn test_render_scale_and_chord() {
+        let config = RenderConfig::default();
+        let scale = Scale::new("C4".parse().unwrap(), ScaleType::Major);
+        assert_eq!(
+            scale.render(&config).len(),
+            scale.notes().len() * samples_for(config.note_duration)
+        );
+
+        let chord = Chord::new("C4".parse().unwrap(), ChordQuality::Major);
+        let samples = chord.render(&config);
+        assert_eq!(samples.len(), samples_for(config.note_duration));
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_render_to_wav() {
+        let path = std::env::temp_dir().join(format!("ear-trainer-{}.wav", std::process::id()));
+        let config = RenderConfig::default();
+        let a4: NoteWithOctave = "A4".parse().unwrap();
+        a4.render_to_wav(&path, &config).unwrap();
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_rate, SAMPLE_RATE);
+        assert_eq!(reader.spec().channels, 1);
+        assert_eq!(reader.len() as usize, samples_for(config.note_duration));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,286 @@
+use crate::stats::{AnswerRecord, Exercise};
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::fs;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+/// A summary of one quiz session, built from the answers given in it
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionReport {
+    pub total: u32,
+    pub correct: u32,
+    /// Mean time to answer in milliseconds, or `None` before any answers
+    pub average_response_ms: Option<u64>,
+    /// One entry per exercise practised, in a fixed order
+    pub exercises: Vec<ExerciseReport>,
+}
+
+/// How one exercise went within a session
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExerciseReport {
+    pub exercise: Exercise,
+    pub total: u32,
+    pub correct: u32,
+    pub average_response_ms: u64,
+    /// Scores per question, e.g. per interval or per chord inversion, sorted by name
+    pub questions: Vec<QuestionReport>,
+    /// Wrong answers given for a question, most frequent first
+    pub confusions: Vec<Confusion>,
+}
+
+/// Score for one question, e.g. "Major 3rd"
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuestionReport {
+    pub question: String,
+    pub total: u32,
+    pub correct: u32,
+}
+
+/// A wrong answer and how often it was given, e.g. "Minor 6th" answered as "Major 6th"
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Confusion {
+    pub expected: String,
+    pub answered: String,
+    pub count: u32,
+}
+
+impl fmt::Display for Confusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} answered as {}", self.expected, self.answered)?;
+        if self.count > 1 {
+            write!(f, " ({} times)", self.count)?;
+        }
+        Ok(())
+    }
+}
+
+/// A summary line, then each exercise with its questions and confusions indented below it
+impl fmt::Display for SessionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} answers, {} right ({:.0}%)",
+            self.total,
+            self.correct,
+            percent(self.correct, self.total)
+        )?;
+        if let Some(ms) = self.average_response_ms {
+            write!(f, ", {:.1}s per answer", ms as f64 / 1000.0)?;
+        }
+        for exercise in &self.exercises {
+            write!(
+                f,
+                "\n{}: {}/{} ({:.0}%)",
+                exercise.exercise,
+                exercise.correct,
+                exercise.total,
+                percent(exercise.correct, exercise.total)
+            )?;
+            for question in &exercise.questions {
+                write!(
+                    f,
+                    "\n  {}: {}/{} ({:.0}%)",
+                    question.question,
+                    question.correct,
+                    question.total,
+                    percent(question.correct, question.total)
+                )?;
+            }
+            for confusion in &exercise.confusions {
+                write!(f, "\n  ❌ {}", confusion)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SessionReport {
+    /// Summarise `records`, usually the answers given since the session started
+    pub fn from_records(records: &[AnswerRecord]) -> Self {
+        let mut by_exercise: BTreeMap<Exercise, Vec<&AnswerRecord>> = BTreeMap::new();
+        for record in records {
+            by_exercise.entry(record.exercise).or_default().push(record);
+        }
+        Self {
+            total: records.len() as u32,
+            correct: records.iter().filter(|r| r.correct).count() as u32,
+            average_response_ms: average_ms(records.iter()),
+            exercises: by_exercise
+                .into_iter()
+                .map(|(exercise, records)| ExerciseReport::new(exercise, &records))
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Fraction of correct answers, 0.0 before any answers
+    pub fn accuracy(&self) -> f64 {
+        percent(self.correct, self.total) / 100.0
+    }
+
+    /// Every confusion in the session, most frequent first
+    pub fn confusions(&self) -> Vec<&Confusion> {
+        let mut confusions: Vec<&Confusion> = self
+            .exercises
+            .iter()
+            .flat_map(|exercise| &exercise.confusions)
+            .collect();
+        confusions.sort_by_key(|confusion| std::cmp::Reverse(confusion.count));
+        confusions
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Write the report to `path` as JSON
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+impl ExerciseReport {
+    fn new(exercise: Exercise, records: &[&AnswerRecord]) -> Self {
+        let mut questions: BTreeMap<&str, QuestionReport> = BTreeMap::new();
+        let mut confusions: BTreeMap<(&str, &str), u32> = BTreeMap::new();
+        for record in records {
+            let question = questions
+                .entry(&record.question)
+                .or_insert_with(|| QuestionReport {
+                    question: record.question.clone(),
+                    total: 0,
+                    correct: 0,
+                });
+            question.total += 1;
+            if record.correct {
+                question.correct += 1;
+            } else {
+                *confusions
+                    .entry((&record.question, &record.answer))
+                    .or_default() += 1;
+            }
+        }
+
+        let mut confusions: Vec<Confusion> = confusions
+            .into_iter()
+            .map(|((expected, answered), count)| Confusion {
+                expected: expected.to_string(),
+                answered: answered.to_string(),
+                count,
+            })
+            .collect();
+        // Stable, so ties stay in alphabetical order
+        confusions.sort_by_key(|confusion| std::cmp::Reverse(confusion.count));
+
+        Self {
+            exercise,
+            total: records.len() as u32,
+            correct: records.iter().filter(|r| r.correct).count() as u32,
+            average_response_ms: average_ms(records.iter().copied()).unwrap_or(0),
+            questions: questions.into_values().collect(),
+            confusions,
+        }
+    }
+}
+
+fn average_ms<'a>(records: impl Iterator<Item = &'a AnswerRecord>) -> Option<u64> {
+    let times: Vec<u64> = records.map(|r| r.response_ms).collect();
+    if times.is_empty() {
+        return None;
+    }
+    Some(times.iter().sum::<u64>() / times.len() as u64)
+}
+
+fn percent(correct: u32, total: u32) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    correct as f64 * 100.0 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn answer(exercise: Exercise, question: &str, answer: &str, ms: u64) -> AnswerRecord {
+        AnswerRecord::new(
+            exercise,
+            question,
+            answer,
+            question == answer,
+            Duration::from_millis(ms),
+        )
+    }
+
+    fn session() -> Vec<AnswerRecord> {
+        vec![
+            answer(Exercise::Intervals, "Minor 6th", "Major 6th", 3000),
+            answer(Exercise::Intervals, "Minor 6th", "Minor 6th", 2000),
+            answer(Exercise::Intervals, "Minor 6th", "Major 6th", 1000),
+            answer(Exercise::Intervals, "Perfect 5th", "Perfect 5th", 2000),
+            answer(Exercise::Cadences, "Half", "Plagal", 4000),
+        ]
+    }
+
+    #[test]
+    fn test_session_report() {
+        let report = SessionReport::from_records(&session());
+        assert_eq!(report.total, 5);
+        assert_eq!(report.correct, 2);
+        assert!((report.accuracy() - 0.4).abs() < 1e-9);
+        assert_eq!(report.average_response_ms, Some(2400));
+
+        let intervals = &report.exercises[0];
+        assert_eq!(intervals.exercise, Exercise::Intervals);
+        assert_eq!(intervals.average_response_ms, 2000);
+        assert_eq!(intervals.questions[0].question, "Minor 6th");
+        assert_eq!(intervals.questions[0].correct, 1);
+        assert_eq!(intervals.questions[1].total, 1);
+        assert_eq!(
+            intervals.confusions[0].to_string(),
+            "Minor 6th answered as Major 6th (2 times)"
+        );
+
+        let confusions = report.confusions();
+        assert_eq!(confusions.len(), 2);
+        assert_eq!(confusions[1].to_string(), "Half answered as Plagal");
+
+        assert!(SessionReport::from_records(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_report_display() {
+        let report = SessionReport::from_records(&session()[3..]);
+        assert_eq!(
+            report.to_string(),
+            "2 answers, 1 right (50%), 3.0s per answer\n\
+             Interval quiz: 1/1 (100%)\n  Perfect 5th: 1/1 (100%)\n\
+             Cadence quiz: 0/1 (0%)\n  Half: 0/1 (0%)\n  ❌ Half answered as Plagal"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_json() {
+        let report = SessionReport::from_records(&session()[3..]);
+        let json = report.to_json().unwrap();
+        assert!(json.contains("\"exercise\": \"cadences\""));
+        assert_eq!(
+            serde_json::from_str::<SessionReport>(&json).unwrap(),
+            report
+        );
+    }
+}
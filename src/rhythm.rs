@@ -0,0 +1,333 @@
+#[cfg(feature = "playback")]
+use crate::audio::AudioEngine;
+use crate::audio::SAMPLE_RATE;
+use crate::metronome::Click;
+#[cfg(feature = "playback")]
+use crate::metronome::Metronome;
+#[cfg(feature = "playback")]
+use crate::tempo::TimeSignature;
+use crate::tempo::{NoteValue, Tempo};
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Beats in a rhythm dictation pattern, one bar of 4/4
+pub const RHYTHM_BEATS: u8 = 4;
+
+/// How far a tap may land from its note and still count as in time
+pub const TAP_TOLERANCE: Duration = Duration::from_millis(80);
+
+/// Values a random rhythm is built from, with how often each is picked
+const RHYTHM_VALUES: [(NoteValue, u32); 4] = [
+    (NoteValue::Half, 1),
+    (NoteValue::DottedQuarter, 1),
+    (NoteValue::Quarter, 4),
+    (NoteValue::Eighth, 4),
+];
+
+/// A pattern of note lengths with no pitch, played as clicks
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rhythm {
+    pub values: Vec<NoteValue>,
+}
+
+/// Written with melody symbols, joining eighths or sixteenths that fill a beat, e.g. "q q ee q"
+impl fmt::Display for Rhythm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens = Vec::new();
+        let mut position = 0.0;
+        let mut i = 0;
+        while i < self.values.len() {
+            let value = self.values[i];
+            let per_beat = (1.0 / value.beats()) as usize;
+            let beamed = matches!(value, NoteValue::Eighth | NoteValue::Sixteenth)
+                && position % 1.0 == 0.0
+                && self.values[i..].iter().take(per_beat).all(|v| *v == value)
+                && self.values.len() - i >= per_beat;
+            let count = if beamed { per_beat } else { 1 };
+            tokens.push(value.symbol().repeat(count));
+            position += value.beats() * count as f64;
+            i += count;
+        }
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+/// Parse space-separated values like "q q e e q", where a run of one symbol such as
+/// "ee" or "ssss" stands for that many notes
+impl FromStr for Rhythm {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut values = Vec::new();
+        for token in s.split_whitespace() {
+            if let Ok(value) = token.parse::<NoteValue>() {
+                values.push(value);
+                continue;
+            }
+            let first = token.chars().next().unwrap_or_default();
+            let value = first
+                .to_string()
+                .parse::<NoteValue>()
+                .ok()
+                .filter(|_| token.chars().all(|c| c == first))
+                .ok_or_else(|| format!("Invalid rhythm value: {}", token))?;
+            values.extend(std::iter::repeat_n(value, token.chars().count()));
+        }
+        if values.is_empty() {
+            return Err("A rhythm needs at least one note".to_string());
+        }
+        Ok(Self { values })
+    }
+}
+
+impl Rhythm {
+    /// A random pattern exactly `beats` quarter notes long. Notes that start off the beat
+    /// are eighths, so the pattern finds its way back to the beat straight away.
+    pub fn random<R: Rng>(rng: &mut R, beats: u8) -> Self {
+        let mut values = Vec::new();
+        let mut remaining = beats as f64;
+        while remaining > 0.0 {
+            let off_beat = remaining % 1.0 != 0.0;
+            let choices: Vec<(NoteValue, u32)> = RHYTHM_VALUES
+                .iter()
+                .filter(|(value, _)| value.beats() <= remaining)
+                .filter(|(value, _)| !off_beat || *value == NoteValue::Eighth)
+                .copied()
+                .collect();
+            let value = WeightedIndex::new(choices.iter().map(|(_, weight)| weight))
+                .map(|weights| choices[weights.sample(rng)].0)
+                .unwrap_or(NoteValue::Eighth);
+            remaining -= value.beats();
+            values.push(value);
+        }
+        Self { values }
+    }
+
+    /// Length in quarter-note beats
+    pub fn beats(&self) -> f64 {
+        self.values.iter().map(|value| value.beats()).sum()
+    }
+
+    /// When each note starts after the first, at `tempo`
+    pub fn onsets(&self, tempo: Tempo) -> Vec<Duration> {
+        let beat = tempo.beat_duration();
+        let mut position = 0.0;
+        self.values
+            .iter()
+            .map(|value| {
+                let onset = beat.mul_f64(position);
+                position += value.beats();
+                onset
+            })
+            .collect()
+    }
+
+    /// The pattern as clicks at `tempo`, in a mono buffer at `SAMPLE_RATE`
+    pub fn render(&self, tempo: Tempo) -> Vec<f32> {
+        let length = tempo.beat_duration().mul_f64(self.beats());
+        let mut samples = vec![0.0; (length.as_secs_f64() * SAMPLE_RATE as f64) as usize];
+        for onset in self.onsets(tempo) {
+            let start = (onset.as_secs_f64() * SAMPLE_RATE as f64) as usize;
+            for (sample, value) in samples.iter_mut().skip(start).zip(Click::Accent.render()) {
+                *sample += value;
+            }
+        }
+        samples
+    }
+
+    /// Compare taps, one per note, with the pattern at `tempo`.
+    /// The first tap is lined up with the first note, so only the spacing matters.
+    pub fn score_taps(&self, taps: &[Instant], tempo: Tempo) -> TapScore {
+        let onsets = self.onsets(tempo);
+        let errors = match taps.first() {
+            Some(&first) => taps
+                .iter()
+                .zip(&onsets)
+                .map(|(&tap, onset)| {
+                    let tapped = (tap - first).as_secs_f64();
+                    (tapped - onset.as_secs_f64()) * 1000.0
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        TapScore {
+            errors,
+            missing: onsets.len().saturating_sub(taps.len()),
+            extra: taps.len().saturating_sub(onsets.len()),
+        }
+    }
+
+    /// Count in a bar of 4/4 clicks, then play the pattern
+    #[cfg(feature = "playback")]
+    pub fn play_with_count_in(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        count_in(engine, tempo)?;
+        engine.play_buffer(self.render(tempo))
+    }
+}
+
+/// Play a bar of 4/4 metronome clicks, e.g. before the user taps along
+#[cfg(feature = "playback")]
+pub fn count_in(engine: &AudioEngine, tempo: Tempo) -> Result<(), Box<dyn std::error::Error>> {
+    engine.play_buffer(Metronome::new(tempo, TimeSignature::COMMON).render_bar())
+}
+
+/// Read `count` taps, one per line (the Enter key), noting when each arrived
+pub fn read_taps(reader: &mut impl BufRead, count: usize) -> io::Result<Vec<Instant>> {
+    let mut taps = Vec::with_capacity(count);
+    let mut line = String::new();
+    while taps.len() < count {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        taps.push(Instant::now());
+    }
+    Ok(taps)
+}
+
+/// How closely taps followed a rhythm
+#[derive(Debug, PartialEq, Clone)]
+pub struct TapScore {
+    /// How far each tap landed from its note in milliseconds, positive when late
+    pub errors: Vec<f64>,
+    /// Notes with no tap
+    pub missing: usize,
+    /// Taps beyond the last note
+    pub extra: usize,
+}
+
+impl fmt::Display for TapScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} notes in time, {:.0} ms off on average",
+            self.in_time(),
+            self.errors.len() + self.missing,
+            self.mean_error_ms()
+        )?;
+        if self.missing > 0 {
+            write!(f, ", {} missed", self.missing)?;
+        }
+        if self.extra > 0 {
+            write!(f, ", {} extra", self.extra)?;
+        }
+        Ok(())
+    }
+}
+
+impl TapScore {
+    /// Taps within `TAP_TOLERANCE` of their note
+    pub fn in_time(&self) -> usize {
+        let tolerance = TAP_TOLERANCE.as_secs_f64() * 1000.0;
+        self.errors.iter().filter(|e| e.abs() <= tolerance).count()
+    }
+
+    /// Average distance of the taps from their notes, early or late
+    pub fn mean_error_ms(&self) -> f64 {
+        if self.errors.is_empty() {
+            return 0.0;
+        }
+        self.errors.iter().map(|e| e.abs()).sum::<f64>() / self.errors.len() as f64
+    }
+
+    /// Every note tapped in time, with nothing extra
+    pub fn is_correct(&self) -> bool {
+        self.missing == 0 && self.extra == 0 && self.in_time() == self.errors.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_parse_rhythm() {
+        let rhythm: Rhythm = "q q ee q".parse().unwrap();
+        assert_eq!(
+            rhythm.values,
+            vec![
+                NoteValue::Quarter,
+                NoteValue::Quarter,
+                NoteValue::Eighth,
+                NoteValue::Eighth,
+                NoteValue::Quarter,
+            ]
+        );
+        assert_eq!(rhythm.beats(), 4.0);
+        assert_eq!("q q e e q".parse::<Rhythm>().unwrap(), rhythm);
+        assert_eq!("q. e ssss q".parse::<Rhythm>().unwrap().values.len(), 7);
+
+        assert!("".parse::<Rhythm>().is_err());
+        assert!("q qe".parse::<Rhythm>().is_err());
+        assert!("x".parse::<Rhythm>().is_err());
+    }
+
+    #[test]
+    fn test_display_beams_whole_beats() {
+        for pattern in ["q q ee q", "q. e ssss q", "h ee ee", "e q q q e"] {
+            assert_eq!(pattern.parse::<Rhythm>().unwrap().to_string(), pattern);
+        }
+    }
+
+    #[test]
+    fn test_random_rhythms_fill_the_bar() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..50 {
+            let rhythm = Rhythm::random(&mut rng, RHYTHM_BEATS);
+            assert_eq!(rhythm.beats(), RHYTHM_BEATS as f64);
+            assert_eq!(rhythm.to_string().parse::<Rhythm>(), Ok(rhythm));
+        }
+    }
+
+    #[test]
+    fn test_onsets_and_render() {
+        let rhythm: Rhythm = "q ee h".parse().unwrap();
+        assert_eq!(
+            rhythm.onsets(Tempo(120)),
+            vec![
+                Duration::ZERO,
+                Duration::from_millis(500),
+                Duration::from_millis(750),
+                Duration::from_secs(1),
+            ]
+        );
+        assert_eq!(rhythm.render(Tempo(120)).len(), 2 * SAMPLE_RATE as usize);
+    }
+
+    #[test]
+    fn test_score_taps() {
+        let rhythm: Rhythm = "q ee q".parse().unwrap();
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        let steady = rhythm.score_taps(&[at(0), at(510), at(740), at(1000)], Tempo(120));
+        assert!(steady.is_correct());
+        assert!((steady.mean_error_ms() - 5.0).abs() < 1e-6);
+
+        let rushed = rhythm.score_taps(&[at(0), at(300), at(740)], Tempo(120));
+        assert!(!rushed.is_correct());
+        assert_eq!(rushed.in_time(), 2);
+        assert_eq!(rushed.missing, 1);
+        assert_eq!(
+            rushed.to_string(),
+            "2 of 4 notes in time, 70 ms off on average, 1 missed"
+        );
+    }
+
+    #[test]
+    fn test_read_taps() {
+        let mut input = io::Cursor::new("\n\n\n\n");
+        assert_eq!(read_taps(&mut input, 3).unwrap().len(), 3);
+        assert_eq!(read_taps(&mut input, 3).unwrap().len(), 1);
+    }
+}
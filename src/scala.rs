@@ -0,0 +1,382 @@
+use crate::notes::NoteWithOctave;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A scale from a Scala `.scl` file: the pitches of one period above the tonic, in cents.
+/// The last pitch is the period itself, usually the 2/1 octave.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ScalaScale {
+    pub description: String,
+    /// Cents above the tonic, excluding the tonic and ending with the period
+    pub cents: Vec<f64>,
+}
+
+/// Parse the text of an `.scl` file. Pitches with a decimal point are cents;
+/// anything else is a ratio like 3/2, or a whole number like 2.
+impl FromStr for ScalaScale {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().filter(|line| !line.starts_with('!'));
+        let description = lines
+            .next()
+            .ok_or("A Scala scale needs a description line")?
+            .trim()
+            .to_string();
+        let count_line = lines.next().ok_or("A Scala scale needs a note count")?;
+        let count = first_token(count_line)
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid note count in Scala scale: {}", count_line.trim()))?;
+        if count == 0 {
+            return Err("A Scala scale needs at least one pitch".to_string());
+        }
+
+        let cents = lines
+            .take(count)
+            .map(|line| parse_pitch(first_token(line)))
+            .collect::<Result<Vec<f64>, String>>()?;
+        if cents.len() < count {
+            return Err(format!(
+                "Scala scale has {} pitches but promises {}",
+                cents.len(),
+                count
+            ));
+        }
+        if cents[count - 1] <= 0.0 {
+            return Err("A Scala scale's period must be above the tonic".to_string());
+        }
+        Ok(Self { description, cents })
+    }
+}
+
+impl ScalaScale {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(std::fs::read_to_string(path)?.parse()?)
+    }
+
+    /// Notes per period
+    pub fn len(&self) -> usize {
+        self.cents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cents.is_empty()
+    }
+
+    /// Size of the period in cents, 1200 for an octave
+    pub fn period(&self) -> f64 {
+        self.cents[self.cents.len() - 1]
+    }
+
+    /// Cents above the tonic of a scale degree, counting on into higher (or lower) periods
+    pub fn degree_cents(&self, degree: i32) -> f64 {
+        let len = self.len() as i32;
+        let periods = degree.div_euclid(len);
+        let step = degree.rem_euclid(len);
+        let within = if step == 0 {
+            0.0
+        } else {
+            self.cents[step as usize - 1]
+        };
+        periods as f64 * self.period() + within
+    }
+}
+
+/// A Scala `.kbm` keyboard mapping: which scale degree each MIDI key plays, and the
+/// frequency that ties the scale to concert pitch
+#[derive(Debug, PartialEq, Clone)]
+pub struct KeyboardMapping {
+    /// Keys in one repetition of `mapping`; 0 maps every key to consecutive degrees
+    pub size: usize,
+    pub first_key: u8,
+    pub last_key: u8,
+    /// Key that plays the scale's tonic
+    pub middle_key: u8,
+    /// Key tuned to `reference_frequency`
+    pub reference_key: u8,
+    pub reference_frequency: f64,
+    /// Degree the mapping repeats at, usually the scale's period
+    pub octave_degree: usize,
+    /// Degree for each key of one repetition, `None` for keys left silent
+    pub mapping: Vec<Option<usize>>,
+}
+
+/// Parse the text of a `.kbm` file. Degrees are written as numbers, or `x` for unmapped keys.
+impl FromStr for KeyboardMapping {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s
+            .lines()
+            .filter(|line| !line.starts_with('!') && !line.trim().is_empty())
+            .map(first_token);
+        let mut header = |name: &str| -> Result<&str, String> {
+            lines
+                .next()
+                .ok_or_else(|| format!("Keyboard mapping is missing its {}", name))
+        };
+        let number = |name: &str, token: &str| -> Result<u8, String> {
+            token
+                .parse::<u8>()
+                .ok()
+                .filter(|&key| key <= 127)
+                .ok_or_else(|| format!("Invalid {} in keyboard mapping: {}", name, token))
+        };
+
+        let size_token = header("size")?;
+        let size = size_token
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid size in keyboard mapping: {}", size_token))?;
+        let first_key = number("first key", header("first key")?)?;
+        let last_key = number("last key", header("last key")?)?;
+        let middle_key = number("middle key", header("middle key")?)?;
+        let reference_key = number("reference key", header("reference key")?)?;
+        let frequency_token = header("reference frequency")?;
+        let reference_frequency = frequency_token
+            .parse::<f64>()
+            .ok()
+            .filter(|f| f.is_finite() && *f > 0.0)
+            .ok_or_else(|| format!("Invalid reference frequency: {}", frequency_token))?;
+        let octave_token = header("octave degree")?;
+        let octave_degree = octave_token
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid octave degree: {}", octave_token))?;
+
+        let mut mapping = lines
+            .take(size)
+            .map(|token| match token {
+                "x" | "X" => Ok(None),
+                _ => token
+                    .parse::<usize>()
+                    .map(Some)
+                    .map_err(|_| format!("Invalid degree in keyboard mapping: {}", token)),
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        // Keys past the end of a short mapping are silent
+        mapping.resize(size, None);
+
+        let keyboard = Self {
+            size,
+            first_key,
+            last_key,
+            middle_key,
+            reference_key,
+            reference_frequency,
+            octave_degree,
+            mapping,
+        };
+        if keyboard.degree(reference_key).is_none() {
+            return Err(format!(
+                "Keyboard mapping leaves its reference key {} unmapped",
+                reference_key
+            ));
+        }
+        Ok(keyboard)
+    }
+}
+
+impl KeyboardMapping {
+    /// Every key on consecutive degrees, with the tonic on middle C and A4 at `a4` Hz
+    pub fn linear(a4: f64) -> Self {
+        Self {
+            size: 0,
+            first_key: 0,
+            last_key: 127,
+            middle_key: 60,
+            reference_key: 69,
+            reference_frequency: a4,
+            octave_degree: 0,
+            mapping: Vec::new(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(std::fs::read_to_string(path)?.parse()?)
+    }
+
+    /// Scale degree the key plays, counted from the tonic on the middle key and
+    /// ignoring the scale's size, or `None` for a silent key
+    fn degree(&self, key: u8) -> Option<i32> {
+        if !(self.first_key..=self.last_key).contains(&key) {
+            return None;
+        }
+        let offset = key as i32 - self.middle_key as i32;
+        if self.size == 0 {
+            return Some(offset);
+        }
+        let repetitions = offset.div_euclid(self.size as i32);
+        let step = offset.rem_euclid(self.size as i32) as usize;
+        let degree = self.mapping[step]?;
+        Some(degree as i32 + repetitions * self.octave_degree as i32)
+    }
+}
+
+/// A Scala scale laid out on the keyboard, giving a frequency for each key
+#[derive(Debug, PartialEq, Clone)]
+pub struct ScalaTuning {
+    pub scale: ScalaScale,
+    pub keyboard: KeyboardMapping,
+}
+
+impl fmt::Display for ScalaTuning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.scale.description.is_empty() {
+            write!(f, "a {}-note Scala tuning", self.scale.len())
+        } else {
+            write!(f, "{}", self.scale.description)
+        }
+    }
+}
+
+impl ScalaTuning {
+    /// `scale` on consecutive keys with its tonic on middle C and A4 at `a4` Hz,
+    /// as Scala does without a keyboard mapping
+    pub fn new(scale: ScalaScale, a4: f64) -> Self {
+        Self {
+            scale,
+            keyboard: KeyboardMapping::linear(a4),
+        }
+    }
+
+    pub fn with_keyboard(scale: ScalaScale, keyboard: KeyboardMapping) -> Self {
+        Self { scale, keyboard }
+    }
+
+    /// Frequency of the key the note is on, or `None` if the mapping leaves it silent
+    pub fn frequency_of(&self, note: &NoteWithOctave) -> Option<f64> {
+        self.key_frequency(note.to_midi()?)
+    }
+
+    /// Frequency of a MIDI key, or `None` if the mapping leaves it silent
+    pub fn key_frequency(&self, key: u8) -> Option<f64> {
+        let degree = self.keyboard.degree(key)?;
+        let reference = self.keyboard.degree(self.keyboard.reference_key)?;
+        let cents = self.scale.degree_cents(degree) - self.scale.degree_cents(reference);
+        Some(self.keyboard.reference_frequency * 2.0_f64.powf(cents / 1200.0))
+    }
+}
+
+fn first_token(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or("")
+}
+
+/// Cents for a pitch written as cents (with a decimal point) or as a ratio
+fn parse_pitch(token: &str) -> Result<f64, String> {
+    let invalid = || format!("Invalid pitch in Scala scale: {}", token);
+    if token.contains('.') {
+        return token.parse::<f64>().map_err(|_| invalid());
+    }
+    let (numerator, denominator) = token.split_once('/').unwrap_or((token, "1"));
+    let numerator = numerator.parse::<u64>().map_err(|_| invalid())?;
+    let denominator = denominator.parse::<u64>().map_err(|_| invalid())?;
+    if numerator == 0 || denominator == 0 {
+        return Err(invalid());
+    }
+    Ok(1200.0 * (numerator as f64 / denominator as f64).log2())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JUST_MAJOR: &str = "! just.scl
+!
+Just major scale
+ 7
+!
+ 9/8
+ 5/4
+ 4/3
+ 3/2
+ 5/3
+ 15/8
+ 2/1
+";
+
+    const WHITE_KEYS: &str = "! white.kbm
+12
+0
+127
+60
+69
+440.0
+7
+0
+x
+1
+x
+2
+3
+x
+4
+x
+5
+x
+6
+";
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_parse_scale() {
+        let scale: ScalaScale = JUST_MAJOR.parse().unwrap();
+        assert_eq!(scale.description, "Just major scale");
+        assert_eq!(scale.len(), 7);
+        assert!(close(scale.period(), 1200.0));
+        assert!(close(scale.cents[1], 1200.0 * 1.25_f64.log2()));
+        assert!(close(scale.degree_cents(9), 1200.0 + scale.cents[1]));
+        assert!(close(scale.degree_cents(-1), -1200.0 + scale.cents[5]));
+
+        let cents: ScalaScale = "Quarter tones\n2\n50.0 cents\n100.0\n".parse().unwrap();
+        assert!(close(cents.period(), 100.0));
+    }
+
+    #[test]
+    fn test_invalid_scales() {
+        assert!("".parse::<ScalaScale>().is_err());
+        assert!("Empty\n0\n".parse::<ScalaScale>().is_err());
+        assert!("Short\n3\n3/2\n2/1\n".parse::<ScalaScale>().is_err());
+        assert!("Bad\n1\nfive\n".parse::<ScalaScale>().is_err());
+        assert!("Zero\n1\n0/1\n".parse::<ScalaScale>().is_err());
+    }
+
+    #[test]
+    fn test_linear_tuning() {
+        let equal: ScalaScale = (1..=12)
+            .map(|i| format!("{}.0", i * 100))
+            .fold("12-TET\n12\n".to_string(), |text, line| text + &line + "\n")
+            .parse()
+            .unwrap();
+        let tuning = ScalaTuning::new(equal, 440.0);
+        let c4: NoteWithOctave = "C4".parse().unwrap();
+        assert!(close(tuning.frequency_of(&c4).unwrap(), c4.frequency()));
+        assert!(close(tuning.key_frequency(81).unwrap(), 880.0));
+    }
+
+    #[test]
+    fn test_keyboard_mapping() {
+        let keyboard: KeyboardMapping = WHITE_KEYS.parse().unwrap();
+        assert_eq!(keyboard.size, 12);
+        assert_eq!(keyboard.mapping[1], None);
+        let tuning = ScalaTuning::with_keyboard(JUST_MAJOR.parse().unwrap(), keyboard);
+
+        // A4 is the reference, so C4 is a just major sixth below it
+        let a4 = tuning.key_frequency(69).unwrap();
+        assert!(close(a4, 440.0));
+        assert!(close(tuning.key_frequency(60).unwrap(), 440.0 * 3.0 / 5.0));
+        assert!(close(tuning.key_frequency(64).unwrap(), 440.0 * 3.0 / 4.0));
+        assert!(close(tuning.key_frequency(72).unwrap(), 440.0 * 6.0 / 5.0));
+        assert_eq!(tuning.key_frequency(61), None);
+        assert_eq!(tuning.to_string(), "Just major scale");
+    }
+
+    #[test]
+    fn test_invalid_keyboard_mappings() {
+        assert!("12\n0\n127\n60\n".parse::<KeyboardMapping>().is_err());
+        let silent_reference = WHITE_KEYS.replacen("\n69\n", "\n70\n", 1);
+        assert!(silent_reference.parse::<KeyboardMapping>().is_err());
+        let bad_frequency = WHITE_KEYS.replacen("440.0", "-1", 1);
+        assert!(bad_frequency.parse::<KeyboardMapping>().is_err());
+    }
+}
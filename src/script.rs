@@ -0,0 +1,259 @@
+#[cfg(feature = "playback")]
+use crate::audio::{AudioEngine, PlaybackOptions};
+use crate::audio::{render_mix, render_tone};
+use crate::chords::Chord;
+use crate::notes::NoteWithOctave;
+use crate::render::{Render, RenderConfig, silence};
+use crate::tempo::{NoteValue, Tempo};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// One thing a script does: sound notes, rest, or change the tempo for what follows
+#[derive(Debug, PartialEq, Clone)]
+pub enum Step {
+    Note(NoteWithOctave, NoteValue),
+    /// Notes sounding together, from a chord symbol or notes joined by "+"
+    Chord(Vec<NoteWithOctave>, NoteValue),
+    Rest(NoteValue),
+    Tempo(Tempo),
+}
+
+/// In script form, e.g. "C4:q", "C4+E4+G4:h", "r:e" or "tempo 90"
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Note(note, value) => write!(f, "{}:{}", note, value.symbol()),
+            Step::Chord(notes, value) => {
+                let names: Vec<String> = notes.iter().map(|n| n.to_string()).collect();
+                write!(f, "{}:{}", names.join("+"), value.symbol())
+            }
+            Step::Rest(value) => write!(f, "r:{}", value.symbol()),
+            Step::Tempo(tempo) => write!(f, "tempo {}", tempo.bpm()),
+        }
+    }
+}
+
+/// A piece of music written as text, to be piped in from other tools and played or
+/// rendered. Tokens are separated by whitespace and "#" starts a comment:
+///
+/// ```text
+/// # Notes and rests like a melody, quarter notes unless given a value
+/// tempo 90
+/// C4 D4:e E4:e r:q
+/// # Chord symbols, or notes joined by "+", sound together
+/// Am:h G7:h C4+E4+G4:w
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let steps: Vec<String> = self.steps.iter().map(|step| step.to_string()).collect();
+        write!(f, "{}", steps.join(" "))
+    }
+}
+
+/// Errors name the line they're on, counting from 1
+impl FromStr for Script {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut script = Script::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("");
+            let mut tokens = line.split_whitespace();
+            while let Some(token) = tokens.next() {
+                let step = if token.eq_ignore_ascii_case("tempo") {
+                    let bpm = tokens.next().unwrap_or("");
+                    bpm.parse().map(Step::Tempo)
+                } else {
+                    parse_step(token)
+                };
+                script
+                    .steps
+                    .push(step.map_err(|e| format!("Line {}: {}", i + 1, e))?);
+            }
+        }
+        Ok(script)
+    }
+}
+
+/// A note, rest or chord with an optional ":value"
+fn parse_step(token: &str) -> Result<Step, String> {
+    let (sound, value) = match token.split_once(':') {
+        Some((sound, value)) => (sound, value.parse::<NoteValue>()?),
+        None => (token, NoteValue::Quarter),
+    };
+    if matches!(sound, "r" | "rest") {
+        return Ok(Step::Rest(value));
+    }
+    if sound.contains('+') {
+        let notes = sound
+            .split('+')
+            .map(|note| note.parse())
+            .collect::<Result<Vec<NoteWithOctave>, String>>()?;
+        return Ok(Step::Chord(notes, value));
+    }
+    if let Ok(note) = sound.parse() {
+        return Ok(Step::Note(note, value));
+    }
+    match sound.parse::<Chord>() {
+        Ok(chord) => Ok(Step::Chord(chord.notes(), value)),
+        Err(_) => Err(format!("Not a note, rest or chord: {}", token)),
+    }
+}
+
+impl Script {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// The notes sounding at each step in order, with how long the step lasts when the
+    /// script starts at `tempo`; rests have no notes
+    pub fn timed_notes(&self, mut tempo: Tempo) -> Vec<(Vec<NoteWithOctave>, Duration)> {
+        let mut timed = Vec::new();
+        for step in &self.steps {
+            let (notes, value) = match step {
+                Step::Note(note, value) => (vec![*note], value),
+                Step::Chord(notes, value) => (notes.clone(), value),
+                Step::Rest(value) => (Vec::new(), value),
+                Step::Tempo(new_tempo) => {
+                    tempo = *new_tempo;
+                    continue;
+                }
+            };
+            timed.push((notes, tempo.duration_of(*value)));
+        }
+        timed
+    }
+
+    /// Play the script, starting at `tempo` until it sets its own
+    #[cfg(feature = "playback")]
+    pub fn play_on(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let opts = PlaybackOptions::new().with_tempo(tempo);
+        for (notes, duration) in self.timed_notes(tempo) {
+            if notes.is_empty() {
+                engine.rest(duration)?;
+            } else {
+                engine.play_notes_with(&notes, duration, &opts)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The script with a quarter note lasting `note_duration` until it sets a tempo
+impl Render for Script {
+    fn render(&self, config: &RenderConfig) -> Vec<f32> {
+        let mut beat = config.note_duration;
+        let mut samples = Vec::new();
+        for step in &self.steps {
+            let (notes, value) = match step {
+                Step::Note(note, value) => (std::slice::from_ref(note), value),
+                Step::Chord(notes, value) => (notes.as_slice(), value),
+                Step::Rest(value) => (&[][..], value),
+                Step::Tempo(tempo) => {
+                    beat = tempo.beat_duration();
+                    continue;
+                }
+            };
+            let duration = value.duration(beat);
+            let frequencies: Vec<f32> = notes
+                .iter()
+                .map(|n| config.tuning.frequency_of(n) as f32)
+                .collect();
+            samples.extend(match frequencies.as_slice() {
+                [] => silence(duration),
+                [frequency] => render_tone(*frequency, duration, config.tone),
+                _ => render_mix(&frequencies, duration, config.tone),
+            });
+        }
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::SAMPLE_RATE;
+
+    fn note(s: &str) -> NoteWithOctave {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script: Script =
+            "# A cadence\ntempo 90\nC4 D4:e r:e # then the chords\nAm:h C4+E4+G4:w"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            script.steps(),
+            [
+                Step::Tempo(Tempo(90)),
+                Step::Note(note("C4"), NoteValue::Quarter),
+                Step::Note(note("D4"), NoteValue::Eighth),
+                Step::Rest(NoteValue::Eighth),
+                Step::Chord(vec![note("A4"), note("C5"), note("E5")], NoteValue::Half),
+                Step::Chord(vec![note("C4"), note("E4"), note("G4")], NoteValue::Whole),
+            ]
+        );
+        assert_eq!(
+            script.to_string(),
+            "tempo 90 C4:q D4:e r:e A4+C5+E5:h C4+E4+G4:w"
+        );
+        assert_eq!(script.to_string().parse::<Script>(), Ok(script));
+        assert!(Script::new().to_string().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_script() {
+        assert_eq!(
+            "C4\nC4 X9".parse::<Script>(),
+            Err("Line 2: Not a note, rest or chord: X9".to_string())
+        );
+        assert!("tempo".parse::<Script>().is_err());
+        assert!("tempo fast".parse::<Script>().is_err());
+        assert!("C4:x".parse::<Script>().is_err());
+        assert!("C4+H4".parse::<Script>().is_err());
+        assert!("  # nothing\n\n".parse::<Script>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tempo_changes() {
+        let script: Script = "C4:h tempo 60 r:q E4+G4:q".parse().unwrap();
+        let timed = script.timed_notes(Tempo(120));
+        assert_eq!(timed[0], (vec![note("C4")], Duration::from_secs(1)));
+        assert_eq!(timed[1], (vec![], Duration::from_secs(1)));
+        assert_eq!(timed[2].1, Duration::from_secs(1));
+
+        let config = RenderConfig {
+            note_duration: Duration::from_millis(500),
+            ..RenderConfig::default()
+        };
+        let samples = script.render(&config);
+        assert!(samples.len().abs_diff(3 * SAMPLE_RATE as usize) <= 3);
+        // The rest is silent
+        let rest = SAMPLE_RATE as usize + 10..2 * SAMPLE_RATE as usize - 10;
+        assert!(samples[rest].iter().all(|&s| s == 0.0));
+    }
+}
@@ -0,0 +1,638 @@
+use crate::arpeggio::Arpeggio;
+use crate::chords::Chord;
+use crate::melody::{Event, Melody};
+use crate::notes::{NoteWithOctave, Scale};
+use crate::progression::Progression;
+use crate::tempo::{NoteValue, Tempo, TimeSignature};
+use std::fs;
+use std::path::Path;
+
+/// Resolution of exported files; divides evenly into every note value, triplets included
+pub const TICKS_PER_QUARTER: u16 = 480;
+
+/// Velocity of exported notes, a comfortable mezzo-forte
+pub const DEFAULT_VELOCITY: u8 = 90;
+
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+const META: u8 = 0xFF;
+const META_TEMPO: u8 = 0x51;
+const META_TIME_SIGNATURE: u8 = 0x58;
+const META_END_OF_TRACK: u8 = 0x2F;
+const SYSEX: u8 = 0xF0;
+const SYSEX_CONTINUATION: u8 = 0xF7;
+
+/// Timing slack when matching imported lengths to note values, in beats
+const QUANTIZE_TOLERANCE: f64 = 1.0 / 24.0;
+
+/// Imported notes sounding for at least this fraction of the time until the next note
+/// are read as lasting until it, so detached playing doesn't turn into extra rests
+const LEGATO_FRACTION: f64 = 1.0 / 3.0;
+
+/// Length of a note value in ticks
+pub fn ticks(value: NoteValue) -> u32 {
+    (value.beats() * TICKS_PER_QUARTER as f64).round() as u32
+}
+
+/// A note placed in time, in ticks from the start
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TimedNote {
+    /// MIDI note number
+    pub key: u8,
+    pub start: u32,
+    pub length: u32,
+    pub velocity: u8,
+}
+
+/// Notes laid out in time, ready to be written as a Standard MIDI File
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct MidiSequence {
+    notes: Vec<TimedNote>,
+    length: u32,
+}
+
+impl MidiSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sound `notes` together at the end of the sequence for `value`, then move past them.
+    /// Notes above G9 have no MIDI number and are left out.
+    pub fn push_notes(&mut self, notes: &[NoteWithOctave], value: NoteValue) {
+        let length = ticks(value);
+        self.notes.extend(notes.iter().filter_map(|note| {
+            Some(TimedNote {
+                key: note.to_midi()?,
+                start: self.length,
+                length,
+                velocity: DEFAULT_VELOCITY,
+            })
+        }));
+        self.length += length;
+    }
+
+    pub fn push_rest(&mut self, value: NoteValue) {
+        self.length += ticks(value);
+    }
+
+    pub fn notes(&self) -> &[TimedNote] {
+        &self.notes
+    }
+
+    /// Total length in ticks, including trailing rests
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// A type-0 file: one track in 4/4 with `tempo` set at the start, notes on channel 1
+    pub fn to_bytes(&self, tempo: Tempo) -> Vec<u8> {
+        // Note-offs sort before note-ons on the same tick so repeated notes retrigger
+        let mut events: Vec<(u32, [u8; 3])> = Vec::with_capacity(self.notes.len() * 2);
+        for note in &self.notes {
+            events.push((note.start + note.length, [NOTE_OFF, note.key & 0x7F, 0]));
+            events.push((note.start, [NOTE_ON, note.key & 0x7F, note.velocity & 0x7F]));
+        }
+        events.sort_by_key(|&(tick, [status, ..])| (tick, status == NOTE_ON));
+
+        let mut track = Vec::new();
+        let micros_per_quarter = 60_000_000 / tempo.bpm().max(1) as u32;
+        write_variable_length(&mut track, 0);
+        track.extend([META, META_TEMPO, 3]);
+        track.extend(&micros_per_quarter.to_be_bytes()[1..]);
+        write_variable_length(&mut track, 0);
+        track.extend([META, META_TIME_SIGNATURE, 4, 4, 2, 24, 8]);
+
+        let mut last = 0;
+        for (tick, message) in events {
+            write_variable_length(&mut track, tick - last);
+            track.extend(message);
+            last = tick;
+        }
+        write_variable_length(&mut track, self.length.saturating_sub(last));
+        track.extend([META, META_END_OF_TRACK, 0]);
+
+        let mut bytes = Vec::with_capacity(22 + track.len());
+        bytes.extend(b"MThd");
+        bytes.extend(6u32.to_be_bytes());
+        // Format 0, one track
+        bytes.extend(0u16.to_be_bytes());
+        bytes.extend(1u16.to_be_bytes());
+        bytes.extend(TICKS_PER_QUARTER.to_be_bytes());
+        bytes.extend(b"MTrk");
+        bytes.extend((track.len() as u32).to_be_bytes());
+        bytes.extend(track);
+        bytes
+    }
+}
+
+/// MIDI's variable-length quantity: seven bits a byte, most significant first
+fn write_variable_length(out: &mut Vec<u8>, value: u32) {
+    let mut groups = vec![(value & 0x7F) as u8];
+    let mut rest = value >> 7;
+    while rest > 0 {
+        groups.push((rest & 0x7F) as u8 | 0x80);
+        rest >>= 7;
+    }
+    out.extend(groups.iter().rev());
+}
+
+/// A melody read from a Standard MIDI File
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportedMelody {
+    pub melody: Melody,
+    /// The file's first tempo, if it sets one
+    pub tempo: Option<Tempo>,
+    /// The file's first time signature, if it sets one
+    pub time_signature: Option<TimeSignature>,
+}
+
+/// Read the melody from the first track with notes in a Standard MIDI File
+pub fn read_melody(path: impl AsRef<Path>) -> Result<ImportedMelody, Box<dyn std::error::Error>> {
+    Ok(parse_melody(&fs::read(path)?)?)
+}
+
+/// Parse a type-0 or type-1 file, taking the notes of the first track that has any.
+/// Chords are reduced to their top note, and lengths are rounded to the nearest note values.
+pub fn parse_melody(bytes: &[u8]) -> Result<ImportedMelody, String> {
+    let mut reader = ByteReader { bytes, position: 0 };
+    if reader.take(4)? != b"MThd" {
+        return Err("Not a Standard MIDI File".to_string());
+    }
+    let header_length = reader.u32()? as usize;
+    let header = reader.take(header_length)?;
+    if header.len() < 6 {
+        return Err("MIDI file header is too short".to_string());
+    }
+    let division = u16::from_be_bytes([header[4], header[5]]);
+    if division & 0x8000 != 0 {
+        return Err("MIDI files timed in SMPTE frames aren't supported".to_string());
+    }
+    if division == 0 {
+        return Err("MIDI file has no ticks per quarter note".to_string());
+    }
+
+    let mut tempo = None;
+    let mut time_signature = None;
+    let mut notes = Vec::new();
+    while reader.position < bytes.len() {
+        let id = reader.take(4)?;
+        let length = reader.u32()? as usize;
+        let chunk = reader.take(length)?;
+        if id != b"MTrk" {
+            continue;
+        }
+        let track = parse_track(chunk)?;
+        tempo = tempo.or(track.tempo);
+        time_signature = time_signature.or(track.time_signature);
+        if notes.is_empty() {
+            notes = track.notes;
+        }
+    }
+    if notes.is_empty() {
+        return Err("No notes found in the MIDI file".to_string());
+    }
+
+    Ok(ImportedMelody {
+        melody: melody_from_notes(&notes, division)?,
+        tempo,
+        time_signature,
+    })
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self.position + count;
+        let taken = self
+            .bytes
+            .get(self.position..end)
+            .ok_or("MIDI file ends unexpectedly")?;
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn variable_length(&mut self) -> Result<u32, String> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Invalid variable-length number in MIDI file".to_string())
+    }
+}
+
+struct Track {
+    notes: Vec<TimedNote>,
+    tempo: Option<Tempo>,
+    time_signature: Option<TimeSignature>,
+}
+
+fn parse_track(bytes: &[u8]) -> Result<Track, String> {
+    let mut reader = ByteReader { bytes, position: 0 };
+    let mut tick = 0u32;
+    let mut running_status = None;
+    let mut held: [Option<(u32, u8)>; 128] = [None; 128];
+    let mut track = Track {
+        notes: Vec::new(),
+        tempo: None,
+        time_signature: None,
+    };
+
+    while reader.position < bytes.len() {
+        tick += reader.variable_length()?;
+        let mut status = reader.u8()?;
+        match status {
+            META => {
+                let kind = reader.u8()?;
+                let length = reader.variable_length()? as usize;
+                let data = reader.take(length)?;
+                if kind == META_END_OF_TRACK {
+                    break;
+                }
+                if kind == META_TEMPO && data.len() == 3 && track.tempo.is_none() {
+                    let micros = u32::from_be_bytes([0, data[0], data[1], data[2]]);
+                    track.tempo = Tempo::from_bpm(60_000_000.0 / micros.max(1) as f64);
+                }
+                // The denominator is stored as a power of two
+                if kind == META_TIME_SIGNATURE && data.len() >= 2 && track.time_signature.is_none()
+                {
+                    track.time_signature = 1u8
+                        .checked_shl(data[1] as u32)
+                        .and_then(|unit| TimeSignature::new(data[0], unit).ok());
+                }
+                running_status = None;
+                continue;
+            }
+            SYSEX | SYSEX_CONTINUATION => {
+                let length = reader.variable_length()? as usize;
+                reader.take(length)?;
+                running_status = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        // A data byte here means the previous status is repeated
+        let first = if status < 0x80 {
+            let data = status;
+            status = running_status.ok_or("MIDI event without a status byte")?;
+            data
+        } else {
+            running_status = Some(status);
+            reader.u8()?
+        };
+        let kind = status & 0xF0;
+        let second = match kind {
+            0xC0 | 0xD0 => 0,
+            _ => reader.u8()?,
+        };
+
+        let key = (first & 0x7F) as usize;
+        if kind == NOTE_ON && second > 0 {
+            held[key] = Some((tick, second));
+        } else if (kind == NOTE_OFF || kind == NOTE_ON)
+            && let Some((start, velocity)) = held[key].take()
+        {
+            track.notes.push(TimedNote {
+                key: key as u8,
+                start,
+                length: tick - start,
+                velocity,
+            });
+        }
+    }
+
+    // Notes never released last until the end of the track
+    for (key, note) in held.iter().enumerate() {
+        if let Some((start, velocity)) = note {
+            track.notes.push(TimedNote {
+                key: key as u8,
+                start: *start,
+                length: tick - start,
+                velocity: *velocity,
+            });
+        }
+    }
+    Ok(track)
+}
+
+/// One line of notes in rhythm: the top note of each chord, with rests filling the gaps
+fn melody_from_notes(notes: &[TimedNote], division: u16) -> Result<Melody, String> {
+    let mut notes = notes.to_vec();
+    notes.sort_by_key(|note| (note.start, std::cmp::Reverse(note.key)));
+    notes.dedup_by_key(|note| note.start);
+
+    let beats = |ticks: u32| ticks as f64 / division as f64;
+    let mut melody = Melody::new();
+    for value in note_values(beats(notes[0].start)) {
+        melody = melody.with_rest(value);
+    }
+    for (i, note) in notes.iter().enumerate() {
+        let pitch = NoteWithOctave::from_midi(note.key)?;
+        let sounding = beats(note.length);
+        let Some(next) = notes.get(i + 1) else {
+            melody = melody.with_note(pitch, nearest_value(sounding));
+            break;
+        };
+        let until_next = beats(next.start - note.start);
+        let value = if sounding >= until_next * LEGATO_FRACTION {
+            nearest_value(until_next)
+        } else {
+            nearest_value(sounding)
+        };
+        melody = melody.with_note(pitch, value);
+        for rest in note_values(until_next - value.beats()) {
+            melody = melody.with_rest(rest);
+        }
+    }
+    Ok(melody)
+}
+
+/// The longest note value that fits in `beats`, or a sixteenth for anything shorter
+fn nearest_value(beats: f64) -> NoteValue {
+    NoteValue::ALL
+        .into_iter()
+        .find(|value| value.beats() <= beats + QUANTIZE_TOLERANCE)
+        .unwrap_or(NoteValue::Sixteenth)
+}
+
+/// Note values adding up to `beats`, longest first, dropping anything under half a sixteenth
+fn note_values(mut beats: f64) -> Vec<NoteValue> {
+    let mut values = Vec::new();
+    while beats >= NoteValue::Sixteenth.beats() / 2.0 {
+        let value = nearest_value(beats);
+        values.push(value);
+        beats -= value.beats();
+    }
+    values
+}
+
+/// Something that can be laid out as MIDI notes and saved for a DAW
+pub trait ToMidi {
+    fn to_midi(&self) -> MidiSequence;
+
+    /// Write a type-0 Standard MIDI File played at `tempo`
+    fn to_midi_file(
+        &self,
+        path: impl AsRef<Path>,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, self.to_midi().to_bytes(tempo))?;
+        Ok(())
+    }
+}
+
+/// The melody in rhythm, rests included
+impl ToMidi for Melody {
+    fn to_midi(&self) -> MidiSequence {
+        let mut sequence = MidiSequence::new();
+        for (event, value) in self.events() {
+            match event {
+                Event::Note(note) => sequence.push_notes(&[*note], *value),
+                Event::Rest => sequence.push_rest(*value),
+            }
+        }
+        sequence
+    }
+}
+
+/// The scale ascending from the root in quarter notes, as it plays
+impl ToMidi for Scale {
+    fn to_midi(&self) -> MidiSequence {
+        Melody::from_scale(self, NoteValue::Quarter).to_midi()
+    }
+}
+
+/// The arpeggio in eighth notes, as it plays
+impl ToMidi for Arpeggio {
+    fn to_midi(&self) -> MidiSequence {
+        self.melody(NoteValue::Eighth).to_midi()
+    }
+}
+
+/// The chord as a whole-note block chord; export `Melody::from_chord` for an arpeggio
+impl ToMidi for Chord {
+    fn to_midi(&self) -> MidiSequence {
+        let mut sequence = MidiSequence::new();
+        sequence.push_notes(&self.notes(), NoteValue::Whole);
+        sequence
+    }
+}
+
+/// Each chord as a half-note block chord, as it plays
+impl ToMidi for Progression {
+    fn to_midi(&self) -> MidiSequence {
+        let mut sequence = MidiSequence::new();
+        for chord in self.chords() {
+            sequence.push_notes(&chord.notes(), NoteValue::Half);
+        }
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arpeggio::ArpeggioPattern;
+    use crate::chords::ChordQuality;
+    use crate::notes::ScaleType;
+
+    fn variable_length(value: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_variable_length(&mut out, value);
+        out
+    }
+
+    #[test]
+    fn test_variable_length() {
+        assert_eq!(variable_length(0), vec![0x00]);
+        assert_eq!(variable_length(0x7F), vec![0x7F]);
+        assert_eq!(variable_length(0x80), vec![0x81, 0x00]);
+        assert_eq!(variable_length(480), vec![0x83, 0x60]);
+        assert_eq!(variable_length(0x0FFF_FFFF), vec![0xFF, 0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn test_melody_sequence() {
+        let melody: Melody = "C4:q r:e E4:h".parse().unwrap();
+        let sequence = melody.to_midi();
+        assert_eq!(
+            sequence.notes(),
+            &[
+                TimedNote {
+                    key: 60,
+                    start: 0,
+                    length: 480,
+                    velocity: DEFAULT_VELOCITY
+                },
+                TimedNote {
+                    key: 64,
+                    start: 720,
+                    length: 960,
+                    velocity: DEFAULT_VELOCITY
+                },
+            ]
+        );
+        assert_eq!(sequence.length(), 1680);
+        assert_eq!(ticks(NoteValue::EighthTriplet), 160);
+    }
+
+    #[test]
+    fn test_chord_and_progression_sequences() {
+        let chord = Chord::new("C4".parse().unwrap(), ChordQuality::Major7);
+        let sequence = chord.to_midi();
+        assert_eq!(sequence.notes().len(), 4);
+        assert!(
+            sequence
+                .notes()
+                .iter()
+                .all(|n| n.start == 0 && n.length == 1920)
+        );
+
+        let key = Scale::new("C4".parse().unwrap(), ScaleType::Major);
+        let progression = Progression::parse(key.clone(), "ii-V-I").unwrap();
+        let sequence = progression.to_midi();
+        assert_eq!(sequence.notes().len(), 9);
+        assert_eq!(sequence.notes()[3].start, 960);
+        assert_eq!(sequence.length(), 3 * 960);
+        assert_eq!(key.to_midi().notes().len(), key.notes().len());
+
+        let arpeggio = Arpeggio::new(chord, ArpeggioPattern::UpDown);
+        let sequence = arpeggio.to_midi();
+        assert_eq!(sequence.notes().len(), 9);
+        assert_eq!(sequence.notes()[1].start, 240);
+    }
+
+    #[test]
+    fn test_file_layout() {
+        let melody: Melody = "A4:q A4:q".parse().unwrap();
+        let bytes = melody.to_midi().to_bytes(Tempo(120));
+        assert_eq!(&bytes[..4], b"MThd");
+        assert_eq!(&bytes[8..14], &[0, 0, 0, 1, 0x01, 0xE0]);
+        assert_eq!(&bytes[14..18], b"MTrk");
+        let length = u32::from_be_bytes(bytes[18..22].try_into().unwrap()) as usize;
+        let track = &bytes[22..];
+        assert_eq!(track.len(), length);
+
+        // 500,000 microseconds per quarter at 120 BPM
+        assert_eq!(&track[..7], &[0, META, META_TEMPO, 3, 0x07, 0xA1, 0x20]);
+        let notes = &track[15..];
+        assert_eq!(
+            notes,
+            &[
+                0x00,
+                NOTE_ON,
+                69,
+                DEFAULT_VELOCITY, // first A4
+                0x83,
+                0x60,
+                NOTE_OFF,
+                69,
+                0, // released after a quarter...
+                0x00,
+                NOTE_ON,
+                69,
+                DEFAULT_VELOCITY, // ...just before the repeat
+                0x83,
+                0x60,
+                NOTE_OFF,
+                69,
+                0, //
+                0x00,
+                META,
+                META_END_OF_TRACK,
+                0,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_round_trip() {
+        for text in [
+            "C4:q D4:e E4:e F4:h",
+            "r:q G4:q. A4:e r:h B4:w",
+            "C5:e3 D5:e3 E5:e3 F5:h3 G5:h3 A5:h3 C5:s C5:s",
+        ] {
+            let melody: Melody = text.parse().unwrap();
+            let imported = parse_melody(&melody.to_midi().to_bytes(Tempo(96))).unwrap();
+            assert_eq!(imported.melody.to_string(), text);
+            assert_eq!(imported.tempo, Some(Tempo(96)));
+            assert_eq!(imported.time_signature, Some(TimeSignature::COMMON));
+        }
+    }
+
+    #[test]
+    fn test_import_hand_written_file() {
+        #[rustfmt::skip]
+        let track: &[u8] = &[
+            // A C major triad held for a quarter, played as separate note-ons
+            0x00, NOTE_ON, 60, 80,
+            0x00, 64, 80, // running status
+            0x00, 67, 80,
+            0x83, 0x60, 60, 0, // a zero-velocity note-on releases the note
+            0x00, 64, 0,
+            0x00, 67, 0,
+            // A detached eighth, well short of the next note, leaves a rest
+            0x00, NOTE_ON, 74, 100,
+            0x81, 0x70, NOTE_OFF, 74, 0,
+            // An E5 on another channel that is never released
+            0x85, 0x50, NOTE_ON | 1, 76, 100,
+            0x83, 0x60, 0xB0, 64, 127, // sustain pedal, ignored
+            0x00, META, META_END_OF_TRACK, 0,
+        ];
+        // Format 1: a tempo-only track comes first
+        let mut bytes = b"MThd\0\0\0\x06\0\x01\0\x02\x01\xE0".to_vec();
+        let tempo_track = [
+            0x00, META, META_TEMPO, 3, 0x09, 0x27, 0xC0, 0x00, META, 0x2F, 0,
+        ];
+        for chunk in [&tempo_track[..], track] {
+            bytes.extend(b"MTrk");
+            bytes.extend((chunk.len() as u32).to_be_bytes());
+            bytes.extend(chunk);
+        }
+
+        let imported = parse_melody(&bytes).unwrap();
+        assert_eq!(imported.tempo, Some(Tempo(100)));
+        assert_eq!(imported.time_signature, None);
+        assert_eq!(imported.melody.to_string(), "G4:q D5:e r:q. E5:q");
+    }
+
+    #[test]
+    fn test_import_rejects_bad_files() {
+        assert!(parse_melody(b"RIFF....").is_err());
+        assert!(parse_melody(b"MThd\0\0\0\x06\0\0\0\x01\xE7\x28").is_err());
+        let empty = MidiSequence::new().to_bytes(Tempo(120));
+        assert!(parse_melody(&empty).unwrap_err().contains("No notes"));
+        assert!(parse_melody(&empty[..empty.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn test_write_midi_file() {
+        let path = std::env::temp_dir().join(format!("ear-trainer-{}.mid", std::process::id()));
+        let scale = Scale::new("D4".parse().unwrap(), ScaleType::Minor);
+        scale.to_midi_file(&path, Tempo(90)).unwrap();
+        assert_eq!(
+            fs::read(&path).unwrap(),
+            scale.to_midi().to_bytes(Tempo(90))
+        );
+        let imported = read_melody(&path).unwrap();
+        assert_eq!(imported.melody.notes(), scale.notes());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,422 @@
+use crate::notes::{Note, NoteWithOctave, is_helmholtz, split_helmholtz};
+use std::fmt;
+use std::str::FromStr;
+
+/// The seven letter names, in scale order from C
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NoteName {
+    C,
+    D,
+    E,
+    F,
+    G,
+    A,
+    B,
+}
+
+impl fmt::Display for NoteName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NoteName::C => "C",
+            NoteName::D => "D",
+            NoteName::E => "E",
+            NoteName::F => "F",
+            NoteName::G => "G",
+            NoteName::A => "A",
+            NoteName::B => "B",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for NoteName {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "C" => Ok(NoteName::C),
+            "D" => Ok(NoteName::D),
+            "E" => Ok(NoteName::E),
+            "F" => Ok(NoteName::F),
+            "G" => Ok(NoteName::G),
+            "A" => Ok(NoteName::A),
+            "B" => Ok(NoteName::B),
+            _ => Err(format!("Invalid note name: {}", s)),
+        }
+    }
+}
+
+impl NoteName {
+    const ALL: [NoteName; 7] = [
+        NoteName::C,
+        NoteName::D,
+        NoteName::E,
+        NoteName::F,
+        NoteName::G,
+        NoteName::A,
+        NoteName::B,
+    ];
+
+    /// Position within the octave, C = 0 to B = 6
+    pub fn index(&self) -> i32 {
+        *self as i32
+    }
+
+    /// Letter name at the given position, wrapping around the octave
+    pub fn from_index(index: i32) -> NoteName {
+        NoteName::ALL[index.rem_euclid(7) as usize]
+    }
+
+    /// Semitones above C of the natural note, e.g. 4 for E
+    pub fn natural_semitone(&self) -> i32 {
+        match self {
+            NoteName::C => 0,
+            NoteName::D => 2,
+            NoteName::E => 4,
+            NoteName::F => 5,
+            NoteName::G => 7,
+            NoteName::A => 9,
+            NoteName::B => 11,
+        }
+    }
+}
+
+/// How far a written note is raised or lowered from its letter
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Accidental {
+    DoubleFlat,
+    Flat,
+    Natural,
+    Sharp,
+    DoubleSharp,
+}
+
+impl fmt::Display for Accidental {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Accidental::DoubleFlat => "bb",
+            Accidental::Flat => "b",
+            Accidental::Natural => "",
+            Accidental::Sharp => "#",
+            Accidental::DoubleSharp => "x",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl Accidental {
+    /// Semitones added to the natural note
+    pub fn semitones(&self) -> i32 {
+        match self {
+            Accidental::DoubleFlat => -2,
+            Accidental::Flat => -1,
+            Accidental::Natural => 0,
+            Accidental::Sharp => 1,
+            Accidental::DoubleSharp => 2,
+        }
+    }
+
+    pub fn from_semitones(semitones: i32) -> Option<Accidental> {
+        match semitones {
+            -2 => Some(Accidental::DoubleFlat),
+            -1 => Some(Accidental::Flat),
+            0 => Some(Accidental::Natural),
+            1 => Some(Accidental::Sharp),
+            2 => Some(Accidental::DoubleSharp),
+            _ => None,
+        }
+    }
+}
+
+/// A note as it is written, so Db and C# stay distinct even though they sound the same
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpelledNote {
+    pub name: NoteName,
+    pub accidental: Accidental,
+}
+
+impl fmt::Display for SpelledNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.name, self.accidental)
+    }
+}
+
+/// Parse a written note like "Db", "C#", "Fx" (or "F##") and "Bbb", keeping the spelling
+impl FromStr for SpelledNote {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid note: {}", s);
+        let split = s.char_indices().nth(1).map_or(s.len(), |(i, _)| i);
+        let (name, accidental) = s.split_at(split);
+        let name = name.parse::<NoteName>().map_err(|_| invalid())?;
+        let accidental = match accidental {
+            "" => Accidental::Natural,
+            "#" => Accidental::Sharp,
+            "b" => Accidental::Flat,
+            "x" | "##" => Accidental::DoubleSharp,
+            "bb" => Accidental::DoubleFlat,
+            _ => return Err(invalid()),
+        };
+        Ok(SpelledNote::new(name, accidental))
+    }
+}
+
+impl SpelledNote {
+    pub fn new(name: NoteName, accidental: Accidental) -> Self {
+        Self { name, accidental }
+    }
+
+    /// The usual spelling of a pitch class: naturals, or sharps for the black keys
+    pub fn from_note(note: &Note) -> SpelledNote {
+        let name = match note {
+            Note::C | Note::CSharp => NoteName::C,
+            Note::D | Note::DSharp => NoteName::D,
+            Note::E => NoteName::E,
+            Note::F | Note::FSharp => NoteName::F,
+            Note::G | Note::GSharp => NoteName::G,
+            Note::A | Note::ASharp => NoteName::A,
+            Note::B => NoteName::B,
+        };
+        SpelledNote::with_name(note, name).expect("sharp spelling is always reachable")
+    }
+
+    /// Spell the pitch class `pitch` using the letter `name`, if an accidental can reach it
+    pub fn with_name(pitch: &Note, name: NoteName) -> Option<SpelledNote> {
+        // Shortest signed distance from the natural note, in -6..=5
+        let offset = (pitch.to_semitone() - name.natural_semitone() + 6).rem_euclid(12) - 6;
+        Some(SpelledNote::new(name, Accidental::from_semitones(offset)?))
+    }
+
+    /// The note reached by moving `fifths` perfect fifths up from C (down when negative)
+    ///
+    /// Panics outside -15 (Fbb) to 19 (Bx), where a triple accidental would be needed.
+    pub fn from_fifths(fifths: i32) -> SpelledNote {
+        let pitch = Note::from_semitone((7 * fifths).rem_euclid(12) as u8)
+            .expect("semitone is within 0-11");
+        // Each fifth moves four letter names up
+        SpelledNote::with_name(&pitch, NoteName::from_index(4 * fifths))
+            .expect("fifths within the documented range need at most a double accidental")
+    }
+
+    /// The sounding pitch class
+    pub fn pitch(&self) -> Note {
+        let semitone = self.name.natural_semitone() + self.accidental.semitones();
+        Note::from_semitone(semitone.rem_euclid(12) as u8).expect("semitone is within 0-11")
+    }
+
+    /// Position on the circle of fifths relative to C, e.g. 7 for C# and -5 for Db
+    pub fn fifths(&self) -> i32 {
+        // F C G D A E B are -1 to 5 fifths from C
+        let natural = (2 * self.name.index() + 1).rem_euclid(7) - 1;
+        natural + 7 * self.accidental.semitones()
+    }
+
+    /// The note `fifths` steps along the circle of fifths, e.g. F moved -1 is Bb.
+    /// `None` where that would take a triple accidental.
+    pub fn shift_fifths(&self, fifths: i32) -> Option<SpelledNote> {
+        let fifths = self.fifths() + fifths;
+        (-15..=19)
+            .contains(&fifths)
+            .then(|| SpelledNote::from_fifths(fifths))
+    }
+}
+
+/// A spelled note in a specific octave. The octave follows the letter name,
+/// so Cb4 sounds the same as B3 and B#3 the same as C4.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpelledPitch {
+    pub note: SpelledNote,
+    pub octave: u8,
+}
+
+impl fmt::Display for SpelledPitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.note, self.octave)
+    }
+}
+
+/// Parse a written note with an optional octave like "Db4", defaulting to octave 4,
+/// or in Helmholtz notation like "db'"
+impl FromStr for SpelledPitch {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (note, octave) = if is_helmholtz(s) {
+            let (name, octave) = split_helmholtz(s)?;
+            (name.parse::<SpelledNote>()?, octave)
+        } else {
+            let (note_str, octave_str) = match s.find(|c: char| c.is_ascii_digit()) {
+                Some(pos) => (&s[..pos], &s[pos..]),
+                None => (s, "4"),
+            };
+            let octave = octave_str.parse::<u8>().map_err(|e| e.to_string())?;
+            (note_str.parse::<SpelledNote>()?, octave)
+        };
+        let spelled = SpelledPitch { note, octave };
+        spelled
+            .pitch()
+            .ok_or_else(|| format!("{} is out of range", spelled))?;
+        Ok(spelled)
+    }
+}
+
+impl SpelledPitch {
+    /// The usual spelling of a sounding note, with sharps for the black keys
+    pub fn from_pitch(pitch: &NoteWithOctave) -> SpelledPitch {
+        SpelledPitch {
+            note: SpelledNote::from_note(&pitch.note),
+            octave: pitch.octave,
+        }
+    }
+
+    /// Spell a sounding note with the letter `name`, moving the written octave if needed
+    pub fn spell(pitch: &NoteWithOctave, name: NoteName) -> Option<SpelledPitch> {
+        let note = SpelledNote::with_name(&pitch.note, name)?;
+        let natural = pitch.absolute_semitone() - note.accidental.semitones();
+        let octave = (natural - name.natural_semitone()).div_euclid(12);
+        Some(SpelledPitch {
+            note,
+            octave: u8::try_from(octave).ok()?,
+        })
+    }
+
+    /// The sounding note, or `None` when it falls outside the supported octaves
+    pub fn pitch(&self) -> Option<NoteWithOctave> {
+        NoteWithOctave::from_absolute_semitone(
+            self.octave as i32 * 12
+                + self.note.name.natural_semitone()
+                + self.note.accidental.semitones(),
+        )
+    }
+}
+
+/// Letter names between a chord or scale root and a note `semitones` above it,
+/// for scales that don't use every letter exactly once
+pub(crate) fn letter_steps(semitones: u8) -> i32 {
+    const STEPS: [i32; 12] = [0, 1, 1, 2, 2, 3, 4, 4, 5, 5, 6, 6];
+    STEPS[(semitones % 12) as usize] + 7 * (semitones / 12) as i32
+}
+
+/// The ways of writing a move by `semitones` as steps along the circle of fifths, simplest
+/// interval first: 5 semitones is a 4th (-1) or an augmented 3rd (11), and 6 is an augmented
+/// 4th (6) or a diminished 5th (-6). Whole octaves keep every note's spelling.
+pub(crate) fn transposition_fifths(semitones: i32) -> Vec<i32> {
+    if semitones.rem_euclid(12) == 0 {
+        return vec![0];
+    }
+    // A semitone is seven fifths, so this is the step in -5..=6
+    let fifths = (7 * semitones + 5).rem_euclid(12) - 5;
+    vec![fifths, fifths - 12, fifths + 12]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spelled_note_pitch() {
+        let d_flat = SpelledNote::new(NoteName::D, Accidental::Flat);
+        let c_sharp = SpelledNote::new(NoteName::C, Accidental::Sharp);
+        assert_eq!(d_flat.pitch(), c_sharp.pitch());
+        assert_ne!(d_flat, c_sharp);
+        assert_eq!(d_flat.to_string(), "Db");
+
+        // Wraps around the octave
+        let c_flat = SpelledNote::new(NoteName::C, Accidental::Flat);
+        assert_eq!(c_flat.pitch(), Note::B);
+    }
+
+    #[test]
+    fn test_spell_with_name() {
+        let e_sharp = SpelledNote::with_name(&Note::F, NoteName::E).unwrap();
+        assert_eq!(e_sharp.to_string(), "E#");
+        let b_sharp = SpelledNote::with_name(&Note::C, NoteName::B).unwrap();
+        assert_eq!(b_sharp.to_string(), "B#");
+        assert!(SpelledNote::with_name(&Note::G, NoteName::C).is_none());
+    }
+
+    #[test]
+    fn test_spell_from_fifths() {
+        assert_eq!(SpelledNote::from_fifths(0).to_string(), "C");
+        assert_eq!(SpelledNote::from_fifths(6).to_string(), "F#");
+        assert_eq!(SpelledNote::from_fifths(7).to_string(), "C#");
+        assert_eq!(SpelledNote::from_fifths(-1).to_string(), "F");
+        assert_eq!(SpelledNote::from_fifths(-6).to_string(), "Gb");
+        assert_eq!(SpelledNote::from_fifths(-8).to_string(), "Fb");
+        assert_eq!(SpelledNote::from_fifths(12).to_string(), "B#");
+    }
+
+    #[test]
+    fn test_parse_spelled_notes() {
+        let d_flat: SpelledNote = "Db".parse().unwrap();
+        assert_eq!(d_flat.to_string(), "Db");
+        assert_eq!(d_flat.pitch(), Note::CSharp);
+        assert_eq!("E#".parse::<SpelledNote>().unwrap().pitch(), Note::F);
+        assert!("H".parse::<SpelledNote>().is_err());
+        assert!("C$".parse::<SpelledNote>().is_err());
+        assert!("".parse::<SpelledNote>().is_err());
+    }
+
+    #[test]
+    fn test_spelled_pitch_octaves() {
+        let c_flat: SpelledPitch = "Cb4".parse().unwrap();
+        assert_eq!(c_flat.pitch().unwrap().to_string(), "B3");
+
+        let b_sharp: SpelledPitch = "B#3".parse().unwrap();
+        assert_eq!(b_sharp.pitch().unwrap().to_string(), "C4");
+
+        let respelled = SpelledPitch::spell(&"C4".parse().unwrap(), NoteName::B).unwrap();
+        assert_eq!(respelled, b_sharp);
+        let respelled = SpelledPitch::spell(&"B3".parse().unwrap(), NoteName::C).unwrap();
+        assert_eq!(respelled, c_flat);
+
+        assert_eq!("Gb".parse::<SpelledPitch>().unwrap().to_string(), "Gb4");
+        assert!("Cb0".parse::<SpelledPitch>().is_err());
+        assert_eq!("db'".parse::<SpelledPitch>().unwrap().to_string(), "Db4");
+        assert_eq!("Bb,".parse::<SpelledPitch>().unwrap().to_string(), "Bb1");
+    }
+
+    #[test]
+    fn test_spelled_note_fifths() {
+        assert_eq!("C".parse::<SpelledNote>().unwrap().fifths(), 0);
+        assert_eq!("F".parse::<SpelledNote>().unwrap().fifths(), -1);
+        assert_eq!("B".parse::<SpelledNote>().unwrap().fifths(), 5);
+        assert_eq!("C#".parse::<SpelledNote>().unwrap().fifths(), 7);
+        assert_eq!("Db".parse::<SpelledNote>().unwrap().fifths(), -5);
+        for fifths in -8..=12 {
+            assert_eq!(SpelledNote::from_fifths(fifths).fifths(), fifths);
+        }
+    }
+
+    #[test]
+    fn test_double_accidentals() {
+        let f_double_sharp: SpelledNote = "Fx".parse().unwrap();
+        assert_eq!(f_double_sharp, "F##".parse().unwrap());
+        assert_eq!(f_double_sharp.to_string(), "Fx");
+        assert_eq!(f_double_sharp.pitch(), Note::G);
+
+        let b_double_flat: SpelledNote = "Bbb".parse().unwrap();
+        assert_eq!(b_double_flat.to_string(), "Bbb");
+        assert_eq!(b_double_flat.pitch(), Note::A);
+        assert_ne!(b_double_flat, "A".parse().unwrap());
+
+        assert!("C###".parse::<SpelledNote>().is_err());
+        assert!("Dbbb".parse::<SpelledNote>().is_err());
+
+        let spelled = SpelledNote::with_name(&Note::G, NoteName::F).unwrap();
+        assert_eq!(spelled.accidental, Accidental::DoubleSharp);
+        assert!(SpelledNote::with_name(&Note::GSharp, NoteName::F).is_none());
+
+        assert_eq!(SpelledNote::from_fifths(13).to_string(), "Fx");
+        assert_eq!(SpelledNote::from_fifths(-9).to_string(), "Bbb");
+        assert_eq!(SpelledNote::from_fifths(19).fifths(), 19);
+
+        // Bx3 sounds as C#4
+        let b_double_sharp: SpelledPitch = "Bx3".parse().unwrap();
+        assert_eq!(b_double_sharp.pitch().unwrap().to_string(), "C#4");
+    }
+}
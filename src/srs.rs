@@ -0,0 +1,251 @@
+use crate::stats::{AnswerRecord, Exercise, StatsStore, unix_now};
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Starting ease factor for an item that has never been reviewed
+pub const DEFAULT_EASE: f64 = 2.5;
+
+/// Lowest ease factor, so hard items still grow their interval a little
+pub const MIN_EASE: f64 = 1.3;
+
+/// How soon a missed item comes back, short enough to see it again this session
+pub const LAPSE_INTERVAL: Duration = Duration::from_secs(60);
+
+const FIRST_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const SECOND_INTERVAL: Duration = Duration::from_secs(6 * 24 * 60 * 60);
+
+/// Grade an answer from 0 (blackout) to 5 (perfect recall), as SM-2 expects.
+/// Wrong answers score 1, right ones score lower the longer they took.
+pub fn grade(correct: bool, response_time: Duration) -> u8 {
+    if !correct {
+        1
+    } else if response_time < Duration::from_secs(3) {
+        5
+    } else if response_time < Duration::from_secs(8) {
+        4
+    } else {
+        3
+    }
+}
+
+/// SM-2 review state for one question, e.g. the tritone in the interval quiz
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ReviewItem {
+    pub ease: f64,
+    /// Correct answers in a row
+    pub repetitions: u32,
+    /// Gap between the last review and the next
+    pub interval: Duration,
+    /// When the item is next due, in seconds since the Unix epoch
+    pub due: u64,
+}
+
+impl Default for ReviewItem {
+    fn default() -> Self {
+        Self {
+            ease: DEFAULT_EASE,
+            repetitions: 0,
+            interval: Duration::ZERO,
+            due: 0,
+        }
+    }
+}
+
+impl ReviewItem {
+    /// Update the schedule after an answer graded 0-5 at `now`
+    pub fn review(&mut self, grade: u8, now: u64) {
+        let grade = grade.min(5);
+        if grade >= 3 {
+            self.interval = match self.repetitions {
+                0 => FIRST_INTERVAL,
+                1 => SECOND_INTERVAL,
+                _ => self.interval.mul_f64(self.ease),
+            };
+            self.repetitions += 1;
+        } else {
+            self.repetitions = 0;
+            self.interval = LAPSE_INTERVAL;
+        }
+        let miss = (5 - grade) as f64;
+        self.ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MIN_EASE);
+        self.due = now + self.interval.as_secs();
+    }
+
+    pub fn is_due(&self, now: u64) -> bool {
+        self.due <= now
+    }
+}
+
+/// Spaced-repetition schedule for every question answered so far,
+/// so missed items come back sooner and mastered ones fade out
+#[derive(Debug, PartialEq, Default)]
+pub struct Scheduler {
+    items: BTreeMap<(Exercise, String), ReviewItem>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild the schedule by replaying every saved answer in order
+    pub fn from_stats(stats: &StatsStore) -> Self {
+        let mut scheduler = Self::new();
+        for record in stats.records() {
+            scheduler.review(record);
+        }
+        scheduler
+    }
+
+    /// Update the question's schedule with an answer
+    pub fn review(&mut self, record: &AnswerRecord) {
+        self.items
+            .entry((record.exercise, record.question.clone()))
+            .or_default()
+            .review(
+                grade(record.correct, record.response_time()),
+                record.timestamp,
+            );
+    }
+
+    pub fn item(&self, exercise: Exercise, question: &str) -> Option<&ReviewItem> {
+        self.items.get(&(exercise, question.to_string()))
+    }
+
+    /// Choose which of `candidates` to ask next, returning its index: the most overdue item
+    /// first, then a random unseen one, then whichever is due soonest
+    pub fn pick<R: Rng>(
+        &self,
+        rng: &mut R,
+        exercise: Exercise,
+        candidates: &[String],
+        now: u64,
+    ) -> Option<usize> {
+        let items: Vec<Option<&ReviewItem>> = candidates
+            .iter()
+            .map(|question| self.item(exercise, question))
+            .collect();
+
+        let overdue = items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| Some((i, (*item)?)))
+            .filter(|(_, item)| item.is_due(now))
+            .min_by_key(|(_, item)| item.due);
+        if let Some((index, _)) = overdue {
+            return Some(index);
+        }
+
+        let unseen: Vec<usize> = (0..items.len()).filter(|&i| items[i].is_none()).collect();
+        if !unseen.is_empty() {
+            return Some(unseen[rng.gen_range(0..unseen.len())]);
+        }
+
+        items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| Some((i, (*item)?)))
+            .min_by_key(|(_, item)| item.due)
+            .map(|(index, _)| index)
+    }
+
+    /// Like `pick`, at the current time
+    pub fn pick_now<R: Rng>(
+        &self,
+        rng: &mut R,
+        exercise: Exercise,
+        candidates: &[String],
+    ) -> Option<usize> {
+        self.pick(rng, exercise, candidates, unix_now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn answer(question: &str, correct: bool, timestamp: u64) -> AnswerRecord {
+        AnswerRecord {
+            timestamp,
+            ..AnswerRecord::new(
+                Exercise::Intervals,
+                question,
+                "",
+                correct,
+                Duration::from_secs(1),
+            )
+        }
+    }
+
+    #[test]
+    fn test_grade() {
+        assert_eq!(grade(false, Duration::from_secs(1)), 1);
+        assert_eq!(grade(true, Duration::from_secs(1)), 5);
+        assert_eq!(grade(true, Duration::from_secs(5)), 4);
+        assert_eq!(grade(true, Duration::from_secs(20)), 3);
+    }
+
+    #[test]
+    fn test_review_intervals_grow() {
+        let mut item = ReviewItem::default();
+        item.review(5, 0);
+        assert_eq!(item.interval, FIRST_INTERVAL);
+        item.review(5, item.due);
+        assert_eq!(item.interval, SECOND_INTERVAL);
+        let ease = item.ease;
+        item.review(5, item.due);
+        assert_eq!(item.interval, SECOND_INTERVAL.mul_f64(ease));
+        assert!(item.ease > DEFAULT_EASE);
+
+        // A miss starts over and makes the item harder
+        let before = item.ease;
+        item.review(1, 1000);
+        assert_eq!(item.repetitions, 0);
+        assert_eq!(item.due, 1000 + LAPSE_INTERVAL.as_secs());
+        assert!(item.ease < before);
+    }
+
+    #[test]
+    fn test_ease_has_a_floor() {
+        let mut item = ReviewItem::default();
+        for now in 0..20 {
+            item.review(0, now);
+        }
+        assert_eq!(item.ease, MIN_EASE);
+    }
+
+    #[test]
+    fn test_pick_prefers_missed_items() {
+        let mut stats = StatsStore::in_memory();
+        stats.record(answer("Perfect 5th", true, 0));
+        stats.record(answer("Tritone", false, 0));
+        stats.record(answer("Minor 6th", false, 30));
+        let scheduler = Scheduler::from_stats(&stats);
+        let candidates: Vec<String> = ["Perfect 5th", "Tritone", "Minor 6th", "Octave"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // Both misses are due after a minute; the one missed first comes back first
+        let now = 120;
+        assert_eq!(
+            scheduler.pick(&mut rng, Exercise::Intervals, &candidates, now),
+            Some(1)
+        );
+        // Before anything is due, unseen items come next
+        assert_eq!(
+            scheduler.pick(&mut rng, Exercise::Intervals, &candidates, 10),
+            Some(3)
+        );
+        // With nothing due or new, the item due soonest is asked
+        assert_eq!(
+            scheduler.pick(&mut rng, Exercise::Intervals, &candidates[..1], 10),
+            Some(0)
+        );
+        assert_eq!(scheduler.pick(&mut rng, Exercise::Intervals, &[], 10), None);
+    }
+}
@@ -0,0 +1,534 @@
+use crate::training::QuizScore;
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// File name of the stats store inside the config directory
+pub const STATS_FILE_NAME: &str = "stats.json";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A pause this long between answers starts a new practice session
+pub const SESSION_GAP: Duration = Duration::from_secs(30 * 60);
+
+/// Which quiz an answer was given in
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Exercise {
+    Intervals,
+    IntervalComparison,
+    EarComparison,
+    Notes,
+    Frequency,
+    Cadences,
+    Inversions,
+    Degrees,
+    SingBack,
+    Intonation,
+    QuarterTones,
+    Rhythm,
+    MelodicDictation,
+}
+
+impl fmt::Display for Exercise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Exercise::Intervals => "Interval quiz",
+            Exercise::IntervalComparison => "Interval comparison",
+            Exercise::EarComparison => "Left/right ear drill",
+            Exercise::Notes => "Note identification",
+            Exercise::Frequency => "Frequency game",
+            Exercise::Cadences => "Cadence quiz",
+            Exercise::Inversions => "Inversion quiz",
+            Exercise::Degrees => "Scale degree quiz",
+            Exercise::SingBack => "Sing-back",
+            Exercise::Intonation => "Intonation drill",
+            Exercise::QuarterTones => "Quarter-tone intervals",
+            Exercise::Rhythm => "Rhythm dictation",
+            Exercise::MelodicDictation => "Melodic dictation",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One answered quiz question
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnswerRecord {
+    pub exercise: Exercise,
+    /// What was asked, e.g. "Perfect 5th", "F#" or "Deceptive"
+    pub question: String,
+    /// What the user answered, as they typed it
+    pub answer: String,
+    pub correct: bool,
+    /// Time from the end of playback to the answer, in milliseconds
+    pub response_ms: u64,
+    /// Key the question was played in, for exercises that have one
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub key: Option<String>,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl AnswerRecord {
+    /// An answer given just now
+    pub fn new(
+        exercise: Exercise,
+        question: impl Into<String>,
+        answer: impl Into<String>,
+        correct: bool,
+        response_time: Duration,
+    ) -> Self {
+        Self {
+            exercise,
+            question: question.into(),
+            answer: answer.into(),
+            correct,
+            response_ms: response_time.as_millis() as u64,
+            key: None,
+            timestamp: unix_now(),
+        }
+    }
+
+    pub fn in_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    pub fn response_time(&self) -> Duration {
+        Duration::from_millis(self.response_ms)
+    }
+}
+
+/// Every quiz answer so far, kept in a JSON file so progress carries across sessions
+#[derive(Debug, PartialEq, Default)]
+pub struct StatsStore {
+    /// Where `save` writes; `None` keeps the answers in memory only
+    path: Option<PathBuf>,
+    records: Vec<AnswerRecord>,
+}
+
+impl StatsStore {
+    /// Load the store at `path`, starting empty if the file doesn't exist yet
+    #[cfg(feature = "serde")]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref().to_path_buf();
+        let records = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path: Some(path),
+            records,
+        })
+    }
+
+    /// Load the store in the user's config directory
+    #[cfg(feature = "serde")]
+    pub fn open_default() -> Result<Self, Box<dyn std::error::Error>> {
+        let path = default_path().ok_or("Could not find a config directory")?;
+        Self::open(path)
+    }
+
+    /// A store that is never written to disk
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn record(&mut self, record: AnswerRecord) {
+        self.records.push(record);
+    }
+
+    /// Write every answer to the store's file, creating its directory if needed
+    #[cfg(feature = "serde")]
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write to a temporary file first so a crash can't leave half a store behind
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_string_pretty(&self.records)?)?;
+        fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    pub fn records(&self) -> &[AnswerRecord] {
+        &self.records
+    }
+
+    /// Answers from one exercise, oldest first
+    pub fn answers(&self, exercise: Exercise) -> impl Iterator<Item = &AnswerRecord> {
+        self.records.iter().filter(move |r| r.exercise == exercise)
+    }
+
+    /// Overall score for an exercise
+    pub fn accuracy(&self, exercise: Exercise) -> QuizScore {
+        tally(self.answers(exercise))
+    }
+
+    /// Scores per question, e.g. per interval in the interval quiz
+    pub fn accuracy_by_question(&self, exercise: Exercise) -> BTreeMap<String, QuizScore> {
+        self.group_by(exercise, |record| Some(record.question.clone()))
+    }
+
+    /// Scores per key, for exercises played in a key
+    pub fn accuracy_by_key(&self, exercise: Exercise) -> BTreeMap<String, QuizScore> {
+        self.group_by(exercise, |record| record.key.clone())
+    }
+
+    /// Scores per day (counted from the Unix epoch, in UTC), optionally for one question,
+    /// to show improvement over time
+    pub fn daily_accuracy(
+        &self,
+        exercise: Exercise,
+        question: Option<&str>,
+    ) -> Vec<(u64, QuizScore)> {
+        let mut days: BTreeMap<u64, QuizScore> = BTreeMap::new();
+        for record in self.answers(exercise) {
+            if question.is_some_and(|q| q != record.question) {
+                continue;
+            }
+            days.entry(record.timestamp / SECONDS_PER_DAY)
+                .or_default()
+                .record(record.correct);
+        }
+        days.into_iter().collect()
+    }
+
+    /// Mean response time for an exercise, or `None` before any answers
+    pub fn average_response_time(&self, exercise: Exercise) -> Option<Duration> {
+        let times: Vec<u64> = self.answers(exercise).map(|r| r.response_ms).collect();
+        if times.is_empty() {
+            return None;
+        }
+        Some(Duration::from_millis(
+            times.iter().sum::<u64>() / times.len() as u64,
+        ))
+    }
+
+    /// Answers split into practice sessions wherever `SESSION_GAP` passed between two of them,
+    /// oldest first
+    pub fn sessions(&self) -> Vec<&[AnswerRecord]> {
+        let gap = SESSION_GAP.as_secs();
+        self.records
+            .chunk_by(|a, b| b.timestamp.saturating_sub(a.timestamp) < gap)
+            .collect()
+    }
+
+    /// Which answers were mixed up with which in an exercise over the last `sessions`
+    /// practice sessions
+    pub fn confusions(&self, exercise: Exercise, sessions: usize) -> ConfusionMatrix {
+        let all = self.sessions();
+        let recent = &all[all.len().saturating_sub(sessions)..];
+        ConfusionMatrix::from_records(
+            recent
+                .iter()
+                .flat_map(|session| session.iter())
+                .filter(|record| record.exercise == exercise),
+        )
+    }
+
+    /// Exercises with at least one answer, in a fixed order
+    pub fn exercises(&self) -> Vec<Exercise> {
+        let mut exercises: Vec<Exercise> = self.records.iter().map(|r| r.exercise).collect();
+        exercises.sort();
+        exercises.dedup();
+        exercises
+    }
+
+    fn group_by(
+        &self,
+        exercise: Exercise,
+        group: impl Fn(&AnswerRecord) -> Option<String>,
+    ) -> BTreeMap<String, QuizScore> {
+        let mut groups: BTreeMap<String, QuizScore> = BTreeMap::new();
+        for record in self.answers(exercise) {
+            if let Some(name) = group(record) {
+                groups.entry(name).or_default().record(record.correct);
+            }
+        }
+        groups
+    }
+}
+
+/// How often each question was answered with each wrong answer, e.g. "Minor 6th" as "Major 6th"
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct ConfusionMatrix {
+    /// Wrong answers per (question, answer) pair
+    mistakes: BTreeMap<(String, String), u32>,
+    /// Times each question was asked
+    asked: BTreeMap<String, u32>,
+}
+
+/// Two answers that were taken for each other, counting both ways round
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ConfusedPair {
+    pub first: String,
+    pub second: String,
+    /// Times `first` was answered as `second`
+    pub first_as_second: u32,
+    /// Times `second` was answered as `first`
+    pub second_as_first: u32,
+}
+
+impl fmt::Display for ConfusedPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ↔ {}: {} mix-ups ({} one way, {} the other)",
+            self.first,
+            self.second,
+            self.total(),
+            self.first_as_second,
+            self.second_as_first
+        )
+    }
+}
+
+impl ConfusedPair {
+    pub fn total(&self) -> u32 {
+        self.first_as_second + self.second_as_first
+    }
+}
+
+impl ConfusionMatrix {
+    pub fn from_records<'a>(records: impl Iterator<Item = &'a AnswerRecord>) -> Self {
+        let mut matrix = Self::default();
+        for record in records {
+            *matrix.asked.entry(record.question.clone()).or_default() += 1;
+            if !record.correct {
+                *matrix
+                    .mistakes
+                    .entry((record.question.clone(), record.answer.clone()))
+                    .or_default() += 1;
+            }
+        }
+        matrix
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mistakes.is_empty()
+    }
+
+    /// Times `question` was answered as `answer`
+    pub fn count(&self, question: &str, answer: &str) -> u32 {
+        self.mistakes
+            .get(&(question.to_string(), answer.to_string()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Fraction of the times `question` was asked that it was answered as `answer`
+    pub fn rate(&self, question: &str, answer: &str) -> f64 {
+        match self.asked.get(question) {
+            Some(&asked) if asked > 0 => self.count(question, answer) as f64 / asked as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// Every pair of answers taken for each other, most mixed up first
+    pub fn pairs(&self) -> Vec<ConfusedPair> {
+        let mut pairs: BTreeMap<(&str, &str), ConfusedPair> = BTreeMap::new();
+        for ((question, answer), &count) in &self.mistakes {
+            let (first, second) = if question <= answer {
+                (question, answer)
+            } else {
+                (answer, question)
+            };
+            let pair = pairs
+                .entry((first, second))
+                .or_insert_with(|| ConfusedPair {
+                    first: first.clone(),
+                    second: second.clone(),
+                    first_as_second: 0,
+                    second_as_first: 0,
+                });
+            if question == first {
+                pair.first_as_second += count;
+            } else {
+                pair.second_as_first += count;
+            }
+        }
+        let mut pairs: Vec<ConfusedPair> = pairs.into_values().collect();
+        pairs.sort_by_key(|pair| std::cmp::Reverse(pair.total()));
+        pairs
+    }
+}
+
+/// `$XDG_CONFIG_HOME/ear-trainer/stats.json`, falling back to `%APPDATA%` or `~/.config`
+pub fn default_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_dir.join("ear-trainer").join(STATS_FILE_NAME))
+}
+
+/// Seconds since the Unix epoch, the clock answers are stamped with
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn tally<'a>(records: impl Iterator<Item = &'a AnswerRecord>) -> QuizScore {
+    let mut score = QuizScore::default();
+    for record in records {
+        score.record(record.correct);
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(exercise: Exercise, question: &str, correct: bool, timestamp: u64) -> AnswerRecord {
+        AnswerRecord {
+            timestamp,
+            ..AnswerRecord::new(exercise, question, "", correct, Duration::from_millis(1500))
+        }
+    }
+
+    #[test]
+    fn test_accuracy_queries() {
+        let mut stats = StatsStore::in_memory();
+        stats.record(answer(Exercise::Intervals, "Perfect 5th", true, 0));
+        stats.record(answer(Exercise::Intervals, "Perfect 5th", false, 0));
+        stats.record(answer(
+            Exercise::Intervals,
+            "Tritone",
+            true,
+            SECONDS_PER_DAY,
+        ));
+        stats.record(answer(Exercise::Notes, "F#", false, 0).in_key("C4"));
+
+        assert_eq!(stats.accuracy(Exercise::Intervals).total, 3);
+        assert_eq!(stats.accuracy(Exercise::Intervals).correct, 2);
+        let by_question = stats.accuracy_by_question(Exercise::Intervals);
+        assert_eq!(by_question["Perfect 5th"].correct, 1);
+        assert_eq!(by_question["Tritone"].total, 1);
+        assert_eq!(stats.accuracy_by_key(Exercise::Notes)["C4"].total, 1);
+        assert!(stats.accuracy_by_key(Exercise::Intervals).is_empty());
+        assert_eq!(
+            stats.average_response_time(Exercise::Notes),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(stats.average_response_time(Exercise::Degrees), None);
+        assert_eq!(
+            stats.exercises(),
+            vec![Exercise::Intervals, Exercise::Notes]
+        );
+    }
+
+    #[test]
+    fn test_daily_accuracy() {
+        let mut stats = StatsStore::in_memory();
+        stats.record(answer(Exercise::Cadences, "Half", false, 10));
+        stats.record(answer(Exercise::Cadences, "Plagal", true, 20));
+        stats.record(answer(
+            Exercise::Cadences,
+            "Half",
+            true,
+            3 * SECONDS_PER_DAY,
+        ));
+
+        let days = stats.daily_accuracy(Exercise::Cadences, None);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].0, 0);
+        assert_eq!(days[0].1.total, 2);
+        assert_eq!(days[1].0, 3);
+
+        let half = stats.daily_accuracy(Exercise::Cadences, Some("Half"));
+        assert_eq!(half[0].1.correct, 0);
+        assert_eq!(half[1].1.correct, 1);
+    }
+
+    #[test]
+    fn test_sessions_and_confusions() {
+        let hour = 60 * 60;
+        let mut stats = StatsStore::in_memory();
+        let mut mix_up = |question: &str, answer: &str, timestamp: u64| {
+            stats.record(AnswerRecord {
+                timestamp,
+                ..AnswerRecord::new(
+                    Exercise::Intervals,
+                    question,
+                    answer,
+                    question == answer,
+                    Duration::ZERO,
+                )
+            });
+        };
+        mix_up("Tritone", "Perfect 5th", 0);
+        mix_up("Minor 6th", "Major 6th", 2 * hour);
+        mix_up("Major 6th", "Minor 6th", 2 * hour + 60);
+        mix_up("Minor 6th", "Minor 6th", 2 * hour + 120);
+        mix_up("Minor 6th", "Major 6th", 5 * hour);
+        stats.record(answer(Exercise::Cadences, "Half", false, 5 * hour + 10));
+
+        let sessions = stats.sessions();
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[1].len(), 3);
+
+        let recent = stats.confusions(Exercise::Intervals, 2);
+        assert_eq!(recent.count("Minor 6th", "Major 6th"), 2);
+        assert_eq!(recent.count("Tritone", "Perfect 5th"), 0);
+        assert!((recent.rate("Minor 6th", "Major 6th") - 2.0 / 3.0).abs() < 1e-9);
+        let pairs = recent.pairs();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].total(), 3);
+        assert_eq!(
+            pairs[0].to_string(),
+            "Major 6th ↔ Minor 6th: 3 mix-ups (1 one way, 2 the other)"
+        );
+
+        let everything = stats.confusions(Exercise::Intervals, usize::MAX);
+        assert_eq!(everything.pairs().len(), 2);
+        assert!(stats.confusions(Exercise::Degrees, 3).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_and_reopen() {
+        let dir = std::env::temp_dir().join(format!("ear-trainer-stats-{}", std::process::id()));
+        let path = dir.join(STATS_FILE_NAME);
+
+        let mut stats = StatsStore::open(&path).unwrap();
+        assert!(stats.records().is_empty());
+        stats.record(answer(Exercise::Degrees, "5", true, 42).in_key("G4 Major"));
+        stats.save().unwrap();
+
+        let reopened = StatsStore::open(&path).unwrap();
+        assert_eq!(reopened.records(), stats.records());
+        assert_eq!(reopened.records()[0].key.as_deref(), Some("G4 Major"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_reject_corrupt_store() {
+        let path =
+            std::env::temp_dir().join(format!("ear-trainer-bad-{}.json", std::process::id()));
+        fs::write(&path, "not json").unwrap();
+        assert!(StatsStore::open(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,400 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Compute a tempo in BPM from the moments a user tapped along to the beat.
+/// Returns `None` until there are at least two taps to measure between.
+pub fn bpm_from_taps(taps: &[Instant]) -> Option<f64> {
+    if taps.len() < 2 {
+        return None;
+    }
+
+    let total: Duration = taps.windows(2).map(|pair| pair[1] - pair[0]).sum();
+    let average = total.as_secs_f64() / (taps.len() - 1) as f64;
+    if average <= 0.0 {
+        return None;
+    }
+
+    Some(60.0 / average)
+}
+
+/// Length of one beat at the given tempo
+pub fn beat_duration(bpm: f64) -> Duration {
+    Duration::from_secs_f64(60.0 / bpm)
+}
+
+/// Speed of the music in quarter-note beats per minute
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tempo(pub u16);
+
+impl Default for Tempo {
+    fn default() -> Self {
+        Tempo(120)
+    }
+}
+
+impl fmt::Display for Tempo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} BPM", self.0)
+    }
+}
+
+impl FromStr for Tempo {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse::<u16>() {
+            Ok(bpm) if bpm > 0 => Ok(Tempo(bpm)),
+            _ => Err(format!("Invalid tempo: {}", s)),
+        }
+    }
+}
+
+impl Tempo {
+    /// Nearest whole tempo to a measured BPM, e.g. from `bpm_from_taps`
+    pub fn from_bpm(bpm: f64) -> Option<Self> {
+        let rounded = bpm.round();
+        if !(1.0..=u16::MAX as f64).contains(&rounded) {
+            return None;
+        }
+        Some(Tempo(rounded as u16))
+    }
+
+    pub fn bpm(&self) -> u16 {
+        self.0
+    }
+
+    /// Length of one quarter-note beat; a tempo of 0 is treated as 1 BPM
+    pub fn beat_duration(&self) -> Duration {
+        beat_duration(self.0.max(1) as f64)
+    }
+
+    /// How long `value` lasts at this tempo
+    pub fn duration_of(&self, value: NoteValue) -> Duration {
+        value.duration(self.beat_duration())
+    }
+}
+
+/// Rhythmic length of a note or rest, relative to a quarter-note beat
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NoteValue {
+    Whole,
+    DottedHalf,
+    Half,
+    HalfTriplet,
+    DottedQuarter,
+    #[default]
+    Quarter,
+    QuarterTriplet,
+    DottedEighth,
+    Eighth,
+    EighthTriplet,
+    Sixteenth,
+}
+
+impl fmt::Display for NoteValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value_name = match self {
+            NoteValue::Whole => "Whole",
+            NoteValue::DottedHalf => "Dotted Half",
+            NoteValue::Half => "Half",
+            NoteValue::HalfTriplet => "Half Triplet",
+            NoteValue::DottedQuarter => "Dotted Quarter",
+            NoteValue::Quarter => "Quarter",
+            NoteValue::QuarterTriplet => "Quarter Triplet",
+            NoteValue::DottedEighth => "Dotted Eighth",
+            NoteValue::Eighth => "Eighth",
+            NoteValue::EighthTriplet => "Eighth Triplet",
+            NoteValue::Sixteenth => "Sixteenth",
+        };
+        write!(f, "{}", value_name)
+    }
+}
+
+impl FromStr for NoteValue {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "whole" | "w" | "1" => Ok(NoteValue::Whole),
+            "dotted half" | "h." | "2." => Ok(NoteValue::DottedHalf),
+            "half" | "h" | "2" => Ok(NoteValue::Half),
+            "half triplet" | "h3" => Ok(NoteValue::HalfTriplet),
+            "dotted quarter" | "q." | "4." => Ok(NoteValue::DottedQuarter),
+            "quarter" | "q" | "4" => Ok(NoteValue::Quarter),
+            "quarter triplet" | "q3" => Ok(NoteValue::QuarterTriplet),
+            "dotted eighth" | "e." | "8." => Ok(NoteValue::DottedEighth),
+            "eighth" | "e" | "8" => Ok(NoteValue::Eighth),
+            "eighth triplet" | "e3" => Ok(NoteValue::EighthTriplet),
+            "sixteenth" | "s" | "16" => Ok(NoteValue::Sixteenth),
+            _ => Err(format!("Invalid note value: {}", s)),
+        }
+    }
+}
+
+impl NoteValue {
+    /// Every value, longest first
+    pub const ALL: [NoteValue; 11] = [
+        NoteValue::Whole,
+        NoteValue::DottedHalf,
+        NoteValue::Half,
+        NoteValue::DottedQuarter,
+        NoteValue::HalfTriplet,
+        NoteValue::Quarter,
+        NoteValue::DottedEighth,
+        NoteValue::QuarterTriplet,
+        NoteValue::Eighth,
+        NoteValue::EighthTriplet,
+        NoteValue::Sixteenth,
+    ];
+
+    /// Length in quarter-note beats. Dots add half the value, triplets fit three in the space of two.
+    pub fn beats(&self) -> f64 {
+        match self {
+            NoteValue::Whole => 4.0,
+            NoteValue::DottedHalf => 3.0,
+            NoteValue::Half => 2.0,
+            NoteValue::HalfTriplet => 4.0 / 3.0,
+            NoteValue::DottedQuarter => 1.5,
+            NoteValue::Quarter => 1.0,
+            NoteValue::QuarterTriplet => 2.0 / 3.0,
+            NoteValue::DottedEighth => 0.75,
+            NoteValue::Eighth => 0.5,
+            NoteValue::EighthTriplet => 1.0 / 3.0,
+            NoteValue::Sixteenth => 0.25,
+        }
+    }
+
+    /// Short name used in melody strings, e.g. "q" for a quarter note
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            NoteValue::Whole => "w",
+            NoteValue::DottedHalf => "h.",
+            NoteValue::Half => "h",
+            NoteValue::HalfTriplet => "h3",
+            NoteValue::DottedQuarter => "q.",
+            NoteValue::Quarter => "q",
+            NoteValue::QuarterTriplet => "q3",
+            NoteValue::DottedEighth => "e.",
+            NoteValue::Eighth => "e",
+            NoteValue::EighthTriplet => "e3",
+            NoteValue::Sixteenth => "s",
+        }
+    }
+
+    /// How long the value lasts when a quarter note lasts `beat`
+    pub fn duration(&self, beat: Duration) -> Duration {
+        beat.mul_f64(self.beats())
+    }
+}
+
+/// How many beats fill a bar and which note value gets one, e.g. 3/4 or 6/8
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeSignature {
+    /// The top number: pulses per bar
+    pub beats: u8,
+    /// The bottom number: 4 for quarter-note pulses, 8 for eighths and so on
+    pub unit: u8,
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        TimeSignature::COMMON
+    }
+}
+
+impl fmt::Display for TimeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.beats, self.unit)
+    }
+}
+
+impl FromStr for TimeSignature {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (beats, unit) = s
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| format!("Invalid time signature: {}", s))?;
+        let beats = beats
+            .trim()
+            .parse::<u8>()
+            .map_err(|_| format!("Invalid time signature: {}", s))?;
+        let unit = unit
+            .trim()
+            .parse::<u8>()
+            .map_err(|_| format!("Invalid time signature: {}", s))?;
+        TimeSignature::new(beats, unit)
+    }
+}
+
+impl TimeSignature {
+    /// Four quarter notes to the bar
+    pub const COMMON: TimeSignature = TimeSignature { beats: 4, unit: 4 };
+
+    /// `beats` pulses of a `unit` note per bar; the unit must be a power of two up to 32
+    pub fn new(beats: u8, unit: u8) -> Result<Self, String> {
+        if beats == 0 {
+            return Err("A bar needs at least one beat".to_string());
+        }
+        if !unit.is_power_of_two() || unit > 32 {
+            return Err(format!("Invalid beat unit: {}", unit));
+        }
+        Ok(Self { beats, unit })
+    }
+
+    /// Compound meters like 6/8 and 12/8 group their pulses in threes
+    pub fn is_compound(&self) -> bool {
+        self.unit >= 8 && self.beats > 3 && self.beats.is_multiple_of(3)
+    }
+
+    /// Pulses grouped into each felt beat: 3 in compound meters, otherwise 1
+    pub fn pulses_per_beat(&self) -> u8 {
+        if self.is_compound() { 3 } else { 1 }
+    }
+
+    /// Length of one pulse (the bottom number's note) when a quarter note lasts `beat`
+    pub fn pulse_duration(&self, beat: Duration) -> Duration {
+        beat.mul_f64(4.0 / self.unit as f64)
+    }
+
+    /// Length of a bar in quarter-note beats
+    pub fn bar_beats(&self) -> f64 {
+        self.beats as f64 * 4.0 / self.unit as f64
+    }
+
+    /// Length of a whole bar at `tempo`
+    pub fn bar_duration(&self, tempo: Tempo) -> Duration {
+        tempo.beat_duration().mul_f64(self.bar_beats())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bpm_from_steady_taps() {
+        let start = Instant::now();
+        let taps: Vec<Instant> = (0..4)
+            .map(|i| start + Duration::from_millis(500 * i))
+            .collect();
+        let bpm = bpm_from_taps(&taps).unwrap();
+        assert!((bpm - 120.0).abs() < 0.01, "Expected 120 BPM, got {}", bpm);
+    }
+
+    #[test]
+    fn test_bpm_averages_uneven_taps() {
+        let start = Instant::now();
+        let taps = [
+            start,
+            start + Duration::from_millis(900),
+            start + Duration::from_millis(2100),
+        ];
+        // Average gap is 1.05s
+        let bpm = bpm_from_taps(&taps).unwrap();
+        assert!((bpm - 60.0 / 1.05).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_bpm_needs_two_taps() {
+        assert!(bpm_from_taps(&[]).is_none());
+        assert!(bpm_from_taps(&[Instant::now()]).is_none());
+    }
+
+    #[test]
+    fn test_beat_duration() {
+        assert_eq!(beat_duration(120.0), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_note_value_durations() {
+        let beat = Duration::from_millis(500);
+        assert_eq!(NoteValue::Whole.duration(beat), Duration::from_secs(2));
+        assert_eq!(NoteValue::Quarter.duration(beat), beat);
+        assert_eq!(
+            NoteValue::Sixteenth.duration(beat),
+            Duration::from_millis(125)
+        );
+    }
+
+    #[test]
+    fn test_note_value_parsing() {
+        assert_eq!("q".parse::<NoteValue>().unwrap(), NoteValue::Quarter);
+        assert_eq!("8".parse::<NoteValue>().unwrap(), NoteValue::Eighth);
+        assert_eq!("Half".parse::<NoteValue>().unwrap(), NoteValue::Half);
+        assert!("x".parse::<NoteValue>().is_err());
+    }
+
+    #[test]
+    fn test_dotted_and_triplet_values() {
+        assert_eq!(NoteValue::DottedQuarter.beats(), 1.5);
+        assert_eq!(NoteValue::DottedEighth.beats(), 0.75);
+
+        // Three triplets take the space of two regular notes
+        assert!((3.0 * NoteValue::EighthTriplet.beats() - 1.0).abs() < 1e-9);
+        assert!((3.0 * NoteValue::QuarterTriplet.beats() - 2.0).abs() < 1e-9);
+
+        assert_eq!("q.".parse::<NoteValue>().unwrap(), NoteValue::DottedQuarter);
+        assert_eq!(
+            "eighth triplet".parse::<NoteValue>().unwrap(),
+            NoteValue::EighthTriplet
+        );
+        assert_eq!(NoteValue::HalfTriplet.symbol(), "h3");
+        assert!(
+            NoteValue::ALL
+                .windows(2)
+                .all(|pair| pair[0].beats() > pair[1].beats())
+        );
+    }
+
+    #[test]
+    fn test_tempo_durations() {
+        let tempo = Tempo(120);
+        assert_eq!(tempo.beat_duration(), Duration::from_millis(500));
+        assert_eq!(
+            tempo.duration_of(NoteValue::DottedHalf),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(Tempo::default(), tempo);
+        assert_eq!(tempo.to_string(), "120 BPM");
+
+        // A zero tempo must not divide by zero
+        assert_eq!(Tempo(0).beat_duration(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_time_signatures() {
+        let waltz: TimeSignature = "3/4".parse().unwrap();
+        assert_eq!(waltz, TimeSignature { beats: 3, unit: 4 });
+        assert_eq!(waltz.to_string(), "3/4");
+        assert!(!waltz.is_compound());
+        assert_eq!(waltz.bar_duration(Tempo(120)), Duration::from_millis(1500));
+
+        let jig: TimeSignature = "6/8".parse().unwrap();
+        assert!(jig.is_compound());
+        assert_eq!(jig.pulses_per_beat(), 3);
+        assert_eq!(
+            jig.pulse_duration(Duration::from_millis(500)),
+            Duration::from_millis(250)
+        );
+        assert_eq!(jig.bar_beats(), 3.0);
+        assert!(!"3/8".parse::<TimeSignature>().unwrap().is_compound());
+
+        assert!("4/3".parse::<TimeSignature>().is_err());
+        assert!("0/4".parse::<TimeSignature>().is_err());
+        assert!("four".parse::<TimeSignature>().is_err());
+        assert_eq!(TimeSignature::default(), TimeSignature::COMMON);
+    }
+
+    #[test]
+    fn test_tempo_parsing() {
+        assert_eq!("90".parse::<Tempo>().unwrap(), Tempo(90));
+        assert!("0".parse::<Tempo>().is_err());
+        assert!("fast".parse::<Tempo>().is_err());
+        assert_eq!(Tempo::from_bpm(89.6), Some(Tempo(90)));
+        assert_eq!(Tempo::from_bpm(0.2), None);
+    }
+}
@@ -0,0 +1,2142 @@
+#[cfg(feature = "playback")]
+use crate::audio::{AudioEngine, PlaybackOptions};
+use crate::chords::{Chord, ChordQuality, Inversion};
+use crate::config::QuizConfig;
+use crate::edo::Edo;
+use crate::generator::{random_diatonic_melody, random_interval, random_melody, random_note};
+use crate::input::{PitchDetector, cents_from};
+use crate::intervals::Interval;
+#[cfg(feature = "playback")]
+use crate::intervals::IntervalPlayStyle;
+use crate::keys::circle_of_fifths;
+use crate::melody::{Event, Melody};
+use crate::naming::NoteNaming;
+use crate::notes::{Note, NoteWithOctave, Pitch, Scale, ScaleType, Tuning};
+use crate::progression::Progression;
+use crate::spelling::{SpelledNote, SpelledPitch};
+use crate::tempo::{NoteValue, Tempo};
+use rand::Rng;
+use rand::distributions::{Distribution, WeightedIndex};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Lowest frequency used by the estimation game (C3)
+pub const ESTIMATION_MIN_HZ: f64 = 130.81;
+
+/// Highest frequency used by the estimation game (C6)
+pub const ESTIMATION_MAX_HZ: f64 = 1046.50;
+
+/// How many cents a sung note may be off and still count as correct
+pub const SING_BACK_TOLERANCE_CENTS: f64 = 30.0;
+
+/// Frames of pitch analysed per second of a sung recording
+const SING_BACK_FRAMES_PER_SECOND: u32 = 50;
+
+/// How far off a frequency guess was, judged on a log scale
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GuessGrade {
+    /// Within a quarter of a semitone
+    Perfect,
+    /// Within a semitone
+    Close,
+    /// Within a minor third
+    Near,
+    Off,
+}
+
+impl fmt::Display for GuessGrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let grade = match self {
+            GuessGrade::Perfect => "Perfect",
+            GuessGrade::Close => "Close",
+            GuessGrade::Near => "Near",
+            GuessGrade::Off => "Off",
+        };
+        write!(f, "{}", grade)
+    }
+}
+
+impl GuessGrade {
+    /// Points awarded for the grade
+    pub fn points(&self) -> u32 {
+        match self {
+            GuessGrade::Perfect => 3,
+            GuessGrade::Close => 2,
+            GuessGrade::Near => 1,
+            GuessGrade::Off => 0,
+        }
+    }
+}
+
+/// Distance between two frequencies in cents (100 cents = 1 semitone)
+pub fn cents_error(actual: f64, guess: f64) -> Result<f64, String> {
+    if actual <= 0.0 || guess <= 0.0 {
+        return Err("Frequency must be positive".to_string());
+    }
+    Ok((1200.0 * (guess / actual).log2()).abs())
+}
+
+/// Grade a frequency guess by its error in cents
+pub fn grade_frequency_guess(actual: f64, guess: f64) -> Result<GuessGrade, String> {
+    let cents = cents_error(actual, guess)?;
+    let grade = if cents <= 25.0 {
+        GuessGrade::Perfect
+    } else if cents <= 100.0 {
+        GuessGrade::Close
+    } else if cents <= 300.0 {
+        GuessGrade::Near
+    } else {
+        GuessGrade::Off
+    };
+    Ok(grade)
+}
+
+/// Pick a random frequency, uniformly on a log scale so every octave is equally likely
+pub fn random_frequency<R: Rng>(rng: &mut R, min_hz: f64, max_hz: f64) -> f64 {
+    let exponent = rng.gen_range(min_hz.log2()..max_hz.log2());
+    2.0_f64.powf(exponent)
+}
+
+/// Check whether the named note is the nearest one to the frequency
+pub fn is_nearest_note(
+    frequency: f64,
+    answer: &NoteWithOctave,
+    tuning: &Tuning,
+) -> Result<bool, String> {
+    let nearest = tuning.nearest_note(frequency)?;
+    Ok(nearest == *answer)
+}
+
+/// Largest interval (in semitones) used by the comparison drill
+pub const COMPARISON_MAX_SEMITONES: u8 = 12;
+
+/// Widest gap (in semitones) between the two notes of the left/right ear drill
+pub const EAR_DRILL_MAX_SEMITONES: u8 = 2;
+
+/// How far out of tune (in cents) the intonation drill's notes are by default
+pub const INTONATION_DEFAULT_CENTS: f64 = 30.0;
+
+/// Largest interval (in quarter tones) asked in the quarter-tone quiz, an octave
+pub const QUARTER_TONE_MAX_STEPS: u8 = 24;
+
+/// Names of the intervals that fall between two semitones, by size in quarter tones
+const QUARTER_TONE_NAMES: [(u8, &str); 5] = [
+    (1, "Quarter Tone"),
+    (3, "Neutral Second"),
+    (7, "Neutral Third"),
+    (17, "Neutral Sixth"),
+    (21, "Neutral Seventh"),
+];
+
+/// An interval of a whole number of 24-EDO quarter tones
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuarterToneInterval {
+    pub steps: u8,
+}
+
+impl fmt::Display for QuarterToneInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.steps.is_multiple_of(2) {
+            return write!(f, "{}", Interval::from_semitones(self.steps / 2));
+        }
+        match QUARTER_TONE_NAMES
+            .iter()
+            .find(|(steps, _)| *steps == self.steps)
+        {
+            Some((_, name)) => write!(f, "{}", name),
+            None => write!(f, "{}.5 semitones", self.steps / 2),
+        }
+    }
+}
+
+/// Parse a size in semitones ("3.5", "3.5 semitones"), a neutral interval name
+/// ("neutral third") or an ordinary interval ("m3", "perfect fifth")
+impl FromStr for QuarterToneInterval {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        if let Some((steps, _)) = QUARTER_TONE_NAMES
+            .iter()
+            .find(|(_, name)| name.to_lowercase() == lower)
+        {
+            return Ok(Self { steps: *steps });
+        }
+        let number = lower
+            .strip_suffix("semitones")
+            .or_else(|| lower.strip_suffix("semitone"))
+            .unwrap_or(&lower)
+            .trim();
+        if let Ok(semitones) = number.parse::<f64>() {
+            let steps = semitones * 2.0;
+            if steps.fract() != 0.0 || !(0.0..=u8::MAX as f64).contains(&steps) {
+                return Err(format!("Not a whole number of quarter tones: {}", s));
+            }
+            return Ok(Self { steps: steps as u8 });
+        }
+        let interval = s
+            .parse::<Interval>()
+            .map_err(|_| format!("Invalid quarter-tone interval: {}", s))?;
+        Ok(Self {
+            steps: interval.semitones().saturating_mul(2),
+        })
+    }
+}
+
+/// A melodic interval of some number of quarter tones above a root, to name by ear
+#[derive(Debug, PartialEq)]
+pub struct QuarterToneQuestion {
+    pub root: NoteWithOctave,
+    pub interval: QuarterToneInterval,
+}
+
+impl QuarterToneQuestion {
+    /// A random interval up to an octave above a root between C3 and B4, or in the
+    /// configured range, falling between two semitones half of the time
+    pub fn random<R: Rng>(rng: &mut R, config: &QuizConfig) -> Self {
+        let semitones = rng.gen_range(0..QUARTER_TONE_MAX_STEPS / 2);
+        let steps = if rng.gen_bool(0.5) {
+            semitones * 2 + 1
+        } else {
+            semitones * 2 + 2
+        };
+        Self {
+            root: random_root(rng, config),
+            interval: QuarterToneInterval { steps },
+        }
+    }
+
+    pub fn is_correct(&self, answer: &QuarterToneInterval) -> bool {
+        self.interval == *answer
+    }
+
+    /// The top note named by its quarter-tone step, e.g. "^E4" a neutral third above C4
+    pub fn top_name(&self) -> String {
+        let root = Edo::QUARTER_TONES.steps_for(self.root.absolute_semitone() - 48);
+        Edo::QUARTER_TONES.step_name(root + self.interval.steps as i32)
+    }
+
+    /// Frequencies of the root and the note above it, tuned from the root
+    pub fn frequencies(&self, tuning: &Tuning) -> (f64, f64) {
+        let root = tuning.frequency_of(&self.root);
+        let top = root * Edo::QUARTER_TONES.ratio(self.interval.steps as i32);
+        (root, top)
+    }
+
+    /// Play the root and then the top note, a beat each
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let beat = tempo.beat_duration();
+        let (root, top) = self.frequencies(engine.tuning());
+        engine.play_frequencies(&[root as f32], beat)?;
+        engine.play_frequencies(&[top as f32], beat)
+    }
+}
+
+/// A melodic interval: a root note and a size in semitones above it
+#[derive(Debug, PartialEq)]
+pub struct IntervalPrompt {
+    pub root: NoteWithOctave,
+    pub semitones: u8,
+}
+
+impl IntervalPrompt {
+    /// Frequency of the upper note of the interval
+    pub fn upper_frequency(&self, tuning: &Tuning) -> f64 {
+        tuning.frequency_of(&self.root) * 2.0_f64.powf(self.semitones as f64 / 12.0)
+    }
+
+    /// The root, then the upper note, as quarter notes
+    pub fn melody(&self) -> Result<Melody, String> {
+        let upper = self.root.transpose(self.semitones as i32)?;
+        Ok(Melody::from_notes(&[self.root, upper], NoteValue::Quarter))
+    }
+
+    /// Play the root, then the upper note
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.melody()?.play_on(engine, tempo)
+    }
+}
+
+/// Which of two intervals was larger
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ComparisonAnswer {
+    First,
+    Second,
+    Same,
+}
+
+impl fmt::Display for ComparisonAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let answer = match self {
+            ComparisonAnswer::First => "the first was larger",
+            ComparisonAnswer::Second => "the second was larger",
+            ComparisonAnswer::Same => "they were the same",
+        };
+        write!(f, "{}", answer)
+    }
+}
+
+impl FromStr for ComparisonAnswer {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" | "first" => Ok(ComparisonAnswer::First),
+            "2" | "second" => Ok(ComparisonAnswer::Second),
+            "s" | "same" => Ok(ComparisonAnswer::Same),
+            _ => Err(format!("Invalid answer: {}", s)),
+        }
+    }
+}
+
+/// Two intervals played back-to-back for the user to compare
+#[derive(Debug, PartialEq)]
+pub struct IntervalComparison {
+    pub first: IntervalPrompt,
+    pub second: IntervalPrompt,
+}
+
+impl IntervalComparison {
+    /// Generate a random pair of the intervals `config` allows, on independent roots.
+    /// Roughly a third of the pairs are the same size so "same" stays a real option.
+    pub fn random<R: Rng>(rng: &mut R, config: &QuizConfig) -> Result<Self, String> {
+        let sizes: Vec<u8> = (1..=COMPARISON_MAX_SEMITONES)
+            .filter(|&size| config.allows_interval(&Interval::from_semitones(size)))
+            .collect();
+        if sizes.is_empty() {
+            return Err("No intervals to choose from".to_string());
+        }
+        let first = rng.gen_range(0..sizes.len());
+        let second = if rng.gen_bool(1.0 / 3.0) || sizes.len() == 1 {
+            first
+        } else {
+            // Pick any other size from the allowed ones
+            let other = rng.gen_range(0..sizes.len() - 1);
+            if other >= first { other + 1 } else { other }
+        };
+
+        Ok(Self {
+            first: random_prompt(rng, sizes[first], config),
+            second: random_prompt(rng, sizes[second], config),
+        })
+    }
+
+    pub fn answer(&self) -> ComparisonAnswer {
+        match self.first.semitones.cmp(&self.second.semitones) {
+            std::cmp::Ordering::Greater => ComparisonAnswer::First,
+            std::cmp::Ordering::Less => ComparisonAnswer::Second,
+            std::cmp::Ordering::Equal => ComparisonAnswer::Same,
+        }
+    }
+
+    pub fn is_correct(&self, answer: &ComparisonAnswer) -> bool {
+        self.answer() == *answer
+    }
+
+    /// Play both intervals with a short pause between them
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.first
+            .melody()?
+            .with_rest(NoteValue::Quarter)
+            .then(self.second.melody()?)
+            .play_on(engine, tempo)
+    }
+}
+
+/// Which ear heard the higher note
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EarAnswer {
+    Left,
+    Right,
+    Same,
+}
+
+impl fmt::Display for EarAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let answer = match self {
+            EarAnswer::Left => "the left was higher",
+            EarAnswer::Right => "the right was higher",
+            EarAnswer::Same => "they were the same",
+        };
+        write!(f, "{}", answer)
+    }
+}
+
+impl FromStr for EarAnswer {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "l" | "left" => Ok(EarAnswer::Left),
+            "r" | "right" => Ok(EarAnswer::Right),
+            "s" | "same" => Ok(EarAnswer::Same),
+            _ => Err(format!("Invalid answer: {}", s)),
+        }
+    }
+}
+
+/// Two close notes, one in each ear, for the user to say which is higher
+#[derive(Debug, PartialEq)]
+pub struct EarComparison {
+    pub left: NoteWithOctave,
+    pub right: NoteWithOctave,
+}
+
+impl EarComparison {
+    /// Generate a random pair at most `EAR_DRILL_MAX_SEMITONES` apart.
+    /// Roughly a third of the pairs are the same note so "same" stays a real option.
+    pub fn random<R: Rng>(rng: &mut R, config: &QuizConfig) -> Self {
+        let left = random_root(rng, config);
+        let offset = if rng.gen_bool(1.0 / 3.0) {
+            0
+        } else {
+            let size = rng.gen_range(1..=EAR_DRILL_MAX_SEMITONES) as i32;
+            if rng.gen_bool(0.5) { size } else { -size }
+        };
+        let right = left.transpose(offset).unwrap_or(left);
+        Self { left, right }
+    }
+
+    /// Semitones from the left note up to the right one
+    pub fn semitones(&self) -> i32 {
+        self.right.absolute_semitone() - self.left.absolute_semitone()
+    }
+
+    pub fn answer(&self) -> EarAnswer {
+        match self.left.cmp(&self.right) {
+            std::cmp::Ordering::Greater => EarAnswer::Left,
+            std::cmp::Ordering::Less => EarAnswer::Right,
+            std::cmp::Ordering::Equal => EarAnswer::Same,
+        }
+    }
+
+    pub fn is_correct(&self, answer: &EarAnswer) -> bool {
+        self.answer() == *answer
+    }
+
+    /// Play the left note hard left, then the right note hard right, a beat apart
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let beat = tempo.beat_duration();
+        engine.play_note_with(&self.left, beat, &PlaybackOptions::new().with_pan(-1.0))?;
+        engine.rest(beat)?;
+        engine.play_note_with(&self.right, beat, &PlaybackOptions::new().with_pan(1.0))
+    }
+}
+
+/// Whether a note was sharp, flat or in tune
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IntonationAnswer {
+    Flat,
+    InTune,
+    Sharp,
+}
+
+impl fmt::Display for IntonationAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let answer = match self {
+            IntonationAnswer::Flat => "flat",
+            IntonationAnswer::InTune => "in tune",
+            IntonationAnswer::Sharp => "sharp",
+        };
+        write!(f, "{}", answer)
+    }
+}
+
+impl FromStr for IntonationAnswer {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "f" | "flat" => Ok(IntonationAnswer::Flat),
+            "i" | "t" | "in tune" | "in-tune" | "tune" => Ok(IntonationAnswer::InTune),
+            "s" | "sharp" => Ok(IntonationAnswer::Sharp),
+            _ => Err(format!("Invalid answer: {}", s)),
+        }
+    }
+}
+
+/// A note played in tune as a reference, then again possibly detuned, for the user
+/// to say whether the second was sharp, flat or in tune
+#[derive(Debug, PartialEq)]
+pub struct IntonationQuestion {
+    pub pitch: Pitch,
+}
+
+impl IntonationQuestion {
+    /// A random note between C3 and B4 or in the configured range, in tune a third of
+    /// the time and otherwise between half of `max_cents` and `max_cents` sharp or flat
+    pub fn random<R: Rng>(rng: &mut R, max_cents: f64, config: &QuizConfig) -> Self {
+        let note = random_root(rng, config);
+        let max_cents = max_cents.abs().max(1.0);
+        let cents = if rng.gen_bool(1.0 / 3.0) {
+            0.0
+        } else {
+            let size = rng.gen_range(max_cents / 2.0..=max_cents).round();
+            if rng.gen_bool(0.5) { size } else { -size }
+        };
+        Self {
+            pitch: Pitch::new(note, cents),
+        }
+    }
+
+    pub fn answer(&self) -> IntonationAnswer {
+        match self.pitch.cents.partial_cmp(&0.0) {
+            Some(std::cmp::Ordering::Greater) => IntonationAnswer::Sharp,
+            Some(std::cmp::Ordering::Less) => IntonationAnswer::Flat,
+            _ => IntonationAnswer::InTune,
+        }
+    }
+
+    pub fn is_correct(&self, answer: &IntonationAnswer) -> bool {
+        self.answer() == *answer
+    }
+
+    /// Play the note in tune, then the question's pitch, a beat apart
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let beat = tempo.beat_duration();
+        let opts = PlaybackOptions::new();
+        engine.play_pitch_with(&Pitch::from(self.pitch.note), beat, &opts)?;
+        engine.rest(beat)?;
+        engine.play_pitch_with(&self.pitch, beat, &opts)
+    }
+}
+
+/// Random interval of the given size with a root between C3 and B4, or in the configured range
+fn random_prompt<R: Rng>(rng: &mut R, semitones: u8, config: &QuizConfig) -> IntervalPrompt {
+    IntervalPrompt {
+        root: random_root(rng, config),
+        semitones,
+    }
+}
+
+/// Random root note between C3 and B4, or in the configured range
+fn random_root<R: Rng>(rng: &mut R, config: &QuizConfig) -> NoteWithOctave {
+    let low = NoteWithOctave {
+        note: Note::C,
+        octave: 3,
+    };
+    let high = NoteWithOctave {
+        note: Note::B,
+        octave: 4,
+    };
+    config.random_note(rng, &low, &high)
+}
+
+/// Running tally of answers in a quiz session
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuizScore {
+    pub correct: u32,
+    pub total: u32,
+}
+
+impl fmt::Display for QuizScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} ({:.0}%)",
+            self.correct,
+            self.total,
+            self.accuracy() * 100.0
+        )
+    }
+}
+
+impl QuizScore {
+    pub fn record(&mut self, correct: bool) {
+        self.total += 1;
+        if correct {
+            self.correct += 1;
+        }
+    }
+
+    /// Fraction of correct answers, 0.0 before any answers
+    pub fn accuracy(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.total as f64
+    }
+}
+
+/// One question of the interval recognition quiz
+#[derive(Debug, PartialEq)]
+pub struct IntervalQuestion {
+    pub root: NoteWithOctave,
+    pub interval: Interval,
+}
+
+impl IntervalQuestion {
+    /// Pick a random root and a random interval from `allowed`
+    pub fn random<R: Rng>(rng: &mut R, allowed: &[Interval]) -> Result<Self, String> {
+        let interval = random_interval(rng, allowed)?;
+        Ok(Self::for_interval(rng, interval))
+    }
+
+    /// Ask about `interval` above a random root
+    pub fn for_interval<R: Rng>(rng: &mut R, interval: Interval) -> Self {
+        Self {
+            root: random_root(rng, &QuizConfig::default()),
+            interval,
+        }
+    }
+
+    /// Ask about `interval` with both notes between `low` and `high` where they fit,
+    /// otherwise with the root on `low`
+    pub fn for_interval_in<R: Rng>(
+        rng: &mut R,
+        interval: Interval,
+        low: &NoteWithOctave,
+        high: &NoteWithOctave,
+    ) -> Self {
+        let highest = high
+            .transpose(-(interval.semitones() as i32))
+            .unwrap_or(*low)
+            .max(*low);
+        let root = random_note(rng, low, &highest).unwrap_or(*low);
+        Self { root, interval }
+    }
+
+    /// The upper note of the question's interval
+    pub fn top(&self) -> Option<NoteWithOctave> {
+        self.interval.apply_to(&self.root)
+    }
+
+    /// Answers are graded by size, so a diminished fifth counts for a tritone
+    pub fn is_correct(&self, answer: &Interval) -> bool {
+        answer.semitones() == self.interval.semitones()
+    }
+
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        style: IntervalPlayStyle,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.interval.play_from(&self.root, engine, style, tempo)
+    }
+}
+
+/// Simple intervals from the minor second up to the octave
+pub fn default_quiz_intervals() -> Vec<Interval> {
+    (1..=12).map(Interval::from_semitones).collect()
+}
+
+/// Reference note played before every mystery note
+pub const NOTE_QUIZ_REFERENCE: NoteWithOctave = NoteWithOctave {
+    note: Note::C,
+    octave: 4,
+};
+
+/// Parse a range of notes like "C4-B4", lowest note first
+pub fn parse_note_range(s: &str) -> Result<(NoteWithOctave, NoteWithOctave), String> {
+    let (low, high) = s
+        .split_once('-')
+        .ok_or_else(|| format!("Invalid note range: {}", s))?;
+    let low: NoteWithOctave = low.trim().parse()?;
+    let high: NoteWithOctave = high.trim().parse()?;
+    if low > high {
+        return Err(format!("{} is above {}", low, high));
+    }
+    Ok((low, high))
+}
+
+/// Per-note tally for the note identification quiz, keyed by note name
+#[derive(Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoteAccuracy {
+    scores: [QuizScore; 12],
+}
+
+impl NoteAccuracy {
+    pub fn record(&mut self, note: &Note, correct: bool) {
+        self.scores[note.to_semitone() as usize].record(correct);
+    }
+
+    pub fn score(&self, note: &Note) -> &QuizScore {
+        &self.scores[note.to_semitone() as usize]
+    }
+
+    /// How often a note should come up: untried and often-missed notes are drilled most
+    pub fn weight(&self, note: &Note) -> f64 {
+        let score = self.score(note);
+        if score.total == 0 {
+            return 3.0;
+        }
+        1.0 + 2.0 * (1.0 - score.accuracy())
+    }
+
+    /// Notes that have been asked at least once, with their scores
+    pub fn results(&self) -> Vec<(Note, &QuizScore)> {
+        (0..12)
+            .filter_map(Note::from_semitone)
+            .map(|note| {
+                let score = self.score(&note);
+                (note, score)
+            })
+            .filter(|(_, score)| score.total > 0)
+            .collect()
+    }
+}
+
+/// One question of the note identification quiz: name the mystery note after the reference
+#[derive(Debug, PartialEq)]
+pub struct NoteQuestion {
+    pub reference: NoteWithOctave,
+    pub mystery: NoteWithOctave,
+}
+
+impl NoteQuestion {
+    /// Pick a mystery note between `low` and `high`, favouring notes missed in `accuracy`
+    pub fn random<R: Rng>(
+        rng: &mut R,
+        low: &NoteWithOctave,
+        high: &NoteWithOctave,
+        accuracy: &NoteAccuracy,
+    ) -> Result<Self, String> {
+        let candidates: Vec<NoteWithOctave> = (low.absolute_semitone()..=high.absolute_semitone())
+            .filter_map(NoteWithOctave::from_absolute_semitone)
+            .collect();
+        let weights = candidates.iter().map(|n| accuracy.weight(&n.note));
+        let index = WeightedIndex::new(weights).map_err(|_| "No notes to choose from")?;
+        let mystery = candidates
+            .into_iter()
+            .nth(index.sample(rng))
+            .ok_or("No notes to choose from")?;
+
+        Ok(Self {
+            reference: NOTE_QUIZ_REFERENCE,
+            mystery,
+        })
+    }
+
+    /// Answers are graded by note name, so any octave counts
+    pub fn is_correct(&self, answer: &Note) -> bool {
+        *answer == self.mystery.note
+    }
+
+    /// Play the reference, then the mystery note after a short pause
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Melody::new()
+            .with_note(self.reference, NoteValue::Quarter)
+            .with_rest(NoteValue::Eighth)
+            .with_note(self.mystery, NoteValue::Quarter)
+            .play_on(engine, tempo)
+    }
+}
+
+/// How a phrase ends, judged by its last two chords
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Cadence {
+    /// V to I, the strongest close
+    Authentic,
+    /// IV to I, the "amen" ending
+    Plagal,
+    /// V to vi, where the resolution is sidestepped
+    Deceptive,
+    /// Ending on V, left hanging
+    Half,
+}
+
+impl fmt::Display for Cadence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Cadence::Authentic => "Authentic",
+            Cadence::Plagal => "Plagal",
+            Cadence::Deceptive => "Deceptive",
+            Cadence::Half => "Half",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Cadence {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "authentic" | "perfect" | "a" => Ok(Cadence::Authentic),
+            "plagal" | "p" => Ok(Cadence::Plagal),
+            "deceptive" | "interrupted" | "d" => Ok(Cadence::Deceptive),
+            "half" | "imperfect" | "h" => Ok(Cadence::Half),
+            _ => Err(format!("Invalid cadence: {}", s)),
+        }
+    }
+}
+
+impl Cadence {
+    pub const ALL: [Cadence; 4] = [
+        Cadence::Authentic,
+        Cadence::Plagal,
+        Cadence::Deceptive,
+        Cadence::Half,
+    ];
+
+    /// A short phrase in a major key that ends with this cadence
+    pub fn numerals(&self) -> &'static str {
+        match self {
+            Cadence::Authentic => "I-IV-V-I",
+            Cadence::Plagal => "I-vi-IV-I",
+            Cadence::Deceptive => "I-IV-V-vi",
+            Cadence::Half => "I-vi-IV-V",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Cadence::ALL.iter().position(|c| c == self).unwrap_or(0)
+    }
+}
+
+/// Per-cadence tally for the cadence quiz
+#[derive(Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CadenceAccuracy {
+    scores: [QuizScore; 4],
+}
+
+impl CadenceAccuracy {
+    pub fn record(&mut self, cadence: &Cadence, correct: bool) {
+        self.scores[cadence.index()].record(correct);
+    }
+
+    pub fn score(&self, cadence: &Cadence) -> &QuizScore {
+        &self.scores[cadence.index()]
+    }
+
+    /// Cadences that have been asked at least once, with their scores
+    pub fn results(&self) -> Vec<(Cadence, &QuizScore)> {
+        Cadence::ALL
+            .into_iter()
+            .map(|cadence| (cadence, self.score(&cadence)))
+            .filter(|(_, score)| score.total > 0)
+            .collect()
+    }
+}
+
+/// One question of the cadence quiz: a phrase in a random major key
+#[derive(Debug, PartialEq)]
+pub struct CadenceQuestion {
+    pub cadence: Cadence,
+    pub progression: Progression,
+}
+
+impl CadenceQuestion {
+    /// Pick a cadence and one of the major keys `config` allows, with the tonic at or above C4
+    pub fn random<R: Rng>(rng: &mut R, config: &QuizConfig) -> Result<Self, String> {
+        let cadence = Cadence::ALL[rng.gen_range(0..Cadence::ALL.len())];
+        Self::in_key(cadence, random_major_tonic(rng, config)?)
+    }
+
+    /// The phrase for `cadence` in the major key on `tonic`
+    pub fn in_key(cadence: Cadence, tonic: SpelledPitch) -> Result<Self, String> {
+        let key = Scale::spelled(tonic, ScaleType::Major)?;
+        Ok(Self {
+            cadence,
+            progression: Progression::parse(key, cadence.numerals())?,
+        })
+    }
+
+    pub fn is_correct(&self, answer: &Cadence) -> bool {
+        *answer == self.cadence
+    }
+
+    /// Play the phrase as block chords, without naming them
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for chord in self.progression.chords() {
+            chord.play_together_on(engine, tempo.duration_of(NoteValue::Half))?;
+        }
+        Ok(())
+    }
+}
+
+/// Chord qualities the inversion quiz is asked about
+pub const INVERSION_QUIZ_QUALITIES: [ChordQuality; 5] = [
+    ChordQuality::Major,
+    ChordQuality::Minor,
+    ChordQuality::Dominant7,
+    ChordQuality::Major7,
+    ChordQuality::Minor7,
+];
+
+/// One question of the inversion quiz: a block chord with one of its tones in the bass
+#[derive(Debug, PartialEq)]
+pub struct InversionQuestion {
+    pub chord: Chord,
+}
+
+impl InversionQuestion {
+    /// Pick a quality from the `INVERSION_QUIZ_QUALITIES` that `config` allows, a root
+    /// between C3 and B4 (or in the configured range) and any inversion the chord has
+    pub fn random<R: Rng>(rng: &mut R, config: &QuizConfig) -> Result<Self, String> {
+        let qualities = config.filter_chords(&INVERSION_QUIZ_QUALITIES);
+        if qualities.is_empty() {
+            return Err("No chord qualities to choose from".to_string());
+        }
+        let quality = qualities[rng.gen_range(0..qualities.len())].clone();
+        let inversion = rng.gen_range(0..quality.intervals().len());
+        Ok(Self {
+            chord: Chord::new(random_root(rng, config), quality).invert(inversion)?,
+        })
+    }
+
+    pub fn is_correct(&self, answer: &Inversion) -> bool {
+        *answer == self.chord.inversion
+    }
+
+    /// Sound the chord as a block chord, without naming it
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.chord
+            .play_together_on(engine, tempo.duration_of(NoteValue::Half))
+    }
+}
+
+/// Movable-do syllables for the degrees of a major scale
+pub const SOLFEGE: [&str; 7] = ["do", "re", "mi", "fa", "sol", "la", "ti"];
+
+/// Parse a major scale degree given as a number ("5") or a syllable ("sol", "so")
+pub fn parse_scale_degree(s: &str) -> Result<usize, String> {
+    let s = s.trim().to_lowercase();
+    if let Ok(degree) = s.parse::<usize>()
+        && (1..=7).contains(&degree)
+    {
+        return Ok(degree);
+    }
+    let syllable = match s.as_str() {
+        "so" => "sol",
+        "si" => "ti",
+        syllable => syllable,
+    };
+    SOLFEGE
+        .iter()
+        .position(|&name| name == syllable)
+        .map(|index| index + 1)
+        .ok_or_else(|| format!("Invalid scale degree: {}", s))
+}
+
+/// Per-degree tally for the scale degree quiz
+#[derive(Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DegreeAccuracy {
+    scores: [QuizScore; 7],
+}
+
+impl DegreeAccuracy {
+    pub fn record(&mut self, degree: usize, correct: bool) {
+        if let Some(score) = self.scores.get_mut(degree.wrapping_sub(1)) {
+            score.record(correct);
+        }
+    }
+
+    /// Score for a degree from 1 to 7
+    pub fn score(&self, degree: usize) -> Option<&QuizScore> {
+        self.scores.get(degree.wrapping_sub(1))
+    }
+
+    /// How often a degree should come up: untried and often-missed degrees are drilled most
+    pub fn weight(&self, degree: usize) -> f64 {
+        match self.score(degree) {
+            Some(score) if score.total > 0 => 1.0 + 2.0 * (1.0 - score.accuracy()),
+            _ => 3.0,
+        }
+    }
+
+    /// Degrees that have been asked at least once, with their scores
+    pub fn results(&self) -> Vec<(usize, &QuizScore)> {
+        (1..=7)
+            .filter_map(|degree| Some((degree, self.score(degree)?)))
+            .filter(|(_, score)| score.total > 0)
+            .collect()
+    }
+}
+
+/// One question of the functional ear training quiz: after a cadence sets up the key,
+/// name the scale degree of a single note
+#[derive(Debug, PartialEq)]
+pub struct DegreeQuestion {
+    /// I-IV-V-I, played first so the tonic is heard as home
+    pub cadence: Progression,
+    /// Scale degree of the mystery note, from 1 to 7
+    pub degree: usize,
+}
+
+impl DegreeQuestion {
+    /// Pick one of the twelve major keys and a degree, favouring degrees missed in `accuracy`
+    pub fn random<R: Rng>(
+        rng: &mut R,
+        accuracy: &DegreeAccuracy,
+        config: &QuizConfig,
+    ) -> Result<Self, String> {
+        let weights = (1..=7).map(|degree| accuracy.weight(degree));
+        let index = WeightedIndex::new(weights).map_err(|_| "No degrees to choose from")?;
+        Self::in_key(index.sample(rng) + 1, random_major_tonic(rng, config)?)
+    }
+
+    /// Degree `degree` of the major key on `tonic`
+    pub fn in_key(degree: usize, tonic: SpelledPitch) -> Result<Self, String> {
+        if !(1..=7).contains(&degree) {
+            return Err(format!("Invalid scale degree: {}", degree));
+        }
+        let key = Scale::spelled(tonic, ScaleType::Major)?;
+        Ok(Self {
+            cadence: Progression::parse(key, Cadence::Authentic.numerals())?,
+            degree,
+        })
+    }
+
+    /// The mystery note, in the octave above the tonic
+    pub fn note(&self) -> Option<NoteWithOctave> {
+        self.cadence.key.degree(self.degree)
+    }
+
+    /// The mystery note as written in the key, with its syllable, e.g. "F#4 (ti)"
+    pub fn answer(&self) -> String {
+        match self.cadence.key.spelled_degree(self.degree) {
+            Some(note) => format!("{} ({})", note, SOLFEGE[self.degree - 1]),
+            None => SOLFEGE[self.degree - 1].to_string(),
+        }
+    }
+
+    pub fn is_correct(&self, degree: usize) -> bool {
+        degree == self.degree
+    }
+
+    /// Play the cadence as block chords, then the mystery note after a pause
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for chord in self.cadence.chords() {
+            chord.play_together_on(engine, tempo.duration_of(NoteValue::Half))?;
+        }
+        self.play_note(engine, tempo)
+    }
+
+    /// Play only the mystery note with a rest before it, for replays
+    #[cfg(feature = "playback")]
+    pub fn play_note(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let note = self.note().ok_or("Scale degree is out of range")?;
+        Melody::new()
+            .with_rest(NoteValue::Quarter)
+            .with_note(note, NoteValue::Half)
+            .play_on(engine, tempo)
+    }
+}
+
+/// One question of the sing-back exercise: a note or short melody to sing after hearing it
+#[derive(Debug, PartialEq)]
+pub struct SingBackQuestion {
+    pub key: Scale,
+    pub melody: Melody,
+}
+
+impl SingBackQuestion {
+    /// A melody of `length` notes in a random major key that `config` allows,
+    /// or one note of the key when `length` is 1
+    pub fn random<R: Rng>(rng: &mut R, length: usize, config: &QuizConfig) -> Result<Self, String> {
+        let key = Scale::spelled(random_major_tonic(rng, config)?, ScaleType::Major)?;
+        let melody = if length == 1 {
+            let degree = rng.gen_range(1..=8);
+            let note = key
+                .degree(degree)
+                .ok_or_else(|| format!("{} has no degree {}", key, degree))?;
+            Melody::from_notes(&[note], NoteValue::Half)
+        } else {
+            random_melody(rng, &key, length, NoteValue::Half)?
+        };
+        Ok(Self { key, melody })
+    }
+
+    /// How long a recording needs to be to hold the melody sung at `tempo`
+    pub fn duration(&self, tempo: Tempo) -> Duration {
+        self.melody.duration(tempo)
+    }
+
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.melody.play_on(engine, tempo)
+    }
+
+    /// Compare a recording of the user singing the melody at `tempo` with the notes played.
+    /// Timing starts from the first sung pitch, and each note is judged on the median pitch
+    /// of the middle of its beat, so scooping into or out of a note isn't held against it.
+    pub fn score(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        tempo: Tempo,
+        detector: &PitchDetector,
+        tuning: &Tuning,
+    ) -> Vec<SungNote> {
+        let hop = (sample_rate / SING_BACK_FRAMES_PER_SECOND).max(1) as usize;
+        let track = detector.track(samples, sample_rate, hop);
+        let frames_per_beat = tempo.beat_duration().as_secs_f64() * sample_rate as f64 / hop as f64;
+
+        let mut start = track
+            .iter()
+            .position(Option::is_some)
+            .unwrap_or(track.len()) as f64;
+        let mut sung = Vec::new();
+        for (event, value) in self.melody.events() {
+            let length = value.beats() * frames_per_beat;
+            if let Event::Note(target) = event {
+                let from = ((start + length * 0.2) as usize).min(track.len());
+                let to = ((start + length * 0.8) as usize).clamp(from, track.len());
+                let mut pitches: Vec<f64> = track[from..to].iter().flatten().copied().collect();
+                pitches.sort_by(f64::total_cmp);
+
+                let frequency = pitches.get(pitches.len() / 2).copied();
+                sung.push(SungNote {
+                    target: *target,
+                    frequency,
+                    cents: frequency.map(|f| {
+                        // Singing in another octave still counts, so fold the error into one
+                        let cents = cents_from(tuning.frequency_of(target), f);
+                        (cents + 600.0).rem_euclid(1200.0) - 600.0
+                    }),
+                });
+            }
+            start += length;
+        }
+        sung
+    }
+}
+
+/// How one note of a sing-back melody was sung
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SungNote {
+    pub target: NoteWithOctave,
+    /// Median pitch sung for the note, or `None` if nothing pitched was heard
+    pub frequency: Option<f64>,
+    /// Distance from the target in cents, ignoring which octave was sung
+    pub cents: Option<f64>,
+}
+
+impl SungNote {
+    pub fn is_correct(&self, tolerance_cents: f64) -> bool {
+        self.cents
+            .is_some_and(|cents| cents.abs() <= tolerance_cents)
+    }
+}
+
+/// Notes in a melodic dictation melody unless the user asks for more or fewer
+pub const DICTATION_DEFAULT_LENGTH: usize = 4;
+
+/// Scale degrees above the tonic a dictation melody may climb by default, up to the octave
+pub const DICTATION_DEFAULT_RANGE: u8 = 7;
+
+/// Biggest leap between dictation notes by default, in scale degrees (a third)
+pub const DICTATION_DEFAULT_MAX_LEAP: u8 = 2;
+
+/// How melodic dictation melodies are made
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DictationOptions {
+    pub length: usize,
+    pub range: u8,
+    pub max_leap: u8,
+}
+
+impl Default for DictationOptions {
+    fn default() -> Self {
+        Self {
+            length: DICTATION_DEFAULT_LENGTH,
+            range: DICTATION_DEFAULT_RANGE,
+            max_leap: DICTATION_DEFAULT_MAX_LEAP,
+        }
+    }
+}
+
+impl DictationOptions {
+    pub fn with_length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    pub fn with_range(mut self, range: u8) -> Self {
+        self.range = range;
+        self
+    }
+
+    pub fn with_max_leap(mut self, max_leap: u8) -> Self {
+        self.max_leap = max_leap;
+        self
+    }
+}
+
+/// How one note of a dictation answer compares with the melody
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NoteFeedback {
+    Correct(NoteWithOctave),
+    Wrong {
+        expected: NoteWithOctave,
+        got: NoteWithOctave,
+    },
+    /// A note of the melody left out of the answer
+    Missing(NoteWithOctave),
+    /// A note in the answer that isn't in the melody
+    Extra(NoteWithOctave),
+}
+
+impl fmt::Display for NoteFeedback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.named(NoteNaming::default(), &Note::C))
+    }
+}
+
+impl NoteFeedback {
+    pub fn is_correct(&self) -> bool {
+        matches!(self, NoteFeedback::Correct(_))
+    }
+
+    /// The feedback with notes named in `naming`, e.g. "❌ Mi4 (you wrote Fa4)"
+    pub fn named(&self, naming: NoteNaming, tonic: &Note) -> String {
+        let name = |note: &NoteWithOctave| naming.name_pitch(note, tonic);
+        match self {
+            NoteFeedback::Correct(note) => format!("✅ {}", name(note)),
+            NoteFeedback::Wrong { expected, got } => {
+                format!("❌ {} (you wrote {})", name(expected), name(got))
+            }
+            NoteFeedback::Missing(note) => format!("❌ {} (missing)", name(note)),
+            NoteFeedback::Extra(note) => format!("❌ {} (extra)", name(note)),
+        }
+    }
+}
+
+/// A dictation answer compared note by note with the melody
+#[derive(Debug, PartialEq, Clone)]
+pub struct DictationScore {
+    pub notes: Vec<NoteFeedback>,
+}
+
+impl fmt::Display for DictationScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} notes right", self.correct(), self.total())
+    }
+}
+
+impl DictationScore {
+    /// Notes of the melody written down right
+    pub fn correct(&self) -> usize {
+        self.notes.iter().filter(|note| note.is_correct()).count()
+    }
+
+    /// Notes in the melody
+    pub fn total(&self) -> usize {
+        self.notes
+            .iter()
+            .filter(|note| !matches!(note, NoteFeedback::Extra(_)))
+            .count()
+    }
+
+    pub fn is_perfect(&self) -> bool {
+        self.notes.iter().all(NoteFeedback::is_correct)
+    }
+}
+
+/// One note of a dictation answer, with or without its octave
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum WrittenNote {
+    Pitch(NoteWithOctave),
+    PitchClass(Note),
+}
+
+impl WrittenNote {
+    fn matches(&self, expected: &NoteWithOctave) -> bool {
+        match self {
+            WrittenNote::Pitch(note) => note == expected,
+            WrittenNote::PitchClass(note) => *note == expected.note,
+        }
+    }
+
+    /// The note as written, placed in the octave of `expected` when no octave was given
+    fn in_octave_of(&self, expected: &NoteWithOctave) -> NoteWithOctave {
+        match self {
+            WrittenNote::Pitch(note) => *note,
+            WrittenNote::PitchClass(note) => NoteWithOctave {
+                note: *note,
+                octave: expected.octave,
+            },
+        }
+    }
+}
+
+/// One question of the melodic dictation exercise: a short melody in a major key to write down
+#[derive(Debug, PartialEq)]
+pub struct MelodicDictationQuestion {
+    pub key: Scale,
+    pub melody: Melody,
+}
+
+impl MelodicDictationQuestion {
+    /// A melody made with `options` in one of the major keys `config` allows
+    pub fn random<R: Rng>(
+        rng: &mut R,
+        options: &DictationOptions,
+        config: &QuizConfig,
+    ) -> Result<Self, String> {
+        let key = Scale::spelled(random_major_tonic(rng, config)?, ScaleType::Major)?;
+        let melody = random_diatonic_melody(
+            rng,
+            &key,
+            options.length,
+            options.range,
+            options.max_leap,
+            NoteValue::Quarter,
+        )?;
+        Ok(Self { key, melody })
+    }
+
+    /// Compare space-separated note names with the melody, lining them up so that one
+    /// missed or added note doesn't mark every note after it wrong.
+    /// Notes written without an octave, like "E", only need the right letter and accidental.
+    /// Notes may also be written in `naming`, with movable do counted from the key's tonic.
+    pub fn score(&self, answer: &str, naming: NoteNaming) -> Result<DictationScore, String> {
+        let tonic = self.key.root.note;
+        let written = answer
+            .split_whitespace()
+            .map(|token| {
+                // A bare "E" would be read as E4, so look for the octave first
+                let written = if naming.has_octave(token) {
+                    naming.parse_pitch(token, &tonic).map(WrittenNote::Pitch)
+                } else {
+                    naming
+                        .parse_note(token, &tonic)
+                        .map(WrittenNote::PitchClass)
+                };
+                written.map_err(|_| format!("Invalid note: {}", token))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if written.is_empty() {
+            return Err("Write the melody as note names, e.g. C4 D4 E4".to_string());
+        }
+        Ok(DictationScore {
+            notes: align_notes(&self.melody.notes(), &written),
+        })
+    }
+
+    /// Play the tonic triad to set the key, then the melody after a rest
+    #[cfg(feature = "playback")]
+    pub fn play(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Chord::new(self.key.root, ChordQuality::Major)
+            .play_together_on(engine, tempo.duration_of(NoteValue::Half))?;
+        self.play_melody(engine, tempo)
+    }
+
+    /// Play only the melody with a rest before it, for replays
+    #[cfg(feature = "playback")]
+    pub fn play_melody(
+        &self,
+        engine: &AudioEngine,
+        tempo: Tempo,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Melody::new()
+            .with_rest(NoteValue::Quarter)
+            .then(self.melody.clone())
+            .play_on(engine, tempo)
+    }
+}
+
+/// Line up `written` with `expected` by edit distance, so each expected note is either
+/// matched, written wrong or missing, and anything left over is extra
+fn align_notes(expected: &[NoteWithOctave], written: &[WrittenNote]) -> Vec<NoteFeedback> {
+    let (rows, columns) = (expected.len() + 1, written.len() + 1);
+    let mut cost = vec![vec![0; columns]; rows];
+    cost[0] = (0..columns).collect();
+    for (i, row) in cost.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..rows {
+        for j in 1..columns {
+            let substitution = usize::from(!written[j - 1].matches(&expected[i - 1]));
+            cost[i][j] = (cost[i - 1][j - 1] + substitution)
+                .min(cost[i - 1][j] + 1)
+                .min(cost[i][j - 1] + 1);
+        }
+    }
+
+    let mut feedback = Vec::new();
+    let (mut i, mut j) = (expected.len(), written.len());
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 {
+            let substitution = usize::from(!written[j - 1].matches(&expected[i - 1]));
+            if cost[i][j] == cost[i - 1][j - 1] + substitution {
+                let wanted = expected[i - 1];
+                feedback.push(if substitution == 0 {
+                    NoteFeedback::Correct(wanted)
+                } else {
+                    NoteFeedback::Wrong {
+                        expected: wanted,
+                        got: written[j - 1].in_octave_of(&wanted),
+                    }
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+        }
+        if i > 0 && cost[i][j] == cost[i - 1][j] + 1 {
+            feedback.push(NoteFeedback::Missing(expected[i - 1]));
+            i -= 1;
+        } else {
+            let neighbour = expected.get(i.saturating_sub(1)).or(expected.first());
+            let got = match neighbour {
+                Some(wanted) => written[j - 1].in_octave_of(wanted),
+                None => written[j - 1].in_octave_of(&NOTE_QUIZ_REFERENCE),
+            };
+            feedback.push(NoteFeedback::Extra(got));
+            j -= 1;
+        }
+    }
+    feedback.reverse();
+    feedback
+}
+
+/// One of the twelve major key tonics that `config` allows, from C4 up to B4
+fn random_major_tonic<R: Rng>(rng: &mut R, config: &QuizConfig) -> Result<SpelledPitch, String> {
+    let tonics: Vec<SpelledNote> = circle_of_fifths()
+        .map(|key| key.major_tonic())
+        .filter(|tonic| config.allows_key(&tonic.pitch()))
+        .collect();
+    if tonics.is_empty() {
+        return Err("No keys to choose from".to_string());
+    }
+    Ok(SpelledPitch {
+        note: tonics[rng.gen_range(0..tonics.len())],
+        octave: 4,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naming::{NamingScheme, PitchNotation};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_cents_error_octave() {
+        let cents = cents_error(440.0, 880.0).unwrap();
+        assert!((cents - 1200.0).abs() < 0.01);
+
+        // Error is symmetric
+        let cents = cents_error(880.0, 440.0).unwrap();
+        assert!((cents - 1200.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_cents_error_rejects_non_positive() {
+        assert!(cents_error(440.0, 0.0).is_err());
+        assert!(cents_error(-1.0, 440.0).is_err());
+    }
+
+    #[test]
+    fn test_grade_frequency_guess() {
+        assert_eq!(
+            grade_frequency_guess(440.0, 445.0).unwrap(),
+            GuessGrade::Perfect
+        );
+        assert_eq!(
+            grade_frequency_guess(440.0, 466.0).unwrap(),
+            GuessGrade::Close
+        );
+        assert_eq!(
+            grade_frequency_guess(440.0, 500.0).unwrap(),
+            GuessGrade::Near
+        );
+        assert_eq!(
+            grade_frequency_guess(440.0, 880.0).unwrap(),
+            GuessGrade::Off
+        );
+    }
+
+    #[test]
+    fn test_random_frequency_in_range() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let frequency = random_frequency(&mut rng, ESTIMATION_MIN_HZ, ESTIMATION_MAX_HZ);
+            assert!((ESTIMATION_MIN_HZ..ESTIMATION_MAX_HZ).contains(&frequency));
+        }
+    }
+
+    #[test]
+    fn test_is_nearest_note() {
+        let a4 = NoteWithOctave {
+            note: Note::A,
+            octave: 4,
+        };
+        let tuning = Tuning::default();
+        assert!(is_nearest_note(445.0, &a4, &tuning).unwrap());
+        assert!(!is_nearest_note(466.16, &a4, &tuning).unwrap());
+    }
+
+    #[test]
+    fn test_interval_prompt_upper_frequency() {
+        let octave = IntervalPrompt {
+            root: NoteWithOctave {
+                note: Note::A,
+                octave: 4,
+            },
+            semitones: 12,
+        };
+        assert!((octave.upper_frequency(&Tuning::default()) - 880.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_comparison_answer_parsing() {
+        assert_eq!(
+            "1".parse::<ComparisonAnswer>().unwrap(),
+            ComparisonAnswer::First
+        );
+        assert_eq!(
+            "second".parse::<ComparisonAnswer>().unwrap(),
+            ComparisonAnswer::Second
+        );
+        assert_eq!(
+            "s".parse::<ComparisonAnswer>().unwrap(),
+            ComparisonAnswer::Same
+        );
+        assert!("3".parse::<ComparisonAnswer>().is_err());
+    }
+
+    #[test]
+    fn test_random_comparisons_are_gradable() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut seen_same = false;
+        for _ in 0..200 {
+            let comparison = IntervalComparison::random(&mut rng, &QuizConfig::default()).unwrap();
+            assert!((1..=COMPARISON_MAX_SEMITONES).contains(&comparison.first.semitones));
+            assert!((1..=COMPARISON_MAX_SEMITONES).contains(&comparison.second.semitones));
+
+            let answer = comparison.answer();
+            seen_same |= answer == ComparisonAnswer::Same;
+            assert!(comparison.is_correct(&answer));
+        }
+        assert!(seen_same, "Same-size pairs should be generated");
+    }
+
+    #[test]
+    fn test_ear_answer_parsing() {
+        assert_eq!("l".parse::<EarAnswer>().unwrap(), EarAnswer::Left);
+        assert_eq!("right".parse::<EarAnswer>().unwrap(), EarAnswer::Right);
+        assert_eq!("same".parse::<EarAnswer>().unwrap(), EarAnswer::Same);
+        assert!("up".parse::<EarAnswer>().is_err());
+    }
+
+    #[test]
+    fn test_random_ear_comparisons_are_close() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut seen = [false; 3];
+        for _ in 0..200 {
+            let comparison = EarComparison::random(&mut rng, &QuizConfig::default());
+            assert!(comparison.semitones().unsigned_abs() <= EAR_DRILL_MAX_SEMITONES as u32);
+
+            let answer = comparison.answer();
+            assert!(comparison.is_correct(&answer));
+            match answer {
+                EarAnswer::Left => seen[0] = true,
+                EarAnswer::Right => seen[1] = true,
+                EarAnswer::Same => seen[2] = true,
+            }
+        }
+        assert_eq!(seen, [true; 3]);
+    }
+
+    #[test]
+    fn test_quarter_tone_intervals() {
+        let parse = |s: &str| s.parse::<QuarterToneInterval>().unwrap().steps;
+        assert_eq!(parse("3.5"), 7);
+        assert_eq!(parse("0.5 semitones"), 1);
+        assert_eq!(parse("Neutral Third"), 7);
+        assert_eq!(parse("P5"), 14);
+        assert_eq!(parse("minor third"), 6);
+        assert!("3.25".parse::<QuarterToneInterval>().is_err());
+        assert!("huge".parse::<QuarterToneInterval>().is_err());
+
+        assert_eq!(
+            QuarterToneInterval { steps: 7 }.to_string(),
+            "Neutral Third"
+        );
+        assert_eq!(QuarterToneInterval { steps: 8 }.to_string(), "Major Third");
+        assert_eq!(
+            QuarterToneInterval { steps: 9 }.to_string(),
+            "4.5 semitones"
+        );
+        for steps in 1..=QUARTER_TONE_MAX_STEPS {
+            let interval = QuarterToneInterval { steps };
+            assert_eq!(
+                interval.to_string().parse::<QuarterToneInterval>(),
+                Ok(interval)
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_quarter_tone_questions() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut between_semitones = 0;
+        for _ in 0..100 {
+            let question = QuarterToneQuestion::random(&mut rng, &QuizConfig::default());
+            let steps = question.interval.steps;
+            assert!((1..=QUARTER_TONE_MAX_STEPS).contains(&steps));
+            between_semitones += steps % 2;
+
+            assert!(question.top_name().ends_with(|c: char| c.is_ascii_digit()));
+            let (root, top) = question.frequencies(&Tuning::STANDARD);
+            let cents = 1200.0 * (top / root).log2();
+            assert!((cents - steps as f64 * 50.0).abs() < 1e-6);
+        }
+        assert!((20..=80).contains(&between_semitones));
+    }
+
+    #[test]
+    fn test_intonation_answer_parsing() {
+        assert_eq!(
+            "f".parse::<IntonationAnswer>().unwrap(),
+            IntonationAnswer::Flat
+        );
+        assert_eq!(
+            "in tune".parse::<IntonationAnswer>().unwrap(),
+            IntonationAnswer::InTune
+        );
+        assert_eq!(
+            "sharp".parse::<IntonationAnswer>().unwrap(),
+            IntonationAnswer::Sharp
+        );
+        assert!("up".parse::<IntonationAnswer>().is_err());
+    }
+
+    #[test]
+    fn test_random_intonation_questions() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut seen = [false; 3];
+        for _ in 0..200 {
+            let question = IntonationQuestion::random(&mut rng, 20.0, &QuizConfig::default());
+            let cents = question.pitch.cents.abs();
+            assert!(cents == 0.0 || (10.0..=20.0).contains(&cents));
+
+            let answer = question.answer();
+            assert!(question.is_correct(&answer));
+            match answer {
+                IntonationAnswer::Flat => seen[0] = true,
+                IntonationAnswer::InTune => seen[1] = true,
+                IntonationAnswer::Sharp => seen[2] = true,
+            }
+        }
+        assert_eq!(seen, [true; 3]);
+    }
+
+    #[test]
+    fn test_random_inversion_questions() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut seen_third = false;
+        for _ in 0..100 {
+            let question = InversionQuestion::random(&mut rng, &QuizConfig::default()).unwrap();
+            let inversion = question.chord.inversion;
+            assert!(inversion.index() < question.chord.quality.intervals().len());
+            assert!(question.is_correct(&inversion));
+            seen_third |= inversion == Inversion::Third;
+        }
+        assert!(
+            seen_third,
+            "Seventh chords should be asked in third inversion"
+        );
+    }
+
+    #[test]
+    fn test_quiz_score() {
+        let mut score = QuizScore::default();
+        assert_eq!(score.accuracy(), 0.0);
+
+        score.record(true);
+        score.record(false);
+        score.record(true);
+        score.record(true);
+        assert_eq!(score.correct, 3);
+        assert_eq!(score.total, 4);
+        assert_eq!(format!("{}", score), "3/4 (75%)");
+    }
+
+    #[test]
+    fn test_interval_question_grading() {
+        let question = IntervalQuestion {
+            root: NoteWithOctave {
+                note: Note::C,
+                octave: 4,
+            },
+            interval: Interval::TRITONE,
+        };
+        let top = question.top().unwrap();
+        assert_eq!(top.note, Note::FSharp);
+
+        // Enharmonic spellings of the same size are accepted
+        assert!(question.is_correct(&"d5".parse().unwrap()));
+        assert!(question.is_correct(&"tritone".parse().unwrap()));
+        assert!(!question.is_correct(&"P5".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_random_interval_questions_use_allowed_set() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let allowed = vec![Interval::from_semitones(4), Interval::from_semitones(7)];
+        for _ in 0..50 {
+            let question = IntervalQuestion::random(&mut rng, &allowed).unwrap();
+            assert!(allowed.contains(&question.interval));
+            assert!(question.top().is_some());
+        }
+        assert!(IntervalQuestion::random(&mut rng, &[]).is_err());
+    }
+
+    #[test]
+    fn test_parse_note_range() {
+        let (low, high) = parse_note_range("C3 - B4").unwrap();
+        assert_eq!(format!("{}-{}", low, high), "C3-B4");
+        assert!(parse_note_range("B4-C3").is_err());
+        assert!(parse_note_range("C3").is_err());
+        assert!(parse_note_range("C3-H4").is_err());
+    }
+
+    #[test]
+    fn test_note_accuracy_weights() {
+        let mut accuracy = NoteAccuracy::default();
+        accuracy.record(&Note::C, true);
+        accuracy.record(&Note::D, false);
+
+        assert_eq!(accuracy.score(&Note::D).total, 1);
+        assert!(accuracy.weight(&Note::D) > accuracy.weight(&Note::C));
+        assert!(accuracy.weight(&Note::E) >= accuracy.weight(&Note::D));
+
+        let results = accuracy.results();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, Note::C);
+        assert_eq!(results[1].0, Note::D);
+    }
+
+    #[test]
+    fn test_note_question_in_range() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let (low, high) = parse_note_range("E3-G3").unwrap();
+        let accuracy = NoteAccuracy::default();
+        for _ in 0..50 {
+            let question = NoteQuestion::random(&mut rng, &low, &high, &accuracy).unwrap();
+            assert_eq!(question.reference, NOTE_QUIZ_REFERENCE);
+            let semitone = question.mystery.absolute_semitone();
+            assert!((low.absolute_semitone()..=high.absolute_semitone()).contains(&semitone));
+            assert!(question.is_correct(&question.mystery.note));
+        }
+    }
+
+    #[test]
+    fn test_note_question_drills_weak_notes() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let (low, high) = parse_note_range("C4-C#4").unwrap();
+        let mut accuracy = NoteAccuracy::default();
+        for _ in 0..10 {
+            accuracy.record(&Note::C, true);
+            accuracy.record(&Note::CSharp, false);
+        }
+
+        let sharps = (0..300)
+            .filter(|_| {
+                let question = NoteQuestion::random(&mut rng, &low, &high, &accuracy).unwrap();
+                question.mystery.note == Note::CSharp
+            })
+            .count();
+        // C# is weighted 3:1 over C
+        assert!(sharps > 180);
+    }
+
+    #[test]
+    fn test_interval_prompt_melody() {
+        let fifth = IntervalPrompt {
+            root: "C4".parse().unwrap(),
+            semitones: 7,
+        };
+        assert_eq!(fifth.melody().unwrap().to_string(), "C4:q G4:q");
+    }
+
+    #[test]
+    fn test_cadence_parsing() {
+        assert_eq!("plagal".parse::<Cadence>().unwrap(), Cadence::Plagal);
+        assert_eq!("D".parse::<Cadence>().unwrap(), Cadence::Deceptive);
+        assert_eq!("perfect".parse::<Cadence>().unwrap(), Cadence::Authentic);
+        assert!("final".parse::<Cadence>().is_err());
+    }
+
+    #[test]
+    fn test_cadence_questions() {
+        let question = CadenceQuestion::in_key(Cadence::Deceptive, "G4".parse().unwrap()).unwrap();
+        let chords = question.progression.chords();
+        let last_two: Vec<String> = chords[2..].iter().map(|c| c.name()).collect();
+        assert_eq!(last_two, vec!["D5 Major", "E5 Minor"]);
+        assert!(question.is_correct(&Cadence::Deceptive));
+        assert!(!question.is_correct(&Cadence::Authentic));
+
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let question = CadenceQuestion::random(&mut rng, &QuizConfig::default()).unwrap();
+            assert_eq!(question.progression.chords().len(), 4);
+            assert_eq!(question.progression.key.root.octave, 4);
+        }
+    }
+
+    #[test]
+    fn test_cadence_accuracy() {
+        let mut accuracy = CadenceAccuracy::default();
+        accuracy.record(&Cadence::Half, true);
+        accuracy.record(&Cadence::Half, false);
+        accuracy.record(&Cadence::Plagal, true);
+        assert_eq!(accuracy.score(&Cadence::Half).total, 2);
+        let asked: Vec<Cadence> = accuracy.results().into_iter().map(|(c, _)| c).collect();
+        assert_eq!(asked, vec![Cadence::Plagal, Cadence::Half]);
+    }
+
+    #[test]
+    fn test_parse_scale_degree() {
+        assert_eq!(parse_scale_degree("5"), Ok(5));
+        assert_eq!(parse_scale_degree("Sol"), Ok(5));
+        assert_eq!(parse_scale_degree("so"), Ok(5));
+        assert_eq!(parse_scale_degree("ti"), Ok(7));
+        assert_eq!(parse_scale_degree("do"), Ok(1));
+        assert!(parse_scale_degree("8").is_err());
+        assert!(parse_scale_degree("0").is_err());
+        assert!(parse_scale_degree("fi").is_err());
+    }
+
+    #[test]
+    fn test_degree_questions() {
+        let question = DegreeQuestion::in_key(7, "G4".parse().unwrap()).unwrap();
+        assert_eq!(question.note(), Some("F#5".parse().unwrap()));
+        assert_eq!(question.answer(), "F#5 (ti)");
+        assert!(question.is_correct(7));
+        assert!(!question.is_correct(1));
+        assert_eq!(question.cadence.chords().len(), 4);
+        assert!(DegreeQuestion::in_key(8, "C4".parse().unwrap()).is_err());
+
+        // Degrees that keep getting missed come up more often
+        let mut accuracy = DegreeAccuracy::default();
+        for degree in 1..=7 {
+            accuracy.record(degree, degree != 4);
+        }
+        let mut rng = StdRng::seed_from_u64(11);
+        let fours = (0..200)
+            .filter(|_| {
+                DegreeQuestion::random(&mut rng, &accuracy, &QuizConfig::default())
+                    .unwrap()
+                    .degree
+                    == 4
+            })
+            .count();
+        assert!(fours > 200 / 7);
+    }
+
+    #[test]
+    fn test_degree_accuracy() {
+        let mut accuracy = DegreeAccuracy::default();
+        accuracy.record(3, false);
+        accuracy.record(8, true);
+        assert_eq!(accuracy.results().len(), 1);
+        assert_eq!(accuracy.weight(3), 3.0);
+        assert_eq!(accuracy.weight(1), 3.0);
+        assert!(accuracy.score(0).is_none());
+    }
+
+    #[test]
+    fn test_interval_question_in_range() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let low: NoteWithOctave = "C3".parse().unwrap();
+        let high: NoteWithOctave = "B3".parse().unwrap();
+        for _ in 0..50 {
+            let question = IntervalQuestion::for_interval_in(
+                &mut rng,
+                Interval::from_semitones(7),
+                &low,
+                &high,
+            );
+            assert!(question.root >= low);
+            assert!(question.top().unwrap() <= high);
+        }
+        // An octave doesn't fit inside B3, so it starts on the lowest note
+        let octave =
+            IntervalQuestion::for_interval_in(&mut rng, Interval::from_semitones(12), &low, &high);
+        assert_eq!(octave.root, low);
+    }
+
+    #[test]
+    fn test_sing_back_question() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let single = SingBackQuestion::random(&mut rng, 1, &QuizConfig::default()).unwrap();
+        assert_eq!(single.melody.notes().len(), 1);
+        let question = SingBackQuestion::random(&mut rng, 4, &QuizConfig::default()).unwrap();
+        assert_eq!(question.melody.notes().len(), 4);
+        assert_eq!(question.melody.notes()[0], question.key.root);
+        assert_eq!(question.duration(Tempo(120)), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_questions_follow_the_quiz_config() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let low: NoteWithOctave = "C4".parse().unwrap();
+        let high: NoteWithOctave = "C5".parse().unwrap();
+        let config = QuizConfig::default()
+            .with_intervals(vec!["M3".parse().unwrap(), "P5".parse().unwrap()])
+            .with_chords(vec![ChordQuality::Minor])
+            .with_keys(vec![Note::G, Note::ASharp])
+            .with_range(low, high);
+        for _ in 0..20 {
+            let comparison = IntervalComparison::random(&mut rng, &config).unwrap();
+            for prompt in [&comparison.first, &comparison.second] {
+                assert!([4, 7].contains(&prompt.semitones));
+                assert!((low..=high).contains(&prompt.root));
+            }
+            let inversion = InversionQuestion::random(&mut rng, &config).unwrap();
+            assert_eq!(inversion.chord.quality, ChordQuality::Minor);
+            let cadence = CadenceQuestion::random(&mut rng, &config).unwrap();
+            let tonic = cadence.progression.key.root.note;
+            assert!(tonic == Note::G || tonic == Note::ASharp);
+            let note = EarComparison::random(&mut rng, &config).left;
+            assert!((low..=high).contains(&note));
+        }
+
+        let flat_keys = QuizConfig::default().with_keys(vec![Note::CSharp, Note::DSharp]);
+        let sing_back = SingBackQuestion::random(&mut rng, 3, &flat_keys).unwrap();
+        assert!([Note::CSharp, Note::DSharp].contains(&sing_back.key.root.note));
+        let seconds_only = QuizConfig::default().with_intervals(vec!["m2".parse().unwrap()]);
+        let same = IntervalComparison::random(&mut rng, &seconds_only).unwrap();
+        assert_eq!(same.answer(), ComparisonAnswer::Same);
+        let ninths_only = QuizConfig::default().with_intervals(vec!["M9".parse().unwrap()]);
+        assert!(IntervalComparison::random(&mut rng, &ninths_only).is_err());
+        let augmented_only = QuizConfig::default().with_chords(vec![ChordQuality::Augmented]);
+        assert!(InversionQuestion::random(&mut rng, &augmented_only).is_err());
+    }
+
+    #[test]
+    fn test_melodic_dictation_question() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let options = DictationOptions::default().with_length(6).with_max_leap(3);
+        let question =
+            MelodicDictationQuestion::random(&mut rng, &options, &QuizConfig::default()).unwrap();
+        let notes = question.melody.notes();
+        assert_eq!(notes.len(), 6);
+        assert_eq!(notes[0], question.key.root);
+
+        let answer: Vec<String> = notes.iter().map(|n| n.to_string()).collect();
+        assert!(
+            question
+                .score(&answer.join(" "), NoteNaming::default())
+                .unwrap()
+                .is_perfect()
+        );
+        assert!(question.score("", NoteNaming::default()).is_err());
+        assert!(question.score("C4 H4", NoteNaming::default()).is_err());
+    }
+
+    #[test]
+    fn test_score_dictation() {
+        let notes: Vec<NoteWithOctave> = ["C4", "D4", "E4", "C4"]
+            .iter()
+            .map(|n| n.parse().unwrap())
+            .collect();
+        let question = MelodicDictationQuestion {
+            key: Scale::new(notes[0], ScaleType::Major),
+            melody: Melody::from_notes(&notes, NoteValue::Quarter),
+        };
+        let note = |s: &str| s.parse::<NoteWithOctave>().unwrap();
+
+        let octaveless = question.score("C D E C", NoteNaming::default()).unwrap();
+        assert!(octaveless.is_perfect());
+        assert_eq!(octaveless.to_string(), "4/4 notes right");
+        let high = MelodicDictationQuestion {
+            key: Scale::new(note("C5"), ScaleType::Major),
+            melody: Melody::from_notes(&[note("C5"), note("B4")], NoteValue::Quarter),
+        };
+        assert!(
+            high.score("C B", NoteNaming::default())
+                .unwrap()
+                .is_perfect()
+        );
+        assert!(
+            !high
+                .score("C4 B4", NoteNaming::default())
+                .unwrap()
+                .is_perfect()
+        );
+
+        // A missed D shouldn't mark the notes after it wrong
+        let skipped = question.score("C4 E4 C4", NoteNaming::default()).unwrap();
+        assert_eq!(skipped.correct(), 3);
+        assert_eq!(skipped.notes[1], NoteFeedback::Missing(note("D4")));
+
+        let wrong = question
+            .score("C4 D4 F4 C4 G4", NoteNaming::default())
+            .unwrap();
+        assert_eq!(
+            wrong.notes[2],
+            NoteFeedback::Wrong {
+                expected: note("E4"),
+                got: note("F4"),
+            }
+        );
+        assert_eq!(wrong.notes[4], NoteFeedback::Extra(note("G4")));
+        assert_eq!(wrong.to_string(), "3/4 notes right");
+        assert_eq!(wrong.notes[2].to_string(), "❌ E4 (you wrote F4)");
+
+        let g_major = MelodicDictationQuestion {
+            key: Scale::new(note("G4"), ScaleType::Major),
+            melody: Melody::from_notes(&[note("G4"), note("B4"), note("F#4")], NoteValue::Quarter),
+        };
+        let low_ti = g_major
+            .score("Do Mi Ti3", NamingScheme::MovableDo.into())
+            .unwrap();
+        assert!(!low_ti.notes[2].is_correct());
+        let movable = g_major
+            .score("do mi ti", NamingScheme::MovableDo.into())
+            .unwrap();
+        assert!(movable.is_perfect());
+        let fixed = g_major
+            .score("Sol Si Fa4", NamingScheme::FixedDo.into())
+            .unwrap();
+        assert_eq!(
+            fixed.notes[2].named(NamingScheme::FixedDo.into(), &Note::G),
+            "❌ Fa#4 (you wrote Fa4)"
+        );
+        assert_eq!(
+            fixed.notes[0].named(NamingScheme::MovableDo.into(), &Note::G),
+            "✅ Do4"
+        );
+        assert!(g_major.score("Do Mi Ti", NoteNaming::default()).is_err());
+
+        let helmholtz = NoteNaming::default().with_notation(PitchNotation::Helmholtz);
+        assert!(g_major.score("g' b' f#'", helmholtz).unwrap().is_perfect());
+        assert_eq!(
+            g_major.score("g' b' f#", helmholtz).unwrap().notes[2].named(helmholtz, &Note::G),
+            "❌ f#' (you wrote f#)"
+        );
+    }
+
+    #[test]
+    fn test_score_sung_notes() {
+        let rate = 44100;
+        let tempo = Tempo(120);
+        let notes: Vec<NoteWithOctave> = ["C4", "E4", "G4", "C5"]
+            .iter()
+            .map(|n| n.parse().unwrap())
+            .collect();
+        let question = SingBackQuestion {
+            key: Scale::new(notes[0], ScaleType::Major),
+            melody: Melody::from_notes(&notes, NoteValue::Quarter),
+        };
+
+        // A late start, a slightly sharp C, an E sung as F, a G an octave down, then silence
+        let tuning = Tuning::default();
+        let sung = [
+            tuning.frequency_of(&notes[0]) * 1.01,
+            tuning.frequency_of(&"F4".parse().unwrap()),
+            tuning.frequency_of(&notes[2]) / 2.0,
+        ];
+        let beat = tempo.beat_duration().as_secs_f64() * rate as f64;
+        let mut samples = vec![0.0; rate as usize / 3];
+        for frequency in sung {
+            samples.extend((0..beat as usize).map(|i| {
+                let t = i as f64 / rate as f64;
+                (0.5 * (std::f64::consts::TAU * frequency * t).sin()) as f32
+            }));
+        }
+        samples.extend(vec![0.0; beat as usize]);
+
+        let scores = question.score(&samples, rate, tempo, &PitchDetector::default(), &tuning);
+        assert_eq!(scores.len(), 4);
+        assert!((scores[0].cents.unwrap() - 17.2).abs() < 3.0);
+        assert!(scores[0].is_correct(SING_BACK_TOLERANCE_CENTS));
+        assert!(!scores[1].is_correct(SING_BACK_TOLERANCE_CENTS));
+        assert!((scores[1].cents.unwrap() - 100.0).abs() < 3.0);
+        assert!(scores[2].is_correct(SING_BACK_TOLERANCE_CENTS));
+        assert_eq!(scores[3].frequency, None);
+        assert!(!scores[3].is_correct(SING_BACK_TOLERANCE_CENTS));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_quiz_results() {
+        let mut score = QuizScore::default();
+        score.record(true);
+        score.record(false);
+        assert_eq!(
+            serde_json::to_string(&score).unwrap(),
+            r#"{"correct":1,"total":2}"#
+        );
+
+        let sung = SungNote {
+            target: "A4".parse().unwrap(),
+            frequency: None,
+            cents: None,
+        };
+        let json = serde_json::to_string(&sung).unwrap();
+        assert_eq!(serde_json::from_str::<SungNote>(&json).unwrap(), sung);
+        assert_eq!(
+            serde_json::from_str::<Cadence>(r#""deceptive""#).unwrap(),
+            Cadence::Deceptive
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use crate::display::Keyboard;
+use crate::report::SessionReport;
+use crate::stats::AnswerRecord;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use rand::Rng;
+use rand::seq::SliceRandom;
+use ratatui::DefaultTerminal;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Padding, Paragraph, Widget};
+use std::io;
+use std::time::Duration;
+
+/// Most answer choices on screen at once, one for each of the keys 1-9
+pub const MAX_CHOICES: usize = 9;
+
+/// Narrowest a pane is drawn, so short prompts don't make a cramped screen
+const MIN_PANE_WIDTH: usize = 40;
+
+/// Confusions listed in the stats pane
+const CONFUSIONS_SHOWN: usize = 3;
+
+/// Shortcut reminder along the bottom of the screen
+const SHORTCUTS: &str = "1-9 answer · r replay · space next · q quit";
+
+/// What a key press asks the drill to do
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    Replay,
+    /// Pick the answer choice at this index, counted from 0
+    Choose(usize),
+    Next,
+    Quit,
+}
+
+impl Action {
+    /// The shortcut bound to `key`: r replays, 1-9 answer, space or Enter moves on and
+    /// q or Esc quits
+    pub fn from_key(key: char) -> Option<Action> {
+        match key {
+            'r' | 'R' => Some(Action::Replay),
+            '1'..='9' => Some(Action::Choose(key as usize - '1' as usize)),
+            ' ' | '\n' | '\r' => Some(Action::Next),
+            'q' | 'Q' | '\x1b' => Some(Action::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Up to `max` of `candidates` to offer as answers, always including `answer`,
+/// in the candidates' own order
+pub fn pick_choices<T: PartialEq + Clone, R: Rng>(
+    rng: &mut R,
+    answer: &T,
+    candidates: &[T],
+    max: usize,
+) -> Vec<T> {
+    if candidates.len() <= max {
+        return candidates.to_vec();
+    }
+    let others: Vec<&T> = candidates.iter().filter(|c| *c != answer).collect();
+    let picked: Vec<&T> = others
+        .choose_multiple(rng, max.saturating_sub(1))
+        .copied()
+        .collect();
+    candidates
+        .iter()
+        .filter(|c| *c == answer || picked.contains(c))
+        .cloned()
+        .collect()
+}
+
+/// Everything on the full-screen drill: the question and its numbered answers, the
+/// keyboard the notes were played on, and how the session is going
+#[derive(Debug, Clone)]
+pub struct TuiScreen {
+    pub title: String,
+    pub prompt: String,
+    pub choices: Vec<String>,
+    /// The result of the last answer, shown until the next question
+    pub feedback: Option<String>,
+    pub keyboard: Keyboard,
+    /// Answers given since the drill started
+    pub records: Vec<AnswerRecord>,
+}
+
+impl Default for TuiScreen {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            prompt: String::new(),
+            choices: Vec::new(),
+            feedback: None,
+            keyboard: Keyboard::new(4, 1),
+            records: Vec::new(),
+        }
+    }
+}
+
+/// The quiz, keyboard and stats panes stacked in boxes of the same width, then the
+/// shortcuts
+impl Widget for &TuiScreen {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let keyboard: Vec<String> = self
+            .keyboard
+            .to_string()
+            .lines()
+            .map(String::from)
+            .collect();
+        let panes = [
+            (self.title.as_str(), self.quiz_lines()),
+            ("Keyboard", keyboard),
+            ("Session", self.stats_lines()),
+        ];
+        let width = panes
+            .iter()
+            .flat_map(|(title, lines)| {
+                lines
+                    .iter()
+                    .map(|l| Line::from(l.as_str()).width())
+                    .chain([Line::from(*title).width() + 2])
+            })
+            .max()
+            .unwrap_or(0)
+            .max(MIN_PANE_WIDTH);
+        // Text plus a space of padding and a border on each side
+        let area = Rect {
+            width: area.width.min(width as u16 + 4),
+            ..area
+        };
+        let heights = panes
+            .iter()
+            .map(|(_, lines)| Constraint::Length(lines.len() as u16 + 2))
+            .chain([Constraint::Length(1)]);
+        let areas = Layout::vertical(heights).split(area);
+        for ((title, lines), area) in panes.into_iter().zip(areas.iter()) {
+            let block = Block::bordered().padding(Padding::horizontal(1));
+            let block = if title.is_empty() {
+                block
+            } else {
+                block.title(format!(" {} ", title))
+            };
+            Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+                .block(block)
+                .render(*area, buf);
+        }
+        Line::from(format!(" {}", SHORTCUTS)).render(areas[3], buf);
+    }
+}
+
+impl TuiScreen {
+    /// Ask a new question, clearing the last answer's feedback
+    pub fn ask(&mut self, prompt: impl Into<String>, choices: Vec<String>, keyboard: Keyboard) {
+        self.prompt = prompt.into();
+        self.choices = choices;
+        self.feedback = None;
+        self.keyboard = keyboard;
+    }
+
+    fn quiz_lines(&self) -> Vec<String> {
+        let mut lines = vec![self.prompt.clone(), String::new()];
+        lines.extend(
+            self.choices
+                .iter()
+                .enumerate()
+                .map(|(i, choice)| format!("[{}] {}", i + 1, choice)),
+        );
+        if let Some(feedback) = &self.feedback {
+            lines.push(String::new());
+            lines.push(feedback.clone());
+        }
+        lines
+    }
+
+    fn stats_lines(&self) -> Vec<String> {
+        let report = SessionReport::from_records(&self.records);
+        if report.is_empty() {
+            return vec!["No answers yet".to_string()];
+        }
+        let mut lines = vec![report.to_string().lines().next().unwrap_or("").to_string()];
+        lines.extend(
+            report
+                .confusions()
+                .iter()
+                .take(CONFUSIONS_SHOWN)
+                .map(|confusion| format!("❌ {}", confusion)),
+        );
+        lines
+    }
+}
+
+/// The terminal switched to a full-screen drill: an alternate screen, keys read as soon as
+/// they're pressed and not echoed. Put back the way it was when dropped.
+pub struct RawTerminal {
+    terminal: DefaultTerminal,
+}
+
+impl RawTerminal {
+    /// Take over the terminal
+    pub fn enter() -> Result<Self, Box<dyn std::error::Error>> {
+        let terminal = ratatui::try_init()
+            .map_err(|e| format!("The full-screen mode needs an interactive terminal: {}", e))?;
+        Ok(Self { terminal })
+    }
+
+    /// Draw `widget` over the whole screen
+    pub fn draw(&mut self, widget: impl Widget) -> io::Result<()> {
+        self.terminal
+            .draw(|frame| frame.render_widget(widget, frame.area()))?;
+        Ok(())
+    }
+
+    /// Wait for the next key bound to a shortcut, or `None` once input ends
+    pub fn next_action(&mut self) -> Option<Action> {
+        loop {
+            if let Event::Key(key) = event::read().ok()?
+                && let Some(action) = key_char(key).and_then(Action::from_key)
+            {
+                return Some(action);
+            }
+        }
+    }
+
+    /// The key pressed within `timeout`, or `None` if there wasn't one, so the caller can
+    /// keep working between key presses
+    pub fn key_within(&mut self, timeout: Duration) -> io::Result<Option<char>> {
+        if !event::poll(timeout)? {
+            return Ok(None);
+        }
+        Ok(match event::read()? {
+            Event::Key(key) => key_char(key),
+            _ => None,
+        })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+/// The character a key press types, with Enter as a newline and Esc as `\x1b`
+fn key_char(key: KeyEvent) -> Option<char> {
+    if key.kind != KeyEventKind::Press {
+        return None;
+    }
+    match key.code {
+        // Raw mode stops Ctrl+C from interrupting, so it quits like Esc
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some('\x1b'),
+        KeyCode::Char(c) => Some(c),
+        KeyCode::Enter => Some('\n'),
+        KeyCode::Esc => Some('\x1b'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::seeded;
+    use crate::stats::Exercise;
+
+    #[test]
+    fn test_shortcuts() {
+        assert_eq!(Action::from_key('r'), Some(Action::Replay));
+        assert_eq!(Action::from_key('1'), Some(Action::Choose(0)));
+        assert_eq!(Action::from_key('9'), Some(Action::Choose(8)));
+        assert_eq!(Action::from_key(' '), Some(Action::Next));
+        assert_eq!(Action::from_key('\x1b'), Some(Action::Quit));
+        assert_eq!(Action::from_key('0'), None);
+        assert_eq!(Action::from_key('x'), None);
+    }
+
+    #[test]
+    fn test_pick_choices() {
+        let mut rng = seeded(4);
+        let candidates: Vec<u8> = (1..=12).collect();
+        for _ in 0..20 {
+            let choices = pick_choices(&mut rng, &7, &candidates, MAX_CHOICES);
+            assert_eq!(choices.len(), MAX_CHOICES);
+            assert!(choices.contains(&7));
+            assert!(choices.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+        assert_eq!(
+            pick_choices(&mut rng, &2, &[1, 2, 3], MAX_CHOICES),
+            [1, 2, 3]
+        );
+    }
+
+    /// The rows `screen` draws on a terminal 60 columns wide, without trailing blanks
+    fn drawn(screen: &TuiScreen) -> Vec<String> {
+        let area = Rect::new(0, 0, 60, 30);
+        let mut buf = Buffer::empty(area);
+        screen.render(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                let mut row = String::new();
+                let mut x = 0;
+                // A wide symbol like an emoji covers the cell after it too
+                while x < area.width {
+                    let symbol = buf[(x, y)].symbol();
+                    row.push_str(symbol);
+                    x += Line::from(symbol).width().max(1) as u16;
+                }
+                row.trim_end().to_string()
+            })
+            .filter(|row| !row.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn test_screen() {
+        let mut screen = TuiScreen {
+            title: "Interval quiz".to_string(),
+            ..TuiScreen::default()
+        };
+        screen.ask(
+            "Name the interval",
+            vec!["m3".to_string(), "M3".to_string()],
+            Keyboard::new(4, 1),
+        );
+        let rows = drawn(&screen);
+        assert!(rows[0].starts_with("┌ Interval quiz ─"));
+        assert!(rows.iter().any(|row| row.starts_with("│ [2] M3 ")));
+        assert!(rows.iter().any(|row| row.starts_with("│ No answers yet ")));
+        assert_eq!(rows.last().unwrap().trim(), SHORTCUTS);
+        // Every row of every box is as wide as the others
+        let widths: Vec<usize> = rows
+            .iter()
+            .filter(|row| !row.starts_with(' '))
+            .map(|row| Line::from(row.as_str()).width())
+            .collect();
+        assert!(widths.iter().all(|w| *w == widths[0]));
+
+        screen.feedback = Some("❌ It was M3".to_string());
+        screen.records.push(AnswerRecord::new(
+            Exercise::Intervals,
+            "M3",
+            "m3",
+            false,
+            Duration::from_secs(2),
+        ));
+        let rows = drawn(&screen).join("\n");
+        assert!(rows.contains("│ ❌ It was M3"));
+        assert!(rows.contains("1 answers, 0 right (0%), 2.0s per answer"));
+        assert!(rows.contains("❌ M3 answered as m3"));
+    }
+
+    #[test]
+    fn test_key_char() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(key_char(key(KeyCode::Char('r'))), Some('r'));
+        assert_eq!(key_char(key(KeyCode::Enter)), Some('\n'));
+        assert_eq!(key_char(key(KeyCode::Esc)), Some('\x1b'));
+        assert_eq!(key_char(key(KeyCode::Left)), None);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(
+            key_char(ctrl_c).and_then(Action::from_key),
+            Some(Action::Quit)
+        );
+    }
+}
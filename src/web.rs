@@ -0,0 +1,177 @@
+use crate::audio::{Drone, PlaybackBackend, SAMPLE_RATE, ToneConfig, Waveform};
+use std::cell::Cell;
+use std::time::Duration;
+use wasm_bindgen::JsValue;
+use web_sys::{AudioBuffer, AudioContext, AudioScheduledSourceNode, OscillatorType};
+
+/// Drones play at half the tone's gain, like the native drone's sink volume
+const DRONE_VOLUME: f32 = 0.5;
+
+/// Plays through the browser's Web Audio API. The browser can't block while a sound plays,
+/// so each sound is scheduled to start when the previous one ends instead.
+pub struct WebAudioBackend {
+    context: AudioContext,
+    /// When the next sound starts, in the context's clock (seconds)
+    next_start: Cell<f64>,
+}
+
+impl WebAudioBackend {
+    /// Create an audio context. Browsers only allow sound after a user gesture,
+    /// so call this from e.g. a click handler.
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            context: AudioContext::new().map_err(js_error)?,
+            next_start: Cell::new(0.0),
+        })
+    }
+
+    pub fn context(&self) -> &AudioContext {
+        &self.context
+    }
+
+    /// Seconds until everything scheduled so far has played
+    pub fn queued(&self) -> f64 {
+        (self.next_start.get() - self.context.current_time()).max(0.0)
+    }
+
+    /// When a sound queued now starts: straight away, or after what is already queued
+    fn start_time(&self) -> f64 {
+        self.next_start.get().max(self.context.current_time())
+    }
+
+    /// Copy interleaved samples into a Web Audio buffer, which keeps each channel separately
+    fn buffer(
+        &self,
+        samples: &[f32],
+        channels: u16,
+    ) -> Result<AudioBuffer, Box<dyn std::error::Error>> {
+        let frames = samples.len() / channels as usize;
+        let buffer = self
+            .context
+            .create_buffer(channels as u32, frames as u32, SAMPLE_RATE as f32)
+            .map_err(js_error)?;
+        for channel in 0..channels as usize {
+            let data: Vec<f32> = samples
+                .iter()
+                .skip(channel)
+                .step_by(channels as usize)
+                .copied()
+                .collect();
+            buffer
+                .copy_to_channel(&data, channel as i32)
+                .map_err(js_error)?;
+        }
+        Ok(buffer)
+    }
+}
+
+impl PlaybackBackend for WebAudioBackend {
+    fn play_samples(
+        &self,
+        samples: Vec<f32>,
+        channels: u16,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let frames = samples.len() / channels as usize;
+        if frames == 0 {
+            return Ok(());
+        }
+        let buffer = self.buffer(&samples, channels)?;
+        let source = self.context.create_buffer_source().map_err(js_error)?;
+        source.set_buffer(Some(&buffer));
+        source
+            .connect_with_audio_node(&self.context.destination())
+            .map_err(js_error)?;
+        let start = self.start_time();
+        source.start_with_when(start).map_err(js_error)?;
+        self.next_start
+            .set(start + frames as f64 / SAMPLE_RATE as f64);
+        Ok(())
+    }
+
+    fn rest(&self, duration: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        self.next_start
+            .set(self.start_time() + duration.as_secs_f64());
+        Ok(())
+    }
+
+    fn start_drone(
+        &self,
+        frequencies: &[f32],
+        tone: ToneConfig,
+    ) -> Result<Drone, Box<dyn std::error::Error>> {
+        let start = self.start_time();
+        let gain = self.context.create_gain().map_err(js_error)?;
+        // Drones have no fixed length, so only the attack applies
+        gain.gain()
+            .set_value_at_time(0.0, start)
+            .map_err(js_error)?;
+        gain.gain()
+            .linear_ramp_to_value_at_time(
+                tone.gain * DRONE_VOLUME,
+                start + tone.envelope.attack.as_secs_f64(),
+            )
+            .map_err(js_error)?;
+        gain.connect_with_audio_node(&self.context.destination())
+            .map_err(js_error)?;
+
+        let mut oscillators = Vec::new();
+        for &frequency in frequencies {
+            let oscillator = self.context.create_oscillator().map_err(js_error)?;
+            oscillator.set_type(oscillator_type(tone.waveform));
+            oscillator.frequency().set_value(frequency);
+            oscillator
+                .connect_with_audio_node(&gain)
+                .map_err(js_error)?;
+            oscillator.start_with_when(start).map_err(js_error)?;
+            oscillators.push(oscillator);
+        }
+        Ok(Drone::new(move || {
+            for oscillator in &oscillators {
+                let _ = oscillator.stop();
+            }
+            let _ = gain.disconnect();
+        }))
+    }
+
+    fn start_loop(
+        &self,
+        samples: Vec<f32>,
+        channels: u16,
+    ) -> Result<Drone, Box<dyn std::error::Error>> {
+        if samples.len() < channels as usize {
+            return Err("Nothing to loop".into());
+        }
+        let source = self.context.create_buffer_source().map_err(js_error)?;
+        source.set_buffer(Some(&self.buffer(&samples, channels)?));
+        source.set_loop(true);
+        source
+            .connect_with_audio_node(&self.context.destination())
+            .map_err(js_error)?;
+        // Loops start with whatever is queued next but don't hold it up
+        source
+            .start_with_when(self.start_time())
+            .map_err(js_error)?;
+        Ok(Drone::new(move || {
+            let node: &AudioScheduledSourceNode = &source;
+            let _ = node.stop();
+            let _ = source.disconnect();
+        }))
+    }
+}
+
+fn oscillator_type(waveform: Waveform) -> OscillatorType {
+    match waveform {
+        Waveform::Sine => OscillatorType::Sine,
+        Waveform::Square => OscillatorType::Square,
+        Waveform::Triangle => OscillatorType::Triangle,
+        Waveform::Sawtooth => OscillatorType::Sawtooth,
+    }
+}
+
+/// JavaScript exceptions as Rust errors
+fn js_error(value: JsValue) -> Box<dyn std::error::Error> {
+    value
+        .as_string()
+        .unwrap_or_else(|| format!("{:?}", value))
+        .into()
+}